@@ -0,0 +1,48 @@
+//! counts the allocations made while rendering the errors of an error heavy file.
+//!
+//! run with `cargo bench --bench render`.
+
+use std::{io, time::Instant};
+
+use dcfrs::{error::ErrorSink, lexer::tokens, span::SpanSource};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::allocations;
+
+/// renders the lexer errors of `code` and returns the number of errors and allocations.
+fn render(code: &SpanSource) -> (usize, usize) {
+    let errors = tokens(code.source())
+        .filter_map(|tok| tok.get().err())
+        .collect::<Vec<_>>();
+    let before = allocations();
+    let mut sink = ErrorSink::new();
+    errors.iter().for_each(|e| sink.error(e));
+    sink.emit(&mut io::sink()).unwrap();
+    (errors.len(), allocations() - before)
+}
+
+fn main() {
+    let cases = [
+        (
+            "non-ascii",
+            "x = \u{e9}\u{e8}\u{ea}\u{eb}\u{ec}\u{ed}\u{ee}\u{ef};\n",
+        ),
+        ("unexpected char", "x = 1 @ 2 # 3;\n"),
+        ("escapes", "x = \"\\q\\z\\y\";\n"),
+    ];
+    for (name, line) in cases {
+        let text = line.repeat(10_000);
+        let code = SpanSource::new(&text);
+        let start = Instant::now();
+        let (errors, allocations) = render(&code);
+        println!(
+            "{:>16}: {} errors, {:.2} allocations per error, {:?}",
+            name,
+            errors,
+            allocations as f64 / errors as f64,
+            start.elapsed()
+        );
+    }
+}
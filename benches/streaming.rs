@@ -0,0 +1,61 @@
+//! compares collecting the tokens of a large file into a vector before processing them with
+//! processing them as a [`TokenStream`] lexes them from a reader.
+//!
+//! run with `cargo bench --bench streaming`.
+
+use std::time::Instant;
+
+use dcfrs::{
+    lexer::{tokens, TokenStream},
+    span::SpanSource,
+};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::allocations;
+
+type Process = fn(&str) -> (usize, usize);
+
+/// the number of tokens and the number of erroneous ones of `code`, the tokens are collected
+/// first.
+fn collected(text: &str) -> (usize, usize) {
+    let code = SpanSource::new(text);
+    let toks = tokens(code.source()).collect::<Vec<_>>();
+    (
+        toks.len(),
+        toks.iter().filter(|tok| tok.get().is_err()).count(),
+    )
+}
+
+/// same as `collected` but the tokens are lexed from a reader and every one is counted as soon as
+/// it is lexed.
+fn streamed(text: &str) -> (usize, usize) {
+    TokenStream::from_reader(text.as_bytes()).fold((0, 0), |(count, errors), tok| {
+        (count + 1, errors + usize::from(tok.unwrap().token.is_err()))
+    })
+}
+
+fn main() {
+    let chunk = "int a[0x10], b; // counters\n\
+                 void f(int x) {\n  \
+                 if (x >= 10 && !b) { a[x] += 'c' - '\\n'; }\n  \
+                 printf(\"%d\\t\", x);\n}\n";
+    for size in [1024 * 1024, 10 * 1024 * 1024] {
+        let text = chunk.repeat(size / chunk.len() + 1);
+        for (name, process) in [("collect", collected as Process), ("streaming", streamed)] {
+            let before = allocations();
+            let start = Instant::now();
+            let (count, errors) = process(&text);
+            println!(
+                "{:>9} {:>3}MB: {} tokens, {} errors, {} allocations, {:?}",
+                name,
+                size / (1024 * 1024),
+                count,
+                errors,
+                allocations() - before,
+                start.elapsed()
+            );
+        }
+    }
+}
@@ -1,12 +1,19 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-use crate::span::Span;
+use crate::span::{HasSpan, SourceRange, Span};
 use crate::cst;
 
+pub mod pretty;
+mod types;
+pub mod visit;
+pub use types::ExprId;
+pub use visit::{VisitMut, Visitor};
+
 pub type SymMap<T> = HashMap<String, T>;
 pub type VarSymMap = SymMap<Var>;
 pub type FuncSymMap = SymMap<Function>;
-pub type ImportSymMap = HashSet<String>;
+/// the imports by name with the spans of their names.
+pub type ImportSymMap = SymMap<SourceRange>;
 pub type SigSymMap = SymMap<FunctionSig>;
 
 pub use crate::cst::Type;
@@ -29,11 +36,12 @@ impl From<i64> for Literal {
 pub struct Typed<T> {
     r#type: Type,
     val: T,
+    span: SourceRange,
 }
 
 impl<T> Typed<T> {
-    pub fn new(r#type: Type, val: T) -> Self {
-        Self { r#type, val }
+    pub fn new(r#type: Type, val: T, span: SourceRange) -> Self {
+        Self { r#type, val, span }
     }
     pub fn r#type(&self) -> Type {
         self.r#type
@@ -46,6 +54,7 @@ impl<T> Typed<T> {
     }
 }
 
+/// the span of a variable is the span of its declaration.
 #[derive(Debug, Clone)]
 pub enum Var {
     Scalar(Typed<Identifier>),
@@ -65,8 +74,21 @@ impl Var {
     pub fn is_scalar(&self) -> bool {
         matches!(self, Self::Scalar(_))
     }
+    /// the value of the variable (of every element for arrays) before it is assigned, variables
+    /// are zero initialized.
+    pub fn initial_value(&self) -> Literal {
+        let ty = match self {
+            Self::Scalar(ty) => ty.r#type,
+            Self::Array { arr, .. } => arr.r#type,
+        };
+        match ty {
+            Type::Int => Literal::Int(0),
+            Type::Bool => Literal::Bool(false),
+        }
+    }
 }
 
+/// the variables of the locations are spanned by the identifiers they are written with.
 #[derive(Debug, Clone)]
 pub enum Location {
     Scalar(Typed<Identifier>),
@@ -74,6 +96,7 @@ pub enum Location {
         arr: Typed<Identifier>,
         size: u64,
         index: Expr,
+        span: SourceRange,
     },
 }
 
@@ -90,17 +113,23 @@ impl Location {
             Self::Index { arr, .. } => arr.r#type,
         }
     }
-    pub fn index(var: Var, index: Expr) -> Self {
+    /// the variable `var` written as the identifier spanned by `ident`.
+    pub fn scalar(var: &Var, ident: SourceRange) -> Self {
+        assert!(var.is_scalar());
+        match var {
+            Var::Scalar(var) => Self::Scalar(Typed::new(var.r#type, var.val.clone(), ident)),
+            Var::Array { .. } => unreachable!(),
+        }
+    }
+    pub fn index(var: Var, ident: SourceRange, index: Expr, span: SourceRange) -> Self {
         assert!(var.is_array());
         match var {
             Var::Scalar(_) => unreachable!(),
             Var::Array { arr, size } => Self::Index {
-                arr: Typed {
-                    r#type: arr.r#type,
-                    val: arr.val,
-                },
+                arr: Typed::new(arr.r#type, arr.val, ident),
                 size,
                 index,
+                span,
             },
         }
     }
@@ -115,7 +144,7 @@ impl Literal {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ArithOp {
     Add,
     Sub,
@@ -138,7 +167,7 @@ impl TryFrom<cst::Op> for ArithOp {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RelOp {
     Less,
     LessEqual,
@@ -159,7 +188,7 @@ impl TryFrom<cst::Op> for RelOp {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EqOp {
     Equal,
     NotEqual,
@@ -176,7 +205,7 @@ impl TryFrom<cst::Op> for EqOp {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CondOp {
     And,
     Or,
@@ -193,40 +222,49 @@ impl TryFrom<cst::Op> for CondOp {
     }
 }
 
+/// the expressions written in parentheses are spanned without them, the expressions desugared
+/// from an assignment, e.g. the `x + 1` of `x++`, are spanned by the assignment.
 #[derive(Debug, Clone)]
 pub enum Expr {
-    Len(u64),
-    Not(Box<Expr>),
-    Neg(Box<Expr>),
+    Len(SourceRange, u64),
+    Not(SourceRange, Box<Expr>),
+    Neg(SourceRange, Box<Expr>),
+    /// an expression written in parentheses, kept so it can be printed as it is written.
+    Nested(SourceRange, Box<Expr>),
     Ter {
         cond: Box<Expr>,
         yes: Box<Expr>,
         no: Box<Expr>,
+        span: SourceRange,
     },
     Call(Call),
     Loc(Box<Location>),
-    IntLiteral(i64),
-    BoolLiteral(bool),
+    IntLiteral(SourceRange, i64),
+    BoolLiteral(SourceRange, bool),
     Arith {
         op: ArithOp,
         lhs: Box<Expr>,
         rhs: Box<Expr>,
+        span: SourceRange,
     },
     Rel {
         op: RelOp,
         lhs: Box<Expr>,
         rhs: Box<Expr>,
+        span: SourceRange,
     },
     Eq {
         op: EqOp,
         lhs: Box<Expr>,
         rhs: Box<Expr>,
+        span: SourceRange,
     },
 
     Cond {
         op: CondOp,
         lhs: Box<Expr>,
         rhs: Box<Expr>,
+        span: SourceRange,
     },
 }
 
@@ -236,24 +274,22 @@ impl From<Location> for Expr {
     }
 }
 
-impl From<Literal> for Expr {
-    fn from(value: Literal) -> Self {
+impl Expr {
+    pub fn literal(value: Literal, span: SourceRange) -> Self {
         match value {
-            Literal::Int(n) => Expr::IntLiteral(n),
-            Literal::Bool(b) => Expr::BoolLiteral(b),
+            Literal::Int(n) => Expr::IntLiteral(span, n),
+            Literal::Bool(b) => Expr::BoolLiteral(span, b),
         }
     }
-}
 
-impl Expr {
-    pub fn new_not(self) -> Self {
+    pub fn new_not(self, span: SourceRange) -> Self {
         assert!(self.r#type() == Type::Bool);
-        Self::Not(Box::new(self))
+        Self::Not(span, Box::new(self))
     }
 
-    pub fn new_neg(self) -> Self {
+    pub fn new_neg(self, span: SourceRange) -> Self {
         assert!(self.r#type() == Type::Int);
-        Self::Neg(Box::new(self))
+        Self::Neg(span, Box::new(self))
     }
 
     pub fn is_boolean(&self) -> bool {
@@ -267,9 +303,10 @@ impl Expr {
     pub fn r#type(&self) -> Type {
         use Expr::*;
         match self {
-            Len(_) | Neg(_) | Arith { .. } | IntLiteral(_) => Type::Int,
-            Cond { .. } | Eq { .. } | Rel { .. } | Not(..) | BoolLiteral(_) => Type::Bool,
+            Len(..) | Neg(..) | Arith { .. } | IntLiteral(..) => Type::Int,
+            Cond { .. } | Eq { .. } | Rel { .. } | Not(..) | BoolLiteral(..) => Type::Bool,
             Ter { yes, .. } => yes.r#type(),
+            Nested(_, e) => e.r#type(),
             Loc(loc) => loc.r#type(),
             Call(call) => call.return_type().unwrap(),
         }
@@ -281,11 +318,13 @@ pub enum Call {
     Extern {
         name: String,
         args: Vec<ExternArg>,
+        span: SourceRange,
     },
     Decaf {
         name: String,
         ret: Option<Type>,
         args: Vec<Expr>,
+        span: SourceRange,
     },
 }
 
@@ -296,11 +335,16 @@ impl Call {
             Self::Decaf { ret, .. } => *ret,
         }
     }
-    pub fn new_extern(name: String, args: Vec<ExternArg>) -> Self {
-        Self::Extern { name, args }
+    pub fn new_extern(name: String, args: Vec<ExternArg>, span: SourceRange) -> Self {
+        Self::Extern { name, args, span }
     }
-    pub fn new_decaf(name: String, ret: Option<Type>, args: Vec<Expr>) -> Self {
-        Self::Decaf { name, ret, args }
+    pub fn new_decaf(name: String, ret: Option<Type>, args: Vec<Expr>, span: SourceRange) -> Self {
+        Self::Decaf {
+            name,
+            ret,
+            args,
+            span,
+        }
     }
 }
 
@@ -308,49 +352,107 @@ impl Call {
 pub enum Stmt {
     Assign(Assign),
     Expr(Expr),
-    Return(Option<Expr>),
-    Break,
-    Continue,
-    If {
-        cond: Expr,
-        yes: Box<Block>,
-        no: Box<Block>,
-    },
+    Return(SourceRange, Option<Expr>),
+    Break(SourceRange),
+    Continue(SourceRange),
+    If(Box<IfChain>),
     While {
         cond: Expr,
         body: Box<Block>,
+        span: SourceRange,
     },
     For {
-        init: Assign,
+        init: Box<Assign>,
         cond: Expr,
-        update: Assign,
+        update: Box<Assign>,
         body: Box<Block>,
+        span: SourceRange,
     },
 }
 
+/// a branch of an [`IfChain`], its body runs if its condition holds and the conditions of the
+/// arms before it do not.
+#[derive(Debug, Clone)]
+pub struct IfArm {
+    pub cond: Expr,
+    pub body: Block,
+    /// from the `if` to the end of its block.
+    pub span: SourceRange,
+}
+
+/// an `if` and the `if`s that are alone in its `else` blocks, decaf has no `else if` so
+/// `if (a) { } else { if (b) { } else { } }` is how a chain of two arms is written.
+#[derive(Debug, Clone)]
+pub struct IfChain {
+    pub arms: Vec<IfArm>,
+    /// the `else` block of the last arm, it is empty if there is none.
+    pub otherwise: Box<Block>,
+    pub span: SourceRange,
+}
+
+impl IfChain {
+    /// the chain of the `if` with the condition `cond` and the blocks `yes` and `no`, the arms
+    /// of `no` are appended if it holds nothing but an `if`.
+    pub fn new(cond: Expr, yes: Block, mut no: Block, span: SourceRange) -> Self {
+        let mut arms = vec![IfArm {
+            cond,
+            span: span.merge(yes.span),
+            body: yes,
+        }];
+        let otherwise = if no.decls.is_empty() && matches!(no.stmts[..], [Stmt::If(_)]) {
+            let Some(Stmt::If(chain)) = no.stmts.pop() else {
+                unreachable!()
+            };
+            arms.extend(chain.arms);
+            chain.otherwise
+        } else {
+            Box::new(no)
+        };
+        Self {
+            arms,
+            otherwise,
+            span,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Function {
     pub name: String,
     pub body: Block,
     pub args: VarSymMap,
+    /// the names of `args` in the order they are declared in.
+    pub params: Vec<Identifier>,
     pub ret: Option<Type>,
+    pub span: SourceRange,
 }
 
 impl Function {
-    pub fn new(name: Span, body: Block, args: VarSymMap, ret: Option<Type>) -> Self {
+    pub fn new(
+        name: Span,
+        body: Block,
+        args: VarSymMap,
+        params: Vec<Identifier>,
+        ret: Option<Type>,
+        span: SourceRange,
+    ) -> Self {
         Self {
             name: name.to_string(),
             body,
             args,
+            params,
             ret,
+            span,
         }
     }
 }
 
+/// the `else` block of an `if` without one is empty and has an empty span.
 #[derive(Debug, Clone, Default)]
 pub struct Block {
     pub decls: VarSymMap,
     pub stmts: Vec<Stmt>,
+    pub span: SourceRange,
 }
 
 impl Block {
@@ -361,7 +463,8 @@ impl Block {
 
 #[derive(Debug, Clone)]
 pub enum FunctionSig {
-    Extern(String),
+    /// an import with the span of its name.
+    Extern(String, SourceRange),
     Decl {
         name: String,
         arg_types: Vec<Type>,
@@ -372,7 +475,7 @@ pub enum FunctionSig {
 impl FunctionSig {
     pub fn name(&self) -> &str {
         match self {
-            Self::Extern(name) => name,
+            Self::Extern(name, _) => name,
             Self::Decl { name, .. } => name,
         }
     }
@@ -384,14 +487,14 @@ impl FunctionSig {
         }
     }
     pub(super) fn from_pimport(import: &cst::Import) -> Self {
-        Self::Extern(import.name().to_string())
+        Self::Extern(import.name().to_string(), import.name().into())
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum ExternArg {
-    String(String),
-    Array(String),
+    String(SourceRange, String),
+    Array(SourceRange, String),
     Expr(Expr),
 }
 
@@ -400,10 +503,127 @@ pub struct Root {
     pub globals: VarSymMap,
     pub functions: FuncSymMap,
     pub imports: ImportSymMap,
+    /// the types of the expressions indexed by their [`ExprId`].
+    pub(crate) types: Vec<Option<Type>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Assign {
     pub lhs: Location,
     pub rhs: Expr,
+    pub span: SourceRange,
+}
+
+impl<T> HasSpan for Typed<T> {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        self.span
+    }
+}
+
+impl HasSpan for Var {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        match self {
+            Self::Scalar(var) => var.span,
+            Self::Array { arr, .. } => arr.span,
+        }
+    }
+}
+
+impl HasSpan for Location {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        match self {
+            Self::Scalar(var) => var.span,
+            Self::Index { span, .. } => *span,
+        }
+    }
+}
+
+impl HasSpan for Expr {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        match self {
+            Self::Len(span, _)
+            | Self::Not(span, _)
+            | Self::Neg(span, _)
+            | Self::Nested(span, _)
+            | Self::IntLiteral(span, _)
+            | Self::BoolLiteral(span, _)
+            | Self::Ter { span, .. }
+            | Self::Arith { span, .. }
+            | Self::Rel { span, .. }
+            | Self::Eq { span, .. }
+            | Self::Cond { span, .. } => *span,
+            Self::Call(call) => call.span(),
+            Self::Loc(loc) => loc.span(),
+        }
+    }
+}
+
+impl HasSpan for Call {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        match self {
+            Self::Extern { span, .. } | Self::Decaf { span, .. } => *span,
+        }
+    }
+}
+
+impl HasSpan for ExternArg {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        match self {
+            Self::String(span, _) | Self::Array(span, _) => *span,
+            Self::Expr(e) => e.span(),
+        }
+    }
+}
+
+impl HasSpan for Stmt {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        match self {
+            Self::Assign(assign) => assign.span,
+            Self::Expr(e) => e.span(),
+            Self::Return(span, _)
+            | Self::Break(span)
+            | Self::Continue(span)
+            | Self::While { span, .. }
+            | Self::For { span, .. } => *span,
+            Self::If(chain) => chain.span,
+        }
+    }
+}
+
+impl HasSpan for Assign {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        self.span
+    }
+}
+
+impl HasSpan for Block {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        self.span
+    }
+}
+
+impl HasSpan for Function {
+    type Span = SourceRange;
+
+    fn span(&self) -> SourceRange {
+        self.span
+    }
 }
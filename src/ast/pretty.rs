@@ -0,0 +1,649 @@
+//! prints a checked program back as decaf source in a canonical layout.
+//!
+//! unlike [`crate::unparse`], which prints a parsed program as it is written, the printed program
+//! only depends on what the program means: the literals are printed in decimal, `len` is replaced
+//! by the size of the array, the compound assignments are printed as plain ones except in the
+//! updates of `for` statements and the parentheses are only printed where the precedence of the
+//! operators needs them, unless [`Parens::AsWritten`] keeps the ones of the source.
+
+use super::visit::{walk_call, walk_expr_mut, VisitMut, Visitor};
+use super::{
+    ArithOp, Assign, Block, Call, CondOp, EqOp, Expr, ExternArg, Function, IfArm, Location, RelOp,
+    Root, Stmt, Type, Var,
+};
+use crate::cst::Op;
+use crate::parser::{BinaryOp, UNARY_PRECEDENCE};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// where the parentheses of the expressions are printed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Parens {
+    /// only where the precedence of the operators needs them, e.g. `a - (b - c)` but `a * b + c`
+    /// for `(a * b) + c`.
+    #[default]
+    Minimal,
+    /// where the source has them, so `(a * b) + c` is printed as it is written.
+    AsWritten,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// the number of spaces each nested block is indented by.
+    pub indent: usize,
+    /// the width the printed lines are kept within where they can be broken.
+    pub width: usize,
+    /// which parentheses of the expressions are printed.
+    pub parens: Parens,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            width: 80,
+            parens: Parens::default(),
+        }
+    }
+}
+
+/// removes the parentheses the source wraps the expressions in.
+struct StripParens;
+
+impl VisitMut for StripParens {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        while let Expr::Nested(_, inner) = expr {
+            *expr = std::mem::replace(&mut **inner, Expr::BoolLiteral(Default::default(), false));
+        }
+        walk_expr_mut(self, expr)
+    }
+}
+
+/// the entry of the precedence table of the parser for the operator of a binary expression.
+fn binary_op(expr: &Expr) -> Option<BinaryOp> {
+    let op = match expr {
+        Expr::Arith { op, .. } => match op {
+            ArithOp::Add => Op::Add,
+            ArithOp::Sub => Op::Sub,
+            ArithOp::Mul => Op::Mul,
+            ArithOp::Div => Op::Div,
+            ArithOp::Mod => Op::Mod,
+        },
+        Expr::Rel { op, .. } => match op {
+            RelOp::Less => Op::Less,
+            RelOp::LessEqual => Op::LessEqual,
+            RelOp::Greater => Op::Greater,
+            RelOp::GreaterEqual => Op::GreaterEqual,
+        },
+        Expr::Eq { op, .. } => match op {
+            EqOp::Equal => Op::Equal,
+            EqOp::NotEqual => Op::NotEqual,
+        },
+        Expr::Cond { op, .. } => match op {
+            CondOp::And => Op::And,
+            CondOp::Or => Op::Or,
+        },
+        _ => return None,
+    };
+    Some(BinaryOp::of_op(op))
+}
+
+/// how tightly an expression binds, the operands of an operator that binds tighter than them
+/// are printed in parentheses.
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Ter { .. } => 0,
+        // a negative literal is written as a negated one
+        Expr::Not(..) | Expr::Neg(..) | Expr::IntLiteral(_, i64::MIN..=-1) => UNARY_PRECEDENCE,
+        expr => binary_op(expr).map_or(UNARY_PRECEDENCE + 1, |op| op.precedence),
+    }
+}
+
+/// the operator and the operands of a binary expression.
+fn binary(expr: &Expr) -> Option<(&'static str, &Expr, &Expr)> {
+    let op = match expr {
+        Expr::Arith { op, .. } => match op {
+            ArithOp::Add => "+",
+            ArithOp::Sub => "-",
+            ArithOp::Mul => "*",
+            ArithOp::Div => "/",
+            ArithOp::Mod => "%",
+        },
+        Expr::Rel { op, .. } => match op {
+            RelOp::Less => "<",
+            RelOp::LessEqual => "<=",
+            RelOp::Greater => ">",
+            RelOp::GreaterEqual => ">=",
+        },
+        Expr::Eq { op, .. } => match op {
+            EqOp::Equal => "==",
+            EqOp::NotEqual => "!=",
+        },
+        Expr::Cond { op, .. } => match op {
+            CondOp::And => "&&",
+            CondOp::Or => "||",
+        },
+        _ => return None,
+    };
+    match expr {
+        Expr::Arith { lhs, rhs, .. }
+        | Expr::Rel { lhs, rhs, .. }
+        | Expr::Eq { lhs, rhs, .. }
+        | Expr::Cond { lhs, rhs, .. } => Some((op, lhs, rhs)),
+        _ => None,
+    }
+}
+
+/// the least precedences of the operands of a binary expression that are printed without
+/// parentheses, the comparisons are printed as if they did not associate since chaining them is
+/// never what the program means.
+fn operand_precedences(expr: &Expr) -> (u8, u8) {
+    let op = binary_op(expr).expect("a binary expression");
+    match expr {
+        Expr::Rel { .. } | Expr::Eq { .. } => (op.precedence + 1, op.precedence + 1),
+        _ => (op.lhs_precedence(), op.rhs_precedence()),
+    }
+}
+
+/// prints `expr` in parentheses if it binds looser than `min`.
+fn operand(expr: &Expr, min: u8) -> String {
+    if precedence(expr) < min {
+        format!("({})", self::expr(expr))
+    } else {
+        self::expr(expr)
+    }
+}
+
+fn expr(expr: &Expr) -> String {
+    if let Some((op, lhs, rhs)) = binary(expr) {
+        let (lmin, rmin) = operand_precedences(expr);
+        return format!("{} {} {}", operand(lhs, lmin), op, operand(rhs, rmin));
+    }
+    match expr {
+        Expr::Len(_, size) => size.to_string(),
+        Expr::Not(_, e) => format!("!{}", operand(e, UNARY_PRECEDENCE)),
+        Expr::Neg(_, e) => {
+            let e = operand(e, UNARY_PRECEDENCE);
+            // `--` is the decrement operator
+            if e.starts_with('-') {
+                format!("-({})", e)
+            } else {
+                format!("-{}", e)
+            }
+        }
+        Expr::Ter { cond, yes, no, .. } => format!(
+            "{} ? {} : {}",
+            operand(cond, 1),
+            self::expr(yes),
+            self::expr(no)
+        ),
+        Expr::Call(call) => format!("{}({})", call_name(call), call_args(call).join(", ")),
+        Expr::Loc(loc) => location(loc),
+        Expr::IntLiteral(_, n) => n.to_string(),
+        Expr::BoolLiteral(_, b) => b.to_string(),
+        // the parentheses of the source make it an operand of any operator
+        Expr::Nested(_, e) => format!("({})", self::expr(e)),
+        _ => unreachable!("binary expressions are printed above"),
+    }
+}
+
+fn call_name(call: &Call) -> &str {
+    match call {
+        Call::Extern { name, .. } | Call::Decaf { name, .. } => name,
+    }
+}
+
+fn call_args(call: &Call) -> Vec<String> {
+    match call {
+        Call::Extern { args, .. } => args
+            .iter()
+            .map(|arg| match arg {
+                // the string keeps the quotes and the escapes it is written with
+                ExternArg::String(_, s) | ExternArg::Array(_, s) => s.clone(),
+                ExternArg::Expr(e) => expr(e),
+            })
+            .collect(),
+        Call::Decaf { args, .. } => args.iter().map(expr).collect(),
+    }
+}
+
+fn location(loc: &Location) -> String {
+    match loc {
+        Location::Scalar(var) => var.val().clone(),
+        Location::Index { arr, index, .. } => format!("{}[{}]", arr.val(), expr(index)),
+    }
+}
+
+/// the update of a `for` statement has to be an increment or a compound assignment, they are
+/// checked as the assignment of the sum or the difference of the location and the operand.
+fn update(update: &Assign) -> String {
+    let lhs = location(&update.lhs);
+    match &update.rhs {
+        Expr::Arith {
+            op, lhs: loc, rhs, ..
+        } if matches!(op, ArithOp::Add | ArithOp::Sub)
+            && matches!(**loc, Expr::Loc(_))
+            && expr(loc) == lhs =>
+        {
+            match (op, &**rhs) {
+                (ArithOp::Add, Expr::IntLiteral(_, 1)) => format!("{}++", lhs),
+                (ArithOp::Sub, Expr::IntLiteral(_, 1)) => format!("{}--", lhs),
+                (ArithOp::Add, rhs) => format!("{} += {}", lhs, expr(rhs)),
+                (_, rhs) => format!("{} -= {}", lhs, expr(rhs)),
+            }
+        }
+        // only a tree that is not checked from source can have another update, the location is
+        // read once more to write it as a compound assignment
+        rhs => format!("{} += {} - {}", lhs, operand(rhs, 5), lhs),
+    }
+}
+
+fn ty(ty: Option<Type>) -> String {
+    ty.map_or("void".to_string(), |ty| ty.to_string())
+}
+
+fn var(var: &Var) -> String {
+    match var {
+        Var::Scalar(var) => format!("{} {}", var.r#type(), var.val()),
+        Var::Array { arr, size } => format!("{} {}[{}]", arr.r#type(), arr.val(), size),
+    }
+}
+
+/// the variables of `vars` sorted by name, the symbol maps do not keep the declaration order.
+fn sorted<'v>(vars: impl IntoIterator<Item = &'v Var>) -> Vec<&'v Var> {
+    let mut vars = vars.into_iter().collect::<Vec<_>>();
+    vars.sort_unstable_by(|a, b| a.name().cmp(b.name()));
+    vars
+}
+
+/// collects the names of the methods called in what it visits.
+#[derive(Default)]
+struct Callees(BTreeSet<String>);
+
+impl Visitor for Callees {
+    fn visit_call(&mut self, call: &Call) {
+        if let Call::Decaf { name, .. } = call {
+            self.0.insert(name.clone());
+        }
+        walk_call(self, call)
+    }
+}
+
+/// the methods of `root` ordered so every method comes after the methods it calls, as a method
+/// has to be declared before it is called, the methods that do not depend on each other are
+/// ordered by name.
+fn declaration_order(root: &Root) -> Vec<&Function> {
+    let mut pending = root
+        .functions
+        .iter()
+        .map(|(name, func)| {
+            let mut callees = Callees::default();
+            callees.visit_block(&func.body);
+            callees.0.remove(name);
+            (name.as_str(), callees.0)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let mut order = vec![];
+    while !pending.is_empty() {
+        // a method that calls a method that is not declared can not be ordered, it is printed
+        // once the others are
+        let name = pending
+            .iter()
+            .find(|(_, callees)| {
+                callees
+                    .iter()
+                    .all(|callee| !pending.contains_key(callee.as_str()))
+            })
+            .or_else(|| pending.iter().next())
+            .map(|(name, _)| *name)
+            .unwrap();
+        pending.remove(name);
+        order.push(&root.functions[name]);
+    }
+    order
+}
+
+struct Printer {
+    out: String,
+    depth: usize,
+    options: PrettyOptions,
+}
+
+impl Printer {
+    fn line(&mut self, text: &str) {
+        if !text.is_empty() {
+            let indent = self.depth * self.options.indent;
+            self.out.push_str(&format!("{:indent$}{}", "", text));
+        }
+        self.out.push('\n');
+    }
+
+    fn fits(&self, text: &str) -> bool {
+        self.depth * self.options.indent + text.len() <= self.options.width
+    }
+
+    /// prints `prefix`, `expr` and `suffix` on one line if they fit in the width, otherwise the
+    /// arguments of a call are printed one per line and the operands of a binary expression
+    /// start the lines after the first with their operator.
+    fn wrapped(&mut self, prefix: &str, e: &Expr, suffix: &str) {
+        let flat = format!("{}{}{}", prefix, expr(e), suffix);
+        if self.fits(&flat) {
+            return self.line(&flat);
+        }
+        match e {
+            Expr::Call(call) if !call_args(call).is_empty() => {
+                self.line(&format!("{}{}(", prefix, call_name(call)));
+                self.depth += 1;
+                let args = call_args(call);
+                let last = args.len() - 1;
+                args.iter().enumerate().for_each(|(i, arg)| {
+                    self.line(&format!("{}{}", arg, if i == last { "" } else { "," }))
+                });
+                self.depth -= 1;
+                self.line(&format!("){}", suffix));
+            }
+            e if binary(e).is_some() => {
+                // the left operands of the same precedence are continued on the same line
+                let prec = precedence(e);
+                let mut operands = vec![];
+                let mut lhs = e;
+                while let Some((op, l, r)) = binary(lhs).filter(|_| precedence(lhs) == prec) {
+                    operands.push(format!("{} {}", op, operand(r, operand_precedences(lhs).1)));
+                    lhs = l;
+                }
+                self.line(&format!(
+                    "{}{}",
+                    prefix,
+                    operand(lhs, operand_precedences(e).0)
+                ));
+                self.depth += 1;
+                let last = operands.len() - 1;
+                operands.iter().rev().enumerate().for_each(|(i, operand)| {
+                    self.line(&format!(
+                        "{}{}",
+                        operand,
+                        if i == last { suffix } else { "" }
+                    ))
+                });
+                self.depth -= 1;
+            }
+            _ => self.line(&flat),
+        }
+    }
+
+    /// prints the contents of the block and its closing `}`, the `{` is printed by the caller.
+    fn block(&mut self, block: &Block) {
+        self.depth += 1;
+        sorted(block.decls.values())
+            .iter()
+            .for_each(|decl| self.line(&format!("{};", var(decl))));
+        block.stmts.iter().for_each(|stmt| self.stmt(stmt));
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Assign(a) => self.wrapped(&format!("{} = ", location(&a.lhs)), &a.rhs, ";"),
+            Stmt::Expr(e) => self.wrapped("", e, ";"),
+            Stmt::Return(_, Some(e)) => self.wrapped("return ", e, ";"),
+            Stmt::Return(_, None) => self.line("return;"),
+            Stmt::Break(_) => self.line("break;"),
+            Stmt::Continue(_) => self.line("continue;"),
+            Stmt::If(chain) => self.if_chain(&chain.arms, &chain.otherwise),
+            Stmt::While { cond, body, .. } => {
+                self.wrapped("while (", cond, ") {");
+                self.block(body);
+            }
+            Stmt::For {
+                init,
+                cond,
+                update,
+                body,
+                ..
+            } => {
+                self.line(&format!(
+                    "for ({} = {}; {}; {}) {{",
+                    location(&init.lhs),
+                    expr(&init.rhs),
+                    expr(cond),
+                    self::update(update)
+                ));
+                self.block(body);
+            }
+        }
+    }
+
+    /// prints the arms of a chain, each one nested in the `else` block of the one before it.
+    fn if_chain(&mut self, arms: &[IfArm], otherwise: &Block) {
+        let (arm, rest) = arms.split_first().unwrap();
+        self.wrapped("if (", &arm.cond, ") {");
+        self.block(&arm.body);
+        if rest.is_empty() && otherwise.decls.is_empty() && otherwise.stmts.is_empty() {
+            return;
+        }
+        // the `}` of the `if` block shares its line with the `else`
+        self.out.truncate(self.out.len() - 1);
+        self.out.push_str(" else {\n");
+        if rest.is_empty() {
+            self.block(otherwise);
+        } else {
+            self.depth += 1;
+            self.if_chain(rest, otherwise);
+            self.depth -= 1;
+            self.line("}");
+        }
+    }
+
+    fn function(&mut self, func: &Function) {
+        let params = func
+            .params
+            .iter()
+            .map(|name| var(&func.args[name]))
+            .collect::<Vec<_>>();
+        let flat = format!("{} {}({}) {{", ty(func.ret), func.name, params.join(", "));
+        if self.fits(&flat) || params.is_empty() {
+            self.line(&flat);
+        } else {
+            self.line(&format!("{} {}(", ty(func.ret), func.name));
+            self.depth += 1;
+            let last = params.len() - 1;
+            params.iter().enumerate().for_each(|(i, param)| {
+                self.line(&format!("{}{}", param, if i == last { "" } else { "," }))
+            });
+            self.depth -= 1;
+            self.line(") {");
+        }
+        self.block(&func.body);
+    }
+}
+
+/// prints `root` with the [default options](PrettyOptions::default).
+pub fn print(root: &Root) -> String {
+    print_with_options(root, PrettyOptions::default())
+}
+
+/// prints `root` as decaf source that parses back to the same program.
+///
+/// the imports and the fields come first sorted by name, then the methods, each preceded by an
+/// empty line, ordered so every method is declared before the methods that call it.
+pub fn print_with_options(root: &Root, options: PrettyOptions) -> String {
+    let mut stripped;
+    let root = match options.parens {
+        Parens::Minimal => {
+            stripped = root.clone();
+            StripParens.visit_root_mut(&mut stripped);
+            &stripped
+        }
+        Parens::AsWritten => root,
+    };
+    let mut printer = Printer {
+        out: String::new(),
+        depth: 0,
+        options,
+    };
+    let mut imports = root.imports.keys().collect::<Vec<_>>();
+    imports.sort_unstable();
+    imports
+        .iter()
+        .for_each(|import| printer.line(&format!("import {};", import)));
+    sorted(root.globals.values())
+        .iter()
+        .for_each(|global| printer.line(&format!("{};", var(global))));
+    declaration_order(root).iter().for_each(|func| {
+        if !printer.out.is_empty() {
+            printer.line("");
+        }
+        printer.function(func)
+    });
+    printer.out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CODE: &str = "import printf;\nint a[0x10];\nbool z;\n\
+                        int f(int x, bool b) { if (b) { while (x > 0) { x -= 1; } } \
+                        else { return -x; } return a[x % len(a)]; }\n\
+                        void main() { int i; for (i = 0; i < 3; i++) { printf(\"%d\\n\", f(i, true)); } }";
+
+    #[test]
+    fn for_update() {
+        let code = "void main() { int i, a[4]; for (i = 0; i < 9; i -= 2) { } \
+                    for (a[1] = 0; i < 9; a[1] += a[0] + 1) { } for (i = 0; i < 9; i--) { } }";
+        let out = print(code, PrettyOptions::default());
+        assert!(out.contains("for (i = 0; i < 9; i -= 2) {"));
+        assert!(out.contains("for (a[1] = 0; i < 9; a[1] += a[0] + 1) {"));
+        assert!(out.contains("for (i = 0; i < 9; i--) {"));
+        assert_eq!(print(&out, PrettyOptions::default()), out);
+    }
+
+    fn print(text: &str, options: PrettyOptions) -> String {
+        print_with_options(
+            &crate::semantic_check(text.as_bytes(), "test.dcf").unwrap(),
+            options,
+        )
+    }
+
+    #[test]
+    fn canonical() {
+        let out = print(CODE, PrettyOptions::default());
+        assert_eq!(
+            out,
+            "import printf;\n\
+             int a[16];\n\
+             bool z;\n\
+             \n\
+             int f(int x, bool b) {\n\
+            \x20   if (b) {\n\
+            \x20       while (x > 0) {\n\
+            \x20           x = x - 1;\n\
+            \x20       }\n\
+            \x20   } else {\n\
+            \x20       return -x;\n\
+            \x20   }\n\
+            \x20   return a[x % 16];\n\
+             }\n\
+             \n\
+             void main() {\n\
+            \x20   int i;\n\
+            \x20   for (i = 0; i < 3; i++) {\n\
+            \x20       printf(\"%d\\n\", f(i, true));\n\
+            \x20   }\n\
+             }\n"
+        );
+        assert_eq!(print(&out, PrettyOptions::default()), out);
+    }
+
+    #[test]
+    fn round_trip() {
+        let code = "bool g(int a, int b) { return a - (b - 1) == -(-a) ? !(a < b) : a * (b + 2) > 0; }\n\
+                    int f(int x) { if (g(x, 1) && (g(1, x) || x != 0)) { return f(x - 1); } return 0x10 - 'a'; }\n\
+                    void main() { int i; f(1); i = i * 2 / 3 % 4; }";
+        let once = print(code, PrettyOptions::default());
+        assert!(once.contains("return a - (b - 1) == -(-a) ? !(a < b) : a * (b + 2) > 0;"));
+        assert!(once.contains("if (g(x, 1) && (g(1, x) || x != 0)) {"));
+        // `g` comes before `f` since `f` calls it
+        assert!(once.find("bool g(").unwrap() < once.find("int f(").unwrap());
+        assert_eq!(print(&once, PrettyOptions::default()), once);
+    }
+
+    #[test]
+    fn parens_as_written() {
+        let code = "bool g(int a, int b) { return ((a * b) + 1) > (a - (b - 1)) && (a < b); }\n\
+                    void main() { if ((g(1, 2))) { } while (g(1, -(2))) { } }";
+        let options = PrettyOptions {
+            parens: Parens::AsWritten,
+            ..PrettyOptions::default()
+        };
+        let written = print(code, options);
+        assert!(written.contains("return ((a * b) + 1) > (a - (b - 1)) && (a < b);"));
+        // the parentheses of the statement are not doubled
+        assert!(written.contains("if ((g(1, 2))) {"));
+        assert!(written.contains("while (g(1, -(2))) {"));
+        assert_eq!(print(&written, options), written);
+
+        let minimal = print(code, PrettyOptions::default());
+        assert!(minimal.contains("return a * b + 1 > a - (b - 1) && a < b;"));
+        assert!(minimal.contains("if (g(1, 2)) {"));
+        assert!(minimal.contains("while (g(1, -2)) {"));
+    }
+
+    #[test]
+    fn if_chain() {
+        let code = "void main() { int x; if (x < 0) { x = 0; } else { if (x > 9) { x = 9; } } }";
+        let out = print(code, PrettyOptions::default());
+        assert!(out.contains(
+            "    if (x < 0) {\n\
+            \x20       x = 0;\n\
+            \x20   } else {\n\
+            \x20       if (x > 9) {\n\
+            \x20           x = 9;\n\
+            \x20       }\n\
+            \x20   }\n"
+        ));
+        assert_eq!(print(&out, PrettyOptions::default()), out);
+    }
+
+    #[test]
+    fn indent() {
+        let out = print(
+            "void main() { if (true) { return; } }",
+            PrettyOptions {
+                indent: 2,
+                ..PrettyOptions::default()
+            },
+        );
+        assert_eq!(out, "void main() {\n  if (true) {\n    return;\n  }\n}\n");
+    }
+
+    #[test]
+    fn width() {
+        let options = PrettyOptions {
+            width: 30,
+            ..PrettyOptions::default()
+        };
+        let code = "import printf;\nvoid main() { int count, total; \
+                    printf(\"%d %d\\n\", count, total + count); \
+                    total = count * 1000 + total * 100 - count; }";
+        let out = print(code, options);
+        assert_eq!(
+            out,
+            "import printf;\n\
+             \n\
+             void main() {\n\
+            \x20   int count;\n\
+            \x20   int total;\n\
+            \x20   printf(\n\
+            \x20       \"%d %d\\n\",\n\
+            \x20       count,\n\
+            \x20       total + count\n\
+            \x20   );\n\
+            \x20   total = count * 1000\n\
+            \x20       + total * 100\n\
+            \x20       - count;\n\
+             }\n"
+        );
+        assert_eq!(print(&out, options), out);
+    }
+}
@@ -0,0 +1,109 @@
+//! the types of the expressions of a checked program, computed once when the program is checked
+//! so the later phases do not have to type the expressions again.
+
+use super::{visit::walk_expr, Expr, Root, Type, Visitor};
+use crate::span::HasSpan;
+
+/// the position of an expression in the order the [`Visitor`] walks the program, an expression
+/// comes right before the expressions nested in it.
+///
+/// the ids are only valid for the program as it was checked, changing the program with a
+/// [`super::VisitMut`] can move its expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExprId(pub(crate) usize);
+
+/// calls `f` on every expression with its id.
+struct EachExpr<F> {
+    next: usize,
+    f: F,
+}
+
+impl<F: FnMut(ExprId, &Expr)> Visitor for EachExpr<F> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        (self.f)(ExprId(self.next), expr);
+        self.next += 1;
+        walk_expr(self, expr)
+    }
+}
+
+impl Root {
+    /// the type of every expression of the root in the order of their ids.
+    pub(crate) fn expr_types(&self) -> Vec<Option<Type>> {
+        let mut types = vec![];
+        self.for_each_expr(|_, expr| {
+            types.push(match expr {
+                Expr::Call(call) => call.return_type(),
+                _ => Some(expr.r#type()),
+            })
+        });
+        types
+    }
+
+    /// calls `f` on every expression of the program with its id, in the order of the ids.
+    pub fn for_each_expr(&self, f: impl FnMut(ExprId, &Expr)) {
+        EachExpr { next: 0, f }.visit_root(self)
+    }
+
+    /// the type of the expression `id`, as it was computed when the program was checked, `None`
+    /// for a call of a `void` method used as a statement.
+    pub fn type_of(&self, id: ExprId) -> Option<Type> {
+        self.types[id.0]
+    }
+
+    /// the number of expressions of the program, the ids go from 0 to it.
+    pub fn expr_count(&self) -> usize {
+        self.types.len()
+    }
+
+    /// the innermost expression that contains the byte at `offset`, e.g. to show the type of the
+    /// expression under the cursor with [`Self::type_of`].
+    pub fn expr_at(&self, offset: usize) -> Option<ExprId> {
+        // the expressions nested in an expression come after it
+        let mut innermost = None;
+        self.for_each_expr(|id, expr| {
+            if expr.span().contains(offset) {
+                innermost = Some(id)
+            }
+        });
+        innermost
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn types() {
+        let text = "int a[2];\nbool f(int x) {\n  return x > a[0] + 1;\n}\n\
+                    void g() {}\nvoid main() {\n  f(len(a));\n  g();\n}\n";
+        let root = crate::semantic_check(text.as_bytes(), "types.dcf").unwrap();
+        let mut exprs = vec![];
+        root.for_each_expr(|id, expr| exprs.push((id, expr.span().position())));
+        assert_eq!(exprs.len(), root.expr_count());
+        let types = exprs
+            .iter()
+            .map(|(id, pos)| (*pos, root.type_of(*id)))
+            .collect::<Vec<_>>();
+        // the functions are walked in the order of their names, `f`, `g` then `main`
+        assert_eq!(
+            types,
+            [
+                ((3, 10), Some(Type::Bool)),
+                ((3, 10), Some(Type::Int)),
+                ((3, 14), Some(Type::Int)),
+                ((3, 14), Some(Type::Int)),
+                ((3, 16), Some(Type::Int)),
+                ((3, 21), Some(Type::Int)),
+                ((7, 3), Some(Type::Bool)),
+                ((7, 5), Some(Type::Int)),
+                ((8, 3), None),
+            ]
+        );
+        let at = |pattern: &str| root.expr_at(text.find(pattern).unwrap());
+        assert_eq!(at("a[0]").and_then(|id| root.type_of(id)), Some(Type::Int));
+        assert_eq!(at("> a").and_then(|id| root.type_of(id)), Some(Type::Bool));
+        assert_eq!(at("g();").map(|id| root.type_of(id)), Some(None));
+        assert_eq!(at("int x"), None);
+    }
+}
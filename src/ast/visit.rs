@@ -0,0 +1,366 @@
+//! traversals of the checked program.
+//!
+//! every `visit_*` method of [`Visitor`] and [`VisitMut`] walks into the children of its node by
+//! default, an analysis overrides the methods of the nodes it is interested in and calls the
+//! matching `walk_*` function to keep walking into their children.
+//!
+//! the globals, the methods and the variables of a scope are visited in the order of their names,
+//! so the traversal does not depend on the order of the symbol maps.
+
+use super::*;
+
+pub trait Visitor {
+    fn visit_root(&mut self, root: &Root) {
+        walk_root(self, root)
+    }
+    fn visit_import(&mut self, _name: &str, _span: SourceRange) {}
+    /// a global, a parameter or a local variable.
+    fn visit_var(&mut self, _var: &Var) {}
+    fn visit_function(&mut self, func: &Function) {
+        walk_function(self, func)
+    }
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block)
+    }
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt)
+    }
+    fn visit_assign(&mut self, assign: &Assign) {
+        walk_assign(self, assign)
+    }
+    fn visit_location(&mut self, loc: &Location) {
+        walk_location(self, loc)
+    }
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr)
+    }
+    fn visit_call(&mut self, call: &Call) {
+        walk_call(self, call)
+    }
+    fn visit_extern_arg(&mut self, arg: &ExternArg) {
+        walk_extern_arg(self, arg)
+    }
+}
+
+/// the values of `map` in the order of their keys.
+fn sorted<T>(map: &SymMap<T>) -> Vec<&T> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|(name, _)| *name);
+    entries.into_iter().map(|(_, val)| val).collect()
+}
+
+fn sorted_mut<T>(map: &mut SymMap<T>) -> Vec<&mut T> {
+    let mut entries = map.iter_mut().collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|(name, _)| *name);
+    entries.into_iter().map(|(_, val)| val).collect()
+}
+
+pub fn walk_root<V: Visitor + ?Sized>(v: &mut V, root: &Root) {
+    let mut imports = root.imports.iter().collect::<Vec<_>>();
+    imports.sort_unstable_by_key(|(name, _)| *name);
+    imports
+        .into_iter()
+        .for_each(|(name, span)| v.visit_import(name, *span));
+    sorted(&root.globals)
+        .into_iter()
+        .for_each(|var| v.visit_var(var));
+    sorted(&root.functions)
+        .into_iter()
+        .for_each(|func| v.visit_function(func));
+}
+
+pub fn walk_function<V: Visitor + ?Sized>(v: &mut V, func: &Function) {
+    sorted(&func.args)
+        .into_iter()
+        .for_each(|var| v.visit_var(var));
+    v.visit_block(&func.body);
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(v: &mut V, block: &Block) {
+    sorted(&block.decls)
+        .into_iter()
+        .for_each(|var| v.visit_var(var));
+    block.stmts.iter().for_each(|stmt| v.visit_stmt(stmt));
+}
+
+pub fn walk_stmt<V: Visitor + ?Sized>(v: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Assign(assign) => v.visit_assign(assign),
+        Stmt::Expr(expr) | Stmt::Return(_, Some(expr)) => v.visit_expr(expr),
+        Stmt::Return(_, None) | Stmt::Break(_) | Stmt::Continue(_) => {}
+        Stmt::If(chain) => {
+            chain.arms.iter().for_each(|arm| {
+                v.visit_expr(&arm.cond);
+                v.visit_block(&arm.body);
+            });
+            v.visit_block(&chain.otherwise);
+        }
+        Stmt::While { cond, body, .. } => {
+            v.visit_expr(cond);
+            v.visit_block(body);
+        }
+        Stmt::For {
+            init,
+            cond,
+            update,
+            body,
+            ..
+        } => {
+            v.visit_assign(init);
+            v.visit_expr(cond);
+            v.visit_assign(update);
+            v.visit_block(body);
+        }
+    }
+}
+
+pub fn walk_assign<V: Visitor + ?Sized>(v: &mut V, assign: &Assign) {
+    v.visit_location(&assign.lhs);
+    v.visit_expr(&assign.rhs);
+}
+
+pub fn walk_location<V: Visitor + ?Sized>(v: &mut V, loc: &Location) {
+    if let Location::Index { index, .. } = loc {
+        v.visit_expr(index);
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(v: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Len(..) | Expr::IntLiteral(..) | Expr::BoolLiteral(..) => {}
+        Expr::Not(_, e) | Expr::Neg(_, e) | Expr::Nested(_, e) => v.visit_expr(e),
+        Expr::Ter { cond, yes, no, .. } => {
+            v.visit_expr(cond);
+            v.visit_expr(yes);
+            v.visit_expr(no);
+        }
+        Expr::Call(call) => v.visit_call(call),
+        Expr::Loc(loc) => v.visit_location(loc),
+        Expr::Arith { lhs, rhs, .. }
+        | Expr::Rel { lhs, rhs, .. }
+        | Expr::Eq { lhs, rhs, .. }
+        | Expr::Cond { lhs, rhs, .. } => {
+            v.visit_expr(lhs);
+            v.visit_expr(rhs);
+        }
+    }
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(v: &mut V, call: &Call) {
+    match call {
+        Call::Extern { args, .. } => args.iter().for_each(|arg| v.visit_extern_arg(arg)),
+        Call::Decaf { args, .. } => args.iter().for_each(|arg| v.visit_expr(arg)),
+    }
+}
+
+pub fn walk_extern_arg<V: Visitor + ?Sized>(v: &mut V, arg: &ExternArg) {
+    if let ExternArg::Expr(expr) = arg {
+        v.visit_expr(expr);
+    }
+}
+
+/// same as [`Visitor`] but the nodes can be changed in place, e.g. to fold constants. the imports
+/// are a set, so they can only be read.
+pub trait VisitMut {
+    fn visit_root_mut(&mut self, root: &mut Root) {
+        walk_root_mut(self, root)
+    }
+    fn visit_import_mut(&mut self, _name: &str, _span: SourceRange) {}
+    fn visit_var_mut(&mut self, _var: &mut Var) {}
+    fn visit_function_mut(&mut self, func: &mut Function) {
+        walk_function_mut(self, func)
+    }
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block)
+    }
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt)
+    }
+    fn visit_assign_mut(&mut self, assign: &mut Assign) {
+        walk_assign_mut(self, assign)
+    }
+    fn visit_location_mut(&mut self, loc: &mut Location) {
+        walk_location_mut(self, loc)
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr)
+    }
+    fn visit_call_mut(&mut self, call: &mut Call) {
+        walk_call_mut(self, call)
+    }
+    fn visit_extern_arg_mut(&mut self, arg: &mut ExternArg) {
+        walk_extern_arg_mut(self, arg)
+    }
+}
+
+pub fn walk_root_mut<V: VisitMut + ?Sized>(v: &mut V, root: &mut Root) {
+    let mut imports = root.imports.iter().collect::<Vec<_>>();
+    imports.sort_unstable_by_key(|(name, _)| *name);
+    imports
+        .into_iter()
+        .for_each(|(name, span)| v.visit_import_mut(name, *span));
+    sorted_mut(&mut root.globals)
+        .into_iter()
+        .for_each(|var| v.visit_var_mut(var));
+    sorted_mut(&mut root.functions)
+        .into_iter()
+        .for_each(|func| v.visit_function_mut(func));
+}
+
+pub fn walk_function_mut<V: VisitMut + ?Sized>(v: &mut V, func: &mut Function) {
+    sorted_mut(&mut func.args)
+        .into_iter()
+        .for_each(|var| v.visit_var_mut(var));
+    v.visit_block_mut(&mut func.body);
+}
+
+pub fn walk_block_mut<V: VisitMut + ?Sized>(v: &mut V, block: &mut Block) {
+    sorted_mut(&mut block.decls)
+        .into_iter()
+        .for_each(|var| v.visit_var_mut(var));
+    block
+        .stmts
+        .iter_mut()
+        .for_each(|stmt| v.visit_stmt_mut(stmt));
+}
+
+pub fn walk_stmt_mut<V: VisitMut + ?Sized>(v: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Assign(assign) => v.visit_assign_mut(assign),
+        Stmt::Expr(expr) | Stmt::Return(_, Some(expr)) => v.visit_expr_mut(expr),
+        Stmt::Return(_, None) | Stmt::Break(_) | Stmt::Continue(_) => {}
+        Stmt::If(chain) => {
+            chain.arms.iter_mut().for_each(|arm| {
+                v.visit_expr_mut(&mut arm.cond);
+                v.visit_block_mut(&mut arm.body);
+            });
+            v.visit_block_mut(&mut chain.otherwise);
+        }
+        Stmt::While { cond, body, .. } => {
+            v.visit_expr_mut(cond);
+            v.visit_block_mut(body);
+        }
+        Stmt::For {
+            init,
+            cond,
+            update,
+            body,
+            ..
+        } => {
+            v.visit_assign_mut(init);
+            v.visit_expr_mut(cond);
+            v.visit_assign_mut(update);
+            v.visit_block_mut(body);
+        }
+    }
+}
+
+pub fn walk_assign_mut<V: VisitMut + ?Sized>(v: &mut V, assign: &mut Assign) {
+    v.visit_location_mut(&mut assign.lhs);
+    v.visit_expr_mut(&mut assign.rhs);
+}
+
+pub fn walk_location_mut<V: VisitMut + ?Sized>(v: &mut V, loc: &mut Location) {
+    if let Location::Index { index, .. } = loc {
+        v.visit_expr_mut(index);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitMut + ?Sized>(v: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Len(..) | Expr::IntLiteral(..) | Expr::BoolLiteral(..) => {}
+        Expr::Not(_, e) | Expr::Neg(_, e) | Expr::Nested(_, e) => v.visit_expr_mut(e),
+        Expr::Ter { cond, yes, no, .. } => {
+            v.visit_expr_mut(cond);
+            v.visit_expr_mut(yes);
+            v.visit_expr_mut(no);
+        }
+        Expr::Call(call) => v.visit_call_mut(call),
+        Expr::Loc(loc) => v.visit_location_mut(loc),
+        Expr::Arith { lhs, rhs, .. }
+        | Expr::Rel { lhs, rhs, .. }
+        | Expr::Eq { lhs, rhs, .. }
+        | Expr::Cond { lhs, rhs, .. } => {
+            v.visit_expr_mut(lhs);
+            v.visit_expr_mut(rhs);
+        }
+    }
+}
+
+pub fn walk_call_mut<V: VisitMut + ?Sized>(v: &mut V, call: &mut Call) {
+    match call {
+        Call::Extern { args, .. } => args.iter_mut().for_each(|arg| v.visit_extern_arg_mut(arg)),
+        Call::Decaf { args, .. } => args.iter_mut().for_each(|arg| v.visit_expr_mut(arg)),
+    }
+}
+
+pub fn walk_extern_arg_mut<V: VisitMut + ?Sized>(v: &mut V, arg: &mut ExternArg) {
+    if let ExternArg::Expr(expr) = arg {
+        v.visit_expr_mut(expr);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CODE: &[u8] = b"import printf;\nint g, a[3];\n\
+        int f(int x) { return -x + a[x]; }\n\
+        void main() {\n  int i;\n  for (i = 0; i < 3; i += 1) { a[i] = f(i) * 2; }\n  \
+        if (g > 0) { printf(\"%d\", g); }\n}\n";
+
+    #[derive(Default)]
+    struct Counter {
+        vars: Vec<String>,
+        calls: usize,
+        literals: Vec<i64>,
+    }
+
+    impl Visitor for Counter {
+        fn visit_var(&mut self, var: &Var) {
+            self.vars.push(var.name().to_string());
+        }
+        fn visit_call(&mut self, call: &Call) {
+            self.calls += 1;
+            walk_call(self, call)
+        }
+        fn visit_expr(&mut self, expr: &Expr) {
+            if let Expr::IntLiteral(_, n) = expr {
+                self.literals.push(*n);
+            }
+            walk_expr(self, expr)
+        }
+    }
+
+    #[test]
+    fn visitor() {
+        let root = crate::semantic_check(CODE, "visit.dcf").unwrap();
+        let mut counter = Counter::default();
+        counter.visit_root(&root);
+        assert_eq!(counter.vars, ["a", "g", "x", "i"]);
+        assert_eq!(counter.calls, 2);
+        // `i += 1` is checked as `i = i + 1`
+        assert_eq!(counter.literals, [0, 3, 1, 2, 0]);
+    }
+
+    /// doubles every int literal.
+    struct Double;
+
+    impl VisitMut for Double {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            if let Expr::IntLiteral(_, n) = expr {
+                *n *= 2;
+            }
+            walk_expr_mut(self, expr)
+        }
+    }
+
+    #[test]
+    fn visit_mut() {
+        let mut root = crate::semantic_check(CODE, "visit.dcf").unwrap();
+        Double.visit_root_mut(&mut root);
+        let mut counter = Counter::default();
+        counter.visit_root(&root);
+        assert_eq!(counter.literals, [0, 6, 2, 4, 0]);
+    }
+}
@@ -0,0 +1,123 @@
+use std::{fmt::Display, fs::read};
+
+use crate::*;
+use dcfrs::{
+    ast::Root,
+    error::{Diagnostic, ErrorSink, Phase, Severity},
+    hir::{lints, Interface, LintOptions},
+    lexer::*,
+    span::SpanSource,
+};
+
+/// runs all the phases and reports a summary of the results.
+pub struct Check;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub tokens: usize,
+    pub declarations: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    /// false if there are errors, or warnings when they are denied, or the parser stopped before
+    /// the end of the file.
+    pub passed: bool,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} tokens, {} declarations, {} errors, {} warnings: {}",
+            self.tokens,
+            self.declarations,
+            self.errors,
+            self.warnings,
+            if self.passed { "PASS" } else { "FAIL" }
+        )
+    }
+}
+
+/// checks `code` reporting the diagnostics of all the phases to `sink`, the calls to the imports
+/// are checked against the signatures of `interface`.
+fn check(
+    code: &SpanSource,
+    interface: &Interface,
+    options: LintOptions,
+    sink: &mut ErrorSink,
+) -> Summary {
+    let toks = tokens(code.source()).collect::<Vec<_>>();
+    sink.enter_phase(Phase::Parse);
+    // the parser reports the lexical errors and recovers their tokens to parse the rest of the file
+    let mut parser = dcfrs::parser::Parser::new(toks.iter().copied(), |e| sink.error(&e));
+    let proot = parser.doc_elems().collect::<dcfrs::cst::PRoot>();
+    let finished = parser.finised();
+    parser.skip_rest();
+    drop(parser);
+    let declarations = proot.imports.len() + proot.decls.len() + proot.funcs.len();
+    sink.enter_phase(Phase::Semantic);
+    lints(&proot, interface, options, sink);
+    if let Err(errs) = Root::from_proot(proot) {
+        errs.iter().for_each(|err| sink.error(err));
+    }
+    Summary {
+        // the end of file and the invalid tokens are not counted
+        tokens: toks
+            .iter()
+            .filter(|tok| !matches!(recover(**tok).get(), Token::ErrorToken | Token::Eof))
+            .count(),
+        declarations,
+        errors: sink.error_count(),
+        warnings: sink.warning_count(),
+        passed: finished && !sink.has_errors(),
+    }
+}
+
+impl Check {
+    /// same as [`App::run`], the warnings fail the check if `deny_warnings` is set.
+    pub fn run_with_options(
+        stderr: &mut dyn std::io::Write,
+        input_file: String,
+        options: SemanticOptions,
+    ) -> ExitStatus {
+        let interface = match semantics::interface(&input_file) {
+            Ok(interface) => interface,
+            Err((path, e)) => {
+                let diagnostic = Diagnostic::from_error(Severity::Error, &e);
+                write!(stderr, "{}", diagnostic.display(&path)).unwrap();
+                return ExitStatus::Fail;
+            }
+        };
+        let text = read(&input_file).unwrap();
+        let code = SpanSource::from_bytes(&text).with_name(&input_file);
+        let mut sink = ErrorSink::new();
+        sink.enter_source(&code);
+        let lint_options = LintOptions {
+            redundant_parens: options.redundant_parens,
+        };
+        let summary = check(&code, &interface, lint_options, &mut sink);
+        let summary = Summary {
+            passed: summary.passed && !(options.deny_warnings && summary.warnings != 0),
+            ..summary
+        };
+        sink.emit(stderr).unwrap();
+        writeln!(stderr, "{}: {}", input_file, summary).unwrap();
+        if summary.passed {
+            ExitStatus::Success
+        } else {
+            ExitStatus::Fail
+        }
+    }
+}
+
+impl App for Check {
+    fn run(
+        _stdout: &mut dyn std::io::Write,
+        stderr: &mut dyn std::io::Write,
+        input_file: String,
+    ) -> ExitStatus {
+        Self::run_with_options(stderr, input_file, SemanticOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod test;
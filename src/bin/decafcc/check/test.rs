@@ -0,0 +1,73 @@
+use super::*;
+
+macro_rules! summary {
+    ($summary:ident, $text:expr) => {
+        let code = SpanSource::new($text);
+        let mut sink = ErrorSink::new();
+        let $summary = check(
+            &code,
+            &Interface::default(),
+            LintOptions::default(),
+            &mut sink,
+        );
+    };
+}
+
+#[test]
+fn legal() {
+    summary!(
+        summary,
+        "import printf;\nint a[2];\nvoid main() {\n  int x;\n  printf(\"%d\", x);\n}\n"
+    );
+    assert_eq!(
+        summary,
+        Summary {
+            tokens: 25,
+            declarations: 3,
+            errors: 0,
+            warnings: 1,
+            passed: true,
+        }
+    );
+    assert_eq!(
+        summary.to_string(),
+        "25 tokens, 3 declarations, 0 errors, 1 warnings: PASS"
+    );
+}
+
+#[test]
+fn illegal() {
+    // a lexer error and an undeclared identifier
+    summary!(summary, "void main() {\n  y = true;\n}\n@\n");
+    assert_eq!(summary.tokens, 10);
+    assert_eq!(summary.errors, 2);
+    assert!(!summary.passed);
+    assert!(summary.to_string().ends_with(": FAIL"));
+}
+
+#[test]
+fn phases() {
+    use dcfrs::error::Phase;
+    let code = SpanSource::new("void main() {\n  int x;\n  y = 1;\n  x = 1\n}\n@\n");
+    let mut sink = ErrorSink::new();
+    check(
+        &code,
+        &Interface::default(),
+        LintOptions::default(),
+        &mut sink,
+    );
+    let phases = sink
+        .diagnostics()
+        .iter()
+        .map(|diag| (diag.phase(), diag.position()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        phases,
+        [
+            // the lexical errors are reported by the parser in the order of the source
+            (Some(Phase::Parse), (4, 8)),
+            (Some(Phase::Lex), (6, 1)),
+            (Some(Phase::Semantic), (3, 3)),
+        ]
+    );
+}
@@ -0,0 +1,75 @@
+use std::fs::read;
+
+use crate::*;
+use dcfrs::{
+    error::{ErrorSink, Suggestion},
+    span::{rewrite, SpanSource},
+};
+
+/// applies the safe suggestions of the diagnostics and writes the fixed source.
+pub struct Fix;
+
+/// the safe suggestions of `diagnostics` ordered by their offsets, a suggestion that overlaps an
+/// earlier one is left out.
+fn safe_fixes(diagnostics: &[dcfrs::error::Diagnostic]) -> Vec<&Suggestion> {
+    let mut fixes = diagnostics
+        .iter()
+        .filter_map(|diag| diag.suggestion())
+        .filter(|suggestion| suggestion.safe)
+        .collect::<Vec<_>>();
+    fixes.sort_by_key(|fix| fix.offset);
+    let mut end = 0;
+    fixes.retain(|fix| {
+        let keep = fix.offset >= end;
+        if keep {
+            end = fix.offset + fix.len;
+        }
+        keep
+    });
+    fixes
+}
+
+impl App for Fix {
+    fn run(
+        stdout: &mut dyn std::io::Write,
+        stderr: &mut dyn std::io::Write,
+        input_file: String,
+    ) -> ExitStatus {
+        let text = read(&input_file).unwrap();
+        let code = SpanSource::from_bytes(&text);
+        // the offsets of the suggestions are in the source with invalid utf-8 replaced
+        let source = code.source().source().as_bytes();
+        let Err(diagnostics) = dcfrs::semantic_check(source, &input_file) else {
+            stdout.write_all(source).unwrap();
+            writeln!(stderr, "{}: applied 0 fixes", input_file).unwrap();
+            return ExitStatus::Success;
+        };
+        let fixes = safe_fixes(&diagnostics);
+        let edits = fixes
+            .iter()
+            .map(|fix| (fix.span(&code), fix.replacement.as_bytes()))
+            .collect::<Vec<_>>();
+        let fixed = rewrite(source, &edits).unwrap();
+        stdout.write_all(&fixed).unwrap();
+        let plural = if fixes.len() == 1 { "" } else { "es" };
+        writeln!(
+            stderr,
+            "{}: applied {} fix{}",
+            input_file,
+            fixes.len(),
+            plural
+        )
+        .unwrap();
+        // the diagnostics the fixes did not resolve
+        let mut sink = ErrorSink::new();
+        if let Err(remaining) = dcfrs::semantic_check(&fixed, &input_file) {
+            sink.extend(remaining);
+        }
+        sink.emit(stderr).unwrap();
+        if sink.has_errors() {
+            ExitStatus::Fail
+        } else {
+            ExitStatus::Success
+        }
+    }
+}
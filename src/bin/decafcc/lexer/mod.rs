@@ -1,84 +1,288 @@
-use dcfrs::{error::*, lexer::tokens, span::SpanSource};
-use std::{fs, io::Read};
+use dcfrs::{
+    cst::json_string,
+    error::*,
+    lexer::{line_counts, tokens, tokens_until_error, tokens_with_trivia, Note, Token},
+    span::{by_position, SpanSource},
+};
+use std::{borrow::Cow, fs, io};
 
 use crate::{App, ExitStatus};
 
 pub struct Lexer;
 
+/// prints the number of lines of code, comment lines, blank lines and lines in total.
+pub struct LinesOfCode;
+
+/// how the tokens are printed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// the format expected by the grader, keywords are printed as their spelling.
+    #[default]
+    Grader,
+    /// same as `Grader` but keywords are prefixed with `KEYWORD`.
+    TaggedKeywords,
+    /// one json object per line for each token.
+    Json,
+    /// a header line and one line of comma separated values for each token.
+    Csv,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DumpOptions {
+    pub format: DumpFormat,
+    /// stop at the first lexer error.
+    pub fail_fast: bool,
+    /// print the tokens from the end of the file to its start, useful to debug the end of file.
+    pub reverse: bool,
+    /// end the `Json` format with the number of tokens of each category.
+    pub summary: bool,
+    /// lex `\r\n` and `\r` as `\n`, so files written on any platform dump the same lines.
+    pub normalize_line_endings: bool,
+    /// report the `/*` inside line comments as notes, they do not start a block comment.
+    pub notes: bool,
+    /// print the control bytes in the messages of the errors as `\xNN`, the invalid characters
+    /// are the only place they can appear in since they are not valid in any token.
+    pub escape_control: bool,
+}
+
+/// the categories the printed tokens are classified into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Keyword,
+    Symbol,
+    Identifier,
+    IntLiteral,
+    StringLiteral,
+    CharLiteral,
+    BoolLiteral,
+}
+
+impl Category {
+    const ALL: [Self; 7] = [
+        Self::Keyword,
+        Self::Symbol,
+        Self::Identifier,
+        Self::IntLiteral,
+        Self::StringLiteral,
+        Self::CharLiteral,
+        Self::BoolLiteral,
+    ];
+
+    /// `None` for the tokens that are not printed.
+    fn of(token: Token) -> Option<Self> {
+        use Token::*;
+        match token {
+            Identifier(_) => Some(Self::Identifier),
+            DecimalLiteral | HexLiteral => Some(Self::IntLiteral),
+            StringLiteral => Some(Self::StringLiteral),
+            CharLiteral(_) => Some(Self::CharLiteral),
+            True | False => Some(Self::BoolLiteral),
+            Space | LineComment | BlockComment | ErrorToken | Eof => None,
+            token if token.is_keyword() => Some(Self::Keyword),
+            _ => Some(Self::Symbol),
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Keyword => "KEYWORD",
+            Self::Symbol => "SYMBOL",
+            Self::Identifier => "IDENTIFIER",
+            Self::IntLiteral => "INTLITERAL",
+            Self::StringLiteral => "STRINGLITERAL",
+            Self::CharLiteral => "CHARLITERAL",
+            Self::BoolLiteral => "BOOLEANLITERAL",
+        }
+    }
+}
+
+/// quotes `text` as a csv field if it has to be.
+fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// replaces the control bytes of `text` with `\xNN`.
+fn escape_control(text: &str) -> Cow<'_, str> {
+    if !text.bytes().any(|b| b.is_ascii_control()) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.chars().fold(String::new(), |mut escaped, c| {
+        if c.is_ascii_control() {
+            escaped.push_str(&format!("\\x{:02x}", c as u8));
+        } else {
+            escaped.push(c);
+        }
+        escaped
+    }))
+}
+
+/// an error with the control bytes of its messages escaped, so they do not reach the terminal.
+struct Escaped<'e, E>(&'e E);
+
+impl<E: CCError> CCError for Escaped<'_, E> {
+    fn msgs(&self) -> Vec<(String, (usize, usize))> {
+        self.0
+            .msgs()
+            .into_iter()
+            .map(|(msg, pos)| (escape_control(&msg).into_owned(), pos))
+            .collect()
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.0.source_name()
+    }
+
+    fn suggestion(&self) -> Option<Suggestion> {
+        self.0.suggestion()
+    }
+}
+
+/// the source of `text` with its line endings normalized if the options ask for it.
+fn source(text: &[u8], options: DumpOptions) -> SpanSource<'_> {
+    let code = SpanSource::from_bytes(text);
+    if options.normalize_line_endings {
+        code.normalize_line_endings()
+    } else {
+        code
+    }
+}
+
+impl Lexer {
+    pub fn run_with_options(
+        stdout: &mut dyn io::Write,
+        stderr: &mut dyn io::Write,
+        input_file: String,
+        options: DumpOptions,
+    ) -> ExitStatus {
+        let buf = fs::read(&input_file).unwrap();
+        let code = source(&buf, options).with_name(&input_file);
+        let mut sink = ErrorSink::new();
+        sink.enter_source(&code);
+        sink.enter_phase(Phase::Lex);
+        dump(stdout, &code, options, &mut sink);
+        sink.emit(stderr).unwrap();
+        if !sink.has_errors() {
+            ExitStatus::Success
+        } else {
+            ExitStatus::Fail
+        }
+    }
+}
+
 impl App for Lexer {
     fn run(
-        stdout: &mut dyn std::io::Write,
-        stderr: &mut dyn std::io::Write,
+        stdout: &mut dyn io::Write,
+        stderr: &mut dyn io::Write,
         input_file: String,
     ) -> ExitStatus {
-        /// shadows std's `println` macro
-        macro_rules! println {
-            ($($arg:tt)*) => ({
-                writeln!(stdout, $($arg)*).unwrap();
-            });
-        }
+        Self::run_with_options(stdout, stderr, input_file, DumpOptions::default())
+    }
+}
+
+impl App for LinesOfCode {
+    fn run(
+        stdout: &mut dyn io::Write,
+        _stderr: &mut dyn io::Write,
+        input_file: String,
+    ) -> ExitStatus {
+        let buf = fs::read(&input_file).unwrap();
+        let code = SpanSource::from_bytes(&buf);
+        let counts = line_counts(code.source());
+        writeln!(
+            stdout,
+            "code {}\ncomment {}\nblank {}\ntotal {}",
+            counts.code, counts.comment, counts.blank, counts.total
+        )
+        .unwrap();
+        ExitStatus::Success
+    }
+}
 
-        let mut buf = String::new();
-        fs::File::open(&input_file)
-            .unwrap()
-            .read_to_string(&mut buf)
-            .unwrap();
-        let code = SpanSource::new(&buf);
-        let err_count = tokens(code.source())
-            .filter_map(|tok| {
-                use dcfrs::lexer::Token::*;
-                match tok.get() {
-                    Ok(Eof) => None,
-                    Ok(
-                        Semicolon | And | Or | EqualEqual | NotEqual | Greater | GreaterEqual
-                        | Less | LessEqual | Minus | Plus | Assign | SubAssign | AddAssign | Colon
-                        | Question | Comma | Void | For | Continue | Break | While | Int | Bool
-                        | If | Else | Return | Len | Star | Slash | Percent | Not | LeftParen
-                        | RightParen | CurlyLeft | CurlyRight | SquareLeft | SquareRight
-                        | Increment | Decrement | Import,
-                    ) => {
-                        println!("{} {}", tok.line(), tok.fragment());
-                        None
-                    }
-                    Ok(Identifier) => {
-                        println!(
-                            "{} IDENTIFIER {}",
-                            tok.line(),
-                            tok.fragment()
-                        );
-                        None
-                    }
-                    Ok(DecimalLiteral | HexLiteral) => {
-                        println!("{} INTLITERAL {}", tok.line(), tok.fragment());
-                        None
-                    }
-                    Ok(StringLiteral) => {
-                        println!("{} STRINGLITERAL {}", tok.line(), tok.fragment());
-                        None
-                    }
-                    Ok(CharLiteral(_)) => {
-                        println!("{} CHARLITERAL {}", tok.line(), tok.fragment());
-                        None
-                    }
-                    Ok(True | False) => {
-                        println!("{} BOOLEANLITERAL {}", tok.line(), tok.fragment());
-                        None
-                    }
-                    // errors are logged in the lexer module anyways
-                    Err(e) => {
-                        write!(stderr, "{}", &e.to_error(&input_file)).unwrap();
-                        Some(())
-                    }
-                    _ => unreachable!(),
+/// prints the tokens of `code` to `stdout` and reports the lexer errors to `sink`.
+fn dump(stdout: &mut dyn io::Write, code: &SpanSource, options: DumpOptions, sink: &mut ErrorSink) {
+    /// shadows std's `println` macro
+    macro_rules! println {
+        ($($arg:tt)*) => ({
+            writeln!(stdout, $($arg)*).unwrap();
+        });
+    }
+
+    // the notes are found in the comments the tokens are lexed with
+    let mut notes = vec![];
+    let toks: Box<dyn Iterator<Item = _>> = if options.fail_fast {
+        Box::new(tokens_until_error(code.source()))
+    } else if options.notes {
+        Box::new(tokens_with_trivia(code.source()).map(|t| {
+            notes.extend(t.leading.into_iter().filter_map(Note::of));
+            t.token
+        }))
+    } else {
+        Box::new(tokens(code.source()))
+    };
+    let toks = if options.reverse {
+        Box::new(toks.collect::<Vec<_>>().into_iter().rev())
+    } else {
+        toks
+    };
+    if options.format == DumpFormat::Csv {
+        println!("offset,line,column,category,text");
+    }
+    // the errors are reported in source order even if the tokens are not
+    let mut errors = vec![];
+    let mut counts = [0; Category::ALL.len()];
+    toks.for_each(|tok| match tok.get() {
+        Ok(token) => {
+            let Some(category) = Category::of(*token) else {
+                return;
+            };
+            counts[category as usize] += 1;
+            // the offset in the file, before the line endings are normalized
+            let offset = code.original_offset(tok.span().offset());
+            match (options.format, category) {
+                (DumpFormat::Json, _) => println!(
+                    "{{\"offset\":{},\"line\":{},\"column\":{},\"category\":\"{}\",\"text\":{}}}",
+                    offset,
+                    tok.line(),
+                    tok.column(),
+                    category.name(),
+                    json_string(tok.fragment())
+                ),
+                (DumpFormat::Csv, _) => println!(
+                    "{},{},{},{},{}",
+                    offset,
+                    tok.line(),
+                    tok.column(),
+                    category.name(),
+                    csv_field(tok.fragment())
+                ),
+                (DumpFormat::Grader, Category::Keyword) | (_, Category::Symbol) => {
+                    println!("{} {}", tok.line(), tok.fragment())
                 }
-            })
-            .count();
-        if err_count == 0 {
-            ExitStatus::Success
-        } else {
-            ExitStatus::Fail
+                _ => println!("{} {} {}", tok.line(), category.name(), tok.fragment()),
+            }
         }
+        Err(e) => errors.push(tok.span().into_spanned(*e)),
+    });
+    if options.format == DumpFormat::Json && options.summary {
+        let counts = Category::ALL
+            .iter()
+            .map(|category| format!("\"{}\":{}", category.name(), counts[*category as usize]))
+            .collect::<Vec<_>>();
+        println!("{{\"summary\":{{{}}}}}", counts.join(","));
     }
+    errors.sort_by(by_position);
+    errors.iter().for_each(|e| {
+        if options.escape_control {
+            sink.error(&Escaped(e.get()))
+        } else {
+            sink.error(e.get())
+        }
+    });
+    notes.iter().for_each(|note| sink.note(note));
 }
 
 #[cfg(test)]
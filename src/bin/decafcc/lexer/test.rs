@@ -145,3 +145,217 @@ mod hidden {
     });
     test_hidden!(variants);
 }
+
+mod format {
+    use crate::lexer::{dump, source, DumpFormat, DumpOptions};
+    use dcfrs::error::{ErrorSink, Severity};
+
+    fn dump_to_string(code: &str, options: DumpOptions, sink: &mut ErrorSink) -> String {
+        let code = source(code.as_bytes(), options);
+        let mut stdout = vec![];
+        dump(&mut stdout, &code, options, sink);
+        String::from_utf8(stdout).unwrap()
+    }
+
+    fn dump_format(code: &str, format: DumpFormat) -> String {
+        let mut sink = ErrorSink::new();
+        let options = DumpOptions {
+            format,
+            ..DumpOptions::default()
+        };
+        let out = dump_to_string(code, options, &mut sink);
+        assert!(!sink.has_errors());
+        out
+    }
+
+    #[test]
+    fn grader() {
+        assert_eq!(
+            dump_format("while x", DumpFormat::Grader),
+            "1 while\n1 IDENTIFIER x\n"
+        );
+    }
+
+    #[test]
+    fn tagged_keywords() {
+        assert_eq!(
+            dump_format("while x", DumpFormat::TaggedKeywords),
+            "1 KEYWORD while\n1 IDENTIFIER x\n"
+        );
+        assert_eq!(
+            dump_format("x += true", DumpFormat::TaggedKeywords),
+            "1 IDENTIFIER x\n1 +=\n1 BOOLEANLITERAL true\n"
+        );
+    }
+
+    #[test]
+    fn fail_fast() {
+        let code = "x\n@ y\n# z";
+        let mut sink = ErrorSink::new();
+        let options = DumpOptions {
+            fail_fast: true,
+            ..DumpOptions::default()
+        };
+        assert_eq!(dump_to_string(code, options, &mut sink), "1 IDENTIFIER x\n");
+        assert_eq!(sink.error_count(), 1);
+        assert_eq!(sink.diagnostics()[0].position(), (2, 1));
+
+        let mut sink = ErrorSink::new();
+        dump_to_string(code, DumpOptions::default(), &mut sink);
+        assert_eq!(sink.error_count(), 2);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut sink = ErrorSink::new();
+        let options = DumpOptions {
+            reverse: true,
+            ..DumpOptions::default()
+        };
+        assert_eq!(
+            dump_to_string("int x;\nx = 0x1;", options, &mut sink),
+            "2 ;\n2 INTLITERAL 0x1\n2 =\n2 IDENTIFIER x\n1 ;\n1 IDENTIFIER x\n1 int\n"
+        );
+        assert!(!sink.has_errors());
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            dump_format("if (c)\n  s = \"a\\\"b\";", DumpFormat::Json),
+            concat!(
+                "{\"offset\":0,\"line\":1,\"column\":1,\"category\":\"KEYWORD\",\"text\":\"if\"}\n",
+                "{\"offset\":3,\"line\":1,\"column\":4,\"category\":\"SYMBOL\",\"text\":\"(\"}\n",
+                "{\"offset\":4,\"line\":1,\"column\":5,\"category\":\"IDENTIFIER\",\"text\":\"c\"}\n",
+                "{\"offset\":5,\"line\":1,\"column\":6,\"category\":\"SYMBOL\",\"text\":\")\"}\n",
+                "{\"offset\":9,\"line\":2,\"column\":3,\"category\":\"IDENTIFIER\",\"text\":\"s\"}\n",
+                "{\"offset\":11,\"line\":2,\"column\":5,\"category\":\"SYMBOL\",\"text\":\"=\"}\n",
+                "{\"offset\":13,\"line\":2,\"column\":7,\"category\":\"STRINGLITERAL\",\"text\":\"\\\"a\\\\\\\"b\\\"\"}\n",
+                "{\"offset\":19,\"line\":2,\"column\":13,\"category\":\"SYMBOL\",\"text\":\";\"}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            dump_format("f(\"a,b\", 'c');", DumpFormat::Csv),
+            concat!(
+                "offset,line,column,category,text\n",
+                "0,1,1,IDENTIFIER,f\n",
+                "1,1,2,SYMBOL,(\n",
+                "2,1,3,STRINGLITERAL,\"\"\"a,b\"\"\"\n",
+                "7,1,8,SYMBOL,\",\"\n",
+                "9,1,10,CHARLITERAL,'c'\n",
+                "12,1,13,SYMBOL,)\n",
+                "13,1,14,SYMBOL,;\n",
+            )
+        );
+    }
+
+    #[test]
+    fn json_summary() {
+        let mut sink = ErrorSink::new();
+        let options = DumpOptions {
+            format: DumpFormat::Json,
+            summary: true,
+            ..DumpOptions::default()
+        };
+        let out = dump_to_string(
+            "import f;\nvoid main() { bool b; b = 'c' == 0x1 || true; f(\"x\"); }",
+            options,
+            &mut sink,
+        );
+        assert!(!sink.has_errors());
+        let (tokens, summary) = out.trim_end().rsplit_once('\n').unwrap();
+        assert_eq!(
+            summary,
+            concat!(
+                "{\"summary\":{\"KEYWORD\":3,\"SYMBOL\":13,\"IDENTIFIER\":5,\"INTLITERAL\":1,",
+                "\"STRINGLITERAL\":1,\"CHARLITERAL\":1,\"BOOLEANLITERAL\":1}}"
+            )
+        );
+        // the summary counts every emitted token once
+        assert_eq!(tokens.lines().count(), 3 + 13 + 5 + 1 + 1 + 1 + 1);
+        assert_eq!(tokens.matches("\"KEYWORD\"").count(), 3);
+        assert_eq!(tokens.matches("\"SYMBOL\"").count(), 13);
+    }
+
+    #[test]
+    fn escape_control() {
+        let code = "x = '\x01';\ny = '\t';";
+        let msgs = |escape_control| {
+            let mut sink = ErrorSink::new();
+            let options = DumpOptions {
+                escape_control,
+                ..DumpOptions::default()
+            };
+            let out = dump_to_string(code, options, &mut sink);
+            // the tokens are printed the same way
+            assert_eq!(out, "1 IDENTIFIER x\n1 =\n1 ;\n2 IDENTIFIER y\n2 =\n2 ;\n");
+            sink.diagnostics()
+                .iter()
+                .map(|diag| diag.msg().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            msgs(true),
+            [
+                "unexpected character: \\x01 after `=`",
+                "unexpected character: \\x09 after `=`"
+            ]
+        );
+        assert_eq!(
+            msgs(false),
+            [
+                "unexpected character: \x01 after `=`",
+                "unexpected character: \t after `=`"
+            ]
+        );
+    }
+
+    #[test]
+    fn line_endings() {
+        let dump = |code: &str, normalize_line_endings| {
+            let mut sink = ErrorSink::new();
+            let options = DumpOptions {
+                normalize_line_endings,
+                ..DumpOptions::default()
+            };
+            let out = dump_to_string(code, options, &mut sink);
+            assert!(!sink.has_errors());
+            out
+        };
+        let expected = "1 int\n1 IDENTIFIER x\n1 ;\n2 IDENTIFIER x\n2 =\n2 INTLITERAL 1\n2 ;\n";
+        let lf = "int x;\nx = 1;\n";
+        let crlf = "int x;\r\nx = 1;\r\n";
+        assert_eq!(dump(lf, false), expected);
+        assert_eq!(dump(crlf, false), expected);
+        assert_eq!(dump(crlf, true), expected);
+        // a lone `\r` only ends a line once normalized
+        let cr = "int x;\rx = 1;\r";
+        assert_eq!(dump(cr, false), expected.replace("2 ", "1 "));
+        assert_eq!(dump(cr, true), expected);
+    }
+
+    #[test]
+    fn notes() {
+        let code = "// foo /* bar\nx // */\n";
+        let mut sink = ErrorSink::new();
+        dump_to_string(code, DumpOptions::default(), &mut sink);
+        assert!(sink.diagnostics().is_empty());
+
+        let mut sink = ErrorSink::new();
+        let options = DumpOptions {
+            notes: true,
+            ..DumpOptions::default()
+        };
+        assert_eq!(dump_to_string(code, options, &mut sink), "2 IDENTIFIER x\n");
+        let notes = sink
+            .diagnostics()
+            .iter()
+            .map(|note| (note.severity(), note.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(notes, [(Severity::Note, (1, 8))]);
+    }
+}
@@ -1,11 +1,22 @@
-use std::io::stderr;
+use std::io::{stderr, stdout};
 
-use crate::{lexer::Lexer, parser::Parser, semantics::Semantics};
+use crate::{
+    check::Check,
+    fix::Fix,
+    lexer::{DumpFormat, DumpOptions, Lexer, LinesOfCode},
+    parser::{Parser, ParserOptions, TreeFormat},
+    semantics::{SemanticOptions, Semantics},
+};
 
+mod check;
+mod fix;
 mod lexer;
 mod parser;
 mod semantics;
 
+#[cfg(test)]
+mod test;
+
 trait App {
     fn run(
         stdout: &mut dyn std::io::Write,
@@ -26,12 +37,20 @@ enum Mode {
     Lexer,
     Parser,
     Semantics,
+    Check,
+    Grammar,
+    ErrorCodes,
+    LinesOfCode,
+    Fix,
 }
 
 struct Config {
     mode: Option<Mode>,
     input_file: Option<String>,
     output_file: Option<String>,
+    dump_options: DumpOptions,
+    semantic_options: SemanticOptions,
+    parser_options: ParserOptions,
     // stderr: Option<String>,
 }
 
@@ -41,6 +60,9 @@ impl Config {
             mode: None,
             input_file: None,
             output_file: None,
+            dump_options: DumpOptions::default(),
+            semantic_options: SemanticOptions::default(),
+            parser_options: ParserOptions::default(),
             // stderr: None,
         }
     }
@@ -53,71 +75,257 @@ impl Config {
             "parse" => Some(Mode::Parser),
             "semantics" => Some(Mode::Semantics),
             "semantic" => Some(Mode::Semantics),
+            "check" => Some(Mode::Check),
+            _ => None,
+        }
+    }
+
+    /// the mode that runs the pipeline up to and including the given phase.
+    fn get_stop_at<T: AsRef<str>>(phase: T) -> Option<Mode> {
+        match phase.as_ref() {
+            "lex" => Some(Mode::Lexer),
+            "parse" => Some(Mode::Parser),
+            "sema" => Some(Mode::Semantics),
             _ => None,
         }
     }
 
-    fn parse(args: impl Iterator<Item = String>) -> Self {
-        fn parse(mut config: Config, mut args: impl Iterator<Item = String>) -> Config {
+    /// whether the mode writes an artifact, such as the fixed source or a dump, that belongs on
+    /// stdout when there is no `-o`. the course modes keep writing to stderr.
+    fn writes_to_stdout(&self) -> bool {
+        matches!(
+            self.mode,
+            Some(Mode::Grammar | Mode::ErrorCodes | Mode::LinesOfCode | Mode::Fix)
+        ) || self.semantic_options.dump_symbols
+            || self.semantic_options.type_at.is_some()
+            || self.parser_options.dump_tree.is_some()
+    }
+
+    /// parses the arguments after the program name, a malformed argument is reported as a usage
+    /// error.
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        fn parse(
+            mut config: Config,
+            mut args: impl Iterator<Item = String>,
+        ) -> Result<Config, String> {
             let first_arg = args.next();
             if let Some(arg) = first_arg {
                 match arg.as_str() {
                     "-t" => {
-                        config.mode = Some(Config::get_mode(args.next().unwrap()).unwrap());
+                        let mode = args.next().ok_or("`-t` expects a mode")?;
+                        config.mode = Some(
+                            Config::get_mode(&mode)
+                                .ok_or_else(|| format!("unknown mode `{}`", mode))?,
+                        );
                         parse(config, args)
                     }
                     "-o" | "--output" => {
-                        config.output_file = Some(args.next().unwrap());
+                        config.output_file = Some(args.next().ok_or("`-o` expects a path")?);
+                        parse(config, args)
+                    }
+                    "--keep-going" => {
+                        config.semantic_options.keep_going = true;
+                        parse(config, args)
+                    }
+                    "--dump-symbols=json" => {
+                        config.mode = Some(Mode::Semantics);
+                        config.semantic_options.dump_symbols = true;
+                        parse(config, args)
+                    }
+                    "--deny-warnings" => {
+                        config.semantic_options.deny_warnings = true;
+                        parse(config, args)
+                    }
+                    "--redundant-parens" => {
+                        config.semantic_options.redundant_parens = true;
+                        parse(config, args)
+                    }
+                    "--tag-keywords" => {
+                        config.dump_options.format = DumpFormat::TaggedKeywords;
+                        parse(config, args)
+                    }
+                    "--json" => {
+                        config.dump_options.format = DumpFormat::Json;
+                        parse(config, args)
+                    }
+                    "--summary" => {
+                        config.dump_options.summary = true;
+                        parse(config, args)
+                    }
+                    "--fail-fast" => {
+                        config.dump_options.fail_fast = true;
+                        config.parser_options.limits.stop_after_first = true;
+                        parse(config, args)
+                    }
+                    s if s.starts_with("--max-errors=") => {
+                        let max_errors = s.trim_start_matches("--max-errors=");
+                        config.parser_options.limits.max_errors = match max_errors.parse() {
+                            Ok(0) | Err(_) => {
+                                return Err(format!(
+                                    "`--max-errors` expects a positive count, got `{}`",
+                                    max_errors
+                                ))
+                            }
+                            Ok(max_errors) => max_errors,
+                        };
+                        parse(config, args)
+                    }
+                    "--profile-parser" => {
+                        config.mode = Some(Mode::Parser);
+                        config.parser_options.profile = true;
+                        parse(config, args)
+                    }
+                    s if s.starts_with("--dump-tree=") => {
+                        config.mode = Some(Mode::Parser);
+                        let format = s.trim_start_matches("--dump-tree=");
+                        config.parser_options.dump_tree = match format {
+                            "sexpr" => Some(TreeFormat::SExpr),
+                            "json" => Some(TreeFormat::Json),
+                            format => return Err(format!("unknown tree format `{}`", format)),
+                        };
+                        parse(config, args)
+                    }
+                    "--explain-grammar" => {
+                        config.mode = Some(Mode::Grammar);
+                        parse(config, args)
+                    }
+                    "--explain-codes" => {
+                        config.mode = Some(Mode::ErrorCodes);
+                        parse(config, args)
+                    }
+                    "--fix" => {
+                        config.mode = Some(Mode::Fix);
+                        parse(config, args)
+                    }
+                    "--count-lines-of-code" => {
+                        config.mode = Some(Mode::LinesOfCode);
+                        parse(config, args)
+                    }
+                    "--normalize-newlines" => {
+                        config.dump_options.normalize_line_endings = true;
+                        parse(config, args)
+                    }
+                    "--notes" => {
+                        config.dump_options.notes = true;
+                        parse(config, args)
+                    }
+                    "--escape-control" => {
+                        config.dump_options.escape_control = true;
+                        parse(config, args)
+                    }
+                    "--reverse" => {
+                        config.dump_options.reverse = true;
+                        parse(config, args)
+                    }
+                    s if s.starts_with("--format=") => {
+                        config.dump_options.format = match s.trim_start_matches("--format=") {
+                            "json" => DumpFormat::Json,
+                            "csv" => DumpFormat::Csv,
+                            "text" => DumpFormat::Grader,
+                            format => return Err(format!("unknown format `{}`", format)),
+                        };
+                        parse(config, args)
+                    }
+                    s if s.starts_with("--type-at=") => {
+                        let offset = s.trim_start_matches("--type-at=");
+                        config.mode = Some(Mode::Semantics);
+                        config.semantic_options.type_at = Some(offset.parse().map_err(|_| {
+                            format!("`--type-at` expects a byte offset, got `{}`", offset)
+                        })?);
+                        parse(config, args)
+                    }
+                    s if s.starts_with("--stop-at=") => {
+                        let phase = s.trim_start_matches("--stop-at=");
+                        config.mode = Some(
+                            Config::get_stop_at(phase)
+                                .ok_or_else(|| format!("unknown phase `{}`", phase))?,
+                        );
                         parse(config, args)
                     }
                     s if !s.is_empty() => {
                         config.input_file = Some(s.to_string());
                         parse(config, args)
                     }
-                    _ => unimplemented!(),
+                    _ => Err("empty argument".to_string()),
                 }
             } else {
-                config
+                Ok(config)
             }
         }
         parse(Config::new(), args.skip(1))
     }
 }
 
+/// runs the app selected by the config's mode.
+fn run(
+    config: Config,
+    output_stream: &mut dyn std::io::Write,
+    stderr: &mut dyn std::io::Write,
+) -> ExitStatus {
+    let input_file = config.input_file.unwrap_or("/dev/stdin".to_string());
+    match config.mode {
+        Some(Mode::Lexer) => {
+            Lexer::run_with_options(output_stream, stderr, input_file, config.dump_options)
+        }
+        Some(Mode::Parser) => {
+            Parser::run_with_options(output_stream, stderr, input_file, config.parser_options)
+        }
+        Some(Mode::Semantics) => {
+            Semantics::run_with_options(output_stream, stderr, input_file, config.semantic_options)
+        }
+        Some(Mode::Check) => Check::run_with_options(stderr, input_file, config.semantic_options),
+        Some(Mode::LinesOfCode) => LinesOfCode::run(output_stream, stderr, input_file),
+        Some(Mode::Fix) => Fix::run(output_stream, stderr, input_file),
+        Some(Mode::Grammar) => {
+            write!(output_stream, "{}", dcfrs::parser::GRAMMAR).unwrap();
+            ExitStatus::Success
+        }
+        Some(Mode::ErrorCodes) => {
+            write!(output_stream, "{}", dcfrs::error::codes::reference()).unwrap();
+            ExitStatus::Success
+        }
+        None => {
+            println!("No mode specified");
+            ExitStatus::Fail
+        }
+    }
+}
+
 fn main() {
-    use std::{env::args, fs, io};
+    use std::{
+        env::args,
+        fs,
+        io::{self, Write},
+    };
 
-    let config = Config::parse(args());
+    let mut config = match Config::parse(args()) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("usage error: {}", message);
+            std::process::exit(1);
+        }
+    };
     eprintln!(
         "mode: {}",
         format!("{:?}", config.mode.unwrap()).to_lowercase()
     );
+    let default_stream: Box<dyn io::Write> = if config.writes_to_stdout() {
+        Box::new(stdout())
+    } else {
+        Box::new(stderr())
+    };
     let mut output_stream: Box<dyn io::Write> = config
         .output_file
+        .take()
         .map(|path| {
             Box::new(io::BufWriter::new(fs::File::create(path).unwrap())) as Box<dyn io::Write>
         })
-        .unwrap_or(Box::new(stderr()));
+        .unwrap_or(default_stream);
     let mut stderr = Box::new(stderr()) as Box<dyn io::Write>;
-    match config.mode {
-        Some(Mode::Lexer) => Lexer::run(
-            &mut output_stream,
-            &mut stderr,
-            config.input_file.unwrap_or("/dev/stdin".to_string()),
-        ),
-        Some(Mode::Parser) => Parser::run(
-            &mut output_stream,
-            &mut stderr,
-            config.input_file.unwrap_or("/dev/stdin".to_string()),
-        ),
-        Some(Mode::Semantics) => Semantics::run(
-            &mut output_stream,
-            &mut stderr,
-            config.input_file.unwrap_or("/dev/stdin".to_string()),
-        ),
-        None => {
-            println!("No mode specified");
-            ExitStatus::Fail
-        }
-    };
+    let exit_status = run(config, &mut output_stream, &mut stderr);
+    // `exit` does not run the destructors, so the output file is flushed before it
+    output_stream.flush().unwrap();
+    if exit_status == ExitStatus::Fail {
+        std::process::exit(1);
+    }
 }
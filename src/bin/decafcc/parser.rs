@@ -1,30 +1,90 @@
-use std::fs::read_to_string;
+use std::{collections::HashMap, fs::read};
 
 use crate::*;
-use dcfrs::{lexer::*, span::SpanSource, error::CCError};
+use dcfrs::{
+    cst::{to_json, to_sexpr, PRoot},
+    error::{ErrorSink, Phase},
+    lexer::*,
+    span::SpanSource,
+};
 
 #[cfg(test)]
 mod test;
 
 pub struct Parser;
 
-impl App for Parser {
-    fn run(
-        _stdout: &mut dyn std::io::Write,
+/// how the parse tree is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeFormat {
+    SExpr,
+    Json,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// print how many times each production was parsed.
+    pub profile: bool,
+    /// print the parse tree in this format, it is printed even if there are parse errors.
+    pub dump_tree: Option<TreeFormat>,
+    /// the limits on the errors the parser reports.
+    pub limits: dcfrs::parser::ParserOptions,
+}
+
+/// writes the number of times each production was parsed, the most parsed first.
+fn write_profile(
+    stdout: &mut dyn std::io::Write,
+    profile: &HashMap<&'static str, usize>,
+) -> std::io::Result<()> {
+    let mut counts = profile.iter().collect::<Vec<_>>();
+    counts.sort_by(|(lname, lcount), (rname, rcount)| rcount.cmp(lcount).then(lname.cmp(rname)));
+    counts
+        .iter()
+        .try_for_each(|(production, count)| writeln!(stdout, "{} {}", production, count))
+}
+
+impl Parser {
+    pub fn run_with_options(
+        stdout: &mut dyn std::io::Write,
         stderr: &mut dyn std::io::Write,
         input_file: String,
+        options: ParserOptions,
     ) -> ExitStatus {
-        let text = read_to_string(&input_file).unwrap();
-        let code = SpanSource::new(&text);
-        let mut parser =
-            dcfrs::parser::Parser::new(tokens(code.source()).map(|s| s.map(|t| t.unwrap())), |e| {
-                write!(stderr, "{}", e.to_error(&input_file)).unwrap();
-            });
-        parser.doc_elems().for_each(|_| {});
-        if parser.finised() && !parser.found_errors() {
+        let text = read(&input_file).unwrap();
+        let code = SpanSource::from_bytes(&text).with_name(&input_file);
+        let mut sink = ErrorSink::new();
+        sink.enter_source(&code);
+        sink.enter_phase(Phase::Parse);
+        let mut parser = dcfrs::parser::Parser::new(tokens(code.source()), |e| sink.error(&e))
+            .with_options(options.limits);
+        if options.profile {
+            parser = parser.with_profile();
+        }
+        let proot = parser.doc_elems().collect::<PRoot>();
+        let finished = parser.finised();
+        parser.skip_rest();
+        if let Some(profile) = parser.profile() {
+            write_profile(stdout, profile).unwrap();
+        }
+        match options.dump_tree {
+            Some(TreeFormat::SExpr) => writeln!(stdout, "{}", to_sexpr(&proot)).unwrap(),
+            Some(TreeFormat::Json) => writeln!(stdout, "{}", to_json(&proot)).unwrap(),
+            None => {}
+        }
+        sink.emit(stderr).unwrap();
+        if finished && !sink.has_errors() {
             ExitStatus::Success
         } else {
             ExitStatus::Fail
         }
     }
 }
+
+impl App for Parser {
+    fn run(
+        stdout: &mut dyn std::io::Write,
+        stderr: &mut dyn std::io::Write,
+        input_file: String,
+    ) -> ExitStatus {
+        Self::run_with_options(stdout, stderr, input_file, ParserOptions::default())
+    }
+}
@@ -1,6 +1,12 @@
 use crate::{parser::Parser, App, ExitStatus};
+use dcfrs::parser::check_file;
 use seq_macro::seq;
 
+// unlike the scanner corpus, the parser corpus has no expected outputs: a program is expected to
+// parse if it is in `legal/` and to fail if it is in `illegal/`, so the exit status is all there is
+// to compare against.
+
+/// a macro to generate tests for legal files, they also have to parse without recovering.
 macro_rules! test_legal {
     ($name:ident, $num:literal, $hidden:literal) => {
         #[test]
@@ -15,7 +21,10 @@ macro_rules! test_legal {
             assert_eq!(
                 Parser::run(&mut std::io::sink(), &mut std::io::sink(), test.to_string()),
                 ExitStatus::Success
-            )
+            );
+            let report = check_file(&std::fs::read(test).unwrap());
+            assert!(report.is_ok(), "{:?}", report);
+            assert_eq!(report.recovered, 0);
         }
     };
     ($name:ident, $num:literal) => {
@@ -23,6 +32,7 @@ macro_rules! test_legal {
     };
 }
 
+/// a macro to generate tests for illegal files.
 macro_rules! test_illegal {
     ($name:ident, $num:literal, $hidden:literal) => {
         #[test]
@@ -37,7 +47,9 @@ macro_rules! test_illegal {
             assert_eq!(
                 Parser::run(&mut std::io::sink(), &mut std::io::sink(), test.to_string()),
                 ExitStatus::Fail
-            )
+            );
+            let report = check_file(&std::fs::read(test).unwrap());
+            assert!(!report.is_ok(), "{:?}", report);
         }
     };
     ($name:ident, $num:literal) => {
@@ -1,36 +1,138 @@
 use super::App;
-use dcfrs::{ast::*, error::*, lexer::*, span::*};
+use dcfrs::{
+    ast::*,
+    cst::PRoot,
+    error::*,
+    hir::{lints, type_at, Hir, Interface, InterfaceError, LintOptions, ScopeTree},
+    lexer::*,
+    span::*,
+};
 
-use std::fs::read_to_string;
+use std::{fs::read, path::Path};
+
+mod symbols;
 
 pub struct Semantics;
 
-impl App for Semantics {
-    fn run(
-        _stdout: &mut dyn std::io::Write,
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SemanticOptions {
+    /// run the semantic checks even if there are parse errors.
+    pub keep_going: bool,
+    /// warn about the parentheses that do not change the precedence of an expression.
+    pub redundant_parens: bool,
+    /// print the symbols of the program as json instead of its tree.
+    pub dump_symbols: bool,
+    /// print the type of the expression at this byte offset instead of the tree.
+    pub type_at: Option<usize>,
+    /// fail if there are warnings, they do not change the exit status otherwise.
+    pub deny_warnings: bool,
+}
+
+/// parses `code`, `None` if there are parse errors unless `keep_going` is set.
+fn parse<'a>(
+    code: &'a SpanSource,
+    sink: &mut ErrorSink,
+    options: SemanticOptions,
+) -> Option<PRoot<'a>> {
+    sink.enter_phase(Phase::Parse);
+    let mut parser = dcfrs::parser::Parser::new(tokens(code.source()), |e| sink.error(&e));
+    let proot = parser.doc_elems().collect();
+    parser.skip_rest();
+    drop(parser);
+    (!sink.has_errors() || options.keep_going).then_some(proot)
+}
+
+/// runs the semantic checks on `proot`, the calls to the imports are checked against the
+/// signatures of `interface`.
+fn check(
+    proot: PRoot,
+    interface: &Interface,
+    sink: &mut ErrorSink,
+    options: SemanticOptions,
+) -> Option<Root> {
+    sink.enter_phase(Phase::Semantic);
+    let lint_options = LintOptions {
+        redundant_parens: options.redundant_parens,
+    };
+    let args_match = lints(&proot, interface, lint_options, sink);
+    Root::from_proot(proot)
+        .map_err(|errs| errs.iter().for_each(|err| sink.error(err)))
+        .ok()
+        .filter(|_| args_match)
+}
+
+/// the interface file next to `input_file`, with the same name and the `dcfi` extension. an
+/// empty interface if there is no such file.
+pub(crate) fn interface(
+    input_file: &str,
+) -> std::result::Result<Interface, (String, InterfaceError)> {
+    let path = Path::new(input_file).with_extension("dcfi");
+    match std::fs::read_to_string(&path) {
+        Ok(text) => Interface::parse(&text).map_err(|e| (path.display().to_string(), e)),
+        Err(_) => Ok(Interface::default()),
+    }
+}
+
+/// parses `code` and runs the semantic checks on it, the semantic checks are skipped if there are
+/// parse errors unless `keep_going` is set.
+#[cfg(test)]
+fn analyze(code: &SpanSource, sink: &mut ErrorSink, options: SemanticOptions) -> Option<Root> {
+    parse(code, sink, options).and_then(|proot| check(proot, &Interface::default(), sink, options))
+}
+
+impl Semantics {
+    pub fn run_with_options(
+        stdout: &mut dyn std::io::Write,
         stderr: &mut dyn std::io::Write,
         input_file: String,
+        options: SemanticOptions,
     ) -> crate::ExitStatus {
-        let text = read_to_string(&input_file).unwrap();
-        let code = SpanSource::new(&text);
-        let mut parser =
-            dcfrs::parser::Parser::new(tokens(code.source()).map(|s| s.map(|t| t.unwrap())), |e| {
-                write!(stderr, "{}", e.to_error(&input_file)).unwrap();
-            });
-        let proot = parser.doc_elems().collect();
-        let hirtree = Root::from_proot(proot);
-        match hirtree {
-            Ok(_) => {
-                println!("{hirtree:#?}");
-                crate::ExitStatus::Success
+        let interface = match interface(&input_file) {
+            Ok(interface) => interface,
+            Err((path, e)) => {
+                let diagnostic = Diagnostic::from_error(Severity::Error, &e);
+                write!(stderr, "{}", diagnostic.display(&path)).unwrap();
+                return crate::ExitStatus::Fail;
             }
-            Err(errs) => {
-                errs.into_iter()
-                    .try_for_each(|err| write!(stderr, "{}", err.to_error(&input_file)))
-                    .unwrap();
-                crate::ExitStatus::Fail
+        };
+        let text = read(&input_file).unwrap();
+        let code = SpanSource::from_bytes(&text).with_name(&input_file);
+        let mut sink = ErrorSink::new();
+        sink.enter_source(&code);
+        if let Some(proot) = parse(&code, &mut sink, options) {
+            // the symbols are only printed if the program is valid
+            match (
+                check(proot, &interface, &mut sink, options),
+                options.type_at,
+            ) {
+                (Some(root), _) if options.dump_symbols => {
+                    let hir = Hir::lower(&root);
+                    writeln!(stdout, "{}", symbols::symbols_json(&ScopeTree::new(&hir))).unwrap()
+                }
+                (Some(root), Some(offset)) => match type_at(&root, offset) {
+                    Some(ty) => writeln!(stdout, "{ty}").unwrap(),
+                    None => writeln!(stdout, "none").unwrap(),
+                },
+                (Some(hirtree), None) => write!(stdout, "{}", hirtree.display()).unwrap(),
+                (None, _) => {}
             }
         }
+        sink.emit(stderr).unwrap();
+        if sink.has_errors() || options.deny_warnings && sink.warning_count() != 0 {
+            crate::ExitStatus::Fail
+        } else {
+            crate::ExitStatus::Success
+        }
+    }
+}
+
+impl App for Semantics {
+    fn run(
+        stdout: &mut dyn std::io::Write,
+        stderr: &mut dyn std::io::Write,
+        input_file: String,
+    ) -> crate::ExitStatus {
+        Self::run_with_options(stdout, stderr, input_file, SemanticOptions::default())
     }
 }
 
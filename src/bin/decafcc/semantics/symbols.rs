@@ -0,0 +1,55 @@
+use dcfrs::{
+    cst::json_string,
+    hir::{Def, DefKind, ScopeKind, ScopeTree},
+};
+
+fn symbol_json(def: &Def) -> String {
+    let kind = match def.kind {
+        DefKind::Import => "import",
+        DefKind::Global => "field",
+        DefKind::Method => "method",
+        DefKind::Param => "parameter",
+        DefKind::Local => "local",
+    };
+    let r#type = match (def.ty, def.size) {
+        (Some(ty), Some(size)) => format!("{}[{}]", ty, size),
+        (Some(ty), None) => ty.to_string(),
+        (None, _) => "void".to_string(),
+    };
+    format!(
+        "{{\"name\":{},\"kind\":\"{}\",\"type\":\"{}\",\"line\":{},\"column\":{}}}",
+        json_string(&def.name),
+        kind,
+        r#type,
+        def.span.line(),
+        def.span.column()
+    )
+}
+
+/// the symbols of the scopes of `tree` as json, every scope refers to the scope it is nested in by
+/// its id and the global scope has the id 0.
+pub fn symbols_json(tree: &ScopeTree) -> String {
+    let hir = tree.hir();
+    let scopes = tree.scopes().map(|(id, scope)| {
+        let name = match scope.kind {
+            ScopeKind::Global => "global".to_string(),
+            ScopeKind::Params(method) => format!("method {}", hir.def(method).name),
+            ScopeKind::Block => "block".to_string(),
+        };
+        format!(
+            "{{\"id\":{},\"parent\":{},\"scope\":{},\"symbols\":[{}]}}",
+            id.index(),
+            scope
+                .parent
+                .map_or("null".to_string(), |parent| parent.index().to_string()),
+            json_string(&name),
+            scope
+                .symbols
+                .iter()
+                .map(|def| symbol_json(hir.def(*def)))
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    });
+    format!("{{\"scopes\":[{}]}}", scopes.collect::<Vec<_>>().join(","))
+}
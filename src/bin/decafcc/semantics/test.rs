@@ -2,6 +2,9 @@ use super::*;
 use crate::ExitStatus;
 use seq_macro::seq;
 
+// like the parser corpus, the semantics corpus has no expected outputs, the programs of `legal/`
+// have to pass the checks and those of `illegal/` have to be reported.
+
 /// a macro to generate tests for illegal files.
 macro_rules! test_illegal {
     ($name:ident, $num:literal, $hidden:literal, $prefix:literal) => {
@@ -16,10 +19,13 @@ macro_rules! test_illegal {
                 stringify!($num),
                 ".dcf"
             );
+            let mut stderr = vec![];
             assert_eq!(
-                Semantics::run(&mut std::io::sink(), &mut std::io::sink(), test.to_string()),
+                Semantics::run(&mut std::io::sink(), &mut stderr, test.to_string()),
                 ExitStatus::Fail
-            )
+            );
+            // the failure is reported, not only the exit status
+            assert!(!stderr.is_empty());
         }
     };
     ($name:ident, $num:literal) => {
@@ -27,6 +33,7 @@ macro_rules! test_illegal {
     };
 }
 
+/// a macro to generate tests for legal files.
 macro_rules! test_legal {
     ($name:ident, $num:literal) => {
         #[test]
@@ -36,10 +43,12 @@ macro_rules! test_legal {
                 stringify!($num),
                 ".dcf"
             );
-
+            let mut stderr = vec![];
             assert_eq!(
-                Semantics::run(&mut std::io::sink(), &mut std::io::sink(), test.to_string()),
-                ExitStatus::Success
+                Semantics::run(&mut std::io::sink(), &mut stderr, test.to_string()),
+                ExitStatus::Success,
+                "{}",
+                String::from_utf8_lossy(&stderr)
             )
         }
     };
@@ -115,3 +124,185 @@ seq!(N in 01..=02 {
 seq!(N in 01..=20 {
     test_legal!(legal_~N, N);
 });
+
+mod keep_going {
+    use crate::semantics::{analyze, SemanticOptions};
+    use dcfrs::{error::ErrorSink, span::SpanSource};
+
+    /// a missing `;` and an undeclared identifier
+    const CODE: &str = "void main() {\n  int x;\n  x = 1\n  y = 2;\n}";
+
+    #[test]
+    fn stops_after_parse_errors() {
+        let code = SpanSource::new(CODE);
+        let mut sink = ErrorSink::new();
+        assert!(analyze(&code, &mut sink, SemanticOptions::default()).is_none());
+        assert_eq!(sink.error_count(), 1);
+        assert_eq!(sink.diagnostics()[0].position(), (3, 8));
+    }
+
+    #[test]
+    fn reports_parse_and_semantic_errors() {
+        let code = SpanSource::new(CODE);
+        let mut sink = ErrorSink::new();
+        let options = SemanticOptions {
+            keep_going: true,
+            ..SemanticOptions::default()
+        };
+        assert!(analyze(&code, &mut sink, options).is_none());
+        let diagnostics = sink
+            .diagnostics()
+            .iter()
+            .map(|diag| (diag.msg(), diag.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                ("expected token: ;, found: identifier", (3, 8)),
+                ("Undeclared identifier `y`", (4, 3)),
+            ]
+        );
+    }
+}
+
+mod empty {
+    use crate::semantics::{analyze, parse, SemanticOptions};
+    use dcfrs::{error::ErrorSink, span::SpanSource};
+
+    #[test]
+    fn parses_but_has_no_main() {
+        let code = SpanSource::new("  \n");
+        let mut sink = ErrorSink::new();
+        assert!(parse(&code, &mut sink, SemanticOptions::default()).is_some());
+        assert!(!sink.has_errors());
+        assert!(analyze(&code, &mut sink, SemanticOptions::default()).is_none());
+        assert_eq!(sink.error_count(), 1);
+    }
+}
+
+mod style {
+    use crate::semantics::{analyze, SemanticOptions};
+    use dcfrs::{error::ErrorSink, span::SpanSource};
+
+    const CODE: &str = "void main() {\n  int x;\n  x = (1) + 2;\n}";
+
+    #[test]
+    fn redundant_parens_off_by_default() {
+        let code = SpanSource::new(CODE);
+        let mut sink = ErrorSink::new();
+        assert!(analyze(&code, &mut sink, SemanticOptions::default()).is_some());
+        assert_eq!(sink.warning_count(), 0);
+    }
+
+    #[test]
+    fn redundant_parens() {
+        let code = SpanSource::new(CODE);
+        let mut sink = ErrorSink::new();
+        let options = SemanticOptions {
+            redundant_parens: true,
+            ..SemanticOptions::default()
+        };
+        assert!(analyze(&code, &mut sink, options).is_some());
+        assert_eq!(sink.warning_count(), 1);
+        assert_eq!(sink.diagnostics()[0].msg(), "redundant parentheses: (1)");
+        assert_eq!(sink.diagnostics()[0].position(), (3, 7));
+    }
+}
+
+mod symbols {
+    use crate::semantics::symbols::symbols_json;
+    use dcfrs::hir::{Hir, ScopeTree};
+
+    #[test]
+    fn json() {
+        let code = "import printf;\nint a[2];\nvoid main() {\n  bool b;\n  if (b) {\n    int c;\n  }\n}\nint f(int x) {\n  return x;\n}\n";
+        let root = dcfrs::semantic_check(code.as_bytes(), "symbols.dcf").unwrap();
+        let hir = Hir::lower(&root);
+        let json = symbols_json(&ScopeTree::new(&hir));
+        // the methods are at the start of their declarations, the other symbols at their names
+        [
+            r#"{"id":0,"parent":null,"scope":"global","symbols":["#,
+            r#"{"name":"printf","kind":"import","type":"int","line":1,"column":8}"#,
+            r#"{"name":"a","kind":"field","type":"int[2]","line":2,"column":5}"#,
+            r#"{"name":"main","kind":"method","type":"void","line":3,"column":1}"#,
+            r#"{"name":"f","kind":"method","type":"int","line":9,"column":1}"#,
+            r#"{"id":1,"parent":0,"scope":"method f","symbols":[{"name":"x","kind":"parameter","type":"int","line":9,"column":11}]}"#,
+            r#"{"id":3,"parent":0,"scope":"method main","symbols":[]}"#,
+            r#"{"id":4,"parent":3,"scope":"block","symbols":[{"name":"b","kind":"local","type":"bool","line":4,"column":8}]}"#,
+            r#"{"id":5,"parent":4,"scope":"block","symbols":[{"name":"c","kind":"local","type":"int","line":6,"column":9}]}"#,
+        ]
+        .iter()
+        .for_each(|entry| assert!(json.contains(entry), "{entry} not in {json}"));
+        assert!(json.starts_with(r#"{"scopes":["#));
+    }
+}
+
+mod dump {
+    use crate::semantics::{SemanticOptions, Semantics};
+    use crate::ExitStatus;
+
+    #[test]
+    fn prints_the_checked_program() {
+        let path = std::env::temp_dir().join("decafcc-semantics-dump.dcf");
+        std::fs::write(&path, "int x;\nvoid main() {\n  x += 1;\n}\n").unwrap();
+        let mut stdout = vec![];
+        let exit_status = Semantics::run_with_options(
+            &mut stdout,
+            &mut std::io::sink(),
+            path.to_str().unwrap().to_string(),
+            SemanticOptions::default(),
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(exit_status, ExitStatus::Success);
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            "root\n  global int x\n  method void main\n    block\n      assign\n        \
+             store x: int\n        binary +: int\n          load x: int\n          int 1: int\n"
+        );
+    }
+}
+
+mod interface {
+    use crate::semantics::Semantics;
+    use crate::{App, ExitStatus};
+
+    /// checks `code` with the interface file `interface` next to it, returns the exit status and
+    /// the error output.
+    fn check(name: &str, code: &str, interface: &str) -> (ExitStatus, String) {
+        let path = std::env::temp_dir().join(format!("decafcc-interface-{name}.dcf"));
+        let interface_path = path.with_extension("dcfi");
+        std::fs::write(&path, code).unwrap();
+        std::fs::write(&interface_path, interface).unwrap();
+        let mut stderr = vec![];
+        let exit_status = Semantics::run(
+            &mut std::io::sink(),
+            &mut stderr,
+            path.to_str().unwrap().to_string(),
+        );
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&interface_path).unwrap();
+        (exit_status, String::from_utf8(stderr).unwrap())
+    }
+
+    const CODE: &str = "import printf;\nvoid main() {\n  printf(\"%d\\n\", 1);\n}\n";
+
+    #[test]
+    fn wrong_number_of_args() {
+        let (status, _) = check("accepted", CODE, "int printf(string, ...);\n");
+        assert_eq!(status, ExitStatus::Success);
+        let (status, stderr) = check("wrong", CODE, "int printf(string);\n");
+        assert_eq!(status, ExitStatus::Fail);
+        assert!(stderr.contains(":3:3: "), "{stderr}");
+        assert!(stderr.contains("wrong number of arguments"), "{stderr}");
+    }
+
+    #[test]
+    fn invalid_interface() {
+        let (status, stderr) = check("invalid", CODE, "int printf(string, ...)\n");
+        assert_eq!(status, ExitStatus::Fail);
+        assert!(
+            stderr.contains("decafcc-interface-invalid.dcfi:1:1: "),
+            "{stderr}"
+        );
+    }
+}
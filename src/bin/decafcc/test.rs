@@ -0,0 +1,219 @@
+use super::*;
+
+/// parses the given arguments as if they followed the program name
+fn config(args: &[&str]) -> Config {
+    Config::parse(
+        std::iter::once("decafcc")
+            .chain(args.iter().copied())
+            .map(String::from),
+    )
+    .unwrap()
+}
+
+/// the usage error for the given arguments
+fn usage_error(args: &[&str]) -> String {
+    Config::parse(
+        std::iter::once("decafcc")
+            .chain(args.iter().copied())
+            .map(String::from),
+    )
+    .err()
+    .unwrap()
+}
+
+#[test]
+fn usage_errors() {
+    assert_eq!(usage_error(&["--stop-at=foo"]), "unknown phase `foo`");
+    assert_eq!(usage_error(&["-t", "foo"]), "unknown mode `foo`");
+    assert_eq!(usage_error(&["--format=foo"]), "unknown format `foo`");
+    assert_eq!(
+        usage_error(&["--dump-tree=foo"]),
+        "unknown tree format `foo`"
+    );
+    assert!(usage_error(&["--max-errors=x"]).contains("`x`"));
+    assert!(usage_error(&["--max-errors=0"]).contains("`0`"));
+    assert!(usage_error(&["--type-at=x"]).contains("`x`"));
+    assert_eq!(usage_error(&["-o"]), "`-o` expects a path");
+}
+
+mod stop_at {
+    use super::*;
+
+    /// parses fine but uses an undeclared identifier
+    const CODE: &str = "void main() {\n  y = 1;\n}\n";
+
+    /// writes `CODE` to a file unique to the test and runs the driver on it with the given phase
+    fn run_stop_at(phase: &str) -> (ExitStatus, String) {
+        let path = std::env::temp_dir().join(format!("decafcc-stop-at-{phase}.dcf"));
+        std::fs::write(&path, CODE).unwrap();
+        let config = config(&[&format!("--stop-at={phase}"), path.to_str().unwrap()]);
+        let (mut stdout, mut stderr) = (vec![], vec![]);
+        let exit_status = run(config, &mut stdout, &mut stderr);
+        std::fs::remove_file(&path).unwrap();
+        (exit_status, String::from_utf8(stderr).unwrap())
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(config(&["--stop-at=lex"]).mode, Some(Mode::Lexer));
+        assert_eq!(config(&["--stop-at=parse"]).mode, Some(Mode::Parser));
+        assert_eq!(config(&["--stop-at=sema"]).mode, Some(Mode::Semantics));
+    }
+
+    #[test]
+    fn parse() {
+        let (exit_status, stderr) = run_stop_at("parse");
+        assert_eq!(exit_status, ExitStatus::Success);
+        assert!(!stderr.contains("Undeclared identifier"), "{}", stderr);
+    }
+
+    #[test]
+    fn sema() {
+        let (exit_status, stderr) = run_stop_at("sema");
+        assert_eq!(exit_status, ExitStatus::Fail);
+        assert!(stderr.contains("Undeclared identifier `y`"), "{}", stderr);
+    }
+}
+
+#[test]
+fn count_lines_of_code() {
+    let path = std::env::temp_dir().join("decafcc-count-lines-of-code.dcf");
+    std::fs::write(&path, "// header\n\nvoid main() {\n  /* a\n  b */\n}\n").unwrap();
+    let config = config(&["--count-lines-of-code", path.to_str().unwrap()]);
+    assert_eq!(config.mode, Some(Mode::LinesOfCode));
+    let (mut stdout, mut stderr) = (vec![], vec![]);
+    let exit_status = run(config, &mut stdout, &mut stderr);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(exit_status, ExitStatus::Success);
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        "code 2\ncomment 3\nblank 1\ntotal 6\n"
+    );
+}
+
+#[test]
+fn fix_missing_semicolon() {
+    let path = std::env::temp_dir().join("decafcc-fix.dcf");
+    std::fs::write(&path, "void main() {\n  int x\n  x = 1;\n}\n").unwrap();
+    let config = config(&["--fix", path.to_str().unwrap()]);
+    assert_eq!(config.mode, Some(Mode::Fix));
+    let (mut stdout, mut stderr) = (vec![], vec![]);
+    let exit_status = run(config, &mut stdout, &mut stderr);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(exit_status, ExitStatus::Success);
+    assert_eq!(
+        String::from_utf8(stdout.clone()).unwrap(),
+        "void main() {\n  int x;\n  x = 1;\n}\n"
+    );
+    assert!(dcfrs::semantic_check(&stdout, "fixed.dcf").is_ok());
+    let stderr = String::from_utf8(stderr).unwrap();
+    assert!(stderr.ends_with("applied 1 fix\n"), "{}", stderr);
+}
+
+#[test]
+fn type_at() {
+    let path = std::env::temp_dir().join("decafcc-type-at.dcf");
+    let code = "void main() {\n  int x;\n  x = 1;\n  if (x < 2) { }\n}\n";
+    std::fs::write(&path, code).unwrap();
+    let run_at = |at: &str| {
+        let offset = code.find(at).unwrap();
+        let config = config(&[&format!("--type-at={offset}"), path.to_str().unwrap()]);
+        assert_eq!(config.semantic_options.type_at, Some(offset));
+        let (mut stdout, mut stderr) = (vec![], vec![]);
+        assert_eq!(run(config, &mut stdout, &mut stderr), ExitStatus::Success);
+        String::from_utf8(stdout).unwrap()
+    };
+    let types = (run_at("1;"), run_at("< 2"), run_at("int"));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(types, ("int\n".into(), "bool\n".into(), "none\n".into()));
+}
+
+#[test]
+fn parse_after_lexer_errors() {
+    let path = std::env::temp_dir().join("decafcc-parse-after-lexer-errors.dcf");
+    std::fs::write(&path, "void main() {\n  @x = \"a\\q\";\n}\n").unwrap();
+    let config = config(&["-t", "parse", path.to_str().unwrap()]);
+    let (mut stdout, mut stderr) = (vec![], vec![]);
+    let exit_status = run(config, &mut stdout, &mut stderr);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(exit_status, ExitStatus::Fail);
+    let stderr = String::from_utf8(stderr).unwrap();
+    assert!(stderr.contains("unexpected character: @"), "{}", stderr);
+    assert!(stderr.contains("invalid escape sequence"), "{}", stderr);
+}
+
+#[test]
+fn dump_formats() {
+    let format = |arg| config(&[arg]).dump_options.format;
+    assert_eq!(format("--format=json"), DumpFormat::Json);
+    assert_eq!(format("--format=csv"), DumpFormat::Csv);
+    assert_eq!(format("--format=text"), DumpFormat::Grader);
+}
+
+#[test]
+fn dump_tree() {
+    let path = std::env::temp_dir().join("decafcc-dump-tree.dcf");
+    std::fs::write(&path, "void main() {\n  x = 1;\n}\n").unwrap();
+    let run_with = |arg: &str| {
+        let config = config(&[arg, path.to_str().unwrap()]);
+        assert_eq!(config.mode, Some(Mode::Parser));
+        let (mut stdout, mut stderr) = (vec![], vec![]);
+        assert_eq!(run(config, &mut stdout, &mut stderr), ExitStatus::Success);
+        String::from_utf8(stdout).unwrap()
+    };
+    let (sexpr, json) = (run_with("--dump-tree=sexpr"), run_with("--dump-tree=json"));
+    std::fs::remove_file(&path).unwrap();
+    assert!(sexpr.starts_with("(root\n  (function main void"), "{sexpr}");
+    assert!(json.starts_with(r#"{"kind":"root","children":["#), "{json}");
+}
+
+#[test]
+fn error_limit() {
+    let path = std::env::temp_dir().join("decafcc-error-limit.dcf");
+    std::fs::write(&path, "void main() {\n  x = ;\n  y = ;\n  z = ;\n}\n").unwrap();
+    let errors_with = |args: &[&str]| {
+        let config = config(&[&["-t", "parse"], args, &[path.to_str().unwrap()]].concat());
+        let (mut stdout, mut stderr) = (vec![], vec![]);
+        assert_eq!(run(config, &mut stdout, &mut stderr), ExitStatus::Fail);
+        String::from_utf8(stderr).unwrap().lines().count()
+    };
+    let counts = [
+        errors_with(&[]),
+        errors_with(&["--max-errors=2"]),
+        errors_with(&["--fail-fast"]),
+    ];
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(counts, [3, 2, 1]);
+}
+
+#[test]
+fn deny_warnings() {
+    let path = std::env::temp_dir().join("decafcc-deny-warnings.dcf");
+    std::fs::write(&path, "void main() {\n  int x;\n  return;\n}\n").unwrap();
+    let status_with = |args: &[&str]| {
+        let config = config(&[args, &[path.to_str().unwrap()]].concat());
+        let (mut stdout, mut stderr) = (vec![], vec![]);
+        let status = run(config, &mut stdout, &mut stderr);
+        let warnings = String::from_utf8(stderr)
+            .unwrap()
+            .matches("warning[W0005]")
+            .count();
+        (status, warnings)
+    };
+    let statuses = [
+        status_with(&["-t", "semantics"]),
+        status_with(&["-t", "semantics", "--deny-warnings"]),
+        status_with(&["-t", "check"]),
+        status_with(&["-t", "check", "--deny-warnings"]),
+    ];
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        statuses,
+        [
+            (ExitStatus::Success, 1),
+            (ExitStatus::Fail, 1),
+            (ExitStatus::Success, 1),
+            (ExitStatus::Fail, 1),
+        ]
+    );
+}
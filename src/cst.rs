@@ -2,6 +2,11 @@ use std::fmt::Display;
 
 use crate::span::*;
 
+mod serialize;
+mod syntax;
+pub use serialize::{json_string, to_json, to_sexpr};
+pub use syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Type {
     Int,
@@ -136,13 +141,16 @@ impl<'a> TryFrom<Literal<'a>> for IntLiteral<'a> {
 pub struct Block<'a> {
     decls: Vec<PVar<'a>>,
     pub stmts: Vec<PStmt<'a>>,
+    /// from the `{` to the `}`, or the `;` of a method without a body.
+    pub span: Span<'a>,
 }
 
 impl<'a> Block<'a> {
-    pub fn new() -> Self {
+    pub fn new(span: Span<'a>) -> Self {
         Self {
             decls: Vec::new(),
             stmts: Vec::new(),
+            span,
         }
     }
     pub fn decls(&self) -> &[PVar<'a>] {
@@ -160,12 +168,6 @@ impl<'a> Block<'a> {
     }
 }
 
-impl<'a> Default for Block<'a> {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// a literal that can be used as an expression
 #[derive(Debug, Clone, Copy)]
 pub enum Literal<'a> {
@@ -213,6 +215,12 @@ pub enum Expr<'a> {
     },
     Call(Call<'a>),
     Loc(Location<'a>),
+    /// an index applied to an expression other than a variable, e.g. `f()[0]` or `a[i][j]`.
+    Index {
+        base: Box<Expr<'a>>,
+        offset: Box<Expr<'a>>,
+        span: Span<'a>,
+    },
     Literal {
         span: Span<'a>,
         value: Literal<'a>,
@@ -223,6 +231,8 @@ pub enum Expr<'a> {
         rhs: Box<Expr<'a>>,
         span: Span<'a>,
     },
+    /// a zero-width placeholder for an expression the parser expected but did not find.
+    Error(Span<'a>),
 }
 
 impl<'a> From<Spanned<'a, Literal<'a>>> for Expr<'a> {
@@ -285,6 +295,13 @@ impl<'a> Expr<'a> {
             span,
         }
     }
+    pub fn new_index(base: Expr<'a>, offset: Expr<'a>, span: Span<'a>) -> Self {
+        Self::Index {
+            base: Box::new(base),
+            offset: Box::new(offset),
+            span,
+        }
+    }
     pub fn literal(&self) -> Option<&Literal<'a>> {
         match self {
             Self::Literal { value, .. } => Some(value),
@@ -299,7 +316,9 @@ impl<'a> Expr<'a> {
             | Self::Literal { span, .. }
             | Self::Nested(span, _)
             | Self::Ter { span, .. }
-            | Self::BinOp { span, .. } => *span,
+            | Self::Index { span, .. }
+            | Self::BinOp { span, .. }
+            | Self::Error(span) => *span,
             Self::Call(call) => call.span(),
             Self::Loc(l) => l.span(),
         }
@@ -351,6 +370,8 @@ pub enum PStmt<'a> {
     },
     Break(Span<'a>),
     Continue(Span<'a>),
+    /// a statement the parser reported and skipped, covering the skipped source.
+    Error(Span<'a>),
 }
 
 impl<'a> From<Assign<'a>> for PStmt<'a> {
@@ -405,6 +426,7 @@ impl<'a> PStmt<'a> {
             Self::Return { span, .. } => *span,
             Self::Break(span) => *span,
             Self::Continue(span) => *span,
+            Self::Error(span) => *span,
         }
     }
 }
@@ -634,6 +656,15 @@ pub struct PRoot<'a> {
     pub imports: Vec<Import<'a>>,
     pub decls: Vec<PVar<'a>>,
     pub funcs: Vec<PFunction<'a>>,
+    span: Option<Span<'a>>,
+}
+
+impl<'a> PRoot<'a> {
+    /// the span from the first token of the program to its last one, `None` if the program is
+    /// empty.
+    pub fn span(&self) -> Option<Span<'a>> {
+        self.span
+    }
 }
 
 impl<'a> FromIterator<PDocElem<'a>> for PRoot<'a> {
@@ -643,8 +674,13 @@ impl<'a> FromIterator<PDocElem<'a>> for PRoot<'a> {
                 decls: vec![],
                 imports: vec![],
                 funcs: vec![],
+                span: None,
             },
             |mut root, elem| {
+                root.span = Some(
+                    root.span
+                        .map_or(elem.span(), |span| span.merge(elem.span())),
+                );
                 match elem {
                     PDocElem::Decl(decls, _) => root.decls.extend(decls),
                     PDocElem::Function(func) => root.funcs.push(func),
@@ -656,6 +692,59 @@ impl<'a> FromIterator<PDocElem<'a>> for PRoot<'a> {
     }
 }
 
+/// the innermost statement of `block` that contains the byte at `offset`.
+fn block_statement_at<'a>(block: &Block<'a>, offset: usize) -> Option<Span<'a>> {
+    block
+        .stmts()
+        .iter()
+        .find(|stmt| stmt.span().contains(offset))
+        .map(|stmt| {
+            let inner = match stmt {
+                PStmt::If { yes, no, .. } => block_statement_at(yes, offset)
+                    .or_else(|| no.as_ref().and_then(|no| block_statement_at(no, offset))),
+                PStmt::While { body, .. } | PStmt::For { body, .. } => {
+                    block_statement_at(body, offset)
+                }
+                _ => None,
+            };
+            inner.unwrap_or(stmt.span())
+        })
+}
+
+/// finds the innermost statement that contains the byte at `offset`, e.g. to select the statement
+/// under the cursor.
+pub fn statement_at<'a>(root: &PRoot<'a>, offset: usize) -> Option<Span<'a>> {
+    root.funcs
+        .iter()
+        .find(|func| func.span().contains(offset))
+        .and_then(|func| block_statement_at(&func.body, offset))
+}
+
+/// implements [`HasSpan`] with the `span` method of each node.
+macro_rules! has_span {
+    ($($node:ident),*) => {
+        $(
+            impl<'a> HasSpan for $node<'a> {
+                type Span = Span<'a>;
+
+                fn span(&self) -> Span<'a> {
+                    $node::span(self)
+                }
+            }
+        )*
+    };
+}
+
+has_span!(Import, Assign, IntLiteral, Expr, PStmt, PVar, Location, Call, PFunction, PBlockElem);
+
+impl<'a> HasSpan for Block<'a> {
+    type Span = Span<'a>;
+
+    fn span(&self) -> Span<'a> {
+        self.span
+    }
+}
+
 pub(super) mod checker {
 
     use super::*;
@@ -681,7 +770,7 @@ pub(super) mod checker {
     impl StmtChecker {
         pub fn check<'a, F: FnMut(Error<'a>)>(stmt: &PStmt<'a>, mut callback: F) {
             let mut check_nested_expr = |e: &Expr<'a>| {
-                if let Expr::Nested(..) = e {
+                if let Expr::Nested(..) | Expr::Error(..) = e {
                 } else {
                     callback(Error::WrapInParens(e.span()))
                 }
@@ -703,7 +792,8 @@ pub(super) mod checker {
                 | PStmt::Continue(..)
                 | PStmt::Break(..)
                 | PStmt::Call(..)
-                | PStmt::Assign(..) => {}
+                | PStmt::Assign(..)
+                | PStmt::Error(..) => {}
             }
         }
     }
@@ -799,3 +889,42 @@ pub(super) mod checker {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn statement_at_offset() {
+        let text = "int g;\nvoid main() {\n  g = 0;\n  while (g < 3) {\n    if (g > 1) { g += 2; }\n    g++;\n  }\n}";
+        let span_source = SpanSource::new(text);
+        let root: PRoot = crate::parse(&span_source).unwrap();
+        let at = |pat: &str| statement_at(&root, text.find(pat).unwrap()).map(|span| span.source());
+        assert_eq!(at("+= 2"), Some("g += 2"));
+        assert_eq!(at("if"), Some("if (g > 1) { g += 2; }"));
+        assert_eq!(at("++"), Some("g++"));
+        assert!(at("while").unwrap().starts_with("while (g < 3) {"));
+        assert_eq!(at("= 0"), Some("g = 0"));
+        // outside of any statement
+        assert_eq!(at("int g"), None);
+        assert_eq!(at("main"), None);
+    }
+
+    #[test]
+    fn program_and_method_spans() {
+        let text =
+            "\n// header\nimport f;\nint g;\nvoid a() {\n  g = 0;\n}\nvoid main() { a(); }\n";
+        let span_source = SpanSource::new(text);
+        let root: PRoot = crate::parse(&span_source).unwrap();
+        let span = root.span().unwrap();
+        assert!(span.source().starts_with("import f;"));
+        assert!(span.source().ends_with("void main() { a(); }"));
+        assert_eq!(span.offset() + span.len(), text.trim_end().len());
+        assert_eq!(root.funcs[0].span().source(), "void a() {\n  g = 0;\n}");
+        assert_eq!(root.funcs[1].span().source(), "void main() { a(); }");
+
+        let span_source = SpanSource::new(" \n");
+        let root: PRoot = crate::parse(&span_source).unwrap();
+        assert!(root.span().is_none());
+    }
+}
@@ -0,0 +1,313 @@
+//! structural dumps of the parse tree, as s-expressions or as json.
+//!
+//! only the structure and the text of the identifiers, the operators and the literals are dumped,
+//! the spans are left out so the output does not change when the source is reformatted.
+
+use super::*;
+use crate::unparse::op;
+
+/// a node of the dump: its kind, the text it holds and the nodes nested in it.
+struct Node {
+    kind: &'static str,
+    atoms: Vec<String>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(kind: &'static str) -> Self {
+        Self {
+            kind,
+            atoms: vec![],
+            children: vec![],
+        }
+    }
+
+    fn atom(mut self, atom: impl ToString) -> Self {
+        self.atoms.push(atom.to_string());
+        self
+    }
+
+    fn child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    fn write_sexpr(&self, depth: usize, out: &mut String) {
+        out.push('(');
+        out.push_str(self.kind);
+        self.atoms.iter().for_each(|atom| {
+            out.push(' ');
+            out.push_str(atom);
+        });
+        self.children.iter().for_each(|child| {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth + 1));
+            child.write_sexpr(depth + 1, out);
+        });
+        out.push(')');
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str(&format!("{{\"kind\":{}", json_string(self.kind)));
+        if !self.atoms.is_empty() {
+            let atoms = self.atoms.iter().map(|atom| json_string(atom));
+            out.push_str(&format!(
+                ",\"atoms\":[{}]",
+                atoms.collect::<Vec<_>>().join(",")
+            ));
+        }
+        if !self.children.is_empty() {
+            out.push_str(",\"children\":[");
+            self.children.iter().enumerate().for_each(|(i, child)| {
+                if i > 0 {
+                    out.push(',');
+                }
+                child.write_json(out);
+            });
+            out.push(']');
+        }
+        out.push('}');
+    }
+}
+
+/// quotes `text` as a json string.
+pub fn json_string(text: &str) -> String {
+    text.chars().fold(String::from('"'), |mut quoted, c| {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+        quoted
+    }) + "\""
+}
+
+fn var(var: &PVar) -> Node {
+    let node = Node::new("var")
+        .atom(var.r#type())
+        .atom(var.name().source());
+    match var {
+        // the span of a hex literal leaves out its `0x`
+        PVar::Array {
+            size: IntLiteral::Hex(digits),
+            ..
+        } => node.atom(format!("0x{}", digits.source())),
+        PVar::Array {
+            size: IntLiteral::Decimal(size),
+            ..
+        } => node.atom(size.source()),
+        PVar::Scalar { .. } => node,
+    }
+}
+
+fn function(func: &PFunction) -> Node {
+    Node::new("function")
+        .atom(func.name.source())
+        .atom(func.ret.map_or("void".to_string(), |ty| ty.to_string()))
+        .child(Node::new("params").children(func.args.iter().map(var)))
+        .child(block(&func.body))
+}
+
+fn block(block: &Block) -> Node {
+    Node::new("block")
+        .children(block.decls().iter().map(var))
+        .children(block.stmts().iter().map(stmt))
+}
+
+fn assign(assign: &Assign) -> Node {
+    let (op, value) = match &assign.op {
+        AssignExpr::Inc => ("++", None),
+        AssignExpr::Dec => ("--", None),
+        AssignExpr::Assign(e) => ("=", Some(e)),
+        AssignExpr::AddAssign(e) => ("+=", Some(e)),
+        AssignExpr::SubAssign(e) => ("-=", Some(e)),
+    };
+    Node::new("assign")
+        .atom(op)
+        .child(location(&assign.lhs))
+        .children(value.map(expr))
+}
+
+fn stmt(stmt: &PStmt) -> Node {
+    match stmt {
+        PStmt::Call(c) => call(c),
+        PStmt::Assign(a) => assign(a),
+        PStmt::If { cond, yes, no, .. } => Node::new("if")
+            .child(expr(cond))
+            .child(block(yes))
+            .children(no.as_ref().map(block)),
+        PStmt::While { cond, body, .. } => Node::new("while").child(expr(cond)).child(block(body)),
+        PStmt::For {
+            init,
+            cond,
+            update,
+            body,
+            ..
+        } => Node::new("for")
+            .child(assign(init))
+            .child(expr(cond))
+            .child(assign(update))
+            .child(block(body)),
+        PStmt::Return { expr: e, .. } => Node::new("return").children(e.as_ref().map(expr)),
+        PStmt::Break(_) => Node::new("break"),
+        PStmt::Continue(_) => Node::new("continue"),
+        PStmt::Error(_) => Node::new("error"),
+    }
+}
+
+fn call(call: &Call) -> Node {
+    Node::new("call")
+        .atom(call.name.source())
+        .children(call.args.iter().map(|arg| match arg {
+            Arg::Expr(e) => expr(e),
+            Arg::String(s) => Node::new("string").atom(s.source()),
+        }))
+}
+
+fn location(loc: &Location) -> Node {
+    let node = Node::new("loc").atom(loc.ident().source());
+    match loc {
+        Location::Scalar(_) => node,
+        Location::Index { offset, .. } => node.child(expr(offset)),
+    }
+}
+
+fn expr(e: &Expr) -> Node {
+    match e {
+        Expr::Len { id, .. } => Node::new("len").atom(id.source()),
+        Expr::Nested(_, e) => Node::new("nested").child(expr(e)),
+        Expr::Not(_, e) => Node::new("not").child(expr(e)),
+        Expr::Neg(_, e) => Node::new("neg").child(expr(e)),
+        Expr::Ter { cond, yes, no, .. } => Node::new("ternary")
+            .child(expr(cond))
+            .child(expr(yes))
+            .child(expr(no)),
+        Expr::Call(c) => call(c),
+        Expr::Loc(loc) => location(loc),
+        Expr::Index { base, offset, .. } => {
+            Node::new("index").child(expr(base)).child(expr(offset))
+        }
+        // the literals are dumped as they are written, e.g. `0x1F` or `'\n'`
+        Expr::Literal { span, .. } => Node::new("literal").atom(span.source()),
+        Expr::BinOp { op, lhs, rhs, .. } => Node::new("binop")
+            .atom(self::op(*op))
+            .child(expr(lhs))
+            .child(expr(rhs)),
+        Expr::Error(_) => Node::new("error"),
+    }
+}
+
+fn root(root: &PRoot) -> Node {
+    Node::new("root")
+        .children(
+            root.imports
+                .iter()
+                .map(|import| Node::new("import").atom(import.name().source())),
+        )
+        .children(root.decls.iter().map(var))
+        .children(root.funcs.iter().map(function))
+}
+
+/// the parse tree of `root` as an s-expression, every nested node on its own indented line, e.g.
+/// `(assign =\n  (loc x)\n  (literal 1))`.
+pub fn to_sexpr(root: &PRoot) -> String {
+    let mut out = String::new();
+    self::root(root).write_sexpr(0, &mut out);
+    out
+}
+
+/// the parse tree of `root` as a single line of json, every node is an object with its `kind`,
+/// and its `atoms` and `children` when it has any.
+pub fn to_json(root: &PRoot) -> String {
+    let mut out = String::new();
+    self::root(root).write_json(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{lexer::tokens, parser::Parser};
+
+    fn parse(text: &str, dump: fn(&PRoot) -> String) -> String {
+        let span_source = SpanSource::new(text);
+        let mut parser = Parser::new(tokens(span_source.source()), |_| {});
+        let root: PRoot = parser.doc_elems().collect();
+        dump(&root)
+    }
+
+    #[test]
+    fn sexpr() {
+        let code = "import printf;\nint a[0x4];\nvoid main() {\n  int i;\n  \
+                    for (i = 0; i < len(a); i++) {\n    a[i] = -(i * 2);\n  }\n  \
+                    printf(\"%d\\n\", a[0]);\n}\n";
+        let expected = r#"(root
+  (import printf)
+  (var int a 0x4)
+  (function main void
+    (params)
+    (block
+      (var int i)
+      (for
+        (assign =
+          (loc i)
+          (literal 0))
+        (binop <
+          (loc i)
+          (len a))
+        (assign ++
+          (loc i))
+        (block
+          (assign =
+            (loc a
+              (loc i))
+            (neg
+              (nested
+                (binop *
+                  (loc i)
+                  (literal 2)))))))
+      (call printf
+        (string "%d\n")
+        (loc a
+          (literal 0))))))"#;
+        assert_eq!(parse(code, to_sexpr), expected);
+    }
+
+    #[test]
+    fn json() {
+        let json = parse("bool f(int x) {\n  return x == '\\\"';\n}\n", to_json);
+        assert_eq!(
+            json,
+            r#"{"kind":"root","children":[{"kind":"function","atoms":["f","bool"],"children":[{"kind":"params","children":[{"kind":"var","atoms":["int","x"]}]},{"kind":"block","children":[{"kind":"return","children":[{"kind":"binop","atoms":["=="],"children":[{"kind":"loc","atoms":["x"]},{"kind":"literal","atoms":["'\\\"'"]}]}]}]}]}]}"#
+        );
+    }
+
+    #[test]
+    fn spans_are_left_out() {
+        let compact = "void main(){x=1;}";
+        let spread = "void main()\n{\n    x = 1; // one\n}\n";
+        assert_eq!(parse(compact, to_sexpr), parse(spread, to_sexpr));
+        assert_eq!(parse(compact, to_json), parse(spread, to_json));
+    }
+
+    #[test]
+    fn placeholders() {
+        let sexpr = parse("void main() {\n  x = !;\n  retrun;\n}\n", to_sexpr);
+        let expected = "(root
+  (function main void
+    (params)
+    (block
+      (assign =
+        (loc x)
+        (not
+          (error)))
+      (error))))";
+        assert_eq!(sexpr, expected);
+    }
+}
@@ -0,0 +1,740 @@
+//! a full-fidelity view of a parsed program, every byte of the source including the spaces and
+//! comments is in a token of the tree.
+//!
+//! the tree is split in two layers: the green nodes only know their kind, their length and their
+//! children, and the [`SyntaxNode`]s built on demand on top of them know their parent and their
+//! offset in the source, so a tool can walk from the token under the cursor up to the enclosing
+//! expression or statement.
+
+use std::{fmt, ops::Range, rc::Rc};
+
+use super::*;
+use crate::lexer::{recover, tokens_with_trivia, Token, TriviaPiece};
+
+/// the kinds of the nodes of the tree, the tokens are kinded by their [`Token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Root,
+    Import,
+    Function,
+    /// a declared variable, a global, a parameter or a local.
+    Var,
+    Block,
+    /// an assignment, also the initialization and the update of a `for` loop.
+    Assign,
+    If,
+    While,
+    For,
+    Return,
+    Break,
+    Continue,
+    /// a method call, as an expression or as a statement.
+    Call,
+    Location,
+    Len,
+    Nested,
+    Not,
+    Neg,
+    Ternary,
+    Index,
+    Literal,
+    BinOp,
+    /// source skipped by the parser after a malformed statement.
+    Error,
+}
+
+impl SyntaxKind {
+    pub const fn is_expr(self) -> bool {
+        matches!(
+            self,
+            Self::Call
+                | Self::Location
+                | Self::Len
+                | Self::Nested
+                | Self::Not
+                | Self::Neg
+                | Self::Ternary
+                | Self::Index
+                | Self::Literal
+                | Self::BinOp
+        )
+    }
+
+    pub const fn is_stmt(self) -> bool {
+        matches!(
+            self,
+            Self::Assign
+                | Self::If
+                | Self::While
+                | Self::For
+                | Self::Return
+                | Self::Break
+                | Self::Continue
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct GreenToken {
+    token: Token,
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+    fn len(&self) -> usize {
+        match self {
+            Self::Node(node) => node.len,
+            Self::Token(token) => token.text.len(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct GreenNode {
+    kind: SyntaxKind,
+    len: usize,
+    children: Vec<GreenElement>,
+    /// the offsets of the children from the start of the node, so a child is found without
+    /// adding up the lengths of the children before it.
+    offsets: Vec<usize>,
+}
+
+impl GreenNode {
+    fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let offsets = children
+            .iter()
+            .scan(0, |offset, child| {
+                let start = *offset;
+                *offset += child.len();
+                Some(start)
+            })
+            .collect();
+        Self {
+            kind,
+            len: children.iter().map(GreenElement::len).sum(),
+            children,
+            offsets,
+        }
+    }
+}
+
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    /// the index of the node among the children of its parent.
+    index: usize,
+    offset: usize,
+}
+
+/// a node of the tree with its position, cloning it is cheap.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+/// a token of the tree with its position, the spaces and comments are tokens too.
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            Self::Node(node) => node.range(),
+            Self::Token(token) => token.range(),
+        }
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        match self {
+            Self::Node(node) => node.parent(),
+            Self::Token(token) => Some(token.parent()),
+        }
+    }
+
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement> {
+        match self {
+            Self::Node(node) => node.next_sibling_or_token(),
+            Self::Token(token) => token.next_sibling_or_token(),
+        }
+    }
+
+    pub fn prev_sibling_or_token(&self) -> Option<SyntaxElement> {
+        match self {
+            Self::Node(node) => node.prev_sibling_or_token(),
+            Self::Token(token) => token.prev_sibling_or_token(),
+        }
+    }
+
+    pub fn into_node(self) -> Option<SyntaxNode> {
+        match self {
+            Self::Node(node) => Some(node),
+            Self::Token(_) => None,
+        }
+    }
+
+    pub fn into_token(self) -> Option<SyntaxToken> {
+        match self {
+            Self::Node(_) => None,
+            Self::Token(token) => Some(token),
+        }
+    }
+}
+
+impl SyntaxNode {
+    /// the tree of the program `root` parsed from `text`, the tokens of `text` the nodes of
+    /// `root` do not cover are given to their innermost enclosing node.
+    ///
+    /// the offsets of the tree are counted from the start of `text`.
+    pub fn new_root(text: Span, root: &PRoot) -> Self {
+        let base = text.offset();
+        let tokens = tokens_with_trivia(text)
+            .flat_map(|tok| {
+                let leading = tok.leading.into_iter().map(|piece| {
+                    let token = match piece {
+                        TriviaPiece::Space(_) => Token::Space,
+                        TriviaPiece::LineComment(_) => Token::LineComment,
+                        TriviaPiece::BlockComment(_) => Token::BlockComment,
+                    };
+                    (token, piece.span())
+                });
+                let token = recover(tok.token);
+                leading.chain(std::iter::once((*token.get(), token.span())))
+            })
+            .filter(|(token, _)| *token != Token::Eof)
+            .map(|(token, span)| (token, span.offset() - base, span.source()))
+            .collect::<Vec<_>>();
+        let mut ranges = Ranges::new(base);
+        ranges.root(root);
+        let mut ranges = ranges.ranges;
+        // the outer nodes come before the nodes they contain
+        ranges.sort_by_key(|(_, range)| (range.start, std::cmp::Reverse(range.end)));
+
+        let mut stack = vec![(SyntaxKind::Root, 0..text.len(), vec![])];
+        let mut ranges = ranges.into_iter().peekable();
+        for (token, offset, text) in tokens {
+            while stack.len() > 1 && stack.last().unwrap().1.end <= offset {
+                close(&mut stack);
+            }
+            while let Some((kind, range)) = ranges.next_if(|(_, range)| range.start <= offset) {
+                // a node that is not inside its parent can only come from a broken parse
+                if range.start == offset && range.end <= stack.last().unwrap().1.end {
+                    stack.push((kind, range, vec![]));
+                }
+            }
+            let token = Rc::new(GreenToken {
+                token,
+                text: text.to_string(),
+            });
+            stack.last_mut().unwrap().2.push(GreenElement::Token(token));
+        }
+        while stack.len() > 1 {
+            close(&mut stack);
+        }
+        let (kind, _, children) = stack.pop().unwrap();
+        Self(Rc::new(NodeData {
+            green: Rc::new(GreenNode::new(kind, children)),
+            parent: None,
+            index: 0,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    /// the byte range of the node in the source.
+    pub fn range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.len
+    }
+
+    /// the source of the node, including the spaces and comments inside it.
+    pub fn text(&self) -> String {
+        self.tokens()
+            .map(|token| token.green.text.clone())
+            .collect()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// the node and its ancestors up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    /// the child at `index`, `None` past the last child.
+    fn child(&self, index: usize) -> Option<SyntaxElement> {
+        let offset = self.0.offset + self.0.green.offsets.get(index)?;
+        Some(match &self.0.green.children[index] {
+            GreenElement::Node(green) => SyntaxElement::Node(Self(Rc::new(NodeData {
+                green: green.clone(),
+                parent: Some(self.clone()),
+                index,
+                offset,
+            }))),
+            GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                green: green.clone(),
+                parent: self.clone(),
+                index,
+                offset,
+            }),
+        })
+    }
+
+    /// the first child node at or after `index` if `forward`, else at or before it.
+    fn child_node(&self, index: usize, forward: bool) -> Option<SyntaxNode> {
+        let children = &self.0.green.children;
+        let is_node = |i: &usize| matches!(children[*i], GreenElement::Node(_));
+        let index = match forward {
+            true => (index..children.len()).find(is_node)?,
+            false => (0..=index).rev().find(is_node)?,
+        };
+        self.child(index).and_then(SyntaxElement::into_node)
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        (0..self.0.green.children.len()).filter_map(|index| self.child(index))
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement> {
+        self.parent()?.child(self.0.index + 1)
+    }
+
+    pub fn prev_sibling_or_token(&self) -> Option<SyntaxElement> {
+        self.parent()?.child(self.0.index.checked_sub(1)?)
+    }
+
+    pub fn next_sibling(&self) -> Option<SyntaxNode> {
+        self.parent()?.child_node(self.0.index + 1, true)
+    }
+
+    pub fn prev_sibling(&self) -> Option<SyntaxNode> {
+        self.parent()?
+            .child_node(self.0.index.checked_sub(1)?, false)
+    }
+
+    /// the node and all the nodes inside it, each node before its children.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let mut children = node.children().collect::<Vec<_>>();
+            children.reverse();
+            stack.extend(children);
+            Some(node)
+        })
+    }
+
+    /// all the tokens of the node in source order.
+    pub fn tokens(&self) -> impl Iterator<Item = SyntaxToken> {
+        let mut stack = vec![SyntaxElement::Node(self.clone())];
+        std::iter::from_fn(move || loop {
+            match stack.pop()? {
+                SyntaxElement::Token(token) => return Some(token),
+                SyntaxElement::Node(node) => {
+                    let mut children = node.children_with_tokens().collect::<Vec<_>>();
+                    children.reverse();
+                    stack.extend(children);
+                }
+            }
+        })
+    }
+
+    /// the token containing the byte at `offset`, `None` if `offset` is not inside the node.
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+        let child = self
+            .children_with_tokens()
+            .find(|child| child.range().contains(&offset))?;
+        match child {
+            SyntaxElement::Node(node) => node.token_at_offset(offset),
+            SyntaxElement::Token(token) => Some(token),
+        }
+    }
+
+    /// the innermost node containing the byte at `offset`, e.g. to walk from the cursor to the
+    /// enclosing expression with [`SyntaxNode::ancestors`].
+    pub fn node_at_offset(&self, offset: usize) -> Option<SyntaxNode> {
+        self.token_at_offset(offset).map(|token| token.parent())
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.range())
+    }
+}
+
+impl SyntaxToken {
+    pub fn token(&self) -> Token {
+        self.green.token
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.text.len()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.token(),
+            Token::Space | Token::LineComment | Token::BlockComment
+        )
+    }
+
+    pub fn next_sibling_or_token(&self) -> Option<SyntaxElement> {
+        self.parent.child(self.index + 1)
+    }
+
+    pub fn prev_sibling_or_token(&self) -> Option<SyntaxElement> {
+        self.parent.child(self.index.checked_sub(1)?)
+    }
+}
+
+impl PartialEq for SyntaxToken {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.green, &other.green) && self.offset == other.offset
+    }
+}
+
+impl Eq for SyntaxToken {}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?} {:?}", self.token(), self.range(), self.text())
+    }
+}
+
+type Stack = Vec<(SyntaxKind, Range<usize>, Vec<GreenElement>)>;
+
+/// closes the innermost open node and adds it to its parent.
+fn close(stack: &mut Stack) {
+    let (kind, _, children) = stack.pop().unwrap();
+    stack
+        .last_mut()
+        .unwrap()
+        .2
+        .push(GreenElement::Node(Rc::new(GreenNode::new(kind, children))));
+}
+
+/// collects the kinds and byte ranges of the nodes of a parsed program.
+struct Ranges {
+    base: usize,
+    ranges: Vec<(SyntaxKind, Range<usize>)>,
+}
+
+impl Ranges {
+    fn new(base: usize) -> Self {
+        Self {
+            base,
+            ranges: vec![],
+        }
+    }
+
+    fn push(&mut self, kind: SyntaxKind, span: Span) {
+        let start = span.offset() - self.base;
+        self.ranges.push((kind, start..start + span.len()));
+    }
+
+    fn root(&mut self, root: &PRoot) {
+        root.imports
+            .iter()
+            .for_each(|import| self.push(SyntaxKind::Import, import.span()));
+        root.decls.iter().for_each(|var| self.var(var));
+        root.funcs.iter().for_each(|func| {
+            self.push(SyntaxKind::Function, func.span());
+            func.args.iter().for_each(|var| self.var(var));
+            self.block(&func.body);
+        });
+    }
+
+    fn var(&mut self, var: &PVar) {
+        self.push(SyntaxKind::Var, var.span());
+    }
+
+    /// adds `block`, the `;` of a method without a body is not a block.
+    fn block(&mut self, block: &Block) {
+        if !block.span.starts_with("{") {
+            return;
+        }
+        self.push(SyntaxKind::Block, block.span);
+        block.decls().iter().for_each(|var| self.var(var));
+        block.stmts().iter().for_each(|stmt| self.stmt(stmt));
+    }
+
+    fn assign(&mut self, assign: &Assign) {
+        self.push(SyntaxKind::Assign, assign.span());
+        self.location(&assign.lhs);
+        if let AssignExpr::Assign(e) | AssignExpr::AddAssign(e) | AssignExpr::SubAssign(e) =
+            assign.op()
+        {
+            self.expr(e);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &PStmt) {
+        match stmt {
+            PStmt::Call(call) => self.call(call),
+            PStmt::Assign(assign) => self.assign(assign),
+            PStmt::If {
+                cond,
+                yes,
+                no,
+                span,
+            } => {
+                self.push(SyntaxKind::If, *span);
+                self.expr(cond);
+                self.block(yes);
+                if let Some(no) = no {
+                    self.block(no);
+                }
+            }
+            PStmt::While { cond, body, span } => {
+                self.push(SyntaxKind::While, *span);
+                self.expr(cond);
+                self.block(body);
+            }
+            PStmt::For {
+                init,
+                cond,
+                update,
+                body,
+                span,
+            } => {
+                self.push(SyntaxKind::For, *span);
+                self.assign(init);
+                self.expr(cond);
+                self.assign(update);
+                self.block(body);
+            }
+            PStmt::Return { expr, span } => {
+                self.push(SyntaxKind::Return, *span);
+                if let Some(expr) = expr {
+                    self.expr(expr);
+                }
+            }
+            PStmt::Break(span) => self.push(SyntaxKind::Break, *span),
+            PStmt::Continue(span) => self.push(SyntaxKind::Continue, *span),
+            PStmt::Error(span) => self.push(SyntaxKind::Error, *span),
+        }
+    }
+
+    fn call(&mut self, call: &Call) {
+        self.push(SyntaxKind::Call, call.span());
+        call.args.iter().for_each(|arg| {
+            if let Arg::Expr(e) = arg {
+                self.expr(e);
+            }
+        });
+    }
+
+    fn location(&mut self, loc: &Location) {
+        self.push(SyntaxKind::Location, loc.span());
+        if let Location::Index { offset, .. } = loc {
+            self.expr(offset);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call(call) => self.call(call),
+            Expr::Loc(loc) => self.location(loc),
+            Expr::Len { span, .. } => self.push(SyntaxKind::Len, *span),
+            Expr::Literal { span, .. } => self.push(SyntaxKind::Literal, *span),
+            Expr::Nested(span, e) | Expr::Not(span, e) | Expr::Neg(span, e) => {
+                let kind = match expr {
+                    Expr::Nested(..) => SyntaxKind::Nested,
+                    Expr::Not(..) => SyntaxKind::Not,
+                    _ => SyntaxKind::Neg,
+                };
+                self.push(kind, *span);
+                self.expr(e);
+            }
+            Expr::Ter {
+                cond,
+                yes,
+                no,
+                span,
+            } => {
+                self.push(SyntaxKind::Ternary, *span);
+                [cond, yes, no].into_iter().for_each(|e| self.expr(e));
+            }
+            Expr::Index { base, offset, span } => {
+                self.push(SyntaxKind::Index, *span);
+                self.expr(base);
+                self.expr(offset);
+            }
+            Expr::BinOp { lhs, rhs, span, .. } => {
+                self.push(SyntaxKind::BinOp, *span);
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            // a placeholder covers no source, so it has no node.
+            Expr::Error(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{lexer::tokens, parser::Parser};
+
+    const CODE: &str = "// globals\nint a[4];\nvoid main() {\n  int i;\n  \
+                        for (i = 0; i < len(a); i += 1) {\n    a[i] = -(i * 2); // twice\n  }\n}\n";
+
+    fn tree(text: &str) -> SyntaxNode {
+        let span_source = SpanSource::new(text);
+        let root: PRoot = crate::parse(&span_source).unwrap();
+        SyntaxNode::new_root(span_source.source(), &root)
+    }
+
+    #[test]
+    fn full_fidelity() {
+        let root = tree(CODE);
+        assert_eq!(root.text(), CODE);
+        assert_eq!(root.range(), 0..CODE.len());
+        // the comment before the program belongs to the root
+        let first = root.children_with_tokens().next().unwrap();
+        assert_eq!(first.into_token().unwrap().token(), Token::LineComment);
+        root.descendants().for_each(|node| {
+            assert_eq!(node.text(), CODE[node.range()]);
+            let children = node.children_with_tokens().collect::<Vec<_>>();
+            let covered = children
+                .iter()
+                .map(|child| child.range().len())
+                .sum::<usize>();
+            assert_eq!(covered, node.range().len());
+        });
+    }
+
+    #[test]
+    fn navigation() {
+        let root = tree(CODE);
+        let at = |pat: &str| CODE.find(pat).unwrap();
+
+        let token = root.token_at_offset(at("* 2")).unwrap();
+        assert_eq!((token.token(), token.text()), (Token::Star, "*"));
+        let exprs = token
+            .parent()
+            .ancestors()
+            .filter(|node| node.kind().is_expr())
+            .map(|node| node.text())
+            .collect::<Vec<_>>();
+        assert_eq!(exprs, ["i * 2", "(i * 2)", "-(i * 2)"]);
+        let stmt = token.parent().ancestors().find(|n| n.kind().is_stmt());
+        assert_eq!(stmt.unwrap().text(), "a[i] = -(i * 2)");
+
+        let kinds = |node: &SyntaxNode| node.children().map(|n| n.kind()).collect::<Vec<_>>();
+        assert_eq!(kinds(&root), [SyntaxKind::Var, SyntaxKind::Function]);
+        let main = root.children().nth(1).unwrap();
+        assert_eq!(main.prev_sibling().unwrap().text(), "a[4]");
+        assert_eq!(main.next_sibling(), None);
+        let for_loop = root.node_at_offset(at("for")).unwrap();
+        assert_eq!(for_loop.kind(), SyntaxKind::For);
+        assert_eq!(
+            kinds(&for_loop),
+            [
+                SyntaxKind::Assign,
+                SyntaxKind::BinOp,
+                SyntaxKind::Assign,
+                SyntaxKind::Block
+            ]
+        );
+        let init = for_loop.children().next().unwrap();
+        let semicolon = init.next_sibling_or_token().unwrap().into_token().unwrap();
+        assert_eq!(semicolon.token(), Token::Semicolon);
+        assert_eq!(init.next_sibling().unwrap().text(), "i < len(a)");
+        assert_eq!(
+            semicolon.prev_sibling_or_token().unwrap().into_node(),
+            Some(init)
+        );
+
+        let comment = root.token_at_offset(at("// twice")).unwrap();
+        assert!(comment.is_trivia());
+        assert_eq!(comment.parent().kind(), SyntaxKind::Block);
+        assert_eq!(root.token_at_offset(CODE.len()), None);
+    }
+
+    #[test]
+    fn blocks() {
+        let code = "import f;\nvoid main() {\n  if (true) { f(1 ? 2 : 3); } else { while (false) { break; } }\n}\n";
+        let root = tree(code);
+        assert_eq!(root.text(), code);
+        let blocks = root
+            .descendants()
+            .filter(|node| node.kind() == SyntaxKind::Block)
+            .map(|node| node.text())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            blocks,
+            [
+                "{\n  if (true) { f(1 ? 2 : 3); } else { while (false) { break; } }\n}",
+                "{ f(1 ? 2 : 3); }",
+                "{ while (false) { break; } }",
+                "{ break; }",
+            ]
+        );
+        let ternary = root.node_at_offset(code.find("? 2").unwrap()).unwrap();
+        assert_eq!(ternary.kind(), SyntaxKind::Ternary);
+        assert_eq!(ternary.parent().unwrap().kind(), SyntaxKind::Call);
+        assert_eq!(root.children().next().unwrap().kind(), SyntaxKind::Import);
+    }
+
+    #[test]
+    fn missing_body() {
+        let code = "void f();\nvoid main() { if (true) { } }\n";
+        let span_source = SpanSource::new(code);
+        let mut parser = Parser::new(tokens(span_source.source()), |_| {});
+        let root: PRoot = parser.doc_elems().collect();
+        assert!(parser.found_errors());
+        let root = SyntaxNode::new_root(span_source.source(), &root);
+        assert_eq!(root.text(), code);
+        let bodies = root
+            .children()
+            .map(|func| {
+                func.children()
+                    .filter(|node| node.kind() == SyntaxKind::Block)
+                    .map(|node| node.text())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(bodies, [vec![], vec!["{ if (true) { } }"]]);
+    }
+}
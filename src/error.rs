@@ -1,31 +1,674 @@
 use core::fmt::Display;
+use std::io;
 
-const ANSI_RED: &'static str = "\x1b[31m";
-const ANSI_RST: &'static str = "\x1b[0m";
+use crate::span::{Span, SpanSource};
+
+pub mod codes;
+pub use codes::ErrorCode;
+
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_BLUE: &str = "\x1b[34m";
+const ANSI_RST: &str = "\x1b[0m";
+
+/// the file name used when neither the caller nor the source of an error name the file.
+const UNNAMED_SOURCE: &str = "<unknown>";
 
 pub trait CCError {
     fn msgs(&self) -> Vec<(String, (usize, usize))>;
+    /// the name of the file the error was found in, if its source is named.
+    fn source_name(&self) -> Option<&str> {
+        None
+    }
+    /// an edit of the source that fixes the error, if there is an obvious one.
+    fn suggestion(&self) -> Option<Suggestion> {
+        None
+    }
+    /// the phase the error belongs to, `None` leaves it to the phase the error is reported in.
+    fn phase(&self) -> Option<Phase> {
+        None
+    }
+    /// the stable code of the error, see [`codes`].
+    fn code(&self) -> Option<ErrorCode> {
+        None
+    }
+    /// the other spans the error is about, e.g. the previous declaration of a redeclared name.
+    fn labels(&self) -> Vec<Label> {
+        vec![]
+    }
     fn to_error(self, file: &str) -> Error<Self>
     where
         Self: Sized,
     {
-        Error { file, error: self }
+        Error {
+            file: Some(file),
+            error: self,
+        }
+    }
+    /// same as `to_error` but the file is taken from the source of the error.
+    fn to_named_error(self) -> Error<'static, Self>
+    where
+        Self: Sized,
+    {
+        Error {
+            file: None,
+            error: self,
+        }
     }
 }
 
 pub struct Error<'a, T: CCError> {
-    file: &'a str,
+    file: Option<&'a str>,
     error: T,
 }
 
 impl<T: CCError> Display for Error<'_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let file = self
+            .file
+            .or_else(|| self.error.source_name())
+            .unwrap_or(UNNAMED_SOURCE);
         self.error.msgs().iter().try_fold((), |_, msg| {
             writeln!(
                 f,
                 "{}:{}:{}: {}error{}: {}",
-                self.file, msg.1 .0, msg.1 .1, ANSI_RED, ANSI_RST, msg.0,
+                file, msg.1 .0, msg.1 .1, ANSI_RED, ANSI_RST, msg.0,
+            )
+        })?;
+        self.error.labels().iter().try_fold((), |_, label| {
+            writeln!(
+                f,
+                "{}:{}:{}: {}note{}: {}",
+                file, label.pos.0, label.pos.1, ANSI_BLUE, ANSI_RST, label.msg,
             )
         })
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    const fn color(self) -> &'static str {
+        match self {
+            Self::Error => ANSI_RED,
+            Self::Warning => ANSI_YELLOW,
+            Self::Note => ANSI_BLUE,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// the compiler phase a diagnostic comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Phase {
+    Lex,
+    Parse,
+    Semantic,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lex => write!(f, "lex"),
+            Self::Parse => write!(f, "parse"),
+            Self::Semantic => write!(f, "semantic"),
+        }
+    }
+}
+
+/// replaces the `len` bytes of the source starting at `offset` with `replacement`, the edit is
+/// kept as offsets so diagnostics do not borrow the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub offset: usize,
+    pub len: usize,
+    pub replacement: String,
+    /// the edit is the only reasonable fix, it can be applied without asking.
+    pub safe: bool,
+}
+
+impl Suggestion {
+    pub fn new(span: Span, replacement: impl Into<String>) -> Self {
+        Self {
+            offset: span.offset(),
+            len: span.len(),
+            replacement: replacement.into(),
+            safe: true,
+        }
+    }
+
+    /// a suggestion that makes the code valid but may not be what the author meant.
+    pub fn guess(span: Span, replacement: impl Into<String>) -> Self {
+        Self {
+            safe: false,
+            ..Self::new(span, replacement)
+        }
+    }
+
+    /// the span of `source` the suggestion replaces, to apply it with [`crate::span::rewrite`].
+    pub fn span<'a>(&self, source: &'a SpanSource) -> Span<'a> {
+        source.source().split_at(self.offset).1.split_at(self.len).0
+    }
+}
+
+/// a secondary span of a diagnostic with what it is, e.g. "previous declaration here". the span
+/// is kept as its position and length so diagnostics do not borrow the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub msg: String,
+    pub pos: (usize, usize),
+    pub len: usize,
+}
+
+impl Label {
+    pub fn new(span: Span, msg: impl Into<String>) -> Self {
+        Self {
+            msg: msg.into(),
+            pos: span.position(),
+            len: span.len(),
+        }
+    }
+}
+
+/// a single rendered message of any phase, the messages following the first one returned by
+/// `CCError::msgs` are kept as notes attached to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    phase: Option<Phase>,
+    code: Option<ErrorCode>,
+    msg: String,
+    pos: (usize, usize),
+    /// the name of the file the diagnostic is in, if it is known.
+    source_name: Option<String>,
+    /// the other spans the diagnostic is about, printed right after it.
+    labels: Vec<Label>,
+    notes: Vec<(String, (usize, usize))>,
+    suggestion: Option<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, msg: String, pos: (usize, usize)) -> Self {
+        Self {
+            severity,
+            phase: None,
+            code: None,
+            msg,
+            pos,
+            source_name: None,
+            labels: vec![],
+            notes: vec![],
+            suggestion: None,
+        }
+    }
+
+    pub fn from_error<T: CCError>(severity: Severity, error: &T) -> Self {
+        let mut msgs = error.msgs().into_iter();
+        let (msg, pos) = msgs.next().unwrap_or_default();
+        Self {
+            severity,
+            phase: error.phase(),
+            code: error.code(),
+            msg,
+            pos,
+            source_name: error.source_name().map(String::from),
+            labels: error.labels(),
+            notes: msgs.collect(),
+            suggestion: error.suggestion(),
+        }
+    }
+
+    pub fn with_phase(self, phase: Phase) -> Self {
+        Self {
+            phase: Some(phase),
+            ..self
+        }
+    }
+
+    pub fn with_source_name(self, name: impl Into<String>) -> Self {
+        Self {
+            source_name: Some(name.into()),
+            ..self
+        }
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// the phase that reported the diagnostic, if it is known.
+    pub fn phase(&self) -> Option<Phase> {
+        self.phase
+    }
+
+    /// the stable code of the diagnostic, if its kind has one.
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code
+    }
+
+    pub fn msg(&self) -> &str {
+        &self.msg
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        self.pos
+    }
+
+    /// the name of the file the diagnostic is in, if its error or its sink know it.
+    pub fn source_name(&self) -> Option<&str> {
+        self.source_name.as_deref()
+    }
+
+    /// the secondary spans of the diagnostic in the order they are printed.
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn notes(&self) -> &[(String, (usize, usize))] {
+        &self.notes
+    }
+
+    /// the edit that fixes the diagnostic, if it has an obvious one.
+    pub fn suggestion(&self) -> Option<&Suggestion> {
+        self.suggestion.as_ref()
+    }
+
+    pub fn display<'a>(&'a self, file: &'a str) -> DiagnosticDisplay<'a> {
+        DiagnosticDisplay {
+            file,
+            diagnostic: self,
+        }
+    }
+}
+
+pub struct DiagnosticDisplay<'a> {
+    file: &'a str,
+    diagnostic: &'a Diagnostic,
+}
+
+impl Display for DiagnosticDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Diagnostic {
+            severity,
+            code,
+            msg,
+            pos,
+            labels,
+            notes,
+            ..
+        } = self.diagnostic;
+        // the code is written like `error[S0001]` after the severity
+        let code = code.map_or(String::new(), |code| format!("[{}]", code));
+        writeln!(
+            f,
+            "{}:{}:{}: {}{}{}{}: {}",
+            self.file,
+            pos.0,
+            pos.1,
+            severity.color(),
+            severity,
+            code,
+            ANSI_RST,
+            msg,
+        )?;
+        // the labels are printed as notes, before the notes of the error
+        labels
+            .iter()
+            .map(|label| (&label.msg, label.pos))
+            .chain(notes.iter().map(|(msg, pos)| (msg, *pos)))
+            .try_fold((), |_, (msg, pos)| {
+                writeln!(
+                    f,
+                    "{}:{}:{}: {}{}{}: {}",
+                    self.file,
+                    pos.0,
+                    pos.1,
+                    Severity::Note.color(),
+                    Severity::Note,
+                    ANSI_RST,
+                    msg,
+                )
+            })
+    }
+}
+
+/// collects the diagnostics of all the phases.
+///
+/// diagnostics past `max_errors` are still counted but they are not kept.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorSink {
+    diagnostics: Vec<Diagnostic>,
+    /// the phase the reported diagnostics are tagged with.
+    phase: Option<Phase>,
+    /// the file name the reported diagnostics without one are tagged with.
+    source_name: Option<String>,
+    max_errors: Option<usize>,
+    errors: usize,
+    warnings: usize,
+}
+
+impl ErrorSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_errors(max_errors: usize) -> Self {
+        Self {
+            max_errors: Some(max_errors),
+            ..Self::default()
+        }
+    }
+
+    /// tags the diagnostics reported from now on with `phase`.
+    pub fn enter_phase(&mut self, phase: Phase) {
+        self.phase = Some(phase);
+    }
+
+    /// tags the diagnostics reported from now on without a file name with the name of `source`,
+    /// e.g. the errors that are not about a span of the source.
+    pub fn enter_source(&mut self, source: &SpanSource) {
+        self.source_name = source.name().map(String::from);
+    }
+
+    /// the diagnostics reported with a phase or a file name keep them, the rest are tagged with
+    /// the current ones.
+    pub fn push(&mut self, mut diagnostic: Diagnostic) {
+        diagnostic.phase = diagnostic.phase.or(self.phase);
+        if diagnostic.source_name.is_none() {
+            diagnostic.source_name.clone_from(&self.source_name);
+        }
+        let full = self.is_full();
+        match diagnostic.severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+            Severity::Note => {}
+        }
+        if !full {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    /// reports an error.
+    pub fn error<T: CCError>(&mut self, error: &T) {
+        self.push(Diagnostic::from_error(Severity::Error, error))
+    }
+
+    /// reports a warning.
+    pub fn warn<T: CCError>(&mut self, warning: &T) {
+        self.push(Diagnostic::from_error(Severity::Warning, warning))
+    }
+
+    /// reports an informational note.
+    pub fn note<T: CCError>(&mut self, note: &T) {
+        self.push(Diagnostic::from_error(Severity::Note, note))
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors != 0
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.errors
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.warnings
+    }
+
+    /// returns true if the sink reached its maximum number of errors.
+    pub fn is_full(&self) -> bool {
+        self.max_errors.is_some_and(|max| self.errors >= max)
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    /// the kept diagnostics ordered by their position with the duplicates removed, the
+    /// diagnostics at the same position stay in the order they were reported in.
+    pub fn into_sorted_vec(mut self) -> Vec<Diagnostic> {
+        self.diagnostics.sort_by_key(|diag| diag.pos);
+        self.diagnostics
+            .into_iter()
+            .fold(vec![], |mut sorted: Vec<Diagnostic>, diag| {
+                let duplicate = sorted
+                    .iter()
+                    .rev()
+                    .take_while(|kept| kept.pos == diag.pos)
+                    .any(|kept| *kept == diag);
+                if !duplicate {
+                    sorted.push(diag);
+                }
+                sorted
+            })
+    }
+
+    /// writes all the kept diagnostics to `out`, each with the name of its file.
+    pub fn emit(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        self.diagnostics.iter().try_for_each(|diag| {
+            let file = diag.source_name().unwrap_or(UNNAMED_SOURCE);
+            write!(out, "{}", diag.display(file))
+        })
+    }
+}
+
+/// the diagnostics are pushed one by one so they are counted and tagged like reported ones.
+impl Extend<Diagnostic> for ErrorSink {
+    fn extend<T: IntoIterator<Item = Diagnostic>>(&mut self, diagnostics: T) {
+        diagnostics
+            .into_iter()
+            .for_each(|diagnostic| self.push(diagnostic))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct TestError(usize);
+
+    impl CCError for TestError {
+        fn msgs(&self) -> Vec<(String, (usize, usize))> {
+            vec![
+                (format!("error {}", self.0), (self.0, 1)),
+                ("hint".to_string(), (self.0, 2)),
+            ]
+        }
+    }
+
+    #[test]
+    fn counting() {
+        let mut sink = ErrorSink::new();
+        assert!(!sink.has_errors());
+        sink.warn(&TestError(1));
+        assert!(!sink.has_errors());
+        sink.error(&TestError(2));
+        sink.error(&TestError(3));
+        assert!(sink.has_errors());
+        assert_eq!(sink.error_count(), 2);
+        assert_eq!(sink.warning_count(), 1);
+        assert_eq!(sink.diagnostics().len(), 3);
+        assert_eq!(sink.diagnostics()[1].msg(), "error 2");
+        assert_eq!(sink.diagnostics()[1].position(), (2, 1));
+        assert_eq!(
+            sink.diagnostics()[1].notes(),
+            [("hint".to_string(), (2, 2))]
+        );
+    }
+
+    #[test]
+    fn capping() {
+        let mut sink = ErrorSink::with_max_errors(2);
+        (1..=5).for_each(|i| sink.error(&TestError(i)));
+        assert!(sink.is_full());
+        assert_eq!(sink.error_count(), 5);
+        assert_eq!(sink.diagnostics().len(), 2);
+        assert_eq!(sink.diagnostics()[1].msg(), "error 2");
+
+        let mut out = vec![];
+        sink.emit(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "<unknown>:1:1: {ANSI_RED}error{ANSI_RST}: error 1\n\
+                 <unknown>:1:2: {ANSI_BLUE}note{ANSI_RST}: hint\n\
+                 <unknown>:2:1: {ANSI_RED}error{ANSI_RST}: error 2\n\
+                 <unknown>:2:2: {ANSI_BLUE}note{ANSI_RST}: hint\n"
+            )
+        );
+    }
+
+    #[test]
+    fn labels() {
+        struct Redeclared;
+
+        impl CCError for Redeclared {
+            fn msgs(&self) -> Vec<(String, (usize, usize))> {
+                vec![("redeclared `x`".to_string(), (2, 5))]
+            }
+
+            fn labels(&self) -> Vec<Label> {
+                vec![Label {
+                    msg: "previous declaration here".to_string(),
+                    pos: (1, 5),
+                    len: 1,
+                }]
+            }
+        }
+
+        let mut sink = ErrorSink::new();
+        sink.enter_source(&SpanSource::named("test.dcf", ""));
+        sink.error(&Redeclared);
+        sink.push(
+            Diagnostic::new(Severity::Warning, "warning".to_string(), (3, 1))
+                .with_source_name("other.dcf")
+                .with_label(Label {
+                    msg: "here".to_string(),
+                    pos: (3, 4),
+                    len: 2,
+                }),
+        );
+        assert_eq!(sink.diagnostics()[0].labels()[0].pos, (1, 5));
+        assert!(sink.diagnostics()[0].notes().is_empty());
+        let mut out = vec![];
+        sink.emit(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "test.dcf:2:5: {ANSI_RED}error{ANSI_RST}: redeclared `x`\n\
+                 test.dcf:1:5: {ANSI_BLUE}note{ANSI_RST}: previous declaration here\n\
+                 other.dcf:3:1: {ANSI_YELLOW}warning{ANSI_RST}: warning\n\
+                 other.dcf:3:4: {ANSI_BLUE}note{ANSI_RST}: here\n"
+            )
+        );
+        assert_eq!(
+            Redeclared.to_error("test.dcf").to_string(),
+            format!(
+                "test.dcf:2:5: {ANSI_RED}error{ANSI_RST}: redeclared `x`\n\
+                 test.dcf:1:5: {ANSI_BLUE}note{ANSI_RST}: previous declaration here\n"
+            )
+        );
+    }
+
+    #[test]
+    fn named_source() {
+        use crate::{lexer::tokens, span::SpanSource};
+        let code = SpanSource::named("test.dcf", "int x;\n@");
+        let error = tokens(code.source())
+            .find_map(|tok| tok.get().err())
+            .unwrap();
+        assert_eq!(
+            error.to_named_error().to_string(),
+            format!("test.dcf:2:1: {ANSI_RED}error{ANSI_RST}: unexpected character: @ after `;`\n")
+        );
+        assert!(error
+            .to_error("other.dcf")
+            .to_string()
+            .starts_with("other.dcf:2:1:"));
+
+        let code = SpanSource::new("@");
+        let error = tokens(code.source())
+            .find_map(|tok| tok.get().err())
+            .unwrap();
+        assert!(error
+            .to_named_error()
+            .to_string()
+            .starts_with(&format!("{UNNAMED_SOURCE}:1:1:")));
+    }
+
+    #[test]
+    fn phases() {
+        let mut sink = ErrorSink::new();
+        sink.error(&TestError(1));
+        sink.enter_phase(Phase::Parse);
+        sink.error(&TestError(2));
+        sink.push(
+            Diagnostic::new(Severity::Error, "lex".to_string(), (3, 1)).with_phase(Phase::Lex),
+        );
+        sink.enter_phase(Phase::Semantic);
+        sink.warn(&TestError(4));
+        assert_eq!(
+            sink.diagnostics()
+                .iter()
+                .map(|diag| diag.phase())
+                .collect::<Vec<_>>(),
+            [
+                None,
+                Some(Phase::Parse),
+                Some(Phase::Lex),
+                Some(Phase::Semantic)
+            ]
+        );
+    }
+
+    #[test]
+    fn extend_and_sort() {
+        let diagnostic = |msg: &str, pos| Diagnostic::new(Severity::Error, msg.to_string(), pos);
+        let mut lex = ErrorSink::new();
+        lex.enter_phase(Phase::Lex);
+        lex.push(diagnostic("b", (2, 1)));
+        let parse = vec![
+            diagnostic("c", (3, 4)).with_phase(Phase::Parse),
+            diagnostic("a", (1, 5)).with_phase(Phase::Parse),
+            diagnostic("c", (3, 4)).with_phase(Phase::Parse),
+        ];
+        let mut sink = ErrorSink::new();
+        sink.extend(lex.into_diagnostics());
+        sink.extend(parse);
+        assert_eq!(sink.error_count(), 4);
+        assert_eq!(
+            sink.into_sorted_vec()
+                .iter()
+                .map(|diag| (diag.msg(), diag.position(), diag.phase()))
+                .collect::<Vec<_>>(),
+            [
+                ("a", (1, 5), Some(Phase::Parse)),
+                ("b", (2, 1), Some(Phase::Lex)),
+                ("c", (3, 4), Some(Phase::Parse)),
+            ]
+        );
+    }
+}
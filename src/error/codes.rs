@@ -0,0 +1,333 @@
+//! the stable codes of the diagnostics of the semantic phase, the errors found while lowering the
+//! parse tree to the [`crate::ast`] and the warnings of the lints.
+//!
+//! the codes never change meaning once published so tools can match on them instead of the text
+//! of the messages, an error that is no longer reported keeps its code unused. this table is the
+//! source of the documentation of the codes, see [`reference`].
+
+use core::fmt::Display;
+
+/// a stable code identifying a kind of diagnostic, e.g. `S0001` for an undeclared identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorCode {
+    /// the letter of the kind of diagnostic, `S` for the errors and `W` for the warnings,
+    /// followed by four digits.
+    pub code: &'static str,
+    /// a one line summary of the diagnostic.
+    pub summary: &'static str,
+    /// what the diagnostic means and how to fix it.
+    pub explanation: &'static str,
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+const fn code(code: &'static str, summary: &'static str, explanation: &'static str) -> ErrorCode {
+    ErrorCode {
+        code,
+        summary,
+        explanation,
+    }
+}
+
+pub const UNDECLARED_IDENTIFIER: ErrorCode = code(
+    "S0001",
+    "undeclared identifier",
+    "the identifier is not declared in the scope it is used in, nor in any scope enclosing it. \
+     declare it before its use or fix its spelling.",
+);
+pub const EXPECTED_ARRAY: ErrorCode = code(
+    "S0002",
+    "expected array variable",
+    "`len` and indexing need an array variable, the variable holds a single value.",
+);
+pub const EXPECTED_SCALAR_VARIABLE: ErrorCode = code(
+    "S0003",
+    "expected scalar variable",
+    "an array is used where a single value is expected, index it to use one of its elements.",
+);
+pub const CANNOT_INDEX_SCALAR: ErrorCode = code(
+    "S0004",
+    "cannot index scalar variable",
+    "only array variables can be indexed, the variable holds a single value.",
+);
+pub const CANNOT_ASSIGN_TO_ARRAY: ErrorCode = code(
+    "S0005",
+    "cannot assign to array variable",
+    "an array cannot be assigned as a whole, assign its elements one by one.",
+);
+pub const EXPECTED_BOOL_EXPR: ErrorCode = code(
+    "S0006",
+    "expected boolean expression",
+    "conditions and the operands of `!`, `&&` and `||` have to be `bool`.",
+);
+pub const EXPECTED_INT_EXPR: ErrorCode = code(
+    "S0007",
+    "expected integer expression",
+    "array indexes, `-` and the arithmetic and ordering operators take `int` operands.",
+);
+pub const RETURN_VALUE_FROM_VOID: ErrorCode = code(
+    "S0008",
+    "cannot return value from void function",
+    "a `void` method can only `return;` without a value.",
+);
+pub const REDEFINITION: ErrorCode = code(
+    "S0009",
+    "redefinition",
+    "the name is already declared in the same scope, rename one of the declarations.",
+);
+pub const DUPLICATE_PARAMETER: ErrorCode = code(
+    "S0010",
+    "duplicate parameter",
+    "two parameters of the same method have the same name.",
+);
+pub const BREAK_OUTSIDE_LOOP: ErrorCode = code(
+    "S0011",
+    "break outside loop",
+    "`break` can only be used in the body of a `for` or a `while` loop.",
+);
+pub const CONTINUE_OUTSIDE_LOOP: ErrorCode = code(
+    "S0012",
+    "continue outside loop",
+    "`continue` can only be used in the body of a `for` or a `while` loop.",
+);
+pub const VOID_FUNC_AS_EXPR: ErrorCode = code(
+    "S0013",
+    "void function used as expression",
+    "a `void` method has no value, it can only be called as a statement.",
+);
+pub const TYPE_MISMATCH: ErrorCode = code(
+    "S0014",
+    "type mismatch",
+    "the operands of a binary operator or the two branches of `?:` have different types.",
+);
+pub const WRONG_NUMBER_OF_ARGS: ErrorCode = code(
+    "S0015",
+    "wrong number of arguments",
+    "a method is called with a different number of arguments than it has parameters.",
+);
+pub const EXPECTED_TYPE: ErrorCode = code(
+    "S0016",
+    "expected type",
+    "an argument does not have the type of the parameter it is passed to.",
+);
+pub const EXPECTED_EXPRESSION: ErrorCode = code(
+    "S0017",
+    "expected expression",
+    "a method that returns a value has to `return` one, `return;` is only allowed in `void` \
+     methods.",
+);
+pub const ZERO_ARRAY_SIZE: ErrorCode = code(
+    "S0018",
+    "array size cannot be zero",
+    "arrays are declared with a positive size.",
+);
+pub const TOO_LARGE_INT: ErrorCode = code(
+    "S0019",
+    "integer literal is too large",
+    "the literal does not fit in a 64-bit signed integer.",
+);
+pub const ROOT_DOES_NOT_CONTAIN_MAIN: ErrorCode = code(
+    "S0020",
+    "missing main function",
+    "a program has to declare a `main` method, where its execution starts.",
+);
+pub const INVALID_MAIN_SIG: ErrorCode = code(
+    "S0021",
+    "main function has invalid signature",
+    "`main` takes no parameters and returns nothing, it is declared as `void main()`.",
+);
+pub const VARIABLE_NOT_A_METHOD: ErrorCode = code(
+    "S0022",
+    "variable is not a method",
+    "a variable is called like a method, it can only be read or assigned.",
+);
+pub const STRING_IN_USER_DEFINED: ErrorCode = code(
+    "S0023",
+    "string literal in user defined method",
+    "string literals can only be passed to imported methods.",
+);
+pub const ASSIGN_OF_DIFFERENT_TYPE: ErrorCode = code(
+    "S0024",
+    "assignment of different type",
+    "the assigned value does not have the type of the variable.",
+);
+pub const INC_NON_INT: ErrorCode = code(
+    "S0025",
+    "cannot increment non-integer variable",
+    "`++` can only be applied to an `int` variable.",
+);
+pub const DEC_NON_INT: ErrorCode = code(
+    "S0026",
+    "cannot decrement non-integer variable",
+    "`--` can only be applied to an `int` variable.",
+);
+pub const METHOD_NOT_A_VARIABLE: ErrorCode = code(
+    "S0028",
+    "method is not a variable",
+    "a method is read or assigned like a variable, it can only be called.",
+);
+pub const CANNOT_INDEX_EXPR: ErrorCode = code(
+    "S0029",
+    "cannot index expression",
+    "only array variables can be indexed, not the values of other expressions.",
+);
+pub const RETURN_TYPE_MISMATCH: ErrorCode = code(
+    "S0030",
+    "return type mismatch",
+    "the returned value does not have the return type of the method.",
+);
+pub const ARRAY_IN_USER_DEFINED: ErrorCode = code(
+    "S0031",
+    "array in user defined method",
+    "arrays can only be passed to imported methods, pass one of the elements instead.",
+);
+pub const COMPOUND_ASSIGN_NON_INT: ErrorCode = code(
+    "S0032",
+    "compound assignment to non-integer variable",
+    "`+=` and `-=` can only be applied to an `int` variable.",
+);
+pub const CONSTANT_OVERFLOW: ErrorCode = code(
+    "S0033",
+    "arithmetic overflow in constant expression",
+    "an operation on constants has a result that does not fit in a 64-bit signed integer, the \
+     program would overflow every time it runs it.",
+);
+pub const MISSING_RETURN: ErrorCode = code(
+    "S0034",
+    "missing return",
+    "a method with a return type can reach the end of its body without returning a value, \
+     return one on every path. a loop is assumed to end unless its condition is `true`.",
+);
+pub const INVALID_INTERFACE: ErrorCode = code(
+    "S0035",
+    "invalid interface file",
+    "a line of the interface file that declares the signatures of the imported methods is not a \
+     declaration like `int printf(string, ...);`.",
+);
+pub const USE_BEFORE_ASSIGN: ErrorCode = code(
+    "W0001",
+    "variable may be used before it is assigned",
+    "a local variable is read on a path where it was not assigned yet, its value is the default \
+     of its type.",
+);
+pub const SHADOWS_IMPORT: ErrorCode = code(
+    "W0002",
+    "declaration shadows an import",
+    "a declaration has the name of an imported method, which cannot be called in its scope.",
+);
+pub const REDUNDANT_PARENS: ErrorCode = code(
+    "W0003",
+    "redundant parentheses",
+    "the parentheses do not change how the expression is grouped and can be removed.",
+);
+pub const UNREACHABLE_CODE: ErrorCode = code(
+    "W0004",
+    "unreachable statement",
+    "the statement follows a `return`, `break` or `continue` on every path and never runs, \
+     remove it.",
+);
+pub const UNUSED_VARIABLE: ErrorCode = code(
+    "W0005",
+    "unused variable",
+    "a local variable is declared but never read nor assigned, remove its declaration.",
+);
+pub const SHADOWS_PARAMETER: ErrorCode = code(
+    "W0006",
+    "declaration shadows a parameter",
+    "a local of a nested block has the name of a parameter of the method, which cannot be used \
+     in the block. rename the local.",
+);
+
+/// all the codes, in order.
+pub const CODES: &[ErrorCode] = &[
+    UNDECLARED_IDENTIFIER,
+    EXPECTED_ARRAY,
+    EXPECTED_SCALAR_VARIABLE,
+    CANNOT_INDEX_SCALAR,
+    CANNOT_ASSIGN_TO_ARRAY,
+    EXPECTED_BOOL_EXPR,
+    EXPECTED_INT_EXPR,
+    RETURN_VALUE_FROM_VOID,
+    REDEFINITION,
+    DUPLICATE_PARAMETER,
+    BREAK_OUTSIDE_LOOP,
+    CONTINUE_OUTSIDE_LOOP,
+    VOID_FUNC_AS_EXPR,
+    TYPE_MISMATCH,
+    WRONG_NUMBER_OF_ARGS,
+    EXPECTED_TYPE,
+    EXPECTED_EXPRESSION,
+    ZERO_ARRAY_SIZE,
+    TOO_LARGE_INT,
+    ROOT_DOES_NOT_CONTAIN_MAIN,
+    INVALID_MAIN_SIG,
+    VARIABLE_NOT_A_METHOD,
+    STRING_IN_USER_DEFINED,
+    ASSIGN_OF_DIFFERENT_TYPE,
+    INC_NON_INT,
+    DEC_NON_INT,
+    METHOD_NOT_A_VARIABLE,
+    CANNOT_INDEX_EXPR,
+    RETURN_TYPE_MISMATCH,
+    ARRAY_IN_USER_DEFINED,
+    COMPOUND_ASSIGN_NON_INT,
+    CONSTANT_OVERFLOW,
+    MISSING_RETURN,
+    INVALID_INTERFACE,
+    USE_BEFORE_ASSIGN,
+    SHADOWS_IMPORT,
+    REDUNDANT_PARENS,
+    UNREACHABLE_CODE,
+    UNUSED_VARIABLE,
+    SHADOWS_PARAMETER,
+];
+
+/// the code written as `code`, e.g. `S0001`.
+pub fn lookup(code: &str) -> Option<ErrorCode> {
+    CODES.iter().copied().find(|known| known.code == code)
+}
+
+/// the documentation of all the codes as markdown, a section for each code.
+pub fn reference() -> String {
+    CODES.iter().fold(String::new(), |mut out, code| {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "## {}: {}\n\n{}\n",
+            code.code, code.summary, code.explanation
+        ));
+        out
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_ordered() {
+        assert!(CODES.windows(2).all(|pair| pair[0].code < pair[1].code));
+        assert!(
+            CODES
+                .iter()
+                .all(|code| code.code.len() == 5
+                    && code.code[1..].bytes().all(|b| b.is_ascii_digit()))
+        );
+    }
+
+    #[test]
+    fn documentation() {
+        assert_eq!(lookup("S0011"), Some(BREAK_OUTSIDE_LOOP));
+        assert_eq!(lookup("S9999"), None);
+        let reference = reference();
+        assert!(reference.starts_with(
+            "## S0001: undeclared identifier\n\nthe identifier is not declared in the scope"
+        ));
+        assert_eq!(reference.matches("## ").count(), CODES.len());
+    }
+}
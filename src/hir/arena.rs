@@ -0,0 +1,595 @@
+//! the checked program flattened into arenas, the nodes refer to each other by ids and what is
+//! known about them (types, spans, parents) is kept in side tables indexed by the same ids.
+//!
+//! the [`Hir`] is lowered from the [`Root`] of a checked program. the names are resolved while it
+//! is lowered, every variable and method is a [`Def`] and the nodes refer to the definitions by
+//! their [`DefId`] instead of their names. the expressions keep the [`ExprId`] they have in the
+//! root, so [`Root::type_of`] and [`Hir::type_of`] agree.
+//!
+//! cloning a [`Hir`] copies a few vectors, and a pass that only needs the expressions can walk
+//! [`Hir::exprs`] without walking the statements.
+//!
+//! the checks and the lints walk the parsed tree and the [`Root`], which keep the spans of the
+//! source their diagnostics point at. the tools that look a program up after it passed them, like
+//! the [`ScopeTree`](super::ScopeTree), refer to its definitions and nodes by id in the [`Hir`].
+
+use crate::ast::{
+    ArithOp, Assign, Block, Call, CondOp, EqOp, Expr, ExprId, ExternArg, Function, Location, RelOp,
+    Root, Stmt, Type, Var,
+};
+use crate::span::{HasSpan, SourceRange};
+
+use std::collections::HashMap;
+
+/// the id of a definition, a variable or a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DefId(usize);
+
+/// the id of a statement, in the order the statements are written in a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StmtId(usize);
+
+/// the id of a block, the body of a method or of a statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    Import,
+    Global,
+    Method,
+    Param,
+    Local,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Def {
+    pub name: String,
+    pub kind: DefKind,
+    /// the type of a variable or the return type of a method, `None` for a `void` method. an
+    /// import returns an `int`.
+    pub ty: Option<Type>,
+    /// the number of elements of an array.
+    pub size: Option<u64>,
+    /// the span of the name of a variable or an import, or of the whole method.
+    pub span: SourceRange,
+}
+
+/// a variable that is read or assigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Place {
+    Var(DefId),
+    Index(DefId, ExprId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    Expr(ExprId),
+    /// a string literal as it is written, passed to an import.
+    String(SourceRange, String),
+    /// an array passed to an import.
+    Array(SourceRange, DefId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprKind {
+    Int(i64),
+    Bool(bool),
+    /// the length of an array, known when the program is checked.
+    Len(u64),
+    Load(Place),
+    Not(ExprId),
+    Neg(ExprId),
+    /// an expression written in parentheses.
+    Paren(ExprId),
+    Ternary {
+        cond: ExprId,
+        yes: ExprId,
+        no: ExprId,
+    },
+    Arith(ArithOp, ExprId, ExprId),
+    Rel(RelOp, ExprId, ExprId),
+    Eq(EqOp, ExprId, ExprId),
+    Cond(CondOp, ExprId, ExprId),
+    Call(DefId, Vec<Arg>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StmtKind {
+    Assign(Place, ExprId),
+    Expr(ExprId),
+    Return(Option<ExprId>),
+    Break,
+    Continue,
+    /// the arms of an [`IfChain`](crate::ast::IfChain) and its `else` block.
+    If(Vec<(ExprId, BlockId)>, BlockId),
+    While(ExprId, BlockId),
+    /// the initialization and the update are assignments.
+    For {
+        init: StmtId,
+        cond: ExprId,
+        update: StmtId,
+        body: BlockId,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockData {
+    /// the locals of the block in the order they are declared in.
+    pub decls: Vec<DefId>,
+    pub stmts: Vec<StmtId>,
+    pub span: SourceRange,
+}
+
+/// the parameters and the body of a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Method {
+    pub def: DefId,
+    pub params: Vec<DefId>,
+    pub body: BlockId,
+}
+
+/// the node a node is nested in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parent {
+    Expr(ExprId),
+    Stmt(StmtId),
+    Block(BlockId),
+    /// the method a body belongs to.
+    Def(DefId),
+}
+
+/// a checked program stored in arenas, see the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hir {
+    defs: Vec<Def>,
+    methods: Vec<Method>,
+    blocks: Vec<BlockData>,
+    block_parents: Vec<Parent>,
+    stmts: Vec<StmtKind>,
+    stmt_spans: Vec<SourceRange>,
+    stmt_parents: Vec<Parent>,
+    exprs: Vec<ExprKind>,
+    expr_spans: Vec<SourceRange>,
+    expr_parents: Vec<Parent>,
+    types: Vec<Option<Type>>,
+    /// the imports, the globals and the methods by name.
+    globals: HashMap<String, DefId>,
+}
+
+impl Hir {
+    /// lowers `root`, the methods are lowered in the order of their names like the
+    /// [`crate::ast::Visitor`] walks them, so the expressions get the same ids.
+    pub fn lower(root: &Root) -> Self {
+        let mut lower = Lower {
+            root,
+            hir: Self::default(),
+            scopes: vec![],
+        };
+        lower.root();
+        debug_assert_eq!(lower.hir.exprs.len(), root.expr_count());
+        lower.hir
+    }
+
+    pub fn def(&self, id: DefId) -> &Def {
+        &self.defs[id.0]
+    }
+
+    /// the definitions with their ids, the imports first then the globals, the methods, and the
+    /// parameters and the locals of each method.
+    pub fn defs(&self) -> impl Iterator<Item = (DefId, &Def)> {
+        self.defs
+            .iter()
+            .enumerate()
+            .map(|(id, def)| (DefId(id), def))
+    }
+
+    /// the import, global or method named `name`.
+    pub fn global(&self, name: &str) -> Option<DefId> {
+        self.globals.get(name).copied()
+    }
+
+    /// the methods in the order of their names.
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    pub fn block(&self, id: BlockId) -> &BlockData {
+        &self.blocks[id.0]
+    }
+
+    pub fn stmt(&self, id: StmtId) -> &StmtKind {
+        &self.stmts[id.0]
+    }
+
+    pub fn expr(&self, id: ExprId) -> &ExprKind {
+        &self.exprs[id.0]
+    }
+
+    /// all the expressions with their ids, in the order of the ids.
+    pub fn exprs(&self) -> impl Iterator<Item = (ExprId, &ExprKind)> {
+        self.exprs.iter().enumerate().map(|(id, e)| (ExprId(id), e))
+    }
+
+    /// the type of the expression `id`, `None` for a call of a `void` method.
+    pub fn type_of(&self, id: ExprId) -> Option<Type> {
+        self.types[id.0]
+    }
+
+    pub fn expr_span(&self, id: ExprId) -> SourceRange {
+        self.expr_spans[id.0]
+    }
+
+    pub fn stmt_span(&self, id: StmtId) -> SourceRange {
+        self.stmt_spans[id.0]
+    }
+
+    /// the expression or the statement `id` is an operand of.
+    pub fn expr_parent(&self, id: ExprId) -> Parent {
+        self.expr_parents[id.0]
+    }
+
+    /// the block of the statement `id`, or the `for` it is the initialization or the update of.
+    pub fn stmt_parent(&self, id: StmtId) -> Parent {
+        self.stmt_parents[id.0]
+    }
+
+    /// the statement of the block `id`, or the method it is the body of.
+    pub fn block_parent(&self, id: BlockId) -> Parent {
+        self.block_parents[id.0]
+    }
+}
+
+/// the variables of `vars` in the order they are declared in.
+fn in_order<'v>(vars: impl IntoIterator<Item = &'v Var>) -> Vec<&'v Var> {
+    let mut vars = vars.into_iter().collect::<Vec<_>>();
+    vars.sort_by_key(|var| var.span().offset());
+    vars
+}
+
+struct Lower<'r> {
+    root: &'r Root,
+    hir: Hir,
+    /// the parameters and the locals in scope, innermost last.
+    scopes: Vec<HashMap<&'r str, DefId>>,
+}
+
+impl<'r> Lower<'r> {
+    fn def(&mut self, def: Def) -> DefId {
+        self.hir.defs.push(def);
+        DefId(self.hir.defs.len() - 1)
+    }
+
+    fn var(&mut self, var: &'r Var, kind: DefKind) -> DefId {
+        let (ty, size) = match var {
+            Var::Scalar(var) => (var.r#type(), None),
+            Var::Array { arr, size } => (arr.r#type(), Some(*size)),
+        };
+        let id = self.def(Def {
+            name: var.name().to_string(),
+            kind,
+            ty: Some(ty),
+            size,
+            span: var.span(),
+        });
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(var.name(), id);
+        }
+        id
+    }
+
+    /// the variable or the method `name` refers to where it is used.
+    fn resolve(&self, name: &str) -> DefId {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .or_else(|| self.hir.global(name))
+            .expect("the names of a checked program are declared")
+    }
+
+    fn root(&mut self) {
+        let root = self.root;
+        let mut imports = root.imports.iter().collect::<Vec<_>>();
+        imports.sort_by_key(|(name, _)| *name);
+        let mut functions = root.functions.values().collect::<Vec<_>>();
+        functions.sort_by_key(|func| &func.name);
+        for (import, span) in imports {
+            let id = self.def(Def {
+                name: import.clone(),
+                kind: DefKind::Import,
+                ty: Some(Type::Int),
+                size: None,
+                span: *span,
+            });
+            self.hir.globals.insert(import.clone(), id);
+        }
+        for global in in_order(root.globals.values()) {
+            let id = self.var(global, DefKind::Global);
+            self.hir.globals.insert(global.name().to_string(), id);
+        }
+        let defs = functions
+            .iter()
+            .map(|func| {
+                let id = self.def(Def {
+                    name: func.name.clone(),
+                    kind: DefKind::Method,
+                    ty: func.ret,
+                    size: None,
+                    span: func.span,
+                });
+                self.hir.globals.insert(func.name.clone(), id);
+                id
+            })
+            .collect::<Vec<_>>();
+        functions
+            .into_iter()
+            .zip(defs)
+            .for_each(|(func, def)| self.function(func, def));
+    }
+
+    fn function(&mut self, func: &'r Function, def: DefId) {
+        self.scopes.push(HashMap::new());
+        let params = func
+            .params
+            .iter()
+            .map(|param| self.var(&func.args[param], DefKind::Param))
+            .collect();
+        let body = self.block(&func.body, Parent::Def(def));
+        self.scopes.pop();
+        self.hir.methods.push(Method { def, params, body });
+    }
+
+    fn block(&mut self, block: &'r Block, parent: Parent) -> BlockId {
+        let id = BlockId(self.hir.blocks.len());
+        self.hir.blocks.push(BlockData::default());
+        self.hir.block_parents.push(parent);
+        self.scopes.push(HashMap::new());
+        let decls = in_order(block.decls.values())
+            .into_iter()
+            .map(|var| self.var(var, DefKind::Local))
+            .collect();
+        let stmts = block
+            .stmts
+            .iter()
+            .map(|stmt| self.stmt(stmt, Parent::Block(id)))
+            .collect();
+        self.scopes.pop();
+        self.hir.blocks[id.0] = BlockData {
+            decls,
+            stmts,
+            span: block.span,
+        };
+        id
+    }
+
+    fn stmt(&mut self, stmt: &'r Stmt, parent: Parent) -> StmtId {
+        // the statement is added before its children so the ids follow the source
+        let id = StmtId(self.hir.stmts.len());
+        self.hir.stmts.push(StmtKind::Break);
+        self.hir.stmt_spans.push(stmt.span());
+        self.hir.stmt_parents.push(parent);
+        let this = Parent::Stmt(id);
+        let kind = match stmt {
+            Stmt::Assign(assign) => self.assign(assign, this),
+            Stmt::Expr(e) => StmtKind::Expr(self.expr(e, this)),
+            Stmt::Return(_, e) => StmtKind::Return(e.as_ref().map(|e| self.expr(e, this))),
+            Stmt::Break(_) => StmtKind::Break,
+            Stmt::Continue(_) => StmtKind::Continue,
+            Stmt::If(chain) => {
+                let arms = chain
+                    .arms
+                    .iter()
+                    .map(|arm| (self.expr(&arm.cond, this), self.block(&arm.body, this)))
+                    .collect();
+                StmtKind::If(arms, self.block(&chain.otherwise, this))
+            }
+            Stmt::While { cond, body, .. } => {
+                StmtKind::While(self.expr(cond, this), self.block(body, this))
+            }
+            Stmt::For {
+                init,
+                cond,
+                update,
+                body,
+                ..
+            } => StmtKind::For {
+                init: self.assign_stmt(init, this),
+                cond: self.expr(cond, this),
+                update: self.assign_stmt(update, this),
+                body: self.block(body, this),
+            },
+        };
+        self.hir.stmts[id.0] = kind;
+        id
+    }
+
+    /// the initialization or the update of a `for`, lowered as a statement of its own.
+    fn assign_stmt(&mut self, assign: &'r Assign, parent: Parent) -> StmtId {
+        let id = StmtId(self.hir.stmts.len());
+        self.hir.stmts.push(StmtKind::Break);
+        self.hir.stmt_spans.push(assign.span);
+        self.hir.stmt_parents.push(parent);
+        self.hir.stmts[id.0] = self.assign(assign, Parent::Stmt(id));
+        id
+    }
+
+    fn assign(&mut self, assign: &'r Assign, parent: Parent) -> StmtKind {
+        let lhs = self.place(&assign.lhs, parent);
+        StmtKind::Assign(lhs, self.expr(&assign.rhs, parent))
+    }
+
+    fn place(&mut self, loc: &'r Location, parent: Parent) -> Place {
+        let var = self.resolve(loc.name());
+        match loc {
+            Location::Scalar(_) => Place::Var(var),
+            Location::Index { index, .. } => Place::Index(var, self.expr(index, parent)),
+        }
+    }
+
+    /// lowers `expr` before the expressions nested in it, in the order the
+    /// [`crate::ast::Visitor`] walks them so it gets the id it has in the root.
+    fn expr(&mut self, expr: &'r Expr, parent: Parent) -> ExprId {
+        let id = ExprId(self.hir.exprs.len());
+        self.hir.exprs.push(ExprKind::Len(0));
+        self.hir.expr_spans.push(expr.span());
+        self.hir.expr_parents.push(parent);
+        self.hir.types.push(self.root.type_of(id));
+        let this = Parent::Expr(id);
+        let kind = match expr {
+            Expr::IntLiteral(_, n) => ExprKind::Int(*n),
+            Expr::BoolLiteral(_, b) => ExprKind::Bool(*b),
+            Expr::Len(_, size) => ExprKind::Len(*size),
+            Expr::Loc(loc) => ExprKind::Load(self.place(loc, this)),
+            Expr::Not(_, e) => ExprKind::Not(self.expr(e, this)),
+            Expr::Neg(_, e) => ExprKind::Neg(self.expr(e, this)),
+            Expr::Nested(_, e) => ExprKind::Paren(self.expr(e, this)),
+            Expr::Ter { cond, yes, no, .. } => ExprKind::Ternary {
+                cond: self.expr(cond, this),
+                yes: self.expr(yes, this),
+                no: self.expr(no, this),
+            },
+            Expr::Arith { op, lhs, rhs, .. } => {
+                ExprKind::Arith(*op, self.expr(lhs, this), self.expr(rhs, this))
+            }
+            Expr::Rel { op, lhs, rhs, .. } => {
+                ExprKind::Rel(*op, self.expr(lhs, this), self.expr(rhs, this))
+            }
+            Expr::Eq { op, lhs, rhs, .. } => {
+                ExprKind::Eq(*op, self.expr(lhs, this), self.expr(rhs, this))
+            }
+            Expr::Cond { op, lhs, rhs, .. } => {
+                ExprKind::Cond(*op, self.expr(lhs, this), self.expr(rhs, this))
+            }
+            Expr::Call(Call::Extern { name, args, .. }) => {
+                let args = args
+                    .iter()
+                    .map(|arg| match arg {
+                        ExternArg::String(span, s) => Arg::String(*span, s.clone()),
+                        ExternArg::Array(span, name) => Arg::Array(*span, self.resolve(name)),
+                        ExternArg::Expr(e) => Arg::Expr(self.expr(e, this)),
+                    })
+                    .collect();
+                ExprKind::Call(self.resolve(name), args)
+            }
+            Expr::Call(Call::Decaf { name, args, .. }) => {
+                let args = args
+                    .iter()
+                    .map(|arg| Arg::Expr(self.expr(arg, this)))
+                    .collect();
+                ExprKind::Call(self.resolve(name), args)
+            }
+        };
+        self.hir.exprs[id.0] = kind;
+        id
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CODE: &str = "import printf;\nint x, a[2];\nint f(int x) {\n  int y;\n  \
+                        y = a[x] + 1;\n  if (y > 0) {\n    bool x;\n    x = !(y == 2);\n  }\n  \
+                        return y;\n}\nvoid main() {\n  for (x = 0; x < len(a); x++) {\n    \
+                        printf(\"%d\\n\", f(x), a);\n  }\n}\n";
+
+    fn hir() -> (Root, Hir) {
+        let root = crate::semantic_check(CODE.as_bytes(), "arena.dcf").unwrap();
+        let hir = Hir::lower(&root);
+        (root, hir)
+    }
+
+    #[test]
+    fn same_ids_as_root() {
+        let (root, hir) = hir();
+        let mut exprs = vec![];
+        root.for_each_expr(|id, expr| exprs.push((id, expr.span(), root.type_of(id))));
+        let lowered = hir
+            .exprs()
+            .map(|(id, _)| (id, hir.expr_span(id), hir.type_of(id)))
+            .collect::<Vec<_>>();
+        assert_eq!(lowered, exprs);
+    }
+
+    #[test]
+    fn resolved_names() {
+        let (_, hir) = hir();
+        let defs = hir
+            .defs()
+            .map(|(_, def)| (def.name.as_str(), def.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            defs,
+            [
+                ("printf", DefKind::Import),
+                ("x", DefKind::Global),
+                ("a", DefKind::Global),
+                ("f", DefKind::Method),
+                ("main", DefKind::Method),
+                ("x", DefKind::Param),
+                ("y", DefKind::Local),
+                ("x", DefKind::Local),
+            ]
+        );
+        // every `x` is resolved to the innermost `x` in scope
+        let stores = (0..)
+            .map(StmtId)
+            .take_while(|id| id.0 < hir.stmts.len())
+            .filter_map(|id| match hir.stmt(id) {
+                StmtKind::Assign(Place::Var(var), _) => Some(hir.def(*var).kind),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stores,
+            [
+                DefKind::Local,
+                DefKind::Local,
+                DefKind::Global,
+                DefKind::Global
+            ]
+        );
+        let a = hir.global("a").unwrap();
+        assert_eq!(hir.def(a).size, Some(2));
+        let calls = hir
+            .exprs()
+            .filter_map(|(_, e)| match e {
+                ExprKind::Call(callee, args) => Some((hir.def(*callee).name.as_str(), args.len())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(calls, [("printf", 3), ("f", 1)]);
+    }
+
+    #[test]
+    fn parents() {
+        let (_, hir) = hir();
+        let main = hir.methods().iter().find(|m| hir.def(m.def).name == "main");
+        let main = main.unwrap();
+        assert_eq!(hir.block_parent(main.body), Parent::Def(main.def));
+        let &[for_stmt] = &hir.block(main.body).stmts[..] else {
+            unreachable!()
+        };
+        let StmtKind::For {
+            init, cond, body, ..
+        } = hir.stmt(for_stmt)
+        else {
+            unreachable!()
+        };
+        assert_eq!(hir.stmt_parent(*init), Parent::Stmt(for_stmt));
+        assert_eq!(hir.expr_parent(*cond), Parent::Stmt(for_stmt));
+        assert_eq!(hir.block_parent(*body), Parent::Stmt(for_stmt));
+        // the call of `printf` is in a statement of the body of the `for`
+        let (arg, _) = hir
+            .exprs()
+            .find(|(_, e)| matches!(e, ExprKind::Call(_, args) if args.len() == 3))
+            .unwrap();
+        let Parent::Stmt(call) = hir.expr_parent(arg) else {
+            unreachable!()
+        };
+        assert_eq!(hir.stmt_parent(call), Parent::Block(*body));
+        assert_eq!(hir.type_of(arg), Some(Type::Int));
+        assert_eq!(hir.clone(), hir);
+    }
+}
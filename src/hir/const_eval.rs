@@ -0,0 +1,175 @@
+use crate::ast::{ArithOp, CondOp, EqOp, Expr, Literal, RelOp};
+use crate::span::HasSpan;
+
+/// the value of `expr` if it can be computed without running the program, i.e. it is built from
+/// literals and `len` with operators only. `None` if it is not constant, or if computing it
+/// overflows or divides by zero.
+pub fn const_eval(expr: &Expr) -> Option<Literal> {
+    match expr {
+        Expr::IntLiteral(_, n) => Some(Literal::Int(*n)),
+        Expr::BoolLiteral(_, b) => Some(Literal::Bool(*b)),
+        Expr::Len(_, size) => i64::try_from(*size).ok().map(Literal::Int),
+        Expr::Nested(_, e) => const_eval(e),
+        Expr::Not(_, e) => match const_eval(e)? {
+            Literal::Bool(b) => Some(Literal::Bool(!b)),
+            Literal::Int(_) => None,
+        },
+        Expr::Neg(_, e) => neg(int(e)?).ok().flatten().map(Literal::Int),
+        Expr::Ter { cond, yes, no, .. } => match const_eval(cond)? {
+            Literal::Bool(true) => const_eval(yes),
+            Literal::Bool(false) => const_eval(no),
+            Literal::Int(_) => None,
+        },
+        Expr::Arith { op, lhs, rhs, .. } => arith(*op, int(lhs)?, int(rhs)?)
+            .ok()
+            .flatten()
+            .map(Literal::Int),
+        Expr::Rel { op, lhs, rhs, .. } => {
+            let (lhs, rhs) = (int(lhs)?, int(rhs)?);
+            Some(Literal::Bool(match op {
+                RelOp::Less => lhs < rhs,
+                RelOp::LessEqual => lhs <= rhs,
+                RelOp::Greater => lhs > rhs,
+                RelOp::GreaterEqual => lhs >= rhs,
+            }))
+        }
+        Expr::Eq { op, lhs, rhs, .. } => {
+            let equal = const_eval(lhs)? == const_eval(rhs)?;
+            Some(Literal::Bool(match op {
+                EqOp::Equal => equal,
+                EqOp::NotEqual => !equal,
+            }))
+        }
+        Expr::Cond { op, lhs, rhs, .. } => {
+            let (lhs, rhs) = (boolean(lhs)?, boolean(rhs)?);
+            Some(Literal::Bool(match op {
+                CondOp::And => lhs && rhs,
+                CondOp::Or => lhs || rhs,
+            }))
+        }
+        Expr::Call(_) | Expr::Loc(_) => None,
+    }
+}
+
+fn int(expr: &Expr) -> Option<i64> {
+    match const_eval(expr)? {
+        Literal::Int(n) => Some(n),
+        Literal::Bool(_) => None,
+    }
+}
+
+fn boolean(expr: &Expr) -> Option<bool> {
+    match const_eval(expr)? {
+        Literal::Bool(b) => Some(b),
+        Literal::Int(_) => None,
+    }
+}
+
+/// the error of a constant operation whose result does not fit in an `int`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Overflow;
+
+fn neg(n: i64) -> Result<Option<i64>, Overflow> {
+    n.checked_neg().map(Some).ok_or(Overflow)
+}
+
+/// `lhs op rhs`, `None` for a division by zero which is left to the program to fail at.
+fn arith(op: ArithOp, lhs: i64, rhs: i64) -> Result<Option<i64>, Overflow> {
+    let value = match op {
+        ArithOp::Add => lhs.checked_add(rhs),
+        ArithOp::Sub => lhs.checked_sub(rhs),
+        ArithOp::Mul => lhs.checked_mul(rhs),
+        ArithOp::Div | ArithOp::Mod if rhs == 0 => return Ok(None),
+        ArithOp::Div => lhs.checked_div(rhs),
+        ArithOp::Mod => lhs.checked_rem(rhs),
+    };
+    value.map(Some).ok_or(Overflow)
+}
+
+/// whether folding the operator at the top of `expr` overflows, its operands are expected to be
+/// checked already as they are lowered before it.
+pub(super) fn check_overflow(expr: &Expr) -> Result<(), Overflow> {
+    match expr {
+        Expr::Neg(_, e) => int(e).map_or(Ok(()), |n| neg(n).map(|_| ())),
+        Expr::Arith { op, lhs, rhs, .. } => match (int(lhs), int(rhs)) {
+            (Some(lhs), Some(rhs)) => arith(*op, lhs, rhs).map(|_| ()),
+            _ => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// `cond` replaced by its value if it is constant, e.g. `while (1 < 2)` is checked and printed
+/// as `while (true)`.
+pub(super) fn fold_condition(cond: Expr) -> Expr {
+    match const_eval(&cond) {
+        Some(Literal::Bool(value)) => Expr::BoolLiteral(cond.span(), value),
+        _ => cond,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        ast::Stmt,
+        error::{Diagnostic, Severity},
+        hir::Root,
+    };
+
+    fn body(stmts: &str) -> Result<Root, Vec<Diagnostic>> {
+        let code = format!("int a[3];\nvoid main() {{\n  int x;\n  {stmts}\n}}\n");
+        crate::semantic_check(code.as_bytes(), "const.dcf")
+    }
+
+    fn returned(expr: &str) -> Option<Literal> {
+        let root = crate::semantic_check(
+            format!("int a[3];\nint f(int x) {{\n  return {expr};\n}}\nvoid main() {{}}\n")
+                .as_bytes(),
+            "const.dcf",
+        )
+        .unwrap();
+        let Some(Stmt::Return(_, Some(expr))) = root.functions["f"].body.stmts.first() else {
+            unreachable!()
+        };
+        const_eval(expr)
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(returned("1 + 2 * 3"), Some(Literal::Int(7)));
+        assert_eq!(returned("-(len(a) - 5) % 2"), Some(Literal::Int(0)));
+        assert_eq!(returned("(2 > 1 && !false) ? 4 : 5"), Some(Literal::Int(4)));
+        assert_eq!(returned("'a' == 97 ? 1 : 0"), Some(Literal::Int(1)));
+        assert_eq!(returned("x + 1"), None);
+        assert_eq!(returned("1 / 0"), None);
+    }
+
+    #[test]
+    fn folded_conditions() {
+        let root = body("while (len(a) > 2 || x > 0) {\n    x += 1;\n  }").unwrap();
+        let Some(Stmt::While { cond, .. }) = root.functions["main"].body.stmts.first() else {
+            unreachable!()
+        };
+        assert!(matches!(cond, Expr::Cond { .. }));
+        let root = body("if (len(a) > 2) {\n    x = 1;\n  }").unwrap();
+        let Some(Stmt::If(chain)) = root.functions["main"].body.stmts.first() else {
+            unreachable!()
+        };
+        assert!(matches!(chain.arms[0].cond, Expr::BoolLiteral(_, true)));
+    }
+
+    #[test]
+    fn overflow() {
+        let errors =
+            body("x = 9223372036854775807 + (1 + x);\n  x = 4611686018427387904 * 2;").unwrap_err();
+        let errors = errors
+            .iter()
+            .filter(|e| e.severity() == Severity::Error)
+            .map(|e| (e.code().unwrap().code, e.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, [("S0033", (5, 7))]);
+        assert!(body("x = -(-9223372036854775807 - 1);").is_err());
+        assert!(body("x = -9223372036854775807 - 1;").is_ok());
+    }
+}
@@ -0,0 +1,353 @@
+//! a dump of a checked program that only changes when what the program means changes, to read
+//! what the semantic checks made of a program and to compare it in tests.
+//!
+//! each node is a line indented by two spaces for each node it is nested in, a line starts with
+//! the kind of the node followed by what it holds, and the expressions end with `: ` and their
+//! type (`void` for a call of a method without a value):
+//!
+//! ```text
+//! root
+//!   import printf
+//!   global int a[2]
+//!   method int f
+//!     param int x
+//!     block
+//!       local bool b
+//!       return
+//!         binary +: int
+//!           index a: int
+//!             int 0: int
+//!           load x: int
+//! ```
+//!
+//! the imports are ordered by name, the other declarations are in the order of the source and the
+//! spans are left out so reformatting the source does not change the dump. the compound
+//! assignments, the increments and the decrements are dumped as the plain assignments they are
+//! checked as.
+
+use crate::ast::*;
+use crate::span::HasSpan;
+use std::fmt::Display;
+
+/// the dump of a program, see the [module documentation](self) for the format.
+pub struct RootDisplay<'r> {
+    root: &'r Root,
+}
+
+impl Root {
+    /// the program in the stable text format of the [`RootDisplay`].
+    pub fn display(&self) -> RootDisplay<'_> {
+        RootDisplay { root: self }
+    }
+}
+
+impl Display for RootDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut dump = Dump::default();
+        dump.root(self.root);
+        write!(f, "{}", dump.out)
+    }
+}
+
+fn ty(ty: Option<Type>) -> String {
+    ty.map_or("void".to_string(), |ty| ty.to_string())
+}
+
+fn var(var: &Var) -> String {
+    match var {
+        Var::Scalar(var) => format!("{} {}", var.r#type(), var.val()),
+        Var::Array { arr, size } => format!("{} {}[{}]", arr.r#type(), arr.val(), size),
+    }
+}
+
+/// the variables of `vars` in the order they are declared in.
+fn in_order<'v>(vars: impl IntoIterator<Item = &'v Var>) -> Vec<&'v Var> {
+    let mut vars = vars.into_iter().collect::<Vec<_>>();
+    vars.sort_by_key(|var| var.span().offset());
+    vars
+}
+
+fn binary_op(expr: &Expr) -> Option<(&'static str, &Expr, &Expr)> {
+    let (op, lhs, rhs) = match expr {
+        Expr::Arith { op, lhs, rhs, .. } => (
+            match op {
+                ArithOp::Add => "+",
+                ArithOp::Sub => "-",
+                ArithOp::Mul => "*",
+                ArithOp::Div => "/",
+                ArithOp::Mod => "%",
+            },
+            lhs,
+            rhs,
+        ),
+        Expr::Rel { op, lhs, rhs, .. } => (
+            match op {
+                RelOp::Less => "<",
+                RelOp::LessEqual => "<=",
+                RelOp::Greater => ">",
+                RelOp::GreaterEqual => ">=",
+            },
+            lhs,
+            rhs,
+        ),
+        Expr::Eq { op, lhs, rhs, .. } => (
+            match op {
+                EqOp::Equal => "==",
+                EqOp::NotEqual => "!=",
+            },
+            lhs,
+            rhs,
+        ),
+        Expr::Cond { op, lhs, rhs, .. } => (
+            match op {
+                CondOp::And => "&&",
+                CondOp::Or => "||",
+            },
+            lhs,
+            rhs,
+        ),
+        _ => return None,
+    };
+    Some((op, lhs, rhs))
+}
+
+#[derive(Debug, Default)]
+struct Dump {
+    depth: usize,
+    out: String,
+}
+
+impl Dump {
+    fn line(&mut self, text: &str) {
+        self.out.push_str(&"  ".repeat(self.depth));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// writes the line of a node and the nodes `children` writes nested in it.
+    fn node(&mut self, text: &str, children: impl FnOnce(&mut Self)) {
+        self.line(text);
+        self.depth += 1;
+        children(self);
+        self.depth -= 1;
+    }
+
+    fn root(&mut self, root: &Root) {
+        self.node("root", |dump| {
+            let mut imports = root.imports.keys().collect::<Vec<_>>();
+            imports.sort();
+            imports
+                .into_iter()
+                .for_each(|import| dump.line(&format!("import {}", import)));
+            in_order(root.globals.values())
+                .into_iter()
+                .for_each(|global| dump.line(&format!("global {}", var(global))));
+            let mut functions = root.functions.values().collect::<Vec<_>>();
+            functions.sort_by_key(|func| func.span.offset());
+            functions.into_iter().for_each(|func| dump.function(func));
+        })
+    }
+
+    fn function(&mut self, func: &Function) {
+        self.node(&format!("method {} {}", ty(func.ret), func.name), |dump| {
+            func.params
+                .iter()
+                .for_each(|param| dump.line(&format!("param {}", var(&func.args[param]))));
+            dump.block(&func.body);
+        })
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.node("block", |dump| {
+            in_order(block.decls.values())
+                .into_iter()
+                .for_each(|local| dump.line(&format!("local {}", var(local))));
+            block.stmts.iter().for_each(|stmt| dump.stmt(stmt));
+        })
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Assign(assign) => self.assign(assign),
+            Stmt::Expr(e) => self.node("expr", |dump| dump.expr(e)),
+            Stmt::Return(_, e) => self.node("return", |dump| {
+                if let Some(e) = e {
+                    dump.expr(e)
+                }
+            }),
+            Stmt::Break(_) => self.line("break"),
+            Stmt::Continue(_) => self.line("continue"),
+            Stmt::If(chain) => self.node("if", |dump| {
+                chain.arms.iter().for_each(|arm| {
+                    dump.node("arm", |dump| {
+                        dump.expr(&arm.cond);
+                        dump.block(&arm.body);
+                    })
+                });
+                let otherwise = &chain.otherwise;
+                if !(otherwise.decls.is_empty() && otherwise.stmts.is_empty()) {
+                    dump.node("else", |dump| dump.block(otherwise))
+                }
+            }),
+            Stmt::While { cond, body, .. } => self.node("while", |dump| {
+                dump.expr(cond);
+                dump.block(body);
+            }),
+            Stmt::For {
+                init,
+                cond,
+                update,
+                body,
+                ..
+            } => self.node("for", |dump| {
+                dump.assign(init);
+                dump.expr(cond);
+                dump.assign(update);
+                dump.block(body);
+            }),
+        }
+    }
+
+    fn assign(&mut self, assign: &Assign) {
+        self.node("assign", |dump| {
+            let lhs = format!("store {}: {}", assign.lhs.name(), assign.lhs.r#type());
+            match &assign.lhs {
+                Location::Scalar(_) => dump.line(&lhs),
+                Location::Index { index, .. } => dump.node(&lhs, |dump| dump.expr(index)),
+            }
+            dump.expr(&assign.rhs);
+        })
+    }
+
+    fn call(&mut self, call: &Call) {
+        match call {
+            Call::Extern { name, args, .. } => self.node(&format!("call {}: int", name), |dump| {
+                args.iter().for_each(|arg| match arg {
+                    ExternArg::String(_, s) => dump.line(&format!("string {}", s)),
+                    ExternArg::Array(_, name) => dump.line(&format!("array {}", name)),
+                    ExternArg::Expr(e) => dump.expr(e),
+                })
+            }),
+            Call::Decaf {
+                name, ret, args, ..
+            } => self.node(&format!("call {}: {}", name, ty(*ret)), |dump| {
+                args.iter().for_each(|arg| dump.expr(arg))
+            }),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        let typed = |text: &str| format!("{}: {}", text, expr.r#type());
+        if let Some((op, lhs, rhs)) = binary_op(expr) {
+            return self.node(&typed(&format!("binary {}", op)), |dump| {
+                dump.expr(lhs);
+                dump.expr(rhs);
+            });
+        }
+        match expr {
+            Expr::IntLiteral(_, n) => self.line(&typed(&format!("int {}", n))),
+            Expr::BoolLiteral(_, b) => self.line(&typed(&format!("bool {}", b))),
+            Expr::Len(_, size) => self.line(&typed(&format!("len {}", size))),
+            Expr::Loc(loc) => match &**loc {
+                Location::Scalar(var) => self.line(&typed(&format!("load {}", var.val()))),
+                Location::Index { arr, index, .. } => self
+                    .node(&typed(&format!("index {}", arr.val())), |dump| {
+                        dump.expr(index)
+                    }),
+            },
+            Expr::Not(_, e) => self.node(&typed("not"), |dump| dump.expr(e)),
+            Expr::Neg(_, e) => self.node(&typed("neg"), |dump| dump.expr(e)),
+            Expr::Nested(_, e) => self.node(&typed("paren"), |dump| dump.expr(e)),
+            Expr::Ter { cond, yes, no, .. } => self.node(&typed("ternary"), |dump| {
+                dump.expr(cond);
+                dump.expr(yes);
+                dump.expr(no);
+            }),
+            Expr::Call(call) => self.call(call),
+            _ => unreachable!("binary expressions are dumped above"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    const CODE: &str = "import printf;\nint x, a[2];\nint f(int y) {\n  bool b;\n  \
+                        if (y > 0) {\n    a[0] += y;\n  } else {\n    if (b) {\n      \
+                        return -(y);\n    }\n  }\n  return b ? len(a) : x;\n}\n\
+                        void main() {\n  for (x = 0; x < 2; x++) {\n    \
+                        printf(\"%d\\n\", f(x));\n    break;\n  }\n}\n";
+
+    const DUMP: &str = "\
+root
+  import printf
+  global int x
+  global int a[2]
+  method int f
+    param int y
+    block
+      local bool b
+      if
+        arm
+          binary >: bool
+            load y: int
+            int 0: int
+          block
+            assign
+              store a: int
+                int 0: int
+              binary +: int
+                index a: int
+                  int 0: int
+                load y: int
+        arm
+          load b: bool
+          block
+            return
+              neg: int
+                paren: int
+                  load y: int
+      return
+        ternary: int
+          load b: bool
+          len 2: int
+          load x: int
+  method void main
+    block
+      for
+        assign
+          store x: int
+          int 0: int
+        binary <: bool
+          load x: int
+          int 2: int
+        assign
+          store x: int
+          binary +: int
+            load x: int
+            int 1: int
+        block
+          expr
+            call printf: int
+              string \"%d\\n\"
+              call f: int
+                load x: int
+          break
+";
+
+    #[test]
+    fn dump() {
+        let root = crate::semantic_check(CODE.as_bytes(), "dump.dcf").unwrap();
+        assert_eq!(root.display().to_string(), DUMP);
+    }
+
+    #[test]
+    fn stable() {
+        // the layout of the source and the order of the imports do not change the dump
+        let code = CODE
+            .replace("import printf;\n", "import scanf;\nimport printf;\n")
+            .replace("\n  ", "\n\t");
+        let root = crate::semantic_check(code.as_bytes(), "dump.dcf").unwrap();
+        let expected = DUMP.replace("  import printf\n", "  import printf\n  import scanf\n");
+        assert_eq!(root.display().to_string(), expected);
+    }
+}
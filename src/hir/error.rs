@@ -1,4 +1,4 @@
-use crate::{error::*, ast::Type, span::*};
+use crate::{error::{codes::*, *}, ast::Type, lexer::IntLitError, span::*};
 
 #[derive(Debug)]
 pub enum Error<'a> {
@@ -10,7 +10,12 @@ pub enum Error<'a> {
     ExpectedBoolExpr(Span<'a>),
     ExpectedIntExpr(Span<'a>),
     ReturnValueFromVoid(Span<'a>),
+    /// a name declared again in the same scope, with its previous declaration.
     Redifinition(Span<'a>, Span<'a>),
+    DuplicateParameter {
+        param: Span<'a>,
+        previous: Span<'a>,
+    },
     BreakOutsideLoop(Span<'a>),
     ContinueOutsideLoop(Span<'a>),
     VoidFuncAsExpr(Span<'a>),
@@ -32,7 +37,8 @@ pub enum Error<'a> {
     },
     ExpectedExpression(Span<'a>),
     ZeroArraySize(Span<'a>),
-    TooLargeInt(Span<'a>),
+    /// an integer literal without a 64-bit value, checked with its sign.
+    TooLargeInt(IntLitError<'a>),
     RootDoesNotContainMain,
     InvalidMainSig(Span<'a>),
     VariableNotAMethod(Span<'a>),
@@ -44,6 +50,69 @@ pub enum Error<'a> {
     },
     IncNonInt(Span<'a>),
     DecNonInt(Span<'a>),
+    MethodNotAVariable(Span<'a>),
+    CannotIndexExpr {
+        span: Span<'a>,
+        ty: Type,
+    },
+    /// a returned value that does not have the return type of the method.
+    ReturnTypeMismatch {
+        expected: Type,
+        found: Type,
+        span: Span<'a>,
+    },
+    /// an array variable passed to a method that is not imported.
+    ArrayInUserDefined(Span<'a>),
+    /// `+=` or `-=` applied to a variable that is not an `int`.
+    CompoundAssignNonInt(Span<'a>),
+    /// a constant operation whose result does not fit in an `int`, found while folding it.
+    ConstantOverflow(Span<'a>),
+    /// a method with a return type that can reach the `}` closing its body.
+    MissingReturn {
+        method: Span<'a>,
+        end: Span<'a>,
+    },
+}
+
+impl<'a> Error<'a> {
+    /// the span the error is reported at, `None` for errors about the whole program.
+    pub fn span(&self) -> Option<Span<'a>> {
+        match *self {
+            Self::UndeclaredIdentifier(span)
+            | Self::ExpectedArray(span)
+            | Self::ExpectedScalarVariable(span)
+            | Self::CannotIndexScalar(span)
+            | Self::CannotAssignToArray(span)
+            | Self::ExpectedBoolExpr(span)
+            | Self::ExpectedIntExpr(span)
+            | Self::ReturnValueFromVoid(span)
+            | Self::Redifinition(span, _)
+            | Self::DuplicateParameter { param: span, .. }
+            | Self::BreakOutsideLoop(span)
+            | Self::ContinueOutsideLoop(span)
+            | Self::VoidFuncAsExpr(span)
+            | Self::TypeMismatch { lspan: span, .. }
+            | Self::WrongNumberOfArgs { span, .. }
+            | Self::ExpectedType { span, .. }
+            | Self::ExpectedExpression(span)
+            | Self::ZeroArraySize(span)
+            | Self::TooLargeInt(IntLitError::Overflow(span) | IntLitError::InvalidDigit(span))
+            | Self::InvalidMainSig(span)
+            | Self::VariableNotAMethod(span)
+            | Self::StringInUserDefined(span)
+            | Self::AssignOfDifferentType { lhs: span, .. }
+            | Self::IncNonInt(span)
+            | Self::DecNonInt(span)
+            | Self::MethodNotAVariable(span)
+            | Self::CannotIndexExpr { span, .. }
+            | Self::ReturnTypeMismatch { span, .. }
+            | Self::ArrayInUserDefined(span)
+            | Self::CompoundAssignNonInt(span)
+            | Self::ConstantOverflow(span)
+            | Self::MissingReturn { end: span, .. } => Some(span),
+            Self::RootDoesNotContainMain => None,
+        }
+    }
 }
 
 impl CCError for Error<'_> {
@@ -113,16 +182,14 @@ impl CCError for Error<'_> {
                 ),
                 span.position(),
             )],
-            Self::Redifinition(lhs, rhs) => vec![
-                (
-                    format!("Redifinition of `{}`", lhs.to_string()),
-                    lhs.position(),
-                ),
-                (
-                    format!("Previous definition of `{}`", rhs.to_string()),
-                    rhs.position(),
-                ),
-            ],
+            Self::Redifinition(lhs, _) => vec![(
+                format!("Redifinition of `{}`", lhs.to_string()),
+                lhs.position(),
+            )],
+            Self::DuplicateParameter { param, .. } => vec![(
+                format!("duplicate parameter `{}`", param.to_string()),
+                param.position(),
+            )],
             Self::BreakOutsideLoop(span) => vec![(
                 format!("Break outside loop `{}`", span.to_string()),
                 span.position(),
@@ -184,10 +251,21 @@ impl CCError for Error<'_> {
                 format!("array size cannot be zero `{}`", span.to_string()),
                 span.position(),
             )],
-            Self::TooLargeInt(span) => vec![(
-                format!("integer literal is too large `{}`", span.to_string()),
+            Self::TooLargeInt(err) => err.msgs(),
+            Self::ConstantOverflow(span) => vec![(
+                format!(
+                    "arithmetic overflow in constant expression `{}`",
+                    span.to_string()
+                ),
                 span.position(),
             )],
+            Self::MissingReturn { method, end } => vec![(
+                format!(
+                    "method `{}` with non-void return type may not return a value on all paths",
+                    method.to_string()
+                ),
+                end.position(),
+            )],
             Self::RootDoesNotContainMain => {
                 vec![("root does not contain main function".to_string(), (0, 0))]
             }
@@ -205,10 +283,201 @@ impl CCError for Error<'_> {
                 format!("variable `{}` is not a method", span.to_string()),
                 span.position(),
             )],
+            Self::MethodNotAVariable(span) => vec![(
+                format!(
+                    "method `{}` is not a variable, it can only be called",
+                    span.to_string()
+                ),
+                span.position(),
+            )],
+            Self::CannotIndexExpr { span, ty } => vec![(
+                format!(
+                    "cannot index `{}` of type `{}`, only array variables can be indexed",
+                    span.to_string(),
+                    ty
+                ),
+                span.position(),
+            )],
             Self::StringInUserDefined(span) => vec![(
                 format!("string literal `{}` in user defined type", span.to_string()),
                 span.position(),
             )],
+            Self::ReturnTypeMismatch {
+                expected,
+                found,
+                span,
+            } => vec![(
+                format!(
+                    "cannot return `{}` of type `{}` from a method returning `{}`",
+                    span.to_string(),
+                    found,
+                    expected
+                ),
+                span.position(),
+            )],
+            Self::ArrayInUserDefined(span) => vec![(
+                format!(
+                    "array `{}` can only be passed to imported methods",
+                    span.to_string()
+                ),
+                span.position(),
+            )],
+            Self::CompoundAssignNonInt(span) => vec![(
+                format!(
+                    "cannot add to or subtract from non-integer variable: {}",
+                    span.to_string()
+                ),
+                span.position(),
+            )],
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        match *self {
+            Self::Redifinition(_, previous) => vec![Label::new(
+                previous,
+                format!("previous declaration of `{}` here", previous.to_string()),
+            )],
+            Self::DuplicateParameter { previous, .. } => vec![Label::new(
+                previous,
+                format!("`{}` is first declared here", previous.to_string()),
+            )],
+            _ => vec![],
+        }
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.span().and_then(|span| span.source_name())
+    }
+
+    fn code(&self) -> Option<ErrorCode> {
+        Some(match self {
+            Self::UndeclaredIdentifier(_) => UNDECLARED_IDENTIFIER,
+            Self::ExpectedArray(_) => EXPECTED_ARRAY,
+            Self::ExpectedScalarVariable(_) => EXPECTED_SCALAR_VARIABLE,
+            Self::CannotIndexScalar(_) => CANNOT_INDEX_SCALAR,
+            Self::CannotAssignToArray(_) => CANNOT_ASSIGN_TO_ARRAY,
+            Self::ExpectedBoolExpr(_) => EXPECTED_BOOL_EXPR,
+            Self::ExpectedIntExpr(_) => EXPECTED_INT_EXPR,
+            Self::ReturnValueFromVoid(_) => RETURN_VALUE_FROM_VOID,
+            Self::Redifinition(..) => REDEFINITION,
+            Self::DuplicateParameter { .. } => DUPLICATE_PARAMETER,
+            Self::BreakOutsideLoop(_) => BREAK_OUTSIDE_LOOP,
+            Self::ContinueOutsideLoop(_) => CONTINUE_OUTSIDE_LOOP,
+            Self::VoidFuncAsExpr(_) => VOID_FUNC_AS_EXPR,
+            Self::TypeMismatch { .. } => TYPE_MISMATCH,
+            Self::WrongNumberOfArgs { .. } => WRONG_NUMBER_OF_ARGS,
+            Self::ExpectedType { .. } => EXPECTED_TYPE,
+            Self::ExpectedExpression(_) => EXPECTED_EXPRESSION,
+            Self::ZeroArraySize(_) => ZERO_ARRAY_SIZE,
+            Self::TooLargeInt(_) => TOO_LARGE_INT,
+            Self::RootDoesNotContainMain => ROOT_DOES_NOT_CONTAIN_MAIN,
+            Self::InvalidMainSig(_) => INVALID_MAIN_SIG,
+            Self::VariableNotAMethod(_) => VARIABLE_NOT_A_METHOD,
+            Self::StringInUserDefined(_) => STRING_IN_USER_DEFINED,
+            Self::AssignOfDifferentType { .. } => ASSIGN_OF_DIFFERENT_TYPE,
+            Self::IncNonInt(_) => INC_NON_INT,
+            Self::DecNonInt(_) => DEC_NON_INT,
+            Self::MethodNotAVariable(_) => METHOD_NOT_A_VARIABLE,
+            Self::CannotIndexExpr { .. } => CANNOT_INDEX_EXPR,
+            Self::ReturnTypeMismatch { .. } => RETURN_TYPE_MISMATCH,
+            Self::ArrayInUserDefined(_) => ARRAY_IN_USER_DEFINED,
+            Self::CompoundAssignNonInt(_) => COMPOUND_ASSIGN_NON_INT,
+            Self::ConstantOverflow(_) => CONSTANT_OVERFLOW,
+            Self::MissingReturn { .. } => MISSING_RETURN,
+        })
+    }
+}
+
+/// diagnostics of the semantic phase that do not make the program invalid.
+#[derive(Debug)]
+pub enum Warning<'a> {
+    UseBeforeAssign(Span<'a>),
+    ShadowsImport { decl: Span<'a>, import: Span<'a> },
+    RedundantParens(Span<'a>),
+    /// a statement after a `return`, `break` or `continue` that can never run.
+    UnreachableCode(Span<'a>),
+    /// a local that is declared but never referred to.
+    UnusedVariable(Span<'a>),
+    /// a local of a nested block that has the name of a parameter of its method.
+    ShadowsParameter { decl: Span<'a>, param: Span<'a> },
+}
+
+impl<'a> Warning<'a> {
+    /// the span the warning is reported at.
+    pub fn span(&self) -> Span<'a> {
+        match *self {
+            Self::UseBeforeAssign(span)
+            | Self::ShadowsImport { decl: span, .. }
+            | Self::RedundantParens(span)
+            | Self::UnreachableCode(span)
+            | Self::UnusedVariable(span)
+            | Self::ShadowsParameter { decl: span, .. } => span,
+        }
+    }
+}
+
+impl CCError for Warning<'_> {
+    fn msgs(&self) -> Vec<(String, (usize, usize))> {
+        match self {
+            Self::UseBeforeAssign(span) => vec![(
+                format!(
+                    "variable `{}` may be used before it is assigned",
+                    span.to_string()
+                ),
+                span.position(),
+            )],
+            Self::ShadowsImport { decl, .. } => vec![(
+                format!(
+                    "`{}` shadows an import, it cannot be called in this scope",
+                    decl.to_string()
+                ),
+                decl.position(),
+            )],
+            Self::RedundantParens(span) => vec![(
+                format!("redundant parentheses: {}", span.to_string()),
+                span.position(),
+            )],
+            Self::UnreachableCode(span) => {
+                vec![("unreachable statement".to_string(), span.position())]
+            }
+            Self::UnusedVariable(span) => vec![(
+                format!("unused variable `{}`", span.to_string()),
+                span.position(),
+            )],
+            Self::ShadowsParameter { decl, .. } => vec![(
+                format!("`{}` shadows a parameter", decl.to_string()),
+                decl.position(),
+            )],
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        match *self {
+            Self::ShadowsImport { import, .. } => vec![Label::new(
+                import,
+                format!("`{}` is imported here", import.to_string()),
+            )],
+            Self::ShadowsParameter { param, .. } => vec![Label::new(
+                param,
+                format!("`{}` is declared here", param.to_string()),
+            )],
+            _ => vec![],
         }
     }
+
+    fn source_name(&self) -> Option<&str> {
+        self.span().source_name()
+    }
+
+    fn code(&self) -> Option<ErrorCode> {
+        Some(match self {
+            Self::UseBeforeAssign(_) => USE_BEFORE_ASSIGN,
+            Self::ShadowsImport { .. } => SHADOWS_IMPORT,
+            Self::RedundantParens(_) => REDUNDANT_PARENS,
+            Self::UnreachableCode(_) => UNREACHABLE_CODE,
+            Self::UnusedVariable(_) => UNUSED_VARIABLE,
+            Self::ShadowsParameter { .. } => SHADOWS_PARAMETER,
+        })
+    }
 }
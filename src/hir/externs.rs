@@ -0,0 +1,462 @@
+//! the signatures of the imported methods, so the calls to them can be checked and compiled.
+//!
+//! an import only gives the name of the method, its signature is either inferred from the calls
+//! of the program or declared in an interface file, a file next to the program with a line for
+//! each method in the syntax of a declaration without a body:
+//!
+//! ```text
+//! // the methods of the c library
+//! int printf(string, ...);
+//! int abs(int);
+//! int sum(int[], int);
+//! ```
+//!
+//! the parameters are `int`, `bool`, `string` or an array (`int[]` or `bool[]`), a trailing `...`
+//! accepts any number of arguments after them. the return type can be left out, an imported
+//! method always returns an `int`.
+
+use crate::{
+    ast::{Call, ExternArg, Root, Type, Visitor},
+    cst::{self, Arg, AssignExpr, Block, Expr, Location, PRoot, PStmt},
+    error::{codes::*, CCError},
+    hir::error::Error::{self, WrongNumberOfArgs},
+};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+/// the kind of a value passed to an imported method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternType {
+    Int,
+    Bool,
+    String,
+    Array,
+}
+
+impl Display for ExternType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int => write!(f, "int"),
+            Self::Bool => write!(f, "bool"),
+            Self::String => write!(f, "string"),
+            Self::Array => write!(f, "int[]"),
+        }
+    }
+}
+
+impl ExternType {
+    fn of(arg: &ExternArg) -> Self {
+        match arg {
+            ExternArg::String(..) => Self::String,
+            ExternArg::Array(..) => Self::Array,
+            ExternArg::Expr(e) => match e.r#type() {
+                Type::Int => Self::Int,
+                Type::Bool => Self::Bool,
+            },
+        }
+    }
+}
+
+/// the parameters of an imported method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternSig {
+    pub params: Vec<ExternType>,
+    /// whether the method takes any number of arguments after its parameters.
+    pub variadic: bool,
+}
+
+impl ExternSig {
+    /// whether the method can be called with `count` arguments.
+    pub fn accepts(&self, count: usize) -> bool {
+        count == self.params.len() || self.variadic && count > self.params.len()
+    }
+}
+
+/// the parameters in parentheses, as they are written in an interface file.
+impl Display for ExternSig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params = self
+            .params
+            .iter()
+            .map(ToString::to_string)
+            .chain(self.variadic.then(|| "...".to_string()))
+            .collect::<Vec<_>>();
+        write!(f, "({})", params.join(", "))
+    }
+}
+
+/// an imported method with the arguments of each call of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extern {
+    pub name: String,
+    /// the signature of the interface file.
+    pub declared: Option<ExternSig>,
+    /// the kinds of the arguments of each call, in the order the calls are walked in.
+    pub calls: Vec<Vec<ExternType>>,
+}
+
+impl Extern {
+    /// the declared signature, or the one inferred from the calls: the arguments the calls agree
+    /// on, variadic if they do not all have these arguments only. `None` for a method that is
+    /// neither declared nor called.
+    pub fn signature(&self) -> Option<ExternSig> {
+        if let Some(declared) = &self.declared {
+            return Some(declared.clone());
+        }
+        let (first, rest) = self.calls.split_first()?;
+        let common = rest.iter().fold(first.len(), |common, call| {
+            first
+                .iter()
+                .zip(call)
+                .take(common)
+                .take_while(|(lhs, rhs)| lhs == rhs)
+                .count()
+        });
+        Some(ExternSig {
+            params: first[..common].to_vec(),
+            variadic: self.calls.iter().any(|call| call.len() != common),
+        })
+    }
+}
+
+/// the signatures of the methods declared in an interface file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interface {
+    sigs: HashMap<String, ExternSig>,
+}
+
+/// an invalid line of an interface file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceError {
+    pub msg: String,
+    pub pos: (usize, usize),
+}
+
+impl CCError for InterfaceError {
+    fn msgs(&self) -> Vec<(String, (usize, usize))> {
+        vec![(self.msg.clone(), self.pos)]
+    }
+
+    fn code(&self) -> Option<ErrorCode> {
+        Some(INVALID_INTERFACE)
+    }
+}
+
+fn param(param: &str) -> Option<ExternType> {
+    match param {
+        "int" => Some(ExternType::Int),
+        "bool" => Some(ExternType::Bool),
+        "string" => Some(ExternType::String),
+        "int[]" | "bool[]" => Some(ExternType::Array),
+        _ => None,
+    }
+}
+
+/// the name and the signature of the declaration `decl`, without its `;`.
+fn declaration(decl: &str) -> Result<(&str, ExternSig), String> {
+    let decl = decl.strip_prefix("int ").unwrap_or(decl).trim_start();
+    let (name, params) = decl
+        .strip_suffix(')')
+        .and_then(|decl| decl.split_once('('))
+        .ok_or_else(|| format!("expected `name(parameters);`, found `{}`", decl))?;
+    let name = name.trim_end();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("`{}` is not a method name", name));
+    }
+    let mut params = params
+        .split(',')
+        .map(|param| param.split_whitespace().collect::<String>())
+        .collect::<Vec<_>>();
+    if params == [""] {
+        params.clear();
+    }
+    let variadic = params.last().is_some_and(|last| last == "...");
+    if variadic {
+        params.pop();
+    }
+    let params = params
+        .iter()
+        .map(|p| param(p).ok_or_else(|| format!("unknown parameter type `{}`", p)))
+        .collect::<Result<_, _>>()?;
+    Ok((name, ExternSig { params, variadic }))
+}
+
+impl Interface {
+    /// parses the text of an interface file, see the [module documentation](self) for its syntax.
+    pub fn parse(text: &str) -> Result<Self, InterfaceError> {
+        let mut sigs = HashMap::new();
+        for (line, text) in text.lines().enumerate() {
+            let decl = text.split("//").next().unwrap_or_default();
+            if decl.trim().is_empty() {
+                continue;
+            }
+            let pos = (line + 1, decl.len() - decl.trim_start().len() + 1);
+            let error = |msg| InterfaceError { msg, pos };
+            let decl = decl
+                .trim()
+                .strip_suffix(';')
+                .ok_or_else(|| error("expected `;` at the end of the declaration".to_string()))?;
+            let (name, sig) = declaration(decl.trim_end()).map_err(error)?;
+            if sigs.insert(name.to_string(), sig).is_some() {
+                return Err(error(format!("method `{}` is declared twice", name)));
+            }
+        }
+        Ok(Self { sigs })
+    }
+
+    /// the declared signature of the method `name`.
+    pub fn get(&self, name: &str) -> Option<&ExternSig> {
+        self.sigs.get(name)
+    }
+}
+
+/// collects the arguments of the calls to imported methods.
+struct ExternCalls<'e> {
+    externs: &'e mut BTreeMap<String, Extern>,
+}
+
+impl Visitor for ExternCalls<'_> {
+    fn visit_call(&mut self, call: &Call) {
+        if let Call::Extern { name, args, .. } = call {
+            if let Some(import) = self.externs.get_mut(name) {
+                import.calls.push(args.iter().map(ExternType::of).collect());
+            }
+        }
+        crate::ast::visit::walk_call(self, call)
+    }
+}
+
+/// the imported methods of a program, ordered by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Externs {
+    externs: BTreeMap<String, Extern>,
+}
+
+impl Externs {
+    /// the imports of `root` with the signatures `interface` declares for them and the arguments
+    /// they are called with.
+    pub fn new(root: &Root, interface: &Interface) -> Self {
+        let mut externs = root
+            .imports
+            .keys()
+            .map(|name| {
+                let import = Extern {
+                    name: name.clone(),
+                    declared: interface.get(name).cloned(),
+                    calls: vec![],
+                };
+                (name.clone(), import)
+            })
+            .collect();
+        ExternCalls {
+            externs: &mut externs,
+        }
+        .visit_root(root);
+        Self { externs }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Extern> {
+        self.externs.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Extern> {
+        self.externs.values()
+    }
+}
+
+/// collects the calls of a program with their arguments.
+fn calls<'s, 'a>(root: &'s PRoot<'a>) -> Vec<&'s cst::Call<'a>> {
+    fn expr<'s, 'a>(e: &'s Expr<'a>, calls: &mut Vec<&'s cst::Call<'a>>) {
+        match e {
+            Expr::Loc(loc) => location(loc, calls),
+            Expr::Nested(_, e) | Expr::Not(_, e) | Expr::Neg(_, e) => expr(e, calls),
+            Expr::Ter { cond, yes, no, .. } => {
+                expr(cond, calls);
+                expr(yes, calls);
+                expr(no, calls);
+            }
+            Expr::BinOp { lhs, rhs, .. }
+            | Expr::Index {
+                base: lhs,
+                offset: rhs,
+                ..
+            } => {
+                expr(lhs, calls);
+                expr(rhs, calls);
+            }
+            Expr::Call(c) => call(c, calls),
+            Expr::Len { .. } | Expr::Literal { .. } | Expr::Error(_) => {}
+        }
+    }
+    fn location<'s, 'a>(loc: &'s Location<'a>, calls: &mut Vec<&'s cst::Call<'a>>) {
+        if let Location::Index { offset, .. } = loc {
+            expr(offset, calls)
+        }
+    }
+    fn call<'s, 'a>(c: &'s cst::Call<'a>, calls: &mut Vec<&'s cst::Call<'a>>) {
+        calls.push(c);
+        c.args.iter().for_each(|arg| {
+            if let Arg::Expr(e) = arg {
+                expr(e, calls)
+            }
+        })
+    }
+    fn assign<'s, 'a>(assign: &'s cst::Assign<'a>, calls: &mut Vec<&'s cst::Call<'a>>) {
+        location(&assign.lhs, calls);
+        if let AssignExpr::Assign(e) | AssignExpr::AddAssign(e) | AssignExpr::SubAssign(e) =
+            &assign.op
+        {
+            expr(e, calls)
+        }
+    }
+    fn block<'s, 'a>(b: &'s Block<'a>, calls: &mut Vec<&'s cst::Call<'a>>) {
+        b.stmts().iter().for_each(|stmt| match stmt {
+            PStmt::Call(c) => call(c, calls),
+            PStmt::Assign(a) => assign(a, calls),
+            PStmt::Return { expr: e, .. } => {
+                if let Some(e) = e {
+                    expr(e, calls)
+                }
+            }
+            PStmt::Break(_) | PStmt::Continue(_) | PStmt::Error(_) => {}
+            PStmt::If { cond, yes, no, .. } => {
+                expr(cond, calls);
+                block(yes, calls);
+                if let Some(no) = no {
+                    block(no, calls)
+                }
+            }
+            PStmt::While { cond, body, .. } => {
+                expr(cond, calls);
+                block(body, calls);
+            }
+            PStmt::For {
+                init,
+                cond,
+                update,
+                body,
+                ..
+            } => {
+                assign(init, calls);
+                expr(cond, calls);
+                assign(update, calls);
+                block(body, calls);
+            }
+        })
+    }
+    let mut calls = vec![];
+    root.funcs
+        .iter()
+        .for_each(|func| block(&func.body, &mut calls));
+    calls
+}
+
+/// finds the calls to imported methods with a number of arguments their declaration in
+/// `interface` does not accept, a variadic method is expected to get at least its parameters.
+///
+/// the methods the interface does not declare take any arguments.
+pub fn wrong_extern_args<'a>(root: &PRoot<'a>, interface: &Interface) -> Vec<Error<'a>> {
+    calls(root)
+        .into_iter()
+        .filter(|call| {
+            root.imports
+                .iter()
+                .any(|import| import.name().source() == call.name.source())
+        })
+        .filter_map(|call| {
+            let sig = interface.get(call.name.source())?;
+            (!sig.accepts(call.args.len())).then_some(WrongNumberOfArgs {
+                expected: sig.params.len(),
+                found: call.args.len(),
+                span: call.name,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::span::SpanSource;
+
+    const CODE: &str = "import printf;\nimport abs;\nimport exit;\nint a[2];\n\
+                        void main() {\n  printf(\"%d\\n\", abs(-1));\n  printf(\"done\\n\");\n  \
+                        printf(\"%d %d\\n\", a[0], 1 < 2);\n  abs(a);\n}\n";
+
+    #[test]
+    fn inferred() {
+        let root = crate::semantic_check(CODE.as_bytes(), "externs.dcf").unwrap();
+        let externs = Externs::new(&root, &Interface::default());
+        let sigs = externs
+            .iter()
+            .map(|import| {
+                let sig = import.signature().map(|sig| sig.to_string());
+                (import.name.as_str(), sig)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sigs,
+            [
+                ("abs", Some("(...)".to_string())),
+                ("exit", None),
+                ("printf", Some("(string, ...)".to_string())),
+            ]
+        );
+        assert_eq!(externs.get("printf").unwrap().calls.len(), 3);
+        assert_eq!(
+            externs.get("printf").unwrap().calls[2],
+            [ExternType::String, ExternType::Int, ExternType::Bool]
+        );
+    }
+
+    #[test]
+    fn interface() {
+        let interface = Interface::parse(
+            "// libc\nint printf(string, ...);\n\n  abs( int );  // no return type\nexit();\n",
+        )
+        .unwrap();
+        let root = crate::semantic_check(CODE.as_bytes(), "externs.dcf").unwrap();
+        let externs = Externs::new(&root, &interface);
+        let abs = externs.get("abs").unwrap();
+        assert_eq!(abs.signature().unwrap().to_string(), "(int)");
+        assert_eq!(abs.calls, [vec![ExternType::Int], vec![ExternType::Array]]);
+        let exit = externs.get("exit").unwrap();
+        assert_eq!(exit.signature().unwrap().to_string(), "()");
+        let errors = [
+            ("printf(string)\n", "expected `;`", (1, 1)),
+            ("  int f(int, char);\n", "type `char`", (1, 3)),
+            ("f();\nf(int);\n", "declared twice", (2, 1)),
+            ("int 1f();\n", "not a method name", (1, 1)),
+        ];
+        for (text, msg, pos) in errors {
+            let error = Interface::parse(text).unwrap_err();
+            assert!(error.msg.contains(msg), "{}", error.msg);
+            assert_eq!(error.pos, pos);
+        }
+    }
+
+    #[test]
+    fn wrong_arity() {
+        let interface = Interface::parse("printf(string, ...);\nabs(int);\n").unwrap();
+        let text = CODE.replace("printf(\"done\\n\")", "printf()");
+        let span_source = SpanSource::new(&text);
+        let root = crate::parse(&span_source).unwrap();
+        let errors = wrong_extern_args(&root, &interface)
+            .into_iter()
+            .map(|e| match e {
+                WrongNumberOfArgs {
+                    expected,
+                    found,
+                    span,
+                } => (span.to_string(), expected, found, span.position()),
+                e => panic!("expected wrong number of arguments, found: {e:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(errors, [("printf".to_string(), 1, 0, (7, 3))]);
+    }
+}
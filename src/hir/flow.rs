@@ -0,0 +1,232 @@
+use crate::{
+    cst::{Block, Expr, Literal, PFunction, PRoot, PStmt},
+    hir::error::{
+        Error::{self, MissingReturn},
+        Warning::{self, UnreachableCode},
+    },
+    span::*,
+};
+
+/// the node control reaches by falling off the end of the body of the method.
+const END: usize = 0;
+/// the node control enters the method at, before its first statement.
+const ENTRY: usize = 1;
+
+/// where `break` and `continue` go in the innermost loop.
+#[derive(Debug, Clone, Copy)]
+struct Loop {
+    brk: usize,
+    cont: usize,
+}
+
+/// the control-flow graph of a method, with a node for each statement.
+///
+/// a statement that holds blocks is the node of its condition, its successors are the first
+/// statements of its blocks and the statement after it. `return` has no successor, so the end of
+/// the method is reachable only if it can run past the last statement without returning.
+#[derive(Debug)]
+struct Cfg<'s, 'a> {
+    /// the statement of each node, `None` for [`END`] and [`ENTRY`].
+    stmts: Vec<Option<&'s PStmt<'a>>>,
+    succs: Vec<Vec<usize>>,
+    /// the nodes of the statements of each block in their order.
+    blocks: Vec<Vec<usize>>,
+}
+
+/// whether the loop with the condition `cond` can only be left by a `break`.
+fn always_true(cond: &Expr) -> bool {
+    match cond {
+        Expr::Nested(_, cond) => always_true(cond),
+        Expr::Literal { value, .. } => matches!(value, Literal::Bool(true)),
+        _ => false,
+    }
+}
+
+impl<'s, 'a> Cfg<'s, 'a> {
+    fn new(func: &'s PFunction<'a>) -> Self {
+        let mut cfg = Self {
+            stmts: vec![None, None],
+            succs: vec![vec![], vec![]],
+            blocks: vec![],
+        };
+        let entry = cfg.block(&func.body, END, None);
+        cfg.succs[ENTRY] = vec![entry];
+        cfg
+    }
+
+    fn node(&mut self, stmt: &'s PStmt<'a>) -> usize {
+        self.stmts.push(Some(stmt));
+        self.succs.push(vec![]);
+        self.stmts.len() - 1
+    }
+
+    /// adds the statements of `block` followed by `next`, returns the node control enters the
+    /// block at.
+    fn block(&mut self, block: &'s Block<'a>, next: usize, loops: Option<Loop>) -> usize {
+        let mut nodes = block
+            .stmts()
+            .iter()
+            .rev()
+            .scan(next, |next, stmt| {
+                *next = self.stmt(stmt, *next, loops);
+                Some(*next)
+            })
+            .collect::<Vec<_>>();
+        nodes.reverse();
+        let entry = nodes.first().copied().unwrap_or(next);
+        self.blocks.push(nodes);
+        entry
+    }
+
+    fn stmt(&mut self, stmt: &'s PStmt<'a>, next: usize, loops: Option<Loop>) -> usize {
+        let node = self.node(stmt);
+        let succs = match stmt {
+            PStmt::Call(_) | PStmt::Assign(_) | PStmt::Error(_) => vec![next],
+            PStmt::Return { .. } => vec![],
+            // outside a loop the statement is an error of its own, do not report it twice
+            PStmt::Break(_) => loops.map_or(vec![next], |l| vec![l.brk]),
+            PStmt::Continue(_) => loops.map_or(vec![next], |l| vec![l.cont]),
+            PStmt::If { yes, no, .. } => {
+                let yes = self.block(yes, next, loops);
+                let no = no.as_ref().map_or(next, |no| self.block(no, next, loops));
+                vec![yes, no]
+            }
+            PStmt::While { cond, body, .. } | PStmt::For { cond, body, .. } => {
+                let inner = Loop {
+                    brk: next,
+                    cont: node,
+                };
+                let body = self.block(body, node, Some(inner));
+                if always_true(cond) {
+                    vec![body]
+                } else {
+                    vec![body, next]
+                }
+            }
+        };
+        self.succs[node] = succs;
+        node
+    }
+
+    fn reachable(&self) -> Vec<bool> {
+        let mut reached = vec![false; self.succs.len()];
+        let mut work = vec![ENTRY];
+        while let Some(node) = work.pop() {
+            if !std::mem::replace(&mut reached[node], true) {
+                work.extend(&self.succs[node]);
+            }
+        }
+        reached
+    }
+}
+
+/// the `}` closing the body of `func`, `None` for a method the parser found no body for.
+fn closing_brace<'a>(func: &PFunction<'a>) -> Option<Span<'a>> {
+    let body = func.body.span;
+    body.ends_with("}")
+        .then(|| body.split_at(body.as_str().len() - 1).1)
+}
+
+/// finds the methods with a return type that can reach the end of their body without returning
+/// a value.
+///
+/// a loop is assumed to run any number of times unless its condition is the literal `true`.
+pub fn missing_returns<'a>(root: &PRoot<'a>) -> Vec<Error<'a>> {
+    root.funcs
+        .iter()
+        .filter(|func| func.ret.is_some())
+        .filter(|func| Cfg::new(func).reachable()[END])
+        .filter_map(|func| {
+            closing_brace(func).map(|end| MissingReturn {
+                method: func.name(),
+                end,
+            })
+        })
+        .collect()
+}
+
+/// finds the statements that follow a `return`, `break` or `continue` and cannot run, only the
+/// first statement of each unreachable run is reported.
+pub fn unreachable_code<'a>(root: &PRoot<'a>) -> Vec<Warning<'a>> {
+    let mut warnings = root
+        .funcs
+        .iter()
+        .flat_map(|func| {
+            let cfg = Cfg::new(func);
+            let reached = cfg.reachable();
+            cfg.blocks
+                .iter()
+                .flat_map(|nodes| nodes.windows(2))
+                .filter(|pair| reached[pair[0]] && !reached[pair[1]])
+                .filter_map(|pair| cfg.stmts[pair[1]])
+                .map(|stmt| UnreachableCode(stmt.span()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    warnings.sort_by_key(|warning| warning.span().offset());
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(text: &str, test: impl FnOnce(&PRoot)) {
+        let span_source = SpanSource::new(text);
+        let root = crate::parse(&span_source).unwrap();
+        test(&root)
+    }
+
+    fn missing(text: &str) -> Vec<(String, (usize, usize))> {
+        let mut missing = vec![];
+        check(text, |root| {
+            missing = missing_returns(root)
+                .into_iter()
+                .map(|e| match e {
+                    MissingReturn { method, end } => (method.to_string(), end.position()),
+                    e => panic!("expected missing return, found: {e:?}"),
+                })
+                .collect()
+        });
+        missing
+    }
+
+    #[test]
+    fn missing_return() {
+        let text = "int f(int x) {\n  if (x > 0) {\n    return 1;\n  }\n}\n\
+                    bool g() {}\nvoid main() {}\n";
+        assert_eq!(
+            missing(text),
+            [("f".to_string(), (5, 1)), ("g".to_string(), (6, 11))]
+        );
+    }
+
+    #[test]
+    fn returns_on_all_paths() {
+        let text = "int f(int x) {\n  if (x > 0) {\n    return 1;\n  } else {\n    return 2;\n  }\n}\n\
+                    int g(int x) {\n  while (true) {\n    if (x > 0) {\n      return x;\n    }\n  }\n}\n\
+                    int h() {\n  for (i = 0; i < 2; i++) {\n    return 1;\n  }\n  return 0;\n}\n";
+        assert!(missing(text).is_empty());
+    }
+
+    #[test]
+    fn break_leaves_infinite_loop() {
+        let text = "int f(int x) {\n  while (true) {\n    break;\n  }\n}\n";
+        assert_eq!(missing(text), [("f".to_string(), (5, 1))]);
+    }
+
+    #[test]
+    fn unreachable() {
+        let text =
+            "void f(int x) {\n  while (x > 0) {\n    x -= 1;\n    continue;\n    x = 0;\n    \
+                    x = 1;\n  }\n  return;\n  if (x > 0) {\n    x = 2;\n  }\n}\n\
+                    void g() {\n  while (true) {}\n  g();\n}\n";
+        check(text, |root| {
+            let warnings = unreachable_code(root)
+                .into_iter()
+                .map(|w| w.span().position())
+                .collect::<Vec<_>>();
+            assert_eq!(warnings, [(5, 5), (9, 3), (15, 3)]);
+        });
+    }
+}
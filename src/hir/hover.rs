@@ -0,0 +1,39 @@
+use crate::ast::{Root, Type};
+
+/// the type of the innermost expression whose span contains the byte at `offset`, e.g. for
+/// showing the type of the expression under the cursor.
+///
+/// `None` if there is no expression at `offset` or it is a call of a `void` method.
+pub fn type_at(root: &Root, offset: usize) -> Option<Type> {
+    root.expr_at(offset).and_then(|id| root.type_of(id))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CODE: &str = "int a[4];\nbool f(int x) { return x > a[0]; }\n\
+                        void main() {\n  int i;\n  if (f(i + 1)) { i = len(a); }\n}\n";
+
+    /// the type of the expression at the first occurrence of `at` in `CODE`.
+    fn type_at(at: &str) -> Option<Type> {
+        let root = crate::semantic_check(CODE.as_bytes(), "hover.dcf").unwrap();
+        super::type_at(&root, CODE.find(at).unwrap())
+    }
+
+    #[test]
+    fn types() {
+        assert_eq!(type_at("x > "), Some(Type::Int));
+        assert_eq!(type_at("> a"), Some(Type::Bool));
+        assert_eq!(type_at("a[0]"), Some(Type::Int));
+        assert_eq!(type_at("f(i +"), Some(Type::Bool));
+        assert_eq!(type_at("+ 1"), Some(Type::Int));
+        assert_eq!(type_at("len"), Some(Type::Int));
+    }
+
+    #[test]
+    fn no_expression() {
+        assert_eq!(type_at("int i"), None);
+        assert_eq!(type_at("void"), None);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{cst::PRoot, error::ErrorSink};
+
+use super::{
+    redundant_parens, shadowed_imports, shadowed_params, uninitialized_reads, unreachable_code,
+    unused_variables, wrong_extern_args, Interface,
+};
+
+/// the checks [`lints`] runs besides the ones it always runs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LintOptions {
+    /// report the parentheses that do not change how an expression is parsed.
+    pub redundant_parens: bool,
+}
+
+/// runs the checks of a parsed program that do not need it lowered and reports them to `sink`:
+/// the calls to the imports are checked against the signatures of `interface` and the lints
+/// report their warnings.
+///
+/// returns whether the calls to the imports match `interface`.
+pub fn lints(
+    proot: &PRoot,
+    interface: &Interface,
+    options: LintOptions,
+    sink: &mut ErrorSink,
+) -> bool {
+    let wrong_args = wrong_extern_args(proot, interface);
+    uninitialized_reads(proot)
+        .iter()
+        .chain(shadowed_imports(proot).iter())
+        .chain(shadowed_params(proot).iter())
+        .chain(unreachable_code(proot).iter())
+        .chain(unused_variables(proot).iter())
+        .for_each(|warning| sink.warn(warning));
+    if options.redundant_parens {
+        redundant_parens(proot)
+            .iter()
+            .for_each(|warning| sink.warn(warning));
+    }
+    wrong_args.iter().for_each(|err| sink.error(err));
+    wrong_args.is_empty()
+}
@@ -1,15 +1,71 @@
 use crate::ast::*;
 use crate::cst::{self, Arg as CArg, Expr as CExpr, Location as CLocation};
+use crate::lexer;
+use crate::span::{SourceRange, Span};
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
+pub mod arena;
+mod const_eval;
+mod dump;
 mod error;
+mod externs;
+mod flow;
+use const_eval::{check_overflow, fold_condition};
 use error::*;
 use Error::*;
 mod sym_map;
 use sym_map::*;
+mod hover;
+mod lints;
+mod parens;
+mod scope;
+mod shadow;
+mod uninit;
+mod unused;
 
+pub use arena::{
+    Arg, BlockData, BlockId, Def, DefId, DefKind, ExprKind, Hir, Method, Parent, Place, StmtId,
+    StmtKind,
+};
+pub use const_eval::const_eval;
+pub use dump::RootDisplay;
+pub use externs::{
+    wrong_extern_args, Extern, ExternSig, ExternType, Externs, Interface, InterfaceError,
+};
+pub use flow::{missing_returns, unreachable_code};
+pub use hover::type_at;
+pub use lints::{lints, LintOptions};
+pub use parens::redundant_parens;
+pub use scope::{Scope, ScopeId, ScopeKind, ScopeTree};
+pub use shadow::{shadowed_imports, shadowed_params};
 pub use sym_map::{FSymMap, VSymMap};
+pub use uninit::uninitialized_reads;
+pub use unused::unused_variables;
+
+/// the error for an identifier that is used as a variable but is not a declared variable.
+fn not_a_variable<'a>(ident: Span<'a>, fst: &FSymMap<'_>) -> Error<'a> {
+    if fst.get_sym(ident).is_some() {
+        MethodNotAVariable(ident)
+    } else {
+        UndeclaredIdentifier(ident)
+    }
+}
+
+/// the condition of an `if` or a `while` without the parentheses the statement requires.
+fn condition(cond: CExpr) -> CExpr {
+    match cond {
+        CExpr::Nested(_, cond) => *cond,
+        cond => cond,
+    }
+}
+
+/// `expr` if folding its operator does not overflow, `span` is the span of the expression.
+fn no_overflow<'a>(expr: Expr, span: Span<'a>) -> Result<Expr, Vec<Error<'a>>> {
+    check_overflow(&expr)
+        .map(|_| expr)
+        .map_err(|_| vec![ConstantOverflow(span)])
+}
 
 impl Expr {
     fn from_pexpr<'a>(
@@ -17,50 +73,78 @@ impl Expr {
         vst: &VSymMap<'_>,
         fst: &FSymMap<'_>,
     ) -> Result<Self, Vec<Error<'a>>> {
+        let span = expr.span();
+        let range = SourceRange::from(span);
         match expr {
             CExpr::Len { id, .. } => match vst.get_sym(id) {
-                None => Err(vec![UndeclaredIdentifier(id)]),
+                None => Err(vec![not_a_variable(id, fst)]),
                 Some(var) => match var {
                     Var::Scalar { .. } => Err(vec![ExpectedArray(id)]),
-                    Var::Array { size, .. } => Ok(Self::Len(*size)),
+                    Var::Array { size, .. } => Ok(Self::Len(range, *size)),
                 },
             },
             CExpr::Not(span, e) => {
                 let e = Self::from_pexpr(*e, vst, fst)?;
                 e.is_boolean()
-                    .then(|| Expr::Not(Box::new(e)))
+                    .then(|| Expr::Not(range, Box::new(e)))
                     .ok_or(vec![ExpectedBoolExpr(span)])
             }
             CExpr::Neg(span, e) => {
                 if let CExpr::Literal { value, .. } = *e {
-                    Literal::from_pliteral(value, true).map(|value| value.into())
+                    // check the whole literal with its sign, e.g. `-9223372036854775808` fits
+                    Literal::from_pliteral(value, span)
+                        .map(|value| Expr::literal(value, range))
+                        .map_err(|e| vec![e])
                 } else {
                     let e = Self::from_pexpr(*e, vst, fst)?;
                     e.is_int()
-                        .then(|| Expr::Neg(Box::new(e)))
+                        .then(|| Expr::Neg(range, Box::new(e)))
                         .ok_or(vec![ExpectedIntExpr(span)])
+                        .and_then(|e| no_overflow(e, span))
                 }
             }
-            CExpr::Nested(_, e) => Self::from_pexpr(*e, vst, fst),
+            CExpr::Nested(_, e) => {
+                Self::from_pexpr(*e, vst, fst).map(|e| Expr::Nested(range, Box::new(e)))
+            }
+            // the parser already reported the missing expression
+            CExpr::Error(_) => Err(vec![]),
 
             CExpr::Loc(CLocation::Index {
                 ident,
                 offset,
                 span,
             }) => match vst.get_sym(ident) {
-                None => Err(vec![UndeclaredIdentifier(ident)]),
+                None => Err(vec![not_a_variable(ident, fst)]),
                 Some(Var::Scalar { .. }) => Err(vec![ExpectedArray(ident)]),
                 Some(var) => {
                     let offset = Self::from_pexpr(*offset, vst, fst)?;
                     offset
                         .is_int()
-                        .then(|| Location::index(var.clone(), offset).into())
+                        .then(|| Location::index(var.clone(), ident.into(), offset, range).into())
                         .ok_or(vec![ExpectedIntExpr(span)])
                 }
             },
+            CExpr::Index { base, offset, .. } => {
+                // only variables hold arrays so any other indexed expression is an error, the base
+                // and the offset are still checked to report their own errors.
+                let base_span = base.span();
+                let base = Self::from_pexpr(*base, vst, fst);
+                let offset = Self::from_pexpr(*offset, vst, fst);
+                let mut errors = match base {
+                    Ok(base) => vec![CannotIndexExpr {
+                        span: base_span,
+                        ty: base.r#type(),
+                    }],
+                    Err(errors) => errors,
+                };
+                if let Err(e) = offset {
+                    errors.extend(e)
+                }
+                Err(errors)
+            }
             CExpr::Loc(CLocation::Scalar(ident)) => match vst.get_sym(ident) {
-                None => Err(vec![UndeclaredIdentifier(ident)]),
-                Some(Var::Scalar(var)) => Ok(Location::Scalar(var.clone()).into()),
+                None => Err(vec![not_a_variable(ident, fst)]),
+                Some(var @ Var::Scalar(_)) => Ok(Location::scalar(var, ident.into()).into()),
                 _ => Err(vec![ExpectedScalarVariable(ident)]),
             },
             CExpr::Ter { cond, yes, no, .. } => {
@@ -75,6 +159,7 @@ impl Expr {
                                 cond: Box::new(cond),
                                 yes: Box::new(yes),
                                 no: Box::new(no),
+                                span: range,
                             })
                         } else {
                             let mut errors = vec![];
@@ -118,6 +203,7 @@ impl Expr {
                                     op,
                                     lhs: Box::new(lhs),
                                     rhs: Box::new(rhs),
+                                    span: range,
                                 })
                             } else {
                                 Err(vec![TypeMismatch {
@@ -133,6 +219,7 @@ impl Expr {
                                     op,
                                     lhs: Box::new(lhs),
                                     rhs: Box::new(rhs),
+                                    span: range,
                                 })
                             } else {
                                 let mut errors = vec![];
@@ -146,16 +233,19 @@ impl Expr {
                             }
                         } else if lhs.r#type() == Type::Int && rhs.r#type() == Type::Int {
                             if let Ok(op) = ArithOp::try_from(op) {
-                                Ok(Self::Arith {
+                                let arith = Self::Arith {
                                     op,
                                     lhs: Box::new(lhs),
                                     rhs: Box::new(rhs),
-                                })
+                                    span: range,
+                                };
+                                no_overflow(arith, span)
                             } else if let Ok(op) = RelOp::try_from(op) {
                                 Ok(Self::Rel {
                                     op,
                                     lhs: Box::new(lhs),
                                     rhs: Box::new(rhs),
+                                    span: range,
                                 })
                             } else {
                                 unreachable!()
@@ -183,9 +273,9 @@ impl Expr {
                     }
                 }
             }
-            CExpr::Literal { value, .. } => {
-                Literal::from_pliteral(value, false).map(|value| value.into())
-            }
+            CExpr::Literal { value, span } => Literal::from_pliteral(value, span)
+                .map(|value| Expr::literal(value, range))
+                .map_err(|e| vec![e]),
             CExpr::Call(call) => {
                 let call_span = call.span();
                 Call::from_pcall(call, vst, fst)
@@ -203,34 +293,13 @@ impl Expr {
 }
 
 impl<'a> Literal {
-    fn from_pliteral(literal: cst::Literal<'a>, is_neg: bool) -> Result<Self, Vec<Error<'a>>> {
-        let map_digit = if is_neg { |dig: i64| -dig } else { |dig| dig };
+    /// the value of `literal`, `span` is the literal with its `0x` prefix and the `-` before it
+    /// if it is negated.
+    fn from_pliteral(literal: cst::Literal<'a>, span: Span<'a>) -> Result<Self, Error<'a>> {
         match literal {
-            cst::Literal::Decimal(num) => {
-                let parsed = num.bytes().try_fold(0, |acc: i64, digit| {
-                    acc.checked_mul(10)
-                        .and_then(|acc| acc.checked_add(map_digit((digit - b'0') as i64)))
-                });
-                parsed.ok_or(vec![TooLargeInt(num)]).map(Literal::Int)
-            }
-            cst::Literal::Hex(num) => num
-                .bytes()
-                .try_fold(0, |acc: i64, digit| match digit {
-                    b'0'..=b'9' => acc
-                        .checked_mul(16)
-                        .and_then(|acc| acc.checked_add(map_digit((digit - b'0') as i64))),
-                    b'a'..=b'f' => acc
-                        .checked_mul(16)
-                        .and_then(|acc| acc.checked_add(map_digit((digit - b'a' + 10) as i64))),
-                    b'A'..=b'F' => acc
-                        .checked_mul(16)
-                        .and_then(|acc| acc.checked_add(map_digit((digit - b'A' + 10) as i64))),
-                    _ => {
-                        unreachable!()
-                    }
-                })
-                .ok_or(vec![TooLargeInt(num)])
-                .map(Literal::Int),
+            cst::Literal::Decimal(_) | cst::Literal::Hex(_) => lexer::parse_int_literal(span)
+                .map(Literal::Int)
+                .map_err(TooLargeInt),
             cst::Literal::Bool(val) => Ok(Literal::Bool(val)),
             cst::Literal::Char(c) => Ok(Literal::Int(c as i64)),
         }
@@ -245,17 +314,17 @@ impl ExternArg {
     ) -> Result<Self, Vec<Error<'a>>> {
         match arg {
             CArg::Expr(CExpr::Loc(CLocation::Scalar(ident))) => {
-                if let Some(ident) = vst
+                if let Some(name) = vst
                     .get_sym(ident)
                     .and_then(|var| var.is_array().then_some(var.name()))
                 {
-                    Ok(Self::Array(ident.to_string()))
+                    Ok(Self::Array(ident.into(), name.to_string()))
                 } else {
                     Expr::from_pexpr(CExpr::Loc(CLocation::Scalar(ident)), vst, fst).map(Self::Expr)
                 }
             }
             CArg::Expr(e) => Expr::from_pexpr(e, vst, fst).map(Self::Expr),
-            CArg::String(s) => Ok(ExternArg::String(s.to_string())),
+            CArg::String(s) => Ok(ExternArg::String(s.into(), s.to_string())),
         }
     }
 }
@@ -286,17 +355,18 @@ impl Call {
         vst: &VSymMap<'_>,
         fst: &FSymMap<'_>,
     ) -> Result<Self, Vec<Error<'a>>> {
+        let range = SourceRange::from(call.span());
         if vst.get_sym(call.name).is_some() {
             Err(vec![VariableNotAMethod(call.name)])
         } else {
             match fst.get_sym(call.name) {
                 None => Err(vec![UndeclaredIdentifier(call.name)]),
-                Some(FunctionSig::Extern(name)) => call
+                Some(FunctionSig::Extern(name, _)) => call
                     .args
                     .into_iter()
                     .map(|arg| ExternArg::extern_from_pcall(arg, vst, fst))
                     .fold_result()
-                    .map(|args| Call::new_extern(name.clone(), args)),
+                    .map(|args| Call::new_extern(name.clone(), args, range)),
                 Some(FunctionSig::Decl {
                     name,
                     arg_types,
@@ -308,6 +378,11 @@ impl Call {
                             .zip(arg_types.iter())
                             .map(|(arg, r#type)| match arg {
                                 CArg::String(s) => Err(vec![StringInUserDefined(s)]),
+                                CArg::Expr(CExpr::Loc(CLocation::Scalar(ident)))
+                                    if vst.get_sym(ident).is_some_and(Var::is_array) =>
+                                {
+                                    Err(vec![ArrayInUserDefined(ident)])
+                                }
                                 CArg::Expr(expr) => {
                                     let span = expr.span();
                                     Expr::from_pexpr(expr, vst, fst).and_then(|expr| {
@@ -324,7 +399,7 @@ impl Call {
                                 }
                             })
                             .fold_result()
-                            .map(|args| Call::new_decaf(name.clone(), *ty, args))
+                            .map(|args| Call::new_decaf(name.clone(), *ty, args, range))
                     } else {
                         Err(vec![WrongNumberOfArgs {
                             expected: arg_types.len(),
@@ -347,7 +422,9 @@ impl Location {
         let loc_span = loc.span();
         let loc_ident = loc.ident();
         match (vst.get_sym(loc.ident()), loc) {
-            (Some(Var::Scalar(var)), CLocation::Scalar(_)) => Ok(Location::Scalar(var.clone())),
+            (Some(var @ Var::Scalar(_)), CLocation::Scalar(_)) => {
+                Ok(Location::scalar(var, loc_ident.into()))
+            }
             (Some(Var::Scalar(_)), CLocation::Index { .. }) => {
                 Err(vec![CannotIndexScalar(loc_span)])
             }
@@ -359,12 +436,17 @@ impl Location {
                 let offset_span = offset.span();
                 let offset = Expr::from_pexpr(*offset, vst, fst)?;
                 if offset.r#type() == cst::Type::Int {
-                    Ok(Location::index(arr.clone(), offset))
+                    Ok(Location::index(
+                        arr.clone(),
+                        loc_ident.into(),
+                        offset,
+                        loc_span.into(),
+                    ))
                 } else {
                     Err(vec![ExpectedIntExpr(offset_span)])
                 }
             }
-            (None, _) => Err(vec![UndeclaredIdentifier(loc_ident)]),
+            (None, _) => Err(vec![not_a_variable(loc_ident, fst)]),
         }
     }
 }
@@ -377,6 +459,7 @@ impl Assign {
     ) -> Result<Self, Vec<Error<'a>>> {
         use cst::AssignExpr::*;
         use ArithOp::*;
+        let range = SourceRange::from(assign.span());
         let lhs_span = assign.lhs.span();
         let loc = Location::from_ploc(assign.lhs, vst, fst)?;
         match assign.op {
@@ -389,8 +472,10 @@ impl Assign {
                         rhs: Expr::Arith {
                             op: Add,
                             lhs: Box::new(loc.into()),
-                            rhs: Box::new(Literal::from(1).into()),
+                            rhs: Box::new(Expr::literal(Literal::Int(1), range)),
+                            span: range,
                         },
+                        span: range,
                     })
                 }
             }
@@ -403,8 +488,10 @@ impl Assign {
                         rhs: Expr::Arith {
                             op: Sub,
                             lhs: Box::new(loc.into()),
-                            rhs: Box::new(Literal::from(1).into()),
+                            rhs: Box::new(Expr::literal(Literal::Int(1), range)),
+                            span: range,
                         },
+                        span: range,
                     })
                 }
             }
@@ -417,7 +504,11 @@ impl Assign {
                         rtype: rhs.r#type(),
                     }])
                 } else {
-                    Ok(Self { lhs: loc, rhs })
+                    Ok(Self {
+                        lhs: loc,
+                        rhs,
+                        span: range,
+                    })
                 }
             }
             AddAssign(expr) => {
@@ -426,7 +517,7 @@ impl Assign {
                 if rhs.r#type() != cst::Type::Int {
                     Err(vec![ExpectedIntExpr(rhs_span)])
                 } else if loc.r#type() != cst::Type::Int {
-                    Err(vec![ExpectedIntExpr(lhs_span)])
+                    Err(vec![CompoundAssignNonInt(lhs_span)])
                 } else {
                     Ok(Self {
                         lhs: loc.clone(),
@@ -434,7 +525,9 @@ impl Assign {
                             op: Add,
                             lhs: Box::new(loc.into()),
                             rhs: Box::new(rhs),
+                            span: range,
                         },
+                        span: range,
                     })
                 }
             }
@@ -444,7 +537,7 @@ impl Assign {
                 if rhs.r#type() != cst::Type::Int {
                     Err(vec![ExpectedIntExpr(rhs_span)])
                 } else if loc.r#type() != cst::Type::Int {
-                    Err(vec![ExpectedIntExpr(lhs_span)])
+                    Err(vec![CompoundAssignNonInt(lhs_span)])
                 } else {
                     Ok(Self {
                         lhs: loc.clone(),
@@ -452,7 +545,9 @@ impl Assign {
                             op: Sub,
                             lhs: Box::new(loc.into()),
                             rhs: Box::new(rhs),
+                            span: range,
                         },
+                        span: range,
                     })
                 }
             }
@@ -462,25 +557,36 @@ impl Assign {
 
 impl Var {
     fn from_pvar<'a>(var: cst::PVar<'a>) -> Result<Self, Error<'a>> {
+        let range = SourceRange::from(var.span());
         match var {
-            cst::PVar::Scalar { ident, ty } => Ok(Var::Scalar(Typed::new(ty, ident.to_string()))),
+            cst::PVar::Scalar { ident, ty } => {
+                Ok(Var::Scalar(Typed::new(ty, ident.to_string(), range)))
+            }
             cst::PVar::Array {
-                ident, size, ty, ..
+                ident,
+                size,
+                ty,
+                span,
             } => {
                 let size_span = size.span();
-                if let Ok(size) = Literal::from_pliteral(cst::Literal::from(size), false)
-                    .map(|size| size.int().unwrap())
-                {
-                    if size == 0 {
-                        Err(ZeroArraySize(size_span))
-                    } else {
-                        Ok(Self::Array {
-                            arr: Typed::new(ty, ident.to_string()),
-                            size: size as u64,
-                        })
+                // the span of a hex size has no `0x`, the literal is checked the way it is written
+                let written = match size {
+                    cst::IntLiteral::Decimal(_) => size_span,
+                    cst::IntLiteral::Hex(_) => {
+                        let (_, rem) = span.split_at(size_span.offset() - span.offset() - 2);
+                        rem.split_at(2).0.merge(size_span)
                     }
-                } else {
-                    Err(TooLargeInt(size_span))
+                };
+                match Literal::from_pliteral(cst::Literal::from(size), written) {
+                    // the size is validated as a constant expression, which a literal always is
+                    Ok(size) => match const_eval(&Expr::literal(size, size_span.into())) {
+                        Some(Literal::Int(size)) if size > 0 => Ok(Self::Array {
+                            arr: Typed::new(ty, ident.to_string(), range),
+                            size: size as u64,
+                        }),
+                        _ => Err(ZeroArraySize(size_span)),
+                    },
+                    Err(e) => Err(e),
                 }
             }
         }
@@ -499,6 +605,7 @@ impl Block {
             .map(Err)
             .unwrap_or(Ok(()))?;
         let block_vst = construct_var_hashmap(block.decls())?;
+        let range = SourceRange::from(block.span);
         let stmts = block
             .stmts
             .into_iter()
@@ -515,6 +622,7 @@ impl Block {
         Ok(Self {
             decls: block_vst,
             stmts,
+            span: range,
         })
     }
 }
@@ -527,6 +635,7 @@ impl Stmt {
         vst: &VSymMap<'_>,
         fst: &FSymMap<'_>,
     ) -> Result<Self, Vec<Error<'a>>> {
+        let range = SourceRange::from(stmt.span());
         match stmt {
             cst::PStmt::Call(call) => {
                 Call::from_pcall(call, vst, fst).map(|call| Self::Expr(Expr::Call(call)))
@@ -536,39 +645,43 @@ impl Stmt {
                     let expr_span = expr.span();
                     Expr::from_pexpr(expr, vst, fst).and_then(|res| match expected_return {
                         None => Err(vec![ReturnValueFromVoid(span)]),
-                        Some(cst::Type::Int) if res.r#type() == cst::Type::Int => Ok(res),
-                        Some(cst::Type::Int) => Err(vec![ExpectedIntExpr(expr_span)]),
-                        Some(cst::Type::Bool) if res.r#type() == cst::Type::Bool => Ok(res),
-                        Some(cst::Type::Bool) => Err(vec![ExpectedBoolExpr(expr_span)]),
+                        Some(ty) if res.r#type() == ty => Ok(res),
+                        Some(ty) => Err(vec![ReturnTypeMismatch {
+                            expected: ty,
+                            found: res.r#type(),
+                            span: expr_span,
+                        }]),
                     })
                 }
-                .map(|expr| Self::Return(Some(expr))),
+                .map(|expr| Self::Return(range, Some(expr))),
                 None => {
                     if expected_return.is_some() {
                         Err(vec![ExpectedExpression(span)])
                     } else {
-                        Ok(Self::Return(None))
+                        Ok(Self::Return(range, None))
                     }
                 }
             },
             cst::PStmt::Break(span) => {
                 if in_loop {
-                    Ok(Self::Break)
+                    Ok(Self::Break(range))
                 } else {
                     Err(vec![BreakOutsideLoop(span)])
                 }
             }
             cst::PStmt::Continue(span) => {
                 if in_loop {
-                    Ok(Self::Continue)
+                    Ok(Self::Continue(range))
                 } else {
                     Err(vec![ContinueOutsideLoop(span)])
                 }
             }
             cst::PStmt::Assign(assign) => Assign::from_passign(assign, vst, fst).map(Self::Assign),
+            // the parser already reported the skipped statement
+            cst::PStmt::Error(_) => Err(vec![]),
             cst::PStmt::If { cond, yes, no, .. } => {
                 let cond_span = cond.span();
-                let cond = Expr::from_pexpr(cond, vst, fst);
+                let cond = Expr::from_pexpr(condition(cond), vst, fst);
                 let yes = Block::from_pblock(yes, in_loop, expected_return, vst, fst);
                 let no = no.map(|no| Block::from_pblock(no, in_loop, expected_return, vst, fst));
                 match (cond, yes, no) {
@@ -576,22 +689,25 @@ impl Stmt {
                         if cond.r#type() != cst::Type::Bool {
                             Err(vec![ExpectedBoolExpr(cond_span)])
                         } else {
-                            Ok(Self::If {
+                            let cond = fold_condition(cond);
+                            Ok(Self::If(Box::new(IfChain::new(
                                 cond,
-                                yes: Box::new(yes),
-                                no: Box::default(),
-                            })
+                                yes,
+                                Block::default(),
+                                range,
+                            ))))
                         }
                     }
                     (Ok(cond), Ok(yes), Some(Ok(no))) => {
                         if cond.r#type() != cst::Type::Bool {
                             Err(vec![ExpectedBoolExpr(cond_span)])
                         } else {
-                            Ok(Self::If {
-                                cond,
-                                yes: Box::new(yes),
-                                no: Box::new(no),
-                            })
+                            Ok(Self::If(Box::new(IfChain::new(
+                                fold_condition(cond),
+                                yes,
+                                no,
+                                range,
+                            ))))
                         }
                     }
                     (cond, yes, no) => {
@@ -613,14 +729,15 @@ impl Stmt {
             }
             cst::PStmt::While { cond, body, .. } => {
                 let cond_span = cond.span();
-                let cond = Expr::from_pexpr(cond, vst, fst);
+                let cond = Expr::from_pexpr(condition(cond), vst, fst);
                 let body = Block::from_pblock(body, true, expected_return, vst, fst);
                 match (cond, body) {
                     (Ok(cond), Ok(body)) => {
                         if cond.is_boolean() {
                             Ok(Self::While {
-                                cond,
+                                cond: fold_condition(cond),
                                 body: Box::new(body),
+                                span: range,
                             })
                         } else {
                             Err(vec![ExpectedBoolExpr(cond_span)])
@@ -654,10 +771,11 @@ impl Stmt {
                     (Ok(init), Ok(cond), Ok(update), Ok(body)) => {
                         if cond.is_boolean() {
                             Ok(Self::For {
-                                init,
-                                cond,
-                                update,
+                                init: Box::new(init),
+                                cond: fold_condition(cond),
+                                update: Box::new(update),
                                 body: Box::new(body),
+                                span: range,
                             })
                         } else {
                             Err(vec![ExpectedBoolExpr(cond_span)])
@@ -691,15 +809,17 @@ impl Function {
         vst: &VSymMap<'_>,
         fst: &FSymMap<'_>,
     ) -> Result<Self, Vec<Error<'a>>> {
+        duplicate_params(&func.args).map(Err).unwrap_or(Ok(()))?;
         get_redefs(
-            func.body
-                .decls()
+            func.args
                 .iter()
                 .map(|v| v.name())
-                .chain(func.args.iter().map(|v| v.name())),
+                .chain(func.body.decls().iter().map(|v| v.name())),
         )
         .map(Err)
         .unwrap_or(Ok(()))?;
+        let range = SourceRange::from(func.span());
+        let params = func.args.iter().map(|arg| arg.name().to_string()).collect();
         let args = construct_var_hashmap(func.args)?;
         let body = Block::from_pblock(
             func.body,
@@ -708,7 +828,7 @@ impl Function {
             &VSymMap::new(&args).parent(&vst),
             fst,
         )?;
-        Ok(Self::new(func.name, body, args, func.ret))
+        Ok(Self::new(func.name, body, args, params, func.ret, range))
     }
 }
 
@@ -731,14 +851,15 @@ impl Root {
             errors.push(RootDoesNotContainMain)
         };
         if errors.is_empty() {
+            let missing_returns = missing_returns(&root);
             let globals = construct_var_hashmap(root.decls)?;
             let mut sigs = construct_sig_hashmap(&root.imports)?;
             let imports = root
                 .imports
                 .into_iter()
-                .map(|imp| imp.name().to_string())
-                .collect::<HashSet<_>>();
-            let functions = root
+                .map(|imp| (imp.name().to_string(), imp.name().into()))
+                .collect::<HashMap<_, _>>();
+            let functions = match root
                 .funcs
                 .into_iter()
                 .map(|f| {
@@ -748,16 +869,289 @@ impl Root {
                             .map(|f| (f.name.clone(), f));
                     r
                 })
-                .fold_result()?
-                .into_iter()
-                .collect::<HashMap<_, _>>();
-            Ok(Self {
+                .fold_result()
+            {
+                Ok(functions) if missing_returns.is_empty() => functions,
+                Ok(_) => return Err(missing_returns),
+                Err(mut errors) => {
+                    errors.extend(missing_returns);
+                    return Err(errors);
+                }
+            }
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+            let mut root = Self {
                 globals,
                 functions,
                 imports,
-            })
+                types: vec![],
+            };
+            root.types = root.expr_types();
+            Ok(root)
         } else {
             Err(errors)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::span::SpanSource;
+
+    macro_rules! errors {
+        ($errors:ident, $text:expr) => {
+            let span_source = SpanSource::new($text);
+            let root = crate::parse(&span_source).unwrap();
+            let $errors = Root::from_proot(root).unwrap_err();
+        };
+    }
+
+    #[test]
+    fn scalar_called() {
+        errors!(errors, "int x;\nvoid main() {\n  x();\n}");
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            VariableNotAMethod(span) => assert_eq!(span.position(), (3, 3)),
+            ref e => panic!("expected variable not a method, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn method_indexed() {
+        errors!(
+            errors,
+            "int f() { return 0; }\nvoid main() {\n  int y;\n  y = f[0];\n}"
+        );
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            MethodNotAVariable(span) => {
+                assert_eq!(span.source(), "f");
+                assert_eq!(span.position(), (4, 7));
+            }
+            ref e => panic!("expected method not a variable, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn chained_index() {
+        errors!(
+            errors,
+            "int a[2];\nint f() { return 0; }\nvoid main() {\n  int y;\n  y = a[0][1] + f()[y];\n}"
+        );
+        let found = errors
+            .iter()
+            .map(|e| match e {
+                CannotIndexExpr { span, ty } => (span.source(), span.position(), *ty),
+                e => panic!("expected cannot index expression, found: {:?}", e),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [("a[0]", (5, 7), Type::Int), ("f()", (5, 17), Type::Int)]
+        );
+    }
+
+    #[test]
+    fn chained_index_base_errors() {
+        // the errors of the base are reported instead of indexing it
+        errors!(errors, "void main() {\n  int y;\n  y = b[0][1];\n}");
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            UndeclaredIdentifier(span) => assert_eq!(span.source(), "b"),
+            ref e => panic!("expected undeclared identifier, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn rules() {
+        use crate::error::{codes::*, CCError};
+        let cases = [
+            ("int a[0];\nvoid main() { }", ZERO_ARRAY_SIZE),
+            (
+                "void main() {\n  int x, y;\n  y = len(x);\n}",
+                EXPECTED_ARRAY,
+            ),
+            ("void main() {\n  break;\n}", BREAK_OUTSIDE_LOOP),
+            ("void main() {\n  continue;\n}", CONTINUE_OUTSIDE_LOOP),
+            (
+                "void main() {\n  int x;\n  x = 9223372036854775808;\n}",
+                TOO_LARGE_INT,
+            ),
+            ("int x;\nvoid main() {\n  x();\n}", VARIABLE_NOT_A_METHOD),
+            (
+                "void f() { }\nvoid main() {\n  int x;\n  x = f;\n}",
+                METHOD_NOT_A_VARIABLE,
+            ),
+            ("int main() {\n  return 0;\n}", INVALID_MAIN_SIG),
+            (
+                "bool f() {\n  return 1;\n}\nvoid main() { }",
+                RETURN_TYPE_MISMATCH,
+            ),
+            (
+                "int a[2];\nvoid f(int x) { }\nvoid main() {\n  f(a);\n}",
+                ARRAY_IN_USER_DEFINED,
+            ),
+            (
+                "void main() {\n  bool b;\n  b += 1;\n}",
+                COMPOUND_ASSIGN_NON_INT,
+            ),
+            (
+                "int f(int x) {\n  if (x > 0) {\n    return x;\n  }\n}\nvoid main() { }",
+                MISSING_RETURN,
+            ),
+        ];
+        cases.iter().for_each(|(text, code)| {
+            errors!(errors, text);
+            let codes = errors.iter().map(|e| e.code()).collect::<Vec<_>>();
+            assert_eq!(codes, [Some(*code)], "{}", text);
+        });
+    }
+
+    #[test]
+    fn return_type_mismatch() {
+        errors!(errors, "bool f() {\n  return 1 + 2;\n}\nvoid main() { }");
+        match errors[..] {
+            [ReturnTypeMismatch {
+                expected: Type::Bool,
+                found: Type::Int,
+                span,
+            }] => assert_eq!(span.source(), "1 + 2"),
+            ref e => panic!("expected a return type mismatch, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn duplicate_parameter() {
+        errors!(
+            errors,
+            "void f(int a, bool a) { }\nvoid main() {\n  f(1, true);\n}"
+        );
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            DuplicateParameter { param, previous } => {
+                assert_eq!(param.position(), (1, 20));
+                assert_eq!(previous.position(), (1, 12));
+            }
+            ref e => panic!("expected duplicate parameter, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn redefinition() {
+        use crate::error::{CCError, Label};
+        let cases = [
+            ("int x;\nbool x;\nvoid main() { }", (2, 6), (1, 5)),
+            ("void f() { }\nvoid f() { }\nvoid main() { }", (2, 6), (1, 6)),
+            ("void f(int a) {\n  int a;\n}\nvoid main() { }", (2, 7), (1, 12)),
+            ("void main() {\n  int i;\n  bool i;\n}", (3, 8), (2, 7)),
+        ];
+        cases.iter().for_each(|(text, redef, first)| {
+            errors!(errors, text);
+            match errors[..] {
+                [ref e @ Redifinition(span, previous)] => {
+                    assert_eq!((span.position(), previous.position()), (*redef, *first));
+                    assert_eq!(e.msgs().len(), 1);
+                    assert_eq!(
+                        e.labels(),
+                        [Label {
+                            msg: format!("previous declaration of `{}` here", previous.to_string()),
+                            pos: *first,
+                            len: 1,
+                        }]
+                    );
+                }
+                ref e => panic!("expected a redefinition, found: {:?}", e),
+            }
+        });
+    }
+
+    #[test]
+    fn distinct_parameters() {
+        let span_source = SpanSource::new("void f(int a, int b) { }\nvoid main() {\n  f(1, 2);\n}");
+        let root = crate::parse(&span_source).unwrap();
+        assert!(Root::from_proot(root).is_ok());
+    }
+
+    #[test]
+    fn spans() {
+        use crate::span::HasSpan;
+        let text = "import f;\nint a[3];\nvoid main() {\n  int i;\n  i++;\n  f(\"%d\", a, (i + 1) * a[i]);\n}\n";
+        let span_source = SpanSource::new(text);
+        let root = crate::semantic_check(text.as_bytes(), "spans.dcf").unwrap();
+        let source = |range: SourceRange| range.span(&span_source).source();
+        assert_eq!(source(root.globals["a"].span()), "a[3]");
+        let main = &root.functions["main"];
+        assert_eq!(source(main.span()).lines().next(), Some("void main() {"));
+        assert_eq!(source(main.body.span()).lines().last(), Some("}"));
+        assert_eq!(main.body.decls["i"].span().position(), (4, 7));
+        let [Stmt::Assign(inc), Stmt::Expr(Expr::Call(call))] = &main.body.stmts[..] else {
+            panic!("expected an assignment and a call, found: {:?}", main.body.stmts);
+        };
+        // the desugared `i + 1` is spanned by the `i++` it comes from
+        assert_eq!(source(inc.span()), "i++");
+        assert_eq!(source(inc.rhs.span()), "i++");
+        assert_eq!(source(inc.lhs.span()), "i");
+        assert_eq!(source(call.span()), "f(\"%d\", a, (i + 1) * a[i])");
+        let Call::Extern { args, .. } = call else {
+            panic!("expected an extern call, found: {:?}", call);
+        };
+        let sources = args.iter().map(|arg| source(arg.span())).collect::<Vec<_>>();
+        assert_eq!(sources, ["\"%d\"", "a", "(i + 1) * a[i]"]);
+        let ExternArg::Expr(Expr::Arith { lhs, rhs, .. }) = &args[2] else {
+            panic!("expected a product");
+        };
+        // the parentheses are kept around the nested expression
+        let Expr::Nested(_, sum) = &**lhs else {
+            panic!("expected a nested expression, found: {:?}", lhs);
+        };
+        assert_eq!(source(lhs.span()), "(i + 1)");
+        assert_eq!(source(sum.span()), "i + 1");
+        assert_eq!(source(rhs.span()), "a[i]");
+        assert_eq!(rhs.span().position(), (6, 24));
+    }
+
+    #[test]
+    fn if_chains() {
+        use crate::span::HasSpan;
+        let text = "void main() {\n  int x;\n  \
+                    if (x == 0) {\n  } else {\n    \
+                    if (x == 1) {\n      x = 2;\n    } else {\n      \
+                    if (x == 2) {\n      } else {\n        x = 0;\n      }\n    }\n  }\n  \
+                    if (true) {\n  } else {\n    int y;\n    if (true) {\n    }\n  }\n}\n";
+        let span_source = SpanSource::new(text);
+        let root = crate::semantic_check(text.as_bytes(), "chains.dcf").unwrap();
+        let source = |range: SourceRange| range.span(&span_source).source();
+        let [Stmt::If(chain), Stmt::If(single)] = &root.functions["main"].body.stmts[..] else {
+            panic!("expected two ifs");
+        };
+        let conds = chain
+            .arms
+            .iter()
+            .map(|arm| source(arm.cond.span()))
+            .collect::<Vec<_>>();
+        assert_eq!(conds, ["x == 0", "x == 1", "x == 2"]);
+        assert_eq!(source(chain.arms[0].span), "if (x == 0) {\n  }");
+        assert_eq!(source(chain.arms[1].span).lines().last(), Some("    }"));
+        assert_eq!(chain.otherwise.stmts.len(), 1);
+        assert_eq!(source(chain.span).lines().last(), Some("  }"));
+        // an `else` block with declarations is not part of the chain
+        assert_eq!(single.arms.len(), 1);
+        assert!(matches!(single.otherwise.stmts[..], [Stmt::If(_)]));
+    }
+
+    #[test]
+    fn zero_initialized() {
+        let arr = Var::Array {
+            arr: Typed::new(Type::Bool, "a".to_string(), SourceRange::default()),
+            size: 2,
+        };
+        assert_eq!(arr.initial_value(), Literal::Bool(false));
+        let x = Var::Scalar(Typed::new(
+            Type::Int,
+            "x".to_string(),
+            SourceRange::default(),
+        ));
+        assert_eq!(x.initial_value(), Literal::Int(0));
+    }
+}
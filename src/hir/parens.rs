@@ -0,0 +1,190 @@
+use crate::{
+    cst::{Arg, Assign, AssignExpr, Block, Call, Expr, Location, PRoot, PStmt},
+    hir::error::Warning::{self, *},
+    parser::{BinaryOp, UNARY_PRECEDENCE as UNIT},
+};
+
+/// the precedence of the expression when it is not wrapped in parentheses, the unary and atomic
+/// expressions never need parentheses.
+fn expr_precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Ter { .. } => 0,
+        Expr::BinOp { op, .. } => BinaryOp::of_op(*op).precedence,
+        _ => UNIT,
+    }
+}
+
+/// reports the parentheses of `expr` that can be removed without changing how it is parsed, an
+/// expression in a position of precedence `min` parses the same without parentheses if its own
+/// precedence is at least `min`.
+fn expr<'a>(expr: &Expr<'a>, min: u8, warnings: &mut Vec<Warning<'a>>) {
+    match expr {
+        Expr::Nested(span, inner) => {
+            if expr_precedence(inner) >= min {
+                warnings.push(RedundantParens(*span));
+            }
+            self::expr(inner, 0, warnings);
+        }
+        Expr::Not(_, e) => self::expr(e, UNIT, warnings),
+        Expr::Neg(_, e) => match &**e {
+            // `-(-x)` without its parentheses is `--x`, which is lexed as a decrement
+            Expr::Nested(_, inner) if matches!(**inner, Expr::Neg(..)) => {
+                self::expr(inner, 0, warnings)
+            }
+            e => self::expr(e, UNIT, warnings),
+        },
+        Expr::Ter { cond, yes, no, .. } => {
+            // the condition is parsed as an `or` expression
+            self::expr(cond, 1, warnings);
+            self::expr(yes, 0, warnings);
+            self::expr(no, 0, warnings);
+        }
+        Expr::BinOp { op, lhs, rhs, .. } => {
+            let op = BinaryOp::of_op(*op);
+            self::expr(lhs, op.lhs_precedence(), warnings);
+            self::expr(rhs, op.rhs_precedence(), warnings);
+        }
+        Expr::Index { base, offset, .. } => {
+            self::expr(base, UNIT, warnings);
+            self::expr(offset, 0, warnings);
+        }
+        Expr::Loc(loc) => location(loc, warnings),
+        Expr::Call(call) => self::call(call, warnings),
+        Expr::Len { .. } | Expr::Literal { .. } | Expr::Error(_) => {}
+    }
+}
+
+fn location<'a>(loc: &Location<'a>, warnings: &mut Vec<Warning<'a>>) {
+    if let Location::Index { offset, .. } = loc {
+        expr(offset, 0, warnings)
+    }
+}
+
+fn call<'a>(call: &Call<'a>, warnings: &mut Vec<Warning<'a>>) {
+    call.args.iter().for_each(|arg| {
+        if let Arg::Expr(e) = arg {
+            expr(e, 0, warnings)
+        }
+    })
+}
+
+fn assign<'a>(assign: &Assign<'a>, warnings: &mut Vec<Warning<'a>>) {
+    location(&assign.lhs, warnings);
+    match &assign.op {
+        AssignExpr::Assign(e) | AssignExpr::AddAssign(e) | AssignExpr::SubAssign(e) => {
+            expr(e, 0, warnings)
+        }
+        AssignExpr::Inc | AssignExpr::Dec => {}
+    }
+}
+
+/// the parentheses around the conditions of `if` and `while` are part of the statement.
+fn condition<'a>(cond: &Expr<'a>, warnings: &mut Vec<Warning<'a>>) {
+    match cond {
+        Expr::Nested(_, inner) => expr(inner, 0, warnings),
+        cond => expr(cond, 0, warnings),
+    }
+}
+
+fn block<'a>(block: &Block<'a>, warnings: &mut Vec<Warning<'a>>) {
+    block.stmts().iter().for_each(|stmt| match stmt {
+        PStmt::Call(call) => self::call(call, warnings),
+        PStmt::Assign(assign) => self::assign(assign, warnings),
+        PStmt::Return { expr: Some(e), .. } => expr(e, 0, warnings),
+        PStmt::Return { expr: None, .. }
+        | PStmt::Break(_)
+        | PStmt::Continue(_)
+        | PStmt::Error(_) => {}
+        PStmt::If { cond, yes, no, .. } => {
+            condition(cond, warnings);
+            self::block(yes, warnings);
+            if let Some(no) = no {
+                self::block(no, warnings);
+            }
+        }
+        PStmt::While { cond, body, .. } => {
+            condition(cond, warnings);
+            self::block(body, warnings);
+        }
+        PStmt::For {
+            init,
+            cond,
+            update,
+            body,
+            ..
+        } => {
+            assign(init, warnings);
+            expr(cond, 0, warnings);
+            assign(update, warnings);
+            self::block(body, warnings);
+        }
+    })
+}
+
+/// finds the parentheses that do not change the precedence of the expression they wrap, e.g.
+/// `(x)` or `((a + b))`.
+///
+/// this is a style check, the parentheses are valid code.
+pub fn redundant_parens<'a>(root: &PRoot<'a>) -> Vec<Warning<'a>> {
+    let mut warnings = vec![];
+    root.funcs
+        .iter()
+        .for_each(|func| block(&func.body, &mut warnings));
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::span::SpanSource;
+
+    macro_rules! warnings {
+        ($warnings:ident, $text:expr) => {
+            let span_source = SpanSource::new($text);
+            let root = crate::parse(&span_source).unwrap();
+            let $warnings = redundant_parens(&root)
+                .iter()
+                .map(|warning| match warning {
+                    RedundantParens(span) => span.source(),
+                    w => panic!("expected redundant parens, found: {:?}", w),
+                })
+                .collect::<Vec<_>>();
+        };
+    }
+
+    #[test]
+    fn redundant() {
+        warnings!(
+            warnings,
+            "void main() {\n  x = (x);\n  x = ((a + b));\n  x = (a * b) + (c * d) - (e - f);\n  x = -(f());\n}"
+        );
+        assert_eq!(
+            warnings,
+            ["(x)", "((a + b))", "(a + b)", "(a * b)", "(c * d)", "(f())"]
+        );
+    }
+
+    #[test]
+    fn needed() {
+        warnings!(
+            warnings,
+            "void main() {\n  x = (a + b) * c;\n  x = a - (b - c);\n  x = !(a && b);\n  if (x) { x = (a || b) && c; }\n  while (x < y) { }\n}"
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn nested_negation() {
+        warnings!(
+            warnings,
+            "void main() {\n  x = -(-x);\n  x = -(-(y));\n  x = -((-x));\n}"
+        );
+        assert_eq!(warnings, ["(y)", "((-x))", "(-x)"]);
+    }
+
+    #[test]
+    fn condition() {
+        warnings!(warnings, "void main() { if ((x)) { } }");
+        assert_eq!(warnings, ["(x)"]);
+    }
+}
@@ -0,0 +1,307 @@
+use crate::span::SourceRange;
+
+use super::arena::{BlockId, DefId, DefKind, Hir, StmtKind};
+
+/// the index of a scope in its [`ScopeTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(usize);
+
+impl ScopeId {
+    /// the position of the scope in [`ScopeTree::scopes`], the global scope is at 0.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// the imports, the globals and the methods of the program.
+    Global,
+    /// the parameters of the method.
+    Params(DefId),
+    /// the locals of a block, the body of a method or of a statement.
+    Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    /// the part of the source the names of the scope are visible in, the global scope covers the
+    /// whole source.
+    pub span: Option<SourceRange>,
+    /// the definitions of the scope, in the order of their declarations.
+    pub symbols: Vec<DefId>,
+    pub children: Vec<ScopeId>,
+}
+
+impl Scope {
+    fn new(kind: ScopeKind, parent: Option<ScopeId>, span: Option<SourceRange>) -> Self {
+        Self {
+            kind,
+            parent,
+            span,
+            symbols: vec![],
+            children: vec![],
+        }
+    }
+
+    fn contains(&self, offset: usize) -> bool {
+        self.span.is_none_or(|span| span.contains(offset))
+    }
+}
+
+/// the scopes of a lowered program nested like in the source, the global scope holds a
+/// [`ScopeKind::Params`] scope for every method which holds the body of the method.
+///
+/// the names are resolved like [`Hir::lower`] resolves them, the innermost declaration of a name
+/// hides the outer ones.
+#[derive(Debug, Clone)]
+pub struct ScopeTree<'h> {
+    hir: &'h Hir,
+    scopes: Vec<Scope>,
+}
+
+impl<'h> ScopeTree<'h> {
+    pub fn new(hir: &'h Hir) -> Self {
+        let mut tree = Self {
+            hir,
+            scopes: vec![Scope::new(ScopeKind::Global, None, None)],
+        };
+        let mut globals = hir
+            .defs()
+            .filter(|(_, def)| {
+                matches!(
+                    def.kind,
+                    DefKind::Import | DefKind::Global | DefKind::Method
+                )
+            })
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        globals.sort_by_key(|id| hir.def(*id).span.offset());
+        tree.scopes[0].symbols = globals;
+        hir.methods().iter().for_each(|method| {
+            let span = hir.def(method.def).span;
+            let params = tree.nested(
+                tree.global(),
+                ScopeKind::Params(method.def),
+                span,
+                &method.params,
+            );
+            tree.block(params, method.body);
+        });
+        // the methods are lowered in the order of their names
+        let offsets = tree
+            .scopes
+            .iter()
+            .map(|scope| scope.span.map(|span| span.offset()))
+            .collect::<Vec<_>>();
+        tree.scopes
+            .iter_mut()
+            .for_each(|scope| scope.children.sort_by_key(|id| offsets[id.0]));
+        tree
+    }
+
+    fn nested(
+        &mut self,
+        parent: ScopeId,
+        kind: ScopeKind,
+        span: SourceRange,
+        symbols: &[DefId],
+    ) -> ScopeId {
+        let id = ScopeId(self.scopes.len());
+        let mut scope = Scope::new(kind, Some(parent), Some(span));
+        scope.symbols = symbols.to_vec();
+        self.scopes.push(scope);
+        self.scopes[parent.0].children.push(id);
+        id
+    }
+
+    fn block(&mut self, parent: ScopeId, block: BlockId) {
+        let hir = self.hir;
+        let data = hir.block(block);
+        let id = self.nested(parent, ScopeKind::Block, data.span, &data.decls);
+        data.stmts.iter().for_each(|stmt| match hir.stmt(*stmt) {
+            StmtKind::If(arms, otherwise) => {
+                arms.iter().for_each(|(_, body)| self.block(id, *body));
+                // an `if` without an `else` is lowered with an empty one
+                let data = hir.block(*otherwise);
+                if !(data.decls.is_empty() && data.stmts.is_empty()) {
+                    self.block(id, *otherwise)
+                }
+            }
+            StmtKind::While(_, body) | StmtKind::For { body, .. } => self.block(id, *body),
+            _ => {}
+        })
+    }
+
+    /// the program the scopes are of, the symbols are its definitions.
+    pub fn hir(&self) -> &'h Hir {
+        self.hir
+    }
+
+    /// the scope of the imports, the globals and the methods.
+    pub fn global(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    /// all the scopes, every scope comes before the scopes nested in it.
+    pub fn scopes(&self) -> impl Iterator<Item = (ScopeId, &Scope)> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .map(|(id, scope)| (ScopeId(id), scope))
+    }
+
+    /// `id` and the scopes it is nested in, from the innermost to the global scope.
+    pub fn ancestors(&self, id: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(id), |id| self.scope(*id).parent)
+    }
+
+    /// the definition named `name` in the scope `id`, without looking at the outer scopes.
+    pub fn get(&self, id: ScopeId, name: &str) -> Option<DefId> {
+        self.scope(id)
+            .symbols
+            .iter()
+            .copied()
+            .find(|def| self.hir.def(*def).name == name)
+    }
+
+    /// the innermost scope that contains the byte at `offset`.
+    pub fn scope_at(&self, offset: usize) -> ScopeId {
+        std::iter::successors(Some(self.global()), |id| {
+            self.scope(*id)
+                .children
+                .iter()
+                .copied()
+                .find(|child| self.scope(*child).contains(offset))
+        })
+        .last()
+        .unwrap()
+    }
+
+    /// the declaration `name` refers to at `offset`, e.g. for going to the definition of the
+    /// identifier under the cursor.
+    pub fn resolve(&self, offset: usize, name: &str) -> Option<DefId> {
+        self.ancestors(self.scope_at(offset))
+            .find_map(|id| self.get(id, name))
+    }
+
+    /// the names that can be used at `offset`, the innermost first, the names hidden by an inner
+    /// declaration are left out, e.g. for completing the identifier under the cursor.
+    pub fn visible_at(&self, offset: usize) -> Vec<DefId> {
+        let mut visible: Vec<DefId> = vec![];
+        self.ancestors(self.scope_at(offset)).for_each(|id| {
+            self.scope(id).symbols.iter().for_each(|def| {
+                let name = &self.hir.def(*def).name;
+                if !visible.iter().any(|seen| self.hir.def(*seen).name == *name) {
+                    visible.push(*def)
+                }
+            })
+        });
+        visible
+    }
+
+    /// the declarations that hide a declaration of a scope they are nested in, each with the
+    /// declaration it hides.
+    pub fn shadowed(&self) -> Vec<(DefId, DefId)> {
+        self.scopes()
+            .filter_map(|(_, scope)| scope.parent.map(|parent| (scope, parent)))
+            .flat_map(|(scope, parent)| {
+                scope.symbols.iter().filter_map(move |def| {
+                    self.ancestors(parent)
+                        .find_map(|id| self.get(id, &self.hir.def(*def).name))
+                        .map(|hidden| (*def, hidden))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Type;
+
+    const CODE: &str = "import printf;\nint x, a[2];\nvoid f(int y) {\n  int x;\n  \
+                        while (y > 0) {\n    bool a;\n    y -= 1;\n  }\n}\n\
+                        void main() {\n  f(x);\n}\n";
+
+    fn hir() -> Hir {
+        Hir::lower(&crate::semantic_check(CODE.as_bytes(), "scope.dcf").unwrap())
+    }
+
+    fn names<'h>(hir: &'h Hir, defs: &[DefId]) -> Vec<&'h str> {
+        defs.iter().map(|def| hir.def(*def).name.as_str()).collect()
+    }
+
+    #[test]
+    fn nesting() {
+        let hir = hir();
+        let tree = ScopeTree::new(&hir);
+        let global = tree.scope(tree.global());
+        assert_eq!(
+            names(&hir, &global.symbols),
+            ["printf", "x", "a", "f", "main"]
+        );
+        let kinds = tree
+            .ancestors(tree.scope_at(CODE.find("bool").unwrap()))
+            .map(|id| tree.scope(id).kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ScopeKind::Block,
+                ScopeKind::Block,
+                ScopeKind::Params(hir.global("f").unwrap()),
+                ScopeKind::Global
+            ]
+        );
+        // `f` and `main` each have their parameters and their body
+        assert_eq!(tree.scopes().count(), 6);
+        assert_eq!(tree.scope_at(0), tree.global());
+    }
+
+    #[test]
+    fn resolve() {
+        let hir = hir();
+        let tree = ScopeTree::new(&hir);
+        let at = |pattern: &str| CODE.find(pattern).unwrap();
+        let def = |offset: usize, name: &str| hir.def(tree.resolve(offset, name).unwrap());
+        assert_eq!(def(at("y -="), "x").span.position(), (4, 7));
+        assert_eq!(def(at("f(x)"), "x").span.position(), (2, 5));
+        let local = def(at("y -="), "a");
+        assert_eq!((local.kind, local.ty), (DefKind::Local, Some(Type::Bool)));
+        let printf = def(at("f(x)"), "printf");
+        assert_eq!(printf.kind, DefKind::Import);
+        assert_eq!(printf.span.position(), (1, 8));
+        assert!(tree.resolve(at("f(x)"), "y").is_none());
+    }
+
+    #[test]
+    fn visible() {
+        let hir = hir();
+        let tree = ScopeTree::new(&hir);
+        let visible = tree.visible_at(CODE.find("y -=").unwrap());
+        assert_eq!(
+            names(&hir, &visible),
+            ["a", "x", "y", "printf", "f", "main"]
+        );
+    }
+
+    #[test]
+    fn shadowed() {
+        let hir = hir();
+        let tree = ScopeTree::new(&hir);
+        let shadowed = tree
+            .shadowed()
+            .into_iter()
+            .map(|(def, hidden)| (hir.def(def).span.line(), hir.def(hidden).span.line()))
+            .collect::<Vec<_>>();
+        assert_eq!(shadowed, [(4, 2), (6, 2)]);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    cst::{Block, PFunction, PRoot, PStmt, PVar},
+    hir::error::Warning::{self, *},
+    span::*,
+};
+
+use std::collections::HashMap;
+
+fn shadowing<'a>(
+    vars: &[PVar<'a>],
+    imports: &HashMap<&'a str, Span<'a>>,
+    warnings: &mut Vec<Warning<'a>>,
+) {
+    warnings.extend(vars.iter().filter_map(|var| {
+        imports
+            .get(var.name().source())
+            .map(|import| ShadowsImport {
+                decl: var.name(),
+                import: *import,
+            })
+    }))
+}
+
+/// calls `f` on the blocks nested in `block`, at any depth.
+fn nested_blocks<'a>(block: &Block<'a>, f: &mut impl FnMut(&Block<'a>)) {
+    block.stmts().iter().for_each(|stmt| match stmt {
+        PStmt::If { yes, no, .. } => {
+            f(yes);
+            nested_blocks(yes, f);
+            if let Some(no) = no {
+                f(no);
+                nested_blocks(no, f);
+            }
+        }
+        PStmt::While { body, .. } | PStmt::For { body, .. } => {
+            f(body);
+            nested_blocks(body, f)
+        }
+        _ => {}
+    })
+}
+
+fn function<'a>(
+    func: &PFunction<'a>,
+    imports: &HashMap<&'a str, Span<'a>>,
+    warnings: &mut Vec<Warning<'a>>,
+) {
+    shadowing(&func.args, imports, warnings);
+    shadowing(func.body.decls(), imports, warnings);
+    nested_blocks(&func.body, &mut |block| {
+        shadowing(block.decls(), imports, warnings)
+    });
+}
+
+/// finds the parameters and locals that have the name of an import.
+///
+/// globals that have the name of an import are redefinitions and they are reported by
+/// `Root::from_proot`.
+pub fn shadowed_imports<'a>(root: &PRoot<'a>) -> Vec<Warning<'a>> {
+    let imports = root
+        .imports
+        .iter()
+        .map(|imp| (imp.name().source(), imp.name()))
+        .collect::<HashMap<_, _>>();
+    let mut warnings = vec![];
+    root.funcs
+        .iter()
+        .for_each(|func| function(func, &imports, &mut warnings));
+    warnings
+}
+
+/// finds the locals of the nested blocks of a method that have the name of one of its parameters.
+///
+/// the locals of the body itself cannot have the name of a parameter, they are redefinitions.
+pub fn shadowed_params<'a>(root: &PRoot<'a>) -> Vec<Warning<'a>> {
+    let mut warnings = vec![];
+    root.funcs.iter().for_each(|func| {
+        let params = func
+            .args
+            .iter()
+            .map(|param| (param.name().source(), param.name()))
+            .collect::<HashMap<_, _>>();
+        nested_blocks(&func.body, &mut |block| {
+            warnings.extend(block.decls().iter().filter_map(|var| {
+                params
+                    .get(var.name().source())
+                    .map(|param| ShadowsParameter {
+                        decl: var.name(),
+                        param: *param,
+                    })
+            }))
+        })
+    });
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ast::Root, hir::error::Error};
+
+    macro_rules! proot {
+        ($root:ident, $text:expr) => {
+            let span_source = SpanSource::new($text);
+            let $root: PRoot = crate::parse(&span_source).unwrap();
+        };
+    }
+
+    #[test]
+    fn global() {
+        proot!(root, "import foo;\nint foo;\nvoid main() {}");
+        let errors = Root::from_proot(root).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            // a global is in the scope of the imports, so it is a redefinition of the import
+            Error::Redifinition(decl, import) => {
+                assert_eq!(decl.position(), (2, 5));
+                assert_eq!(import.position(), (1, 8));
+            }
+            ref e => panic!("expected a redefinition, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn local() {
+        proot!(
+            root,
+            "import foo;\nimport bar;\nvoid f(int bar) {\n  if (true) { int foo; }\n}\nvoid main() {}"
+        );
+        let warnings = shadowed_imports(&root)
+            .into_iter()
+            .map(|w| match w {
+                ShadowsImport { decl, import } => (decl.position(), import.position()),
+                w => panic!("expected shadowed import, found: {:?}", w),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(warnings, [((3, 12), (2, 8)), ((4, 19), (1, 8))]);
+        assert!(Root::from_proot(root).is_ok());
+    }
+
+    #[test]
+    fn parameter() {
+        proot!(
+            root,
+            "void f(int x, bool y) {\n  int z;\n  while (y) {\n    int y;\n    \
+             if (y) { int x; }\n  }\n}\nvoid main() {}"
+        );
+        let warnings = shadowed_params(&root)
+            .into_iter()
+            .map(|w| match w {
+                ShadowsParameter { decl, param } => (decl.position(), param.position()),
+                w => panic!("expected shadowed parameter, found: {:?}", w),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(warnings, [((4, 9), (1, 20)), ((5, 18), (1, 12))]);
+    }
+}
@@ -77,11 +77,31 @@ pub(super) fn construct_var_hashmap<'a, T: AsRef<[PVar<'a>]>>(
         })
 }
 
+/// the parameters that have the name of an earlier parameter of the same method.
+pub(super) fn duplicate_params<'a>(params: &[PVar<'a>]) -> Option<Vec<Error<'a>>> {
+    let mut seen = HashMap::new();
+    let duplicates = params
+        .iter()
+        .filter_map(|param| {
+            let param = param.name();
+            match seen.get(param.source()) {
+                Some(&previous) => Some(DuplicateParameter { param, previous }),
+                None => {
+                    seen.insert(param.source(), param);
+                    None
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    (!duplicates.is_empty()).then_some(duplicates)
+}
+
+/// the names of `syms` that are declared again, with their first declaration.
 pub(super) fn get_redefs<'a>(syms: impl Iterator<Item = Span<'a>>) -> Option<Vec<Error<'a>>> {
     let redefs = syms
         .fold((vec![], HashSet::new()), |(mut redefs, mut syms), sym| {
-            if let Some(old_f) = syms.get(&sym) {
-                redefs.push(Error::Redifinition(*old_f, sym));
+            if let Some(previous) = syms.get(&sym) {
+                redefs.push(Error::Redifinition(sym, *previous));
             } else {
                 syms.insert(sym);
             }
@@ -0,0 +1,262 @@
+use crate::{
+    cst::{Arg, Assign, AssignExpr, Block, Call, Expr, Location, PFunction, PRoot, PStmt, PVar},
+    hir::error::Warning::{self, *},
+    span::*,
+};
+
+use std::collections::{HashMap, HashSet};
+
+/// a local scalar is identified by the offset of its declaration.
+type VarId = usize;
+
+/// the local scalars that are assigned on every path reaching a statement, `None` if the statement
+/// is not reachable.
+type Assigned = Option<HashSet<VarId>>;
+
+#[derive(Debug, Default)]
+struct Checker<'a> {
+    /// `None` marks the symbols that are not tracked (parameters and arrays).
+    scopes: Vec<HashMap<&'a str, Option<VarId>>>,
+    warned: HashSet<VarId>,
+    warnings: Vec<Warning<'a>>,
+}
+
+impl<'a> Checker<'a> {
+    fn lookup(&self, name: &str) -> Option<VarId> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+            .flatten()
+    }
+
+    /// reports the first read of each unassigned variable.
+    fn read(&mut self, ident: Span<'a>, assigned: &HashSet<VarId>) {
+        if let Some(id) = self.lookup(ident.source()) {
+            if !assigned.contains(&id) && self.warned.insert(id) {
+                self.warnings.push(UseBeforeAssign(ident));
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr<'a>, assigned: &HashSet<VarId>) {
+        match expr {
+            Expr::Loc(Location::Scalar(ident)) => self.read(*ident, assigned),
+            Expr::Loc(Location::Index { offset, .. }) => self.expr(offset, assigned),
+            Expr::Nested(_, e) | Expr::Not(_, e) | Expr::Neg(_, e) => self.expr(e, assigned),
+            Expr::Ter { cond, yes, no, .. } => {
+                self.expr(cond, assigned);
+                self.expr(yes, assigned);
+                self.expr(no, assigned);
+            }
+            Expr::BinOp { lhs, rhs, .. }
+            | Expr::Index {
+                base: lhs,
+                offset: rhs,
+                ..
+            } => {
+                self.expr(lhs, assigned);
+                self.expr(rhs, assigned);
+            }
+            Expr::Call(call) => self.call(call, assigned),
+            Expr::Len { .. } | Expr::Literal { .. } | Expr::Error(_) => {}
+        }
+    }
+
+    fn call(&mut self, call: &Call<'a>, assigned: &HashSet<VarId>) {
+        call.args.iter().for_each(|arg| {
+            if let Arg::Expr(e) = arg {
+                self.expr(e, assigned)
+            }
+        })
+    }
+
+    fn assign(&mut self, assign: &Assign<'a>, assigned: &mut HashSet<VarId>) {
+        let scalar = match &assign.lhs {
+            Location::Scalar(ident) => Some(*ident),
+            Location::Index { offset, .. } => {
+                self.expr(offset, assigned);
+                None
+            }
+        };
+        match &assign.op {
+            AssignExpr::Assign(e) => {
+                self.expr(e, assigned);
+                if let Some(id) = scalar.and_then(|ident| self.lookup(ident.source())) {
+                    assigned.insert(id);
+                }
+            }
+            AssignExpr::AddAssign(e) | AssignExpr::SubAssign(e) => {
+                self.expr(e, assigned);
+                if let Some(ident) = scalar {
+                    self.read(ident, assigned)
+                }
+            }
+            AssignExpr::Inc | AssignExpr::Dec => {
+                if let Some(ident) = scalar {
+                    self.read(ident, assigned)
+                }
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block<'a>, assigned: Assigned) -> Assigned {
+        self.scopes.push(
+            block
+                .decls()
+                .iter()
+                .map(|var| match var {
+                    PVar::Scalar { ident, .. } => (ident.source(), Some(ident.offset())),
+                    PVar::Array { ident, .. } => (ident.source(), None),
+                })
+                .collect(),
+        );
+        let assigned = block
+            .stmts()
+            .iter()
+            .fold(assigned, |assigned, stmt| self.stmt(stmt, assigned));
+        self.scopes.pop();
+        assigned
+    }
+
+    fn stmt(&mut self, stmt: &PStmt<'a>, assigned: Assigned) -> Assigned {
+        let mut assigned = assigned?;
+        match stmt {
+            PStmt::Call(call) => {
+                self.call(call, &assigned);
+                Some(assigned)
+            }
+            PStmt::Assign(assign) => {
+                self.assign(assign, &mut assigned);
+                Some(assigned)
+            }
+            PStmt::Return { expr, .. } => {
+                if let Some(e) = expr {
+                    self.expr(e, &assigned)
+                }
+                None
+            }
+            PStmt::Break(_) | PStmt::Continue(_) => None,
+            PStmt::Error(_) => Some(assigned),
+            PStmt::If { cond, yes, no, .. } => {
+                self.expr(cond, &assigned);
+                let yes = self.block(yes, Some(assigned.clone()));
+                let no = match no {
+                    Some(no) => self.block(no, Some(assigned)),
+                    None => Some(assigned),
+                };
+                match (yes, no) {
+                    (Some(yes), Some(no)) => Some(yes.intersection(&no).copied().collect()),
+                    (yes, no) => yes.or(no),
+                }
+            }
+            // the body of a loop may not run at all so it does not assign anything.
+            PStmt::While { cond, body, .. } => {
+                self.expr(cond, &assigned);
+                self.block(body, Some(assigned.clone()));
+                Some(assigned)
+            }
+            PStmt::For {
+                init,
+                cond,
+                update,
+                body,
+                ..
+            } => {
+                self.assign(init, &mut assigned);
+                self.expr(cond, &assigned);
+                self.block(body, Some(assigned.clone()));
+                // the update may be reached by a `continue` before anything in the body is assigned.
+                self.assign(update, &mut assigned.clone());
+                Some(assigned)
+            }
+        }
+    }
+
+    fn function(&mut self, func: &PFunction<'a>) {
+        self.scopes.push(
+            func.args
+                .iter()
+                .map(|arg| (arg.name().source(), None))
+                .collect(),
+        );
+        self.block(&func.body, Some(HashSet::new()));
+        self.scopes.pop();
+    }
+}
+
+/// finds the local scalars that are read before they are assigned on some path.
+///
+/// globals, parameters and arrays are not checked, the elements of arrays are zero initialized so
+/// reading them before a store is valid.
+pub fn uninitialized_reads<'a>(root: &PRoot<'a>) -> Vec<Warning<'a>> {
+    let mut checker = Checker::default();
+    root.funcs.iter().for_each(|func| checker.function(func));
+    checker.warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    macro_rules! warnings {
+        ($warnings:ident, $text:expr) => {
+            let span_source = SpanSource::new($text);
+            let root = crate::parse(&span_source).unwrap();
+            let $warnings = uninitialized_reads(&root);
+        };
+    }
+
+    #[test]
+    fn use_before_assign() {
+        warnings!(
+            warnings,
+            "void main() {\n  int x, y;\n  y = x + 1;\n  y = x;\n}"
+        );
+        assert_eq!(warnings.len(), 1);
+        let UseBeforeAssign(span) = warnings[0] else {
+            panic!("expected use before assign, found: {:?}", warnings[0]);
+        };
+        assert_eq!(span.source(), "x");
+        assert_eq!(span.position(), (3, 7));
+    }
+
+    #[test]
+    fn assigned_on_some_path() {
+        warnings!(
+            warnings,
+            "void main() { int x; if (true) { x = 1; } x += 1; }"
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn initialized() {
+        warnings!(
+            warnings,
+            "int g;
+            void f(int p) {
+                int x, y, a[10];
+                if (p > 0) { x = 1; } else { x = 2; }
+                if (p > 1) { y = 1; } else { return; }
+                for (p = 0; p < 10; p++) { a[p] = x + y + g; }
+            }"
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn array_elements_are_initialized() {
+        warnings!(
+            warnings,
+            "void main() {\n  int a[4], i, x;\n  x = a[2];\n  x = a[i];\n}"
+        );
+        // only the uninitialized index is reported, not the element
+        assert_eq!(warnings.len(), 1);
+        let UseBeforeAssign(span) = warnings[0] else {
+            panic!("expected use before assign, found: {:?}", warnings[0]);
+        };
+        assert_eq!((span.source(), span.position()), ("i", (4, 9)));
+    }
+}
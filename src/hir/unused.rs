@@ -0,0 +1,189 @@
+use crate::{
+    cst::{Arg, Assign, AssignExpr, Block, Call, Expr, Location, PFunction, PRoot, PStmt},
+    hir::error::Warning::{self, *},
+    span::*,
+};
+
+use std::collections::HashSet;
+
+/// a declaration in scope, `None` marks the declarations that are not checked (parameters).
+type Decl<'a> = (&'a str, Option<Span<'a>>);
+
+#[derive(Debug, Default)]
+struct Checker<'a> {
+    scopes: Vec<Vec<Decl<'a>>>,
+    /// the offsets of the declarations that are referred to.
+    used: HashSet<usize>,
+    warnings: Vec<Warning<'a>>,
+}
+
+impl<'a> Checker<'a> {
+    fn refer(&mut self, ident: Span<'a>) {
+        let decl = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.iter().find(|(name, _)| *name == ident.source()));
+        if let Some((_, Some(decl))) = decl {
+            self.used.insert(decl.offset());
+        }
+    }
+
+    fn location(&mut self, loc: &Location<'a>) {
+        self.refer(loc.ident());
+        if let Location::Index { offset, .. } = loc {
+            self.expr(offset)
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::Loc(loc) => self.location(loc),
+            Expr::Len { id, .. } => self.refer(*id),
+            Expr::Nested(_, e) | Expr::Not(_, e) | Expr::Neg(_, e) => self.expr(e),
+            Expr::Ter { cond, yes, no, .. } => {
+                self.expr(cond);
+                self.expr(yes);
+                self.expr(no);
+            }
+            Expr::BinOp { lhs, rhs, .. }
+            | Expr::Index {
+                base: lhs,
+                offset: rhs,
+                ..
+            } => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            Expr::Call(call) => self.call(call),
+            Expr::Literal { .. } | Expr::Error(_) => {}
+        }
+    }
+
+    fn call(&mut self, call: &Call<'a>) {
+        call.args.iter().for_each(|arg| {
+            if let Arg::Expr(e) = arg {
+                self.expr(e)
+            }
+        })
+    }
+
+    fn assign(&mut self, assign: &Assign<'a>) {
+        self.location(&assign.lhs);
+        if let AssignExpr::Assign(e) | AssignExpr::AddAssign(e) | AssignExpr::SubAssign(e) =
+            &assign.op
+        {
+            self.expr(e)
+        }
+    }
+
+    fn block(&mut self, block: &Block<'a>) {
+        self.scopes.push(
+            block
+                .decls()
+                .iter()
+                .map(|var| (var.name().source(), Some(var.name())))
+                .collect(),
+        );
+        block.stmts().iter().for_each(|stmt| self.stmt(stmt));
+        let scope = self.scopes.pop().unwrap_or_default();
+        self.warnings.extend(
+            scope
+                .into_iter()
+                .filter_map(|(_, decl)| decl)
+                .filter(|decl| !self.used.contains(&decl.offset()))
+                .map(UnusedVariable),
+        );
+    }
+
+    fn stmt(&mut self, stmt: &PStmt<'a>) {
+        match stmt {
+            PStmt::Call(call) => self.call(call),
+            PStmt::Assign(assign) => self.assign(assign),
+            PStmt::Return { expr, .. } => {
+                if let Some(e) = expr {
+                    self.expr(e)
+                }
+            }
+            PStmt::Break(_) | PStmt::Continue(_) | PStmt::Error(_) => {}
+            PStmt::If { cond, yes, no, .. } => {
+                self.expr(cond);
+                self.block(yes);
+                if let Some(no) = no {
+                    self.block(no)
+                }
+            }
+            PStmt::While { cond, body, .. } => {
+                self.expr(cond);
+                self.block(body);
+            }
+            PStmt::For {
+                init,
+                cond,
+                update,
+                body,
+                ..
+            } => {
+                self.assign(init);
+                self.expr(cond);
+                self.assign(update);
+                self.block(body);
+            }
+        }
+    }
+
+    fn function(&mut self, func: &PFunction<'a>) {
+        self.scopes.push(
+            func.args
+                .iter()
+                .map(|arg| (arg.name().source(), None))
+                .collect(),
+        );
+        self.block(&func.body);
+        self.scopes.pop();
+    }
+}
+
+/// finds the locals that are declared but never referred to, neither read nor assigned.
+///
+/// globals and parameters are not checked, a global may be there for another file and a parameter
+/// is part of the signature of the method.
+pub fn unused_variables<'a>(root: &PRoot<'a>) -> Vec<Warning<'a>> {
+    let mut checker = Checker::default();
+    root.funcs.iter().for_each(|func| checker.function(func));
+    checker
+        .warnings
+        .sort_by_key(|warning| warning.span().offset());
+    checker.warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unused(text: &str) -> Vec<(String, (usize, usize))> {
+        let span_source = SpanSource::new(text);
+        let root = crate::parse(&span_source).unwrap();
+        unused_variables(&root)
+            .into_iter()
+            .map(|w| (w.span().to_string(), w.span().position()))
+            .collect()
+    }
+
+    #[test]
+    fn unused_locals() {
+        let text = "int g;\nvoid f(int p) {\n  int x, y, a[2];\n  if (p > 0) {\n    int x;\n  \
+                    }\n  x = len(a);\n}\nvoid main() {}\n";
+        assert_eq!(
+            unused(text),
+            [("y".to_string(), (3, 10)), ("x".to_string(), (5, 9))]
+        );
+    }
+
+    #[test]
+    fn used() {
+        let text = "void main() {\n  int i, n, a[4];\n  for (i = 0; i < 4; i++) {\n    \
+                    a[i] += n;\n  }\n}\n";
+        assert!(unused(text).is_empty());
+    }
+}
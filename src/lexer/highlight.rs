@@ -0,0 +1,61 @@
+use crate::span::Span;
+
+use super::{all_tokens, LexerOptions, Token};
+
+/// the token types of [`semantic_tokens`] in the order of their indices, named after the standard
+/// semantic token types of the language server protocol.
+pub const TOKEN_TYPES: [&str; 6] = [
+    "keyword", "operator", "number", "string", "variable", "comment",
+];
+
+/// the index in [`TOKEN_TYPES`] of the type `token` is highlighted as, `None` for spaces, the
+/// invalid tokens and `Eof`.
+pub const fn token_type_index(token: Token) -> Option<u32> {
+    match token {
+        Token::Space | Token::ErrorToken | Token::Eof => None,
+        Token::LineComment | Token::BlockComment => Some(5),
+        Token::Identifier(_) => Some(4),
+        Token::StringLiteral | Token::CharLiteral(_) => Some(3),
+        Token::DecimalLiteral | Token::HexLiteral => Some(2),
+        token if token.is_keyword() => Some(0),
+        _ => Some(1),
+    }
+}
+
+/// encodes the tokens of `text` like the semantic tokens of the language server protocol, each
+/// token is `[delta line, delta start, length, token type, modifiers]` where the start is
+/// relative to the previous token if both are on the same line.
+///
+/// the tokens that span multiple lines are split into one token per line, the erroneous tokens are
+/// left out and the lengths are in bytes.
+pub fn semantic_tokens(text: Span) -> Vec<[u32; 5]> {
+    let mut prev = (0, 0);
+    all_tokens(text, LexerOptions::default())
+        .filter_map(|tok| {
+            let index = token_type_index(*tok.get().as_ref().ok()?)?;
+            let lines = tok
+                .span()
+                .source()
+                .split('\n')
+                .scan(tok.position(), |pos, line| {
+                    let start = *pos;
+                    *pos = (pos.0 + 1, 1);
+                    Some((start, line.len()))
+                });
+            Some(lines.map(move |pos| (pos, index)))
+        })
+        .flatten()
+        .filter(|&((_, len), _)| len > 0)
+        .map(|(((line, column), len), index)| {
+            let (line, start) = (line as u32 - 1, column as u32 - 1);
+            let delta_start = if line == prev.0 {
+                start - prev.1
+            } else {
+                start
+            };
+            let token = [line - prev.0, delta_start, len as u32, index, 0];
+            prev = (line, start);
+            token
+        })
+        .collect()
+}
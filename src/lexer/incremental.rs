@@ -0,0 +1,232 @@
+use crate::{
+    error::CCError,
+    span::{Span, SpanSource, Spanned},
+};
+
+use super::{all_tokens, Interner, LexerOptions, Result, Token};
+
+/// a token of a [`TokenCache`], the spaces and comments included, owned so the cache does not
+/// borrow its text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexedToken {
+    /// the token or the messages of the error, the unexpected characters are reported without
+    /// the token before them since an edit of that token does not relex them.
+    pub token: std::result::Result<Token, Vec<String>>,
+    /// the byte offset of the token from the start of the text.
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl LexedToken {
+    /// `tok` lexed from the text starting at the byte offset `base`.
+    fn new(tok: Spanned<Result>, base: usize) -> Self {
+        Self {
+            token: tok
+                .get()
+                .map_err(|err| err.msgs().into_iter().map(|(msg, _)| msg).collect()),
+            offset: tok.span().offset() - base,
+            len: tok.span().len(),
+        }
+    }
+}
+
+/// replaces the `removed` bytes at the byte offset `offset` of a text with `inserted`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub offset: usize,
+    pub removed: usize,
+    pub inserted: String,
+}
+
+/// how the tokens of a text change with an edit: the `removed` tokens from the index `start` are
+/// replaced with `inserted`, and the tokens after them move by `shift` bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenDelta {
+    pub start: usize,
+    pub removed: usize,
+    pub inserted: Vec<LexedToken>,
+    pub shift: isize,
+}
+
+impl TokenDelta {
+    /// updates the tokens the delta was computed from to the tokens of the edited text.
+    pub fn apply(&self, tokens: &mut Vec<LexedToken>) {
+        let end = self.start + self.removed;
+        tokens[end..]
+            .iter_mut()
+            .for_each(|tok| tok.offset = (tok.offset as isize + self.shift) as usize);
+        tokens.splice(self.start..end, self.inserted.iter().cloned());
+    }
+}
+
+/// lexes the part of `text` that `edit` damaged, `old_tokens` are the tokens of the text before
+/// the edit and `text` is the text after it.
+///
+/// the lexing starts at the token the edit starts in, or at the token ending where the edit
+/// starts since the edit can extend it, e.g. typing a letter after an identifier. it stops at the
+/// first token after the edit that starts where an old token starts, the text from there on is
+/// not edited so its tokens are the old ones.
+pub fn relex(
+    old_tokens: &[LexedToken],
+    text: Span,
+    edit: &TextEdit,
+    options: LexerOptions,
+) -> TokenDelta {
+    let start = old_tokens.partition_point(|tok| tok.offset + tok.len < edit.offset);
+    let from = old_tokens.get(start).map_or(0, |tok| tok.offset);
+    let shift = edit.inserted.len() as isize - edit.removed as isize;
+    let edit_end = edit.offset + edit.inserted.len();
+    let mut end = old_tokens.len();
+    let mut inserted = vec![];
+    for tok in all_tokens(text.split_at(from).1, options) {
+        let offset = tok.span().offset() - text.offset();
+        if offset >= edit_end {
+            let old_offset = (offset as isize - shift) as usize;
+            if let Ok(i) = old_tokens[start..].binary_search_by_key(&old_offset, |tok| tok.offset) {
+                end = start + i;
+                break;
+            }
+        }
+        inserted.push(LexedToken::new(tok, text.offset()));
+    }
+    TokenDelta {
+        start,
+        removed: end - start,
+        inserted,
+        shift,
+    }
+}
+
+/// the tokens of a text kept up to date as it is edited, e.g. the text of an editor, without
+/// lexing the whole text again after each edit.
+#[derive(Debug, Clone)]
+pub struct TokenCache {
+    text: String,
+    tokens: Vec<LexedToken>,
+    options: LexerOptions,
+    /// the names of the identifiers, kept across the edits so a name keeps its symbol.
+    interner: Interner,
+}
+
+impl TokenCache {
+    pub fn new(text: String) -> Self {
+        Self::with_options(text, LexerOptions::default())
+    }
+
+    pub fn with_options(text: String, options: LexerOptions) -> Self {
+        let code = SpanSource::new(&text);
+        let tokens = all_tokens(code.source(), options)
+            .map(|tok| LexedToken::new(tok, 0))
+            .collect();
+        let interner = code.into_interner();
+        Self {
+            text,
+            tokens,
+            options,
+            interner,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// the tokens of the text, without the `Eof` token.
+    pub fn tokens(&self) -> &[LexedToken] {
+        &self.tokens
+    }
+
+    /// the names of the symbols of the identifiers of the tokens.
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    /// applies `edit` to the text and relexes the part it damaged, panics if the edited bytes are
+    /// not in the text or do not start and end at char boundaries.
+    pub fn edit(&mut self, edit: &TextEdit) -> TokenDelta {
+        self.text
+            .replace_range(edit.offset..edit.offset + edit.removed, &edit.inserted);
+        let code = SpanSource::new(&self.text).with_interner(std::mem::take(&mut self.interner));
+        let delta = relex(&self.tokens, code.source(), edit, self.options);
+        self.interner = code.into_interner();
+        delta.apply(&mut self.tokens);
+        delta
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lexer::Token::BlockComment;
+
+    #[test]
+    fn relex() {
+        let mut cache = TokenCache::new("int abc = 1 + 2;\n/* c */ x".to_string());
+        // lexes the whole text with the names of the cache so the identifiers get its symbols
+        let fresh = |cache: &TokenCache| {
+            let code = SpanSource::new(cache.text()).with_interner(cache.interner().clone());
+            all_tokens(code.source(), LexerOptions::default())
+                .map(|tok| LexedToken::new(tok, 0))
+                .collect::<Vec<_>>()
+        };
+
+        // typing at the end of an identifier relexes only the identifier
+        let delta = cache.edit(&TextEdit {
+            offset: 7,
+            removed: 0,
+            inserted: "d".to_string(),
+        });
+        assert_eq!(
+            (delta.start, delta.removed, delta.inserted.len()),
+            (2, 1, 1)
+        );
+        assert_eq!(delta.inserted[0].len, 4);
+        assert_eq!(cache.text(), "int abcd = 1 + 2;\n/* c */ x");
+        assert_eq!(cache.tokens(), fresh(&cache));
+
+        // opening a comment swallows the tokens up to the next `*/`
+        let delta = cache.edit(&TextEdit {
+            offset: 11,
+            removed: 0,
+            inserted: "/*".to_string(),
+        });
+        assert_eq!(delta.start, 5);
+        assert_eq!(delta.inserted[1].token, Ok(BlockComment));
+        assert_eq!(delta.inserted[1].len, "/*1 + 2;\n/* c */".len());
+        assert_eq!(cache.tokens(), fresh(&cache));
+
+        // random edits keep the tokens the same as lexing the whole text
+        let alphabet = [
+            "a", "1", " ", "\n", "/*", "*/", "//", "\"", "'", "=", "<", "é", "x;",
+        ];
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize
+        };
+        (0..300).for_each(|_| {
+            let text = cache.text();
+            let boundaries = (0..=text.len())
+                .filter(|i| text.is_char_boundary(*i))
+                .collect::<Vec<_>>();
+            let offset = boundaries[next() % boundaries.len()];
+            let removed = boundaries
+                .iter()
+                .filter(|i| **i >= offset && **i <= offset + 3)
+                .nth(next() % 2)
+                .map_or(0, |end| end - offset);
+            let inserted = (0..next() % 3)
+                .map(|_| alphabet[next() % alphabet.len()])
+                .collect();
+            let edit = TextEdit {
+                offset,
+                removed,
+                inserted,
+            };
+            cache.edit(&edit);
+            assert_eq!(cache.tokens(), fresh(&cache), "{edit:?}");
+        });
+    }
+}
@@ -0,0 +1,46 @@
+use crate::span::Span;
+
+use super::{all_tokens, LexerOptions, Token};
+
+/// the number of lines of a program by what they contain.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LineCounts {
+    /// the lines with at least one token that is not a space or a comment.
+    pub code: usize,
+    /// the lines with comments and no other tokens.
+    pub comment: usize,
+    /// the lines with nothing but spaces.
+    pub blank: usize,
+    pub total: usize,
+}
+
+/// counts the lines of `text` by the kind of tokens they contain, a token that spans multiple
+/// lines counts for all of them and the erroneous tokens count as code.
+pub fn line_counts(text: Span) -> LineCounts {
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    enum Kind {
+        Blank,
+        Comment,
+        Code,
+    }
+    let mut lines = vec![Kind::Blank; text.source().lines().count()];
+    all_tokens(text, LexerOptions::default()).for_each(|tok| {
+        let kind = match tok.get() {
+            Ok(Token::Space) => return,
+            Ok(Token::LineComment) | Ok(Token::BlockComment) => Kind::Comment,
+            _ => Kind::Code,
+        };
+        let first = tok.line() - 1;
+        let last = first + tok.fragment().trim_end_matches('\n').matches('\n').count();
+        lines[first..=last]
+            .iter_mut()
+            .for_each(|line| *line = (*line).max(kind));
+    });
+    let count = |kind| lines.iter().filter(|&&line| line == kind).count();
+    LineCounts {
+        code: count(Kind::Code),
+        comment: count(Kind::Comment),
+        blank: count(Kind::Blank),
+        total: lines.len(),
+    }
+}
@@ -0,0 +1,1159 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    error::{CCError, Phase, Suggestion},
+    span::*,
+};
+
+mod highlight;
+mod incremental;
+mod lines;
+mod stream;
+mod trivia;
+mod verify;
+
+pub use highlight::{semantic_tokens, token_type_index, TOKEN_TYPES};
+pub use incremental::{relex, LexedToken, TextEdit, TokenCache, TokenDelta};
+pub use lines::{line_counts, LineCounts};
+pub use stream::{StreamedToken, TokenStream};
+pub use trivia::{tokens_equal_ignoring_trivia, tokens_with_trivia, TokenWithTrivia, TriviaPiece};
+pub use verify::{verify, verify_with_options, Violation};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Error<'a> {
+    EmptyHexLiteral(Span<'a>),
+    InvalidEscape(char, Span<'a>),
+    /// the character and the token before it, the token is only known for the errors yielded by
+    /// `tokens`.
+    UnexpectedChar(char, Span<'a>, Option<Spanned<'a, Token>>),
+    EmptyChar(Span<'a>),
+    NonAsciiChars(Span<'a>),
+    /// the string literal and whether it can contain unicode characters, see
+    /// [`LexerOptions::utf8`].
+    StringLiteral(Span<'a>, bool),
+    UnterminatedString(Span<'a>),
+    UnterminatedComment(Span<'a>),
+    UnterminatedChar(Span<'a>),
+    /// the string literal and the maximum length of the strings.
+    StringTooLong(Span<'a>, usize),
+}
+
+impl<'a> Error<'a> {
+    pub fn span(self) -> Span<'a> {
+        match self {
+            Error::EmptyHexLiteral(pos)
+            | Error::InvalidEscape(_, pos)
+            | Error::UnexpectedChar(_, pos, _)
+            | Error::EmptyChar(pos)
+            | Error::NonAsciiChars(pos)
+            | Error::StringLiteral(pos, _)
+            | Error::UnterminatedString(pos)
+            | Error::UnterminatedComment(pos)
+            | Error::UnterminatedChar(pos)
+            | Error::StringTooLong(pos, _) => pos,
+        }
+    }
+
+    fn position(self) -> (usize, usize) {
+        self.span().position()
+    }
+}
+/// an interned name, two identifiers of a source have the same symbol if and only if they have the
+/// same name so they are compared without looking at their text.
+///
+/// the symbols are given by the interner of the source the identifiers are lexed from, see
+/// [`SpanSource::resolve`], the symbols of different sources are not comparable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// gives every distinct name a [`Symbol`], every [`SpanSource`] owns the interner of its
+/// identifiers so the names are dropped with it.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    names: Vec<Rc<str>>,
+    symbols: HashMap<Rc<str>, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+        let name: Rc<str> = name.into();
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(name.clone());
+        self.symbols.insert(name, symbol);
+        symbol
+    }
+
+    /// the name of `symbol`, which has to be interned by this interner.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.0 as usize]
+    }
+
+    /// the number of distinct names.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    // keywords
+    Import,
+    If,
+    Else,
+    While,
+    For,
+    Break,
+    Continue,
+    Return,
+    Int,
+    Bool,
+    True,
+    False,
+    Void,
+    Len,
+    // operators
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    EqualEqual,
+    NotEqual,
+    And,
+    Or,
+    Not,
+    Question,
+    Colon,
+    Assign,
+    AddAssign,
+    SubAssign,
+    Increment,
+    Decrement,
+    // delimiters
+    Semicolon,
+    Comma,
+    LeftParen,
+    RightParen,
+    SquareLeft,
+    SquareRight,
+    CurlyLeft,
+    CurlyRight,
+    // literals
+    Identifier(Symbol),
+    DecimalLiteral,
+    HexLiteral,
+    StringLiteral,
+    CharLiteral(char),
+
+    Space,
+    LineComment,
+    BlockComment,
+
+    /// stands in for the text the lexer reported an error for, see [`recover`].
+    ErrorToken,
+
+    // end of file
+    Eof,
+}
+
+impl Token {
+    /// returns true for the reserved words, `true` and `false` included.
+    pub const fn is_keyword(self) -> bool {
+        matches!(
+            self,
+            Token::Import
+                | Token::If
+                | Token::Else
+                | Token::While
+                | Token::For
+                | Token::Break
+                | Token::Continue
+                | Token::Return
+                | Token::Int
+                | Token::Bool
+                | Token::True
+                | Token::False
+                | Token::Void
+                | Token::Len
+        )
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Import => write!(f, "import"),
+            Token::If => write!(f, "if"),
+            Token::Else => write!(f, "else"),
+            Token::While => write!(f, "while"),
+            Token::For => write!(f, "for"),
+            Token::Break => write!(f, "break"),
+            Token::Continue => write!(f, "continue"),
+            Token::Return => write!(f, "return"),
+            Token::Int => write!(f, "int"),
+            Token::Bool => write!(f, "bool"),
+            Token::True => write!(f, "true"),
+            Token::False => write!(f, "false"),
+            Token::Void => write!(f, "void"),
+            Token::Len => write!(f, "len"),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Star => write!(f, "*"),
+            Token::Slash => write!(f, "!"),
+            Token::Percent => write!(f, "%"),
+            Token::Less => write!(f, "<"),
+            Token::LessEqual => write!(f, "<="),
+            Token::Greater => write!(f, ">"),
+            Token::GreaterEqual => write!(f, ">="),
+            Token::EqualEqual => write!(f, "=="),
+            Token::NotEqual => write!(f, "!="),
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
+            Token::Not => write!(f, "!"),
+            Token::Question => write!(f, "?"),
+            Token::Colon => write!(f, ":"),
+            Token::Assign => write!(f, "="),
+            Token::AddAssign => write!(f, "+="),
+            Token::SubAssign => write!(f, "-="),
+            Token::Increment => write!(f, "++"),
+            Token::Decrement => write!(f, "--"),
+            Token::Semicolon => write!(f, ";"),
+            Token::Comma => write!(f, ","),
+            Token::LeftParen => write!(f, "("),
+            Token::RightParen => write!(f, ")"),
+            Token::SquareLeft => write!(f, "["),
+            Token::SquareRight => write!(f, "]"),
+            Token::CurlyLeft => write!(f, "{{"),
+            Token::CurlyRight => write!(f, "}}"),
+            Token::Identifier(_) => write!(f, "identifier"),
+            Token::DecimalLiteral => write!(f, "decimal literal"),
+            Token::HexLiteral => write!(f, "hex literal"),
+            Token::StringLiteral => write!(f, "string literal"),
+            Token::CharLiteral(c) => write!(f, "char literal '{}'", *c as char),
+            Token::Space => write!(f, "space"),
+            Token::LineComment => write!(f, "line comment"),
+            Token::BlockComment => write!(f, "block comment"),
+            Token::ErrorToken => write!(f, "invalid token"),
+            Token::Eof => write!(f, "end of file"),
+        }
+    }
+}
+
+pub type Result<'a> = std::result::Result<Token, Error<'a>>;
+
+/// the errors in the string literal `span`, the unicode characters are only errors if `utf8` is not
+/// set.
+fn get_string_errors<'a>(span: Span<'a>, utf8: bool) -> impl Iterator<Item = Error<'a>> + 'a {
+    let mut escape_next = true;
+    let error_checker = move |s: Span<'a>| {
+        let c = s.first().unwrap();
+        if escape_next {
+            escape_next = false;
+            if !is_escaped_char(c) {
+                Some(Error::InvalidEscape(c, s))
+            } else {
+                None
+            }
+        } else if c == '\\' {
+            escape_next = true;
+            None
+        } else if !is_dcf_char(c) && (!utf8 || c.is_ascii()) {
+            Some(Error::UnexpectedChar(c, s, None))
+        } else {
+            None
+        }
+    };
+    // a lone `"` at the end of the file is the opening quote
+    let terminated = if span.len() < 2 || span.ends_with("\\\"") || !span.ends_with("\"") {
+        Some(Error::UnterminatedString(span))
+    } else {
+        None
+    };
+    // the closing quote is not checked, an unterminated literal does not have one
+    let body = match terminated {
+        None => span.split_at(span.len() - 1).0,
+        Some(_) => span,
+    };
+    body.source()
+        .char_indices()
+        .map(move |(i, c)| body.split_at(i).1.split_at(c.len_utf8()).0)
+        .filter_map(error_checker)
+        .chain(terminated)
+}
+
+fn symbol(span: Span) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    {
+        // a non-ascii char may follow a stray char
+        if span.len() > 1 && span.source().is_char_boundary(2) {
+            let (ch, rem) = span.split_at(2);
+            match &ch[..] {
+                "<=" => Some((ch.into_spanned(Ok(Token::LessEqual)), rem)),
+                ">=" => Some((ch.into_spanned(Ok(Token::GreaterEqual)), rem)),
+                "==" => Some((ch.into_spanned(Ok(Token::EqualEqual)), rem)),
+                "!=" => Some((ch.into_spanned(Ok(Token::NotEqual)), rem)),
+                "+=" => Some((ch.into_spanned(Ok(Token::AddAssign)), rem)),
+                "-=" => Some((ch.into_spanned(Ok(Token::SubAssign)), rem)),
+                "&&" => Some((ch.into_spanned(Ok(Token::And)), rem)),
+                "||" => Some((ch.into_spanned(Ok(Token::Or)), rem)),
+                "--" => Some((ch.into_spanned(Ok(Token::Decrement)), rem)),
+                "++" => Some((ch.into_spanned(Ok(Token::Increment)), rem)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+    .or_else(|| {
+        let (ch, rem) = span.split_at(1);
+        match ch.first().unwrap() {
+            '+' => Some((ch.into_spanned(Ok(Token::Plus)), rem)),
+            '-' => Some((ch.into_spanned(Ok(Token::Minus)), rem)),
+            '*' => Some((ch.into_spanned(Ok(Token::Star)), rem)),
+            '/' => Some((ch.into_spanned(Ok(Token::Slash)), rem)),
+            '%' => Some((ch.into_spanned(Ok(Token::Percent)), rem)),
+            '!' => Some((ch.into_spanned(Ok(Token::Not)), rem)),
+            ';' => Some((ch.into_spanned(Ok(Token::Semicolon)), rem)),
+            '<' => Some((ch.into_spanned(Ok(Token::Less)), rem)),
+            '>' => Some((ch.into_spanned(Ok(Token::Greater)), rem)),
+            '=' => Some((ch.into_spanned(Ok(Token::Assign)), rem)),
+            '{' => Some((ch.into_spanned(Ok(Token::CurlyLeft)), rem)),
+            '}' => Some((ch.into_spanned(Ok(Token::CurlyRight)), rem)),
+            '[' => Some((ch.into_spanned(Ok(Token::SquareLeft)), rem)),
+            ']' => Some((ch.into_spanned(Ok(Token::SquareRight)), rem)),
+            ',' => Some((ch.into_spanned(Ok(Token::Comma)), rem)),
+            '(' => Some((ch.into_spanned(Ok(Token::LeftParen)), rem)),
+            ')' => Some((ch.into_spanned(Ok(Token::RightParen)), rem)),
+            '?' => Some((ch.into_spanned(Ok(Token::Question)), rem)),
+            ':' => Some((ch.into_spanned(Ok(Token::Colon)), rem)),
+            c if !c.is_ascii_alphanumeric() => Some((
+                ch.into_spanned(Err(Error::UnexpectedChar(c, ch, None))),
+                rem,
+            )),
+            _ => None,
+        }
+    })
+}
+
+/// the dialect options of the lexer, the defaults lex standard decaf.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexerOptions {
+    /// identifiers can start with `$`.
+    pub dollar_start: bool,
+    /// identifiers can contain `$` after their first character.
+    pub dollar_continue: bool,
+    /// the longest a string literal can be, counting an escape sequence as one character.
+    pub max_string_len: Option<usize>,
+    /// report the non-ascii characters in comments, which are accepted by default.
+    pub ascii_only_comments: bool,
+    /// the columns a tab takes in the positions given by [`LexerOptions::position`].
+    pub tab_width: usize,
+    /// lex `While` or `WHILE` as the keyword `while`.
+    pub case_insensitive_keywords: bool,
+    /// accept unicode in the string literals and count the columns of
+    /// [`LexerOptions::position`] in characters, the unicode characters are still errors
+    /// anywhere else outside comments.
+    pub utf8: bool,
+    /// block comments can contain other block comments, `/* /* */ */` is a single comment and an
+    /// unterminated comment is reported from the outermost `/*`.
+    pub nested_comments: bool,
+}
+
+impl Default for LexerOptions {
+    fn default() -> Self {
+        Self {
+            dollar_start: false,
+            dollar_continue: false,
+            max_string_len: None,
+            ascii_only_comments: false,
+            tab_width: 1,
+            case_insensitive_keywords: false,
+            utf8: false,
+            nested_comments: false,
+        }
+    }
+}
+
+impl LexerOptions {
+    /// the line and column of `span` with the tabs taking `tab_width` columns, the columns count
+    /// the characters instead of the bytes in `utf8` mode.
+    pub fn position(&self, span: Span) -> (usize, usize) {
+        let column = if self.utf8 {
+            span.char_column(self.tab_width)
+        } else {
+            span.column_with_tab_width(self.tab_width)
+        };
+        (span.line(), column)
+    }
+}
+
+/// the text `name` is looked up by among the keywords, lower case if the keywords are case
+/// insensitive. the keywords are short enough to be lowered in place without allocating.
+fn keyword_key<'n>(name: &'n str, options: LexerOptions, buf: &'n mut [u8; 8]) -> &'n str {
+    if options.case_insensitive_keywords && name.len() <= buf.len() {
+        let lower = &mut buf[..name.len()];
+        lower.copy_from_slice(name.as_bytes());
+        lower.make_ascii_lowercase();
+        // lowering ascii letters keeps the name valid utf-8
+        std::str::from_utf8(lower).unwrap()
+    } else {
+        name
+    }
+}
+
+fn identifier<'a>(span: Span<'a>, options: LexerOptions) -> Option<(Spanned<Result>, Span<'a>)> {
+    assert!(!span.is_empty());
+    let first = span.first().unwrap();
+    if !first.is_ascii_alphabetic() && first != '_' && !(options.dollar_start && first == '$') {
+        None
+    } else {
+        let keyword =
+            |(span, rem): (Span<'a>, _)| match keyword_key(span.source(), options, &mut [0; 8]) {
+                "import" => (span.into_spanned(Ok(Token::Import)), rem),
+                "void" => (span.into_spanned(Ok(Token::Void)), rem),
+                "int" => (span.into_spanned(Ok(Token::Int)), rem),
+                "bool" => (span.into_spanned(Ok(Token::Bool)), rem),
+                "if" => (span.into_spanned(Ok(Token::If)), rem),
+                "else" => (span.into_spanned(Ok(Token::Else)), rem),
+                "for" => (span.into_spanned(Ok(Token::For)), rem),
+                "while" => (span.into_spanned(Ok(Token::While)), rem),
+                "break" => (span.into_spanned(Ok(Token::Break)), rem),
+                "continue" => (span.into_spanned(Ok(Token::Continue)), rem),
+                "return" => (span.into_spanned(Ok(Token::Return)), rem),
+                "len" => (span.into_spanned(Ok(Token::Len)), rem),
+                "true" => (span.into_spanned(Ok(Token::True)), rem),
+                "false" => (span.into_spanned(Ok(Token::False)), rem),
+                _ => (span.into_spanned(Ok(Token::Identifier(span.symbol()))), rem),
+            };
+        let is_continue = |c: char| {
+            c.is_ascii_alphanumeric() || c == '_' || (options.dollar_continue && c == '$')
+        };
+        // the first character is ascii so the rest starts at the second byte
+        let len = span.source()[1..]
+            .find(|c| !is_continue(c))
+            .map_or(span.len(), |len| len + 1);
+        Some(keyword(span.split_at(len)))
+    }
+}
+
+fn skip_spaces(span: Span) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    let (spaces, rem) = span.take_while(|c| c.is_ascii_whitespace() && c != '\x0c');
+    if spaces.is_empty() {
+        None
+    } else {
+        Some((spaces.into_spanned(Ok(Token::Space)), rem))
+    }
+}
+
+fn skip_line_comment(span: Span) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    span.starts_with("//").then(|| {
+        let (cmt, rem) = span
+            .split_once(|c| c == '\n')
+            .unwrap_or_else(|| (span, span.split_at(span.len()).1));
+        (cmt.into_spanned(Ok(Token::LineComment)), rem)
+    })
+}
+
+/// the length of the block comment at the start of `text` up to its closing `*/`, the comments
+/// opened inside it have to be closed first if `nested` is set.
+fn block_comment_len(text: &str, nested: bool) -> Option<usize> {
+    if !nested {
+        return text[2..].find("*/").map(|i| i + 4);
+    }
+    let bytes = text.as_bytes();
+    let (mut depth, mut i) = (0usize, 0);
+    while i + 1 < bytes.len() {
+        match &bytes[i..i + 2] {
+            b"/*" => depth += 1,
+            b"*/" => depth -= 1,
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+        if depth == 0 {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn skip_block_comment(span: Span, nested: bool) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    if span.starts_with("/*") {
+        let split = block_comment_len(span.source(), nested).map(|i| span.split_at(i));
+        if let Some((comment, rem)) = split {
+            Some((comment.into_spanned(Ok(Token::BlockComment)), rem))
+        } else {
+            // the comment runs to the end of the file, the error does not cover the trailing spaces
+            let comment = span.split_at(span.source().trim_end().len()).0;
+            Some((
+                span.into_spanned(Err(Error::UnterminatedComment(comment))),
+                span.split_at(span.len()).1,
+            ))
+        }
+    } else {
+        None
+    }
+}
+
+fn int_literal(span: Span) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    if span.first().unwrap().is_ascii_digit() {
+        if span.starts_with("0x") {
+            let digits = span.split_at(2).1;
+            let (lit, _rem) = digits
+                .split_once(|c| !c.is_ascii_hexdigit())
+                .unwrap_or_else(|| digits.split_at(digits.len()));
+            if lit.is_empty() {
+                let (err, rem) = span.split_at(2);
+                Some((err.into_spanned(Err(Error::EmptyHexLiteral(err))), rem))
+            } else {
+                let (lit, rem) = span.split_at(lit.len() + 2);
+                Some((lit.into_spanned(Ok(Token::HexLiteral)), rem))
+            }
+        } else {
+            let (lit, rem) = span
+                .split_once(|c| !c.is_ascii_digit())
+                .unwrap_or(span.split_at(span.len()));
+            Some((lit.into_spanned(Ok(Token::DecimalLiteral)), rem))
+        }
+    } else {
+        None
+    }
+}
+
+const fn is_escaped_char(c: char) -> bool {
+    matches!(c, 'n' | 't' | '\\' | '\'' | '"')
+}
+
+fn escaped_char(span: Span) -> Spanned<Result> {
+    assert!(span.chars().count() == 4);
+    assert!(span.starts_with("'\\"));
+    if span.chars().nth(3).unwrap() != '\'' {
+        span.into_spanned(Err(Error::UnterminatedChar(span)))
+    } else {
+        let c = span.chars().nth(2).unwrap();
+        match c {
+            'n' => span.into_spanned(Ok(Token::CharLiteral('\n'))),
+            't' => span.into_spanned(Ok(Token::CharLiteral('\t'))),
+            '\\' => span.into_spanned(Ok(Token::CharLiteral('\\'))),
+            '\'' => span.into_spanned(Ok(Token::CharLiteral('\''))),
+            '"' => span.into_spanned(Ok(Token::CharLiteral('"'))),
+            c => span.into_spanned(Err(Error::InvalidEscape(c, span))),
+        }
+    }
+}
+
+const fn is_dcf_char(c: char) -> bool {
+    matches!(c as u32, 32..=33 | 35..=38 | 40..=91 | 93..=126)
+}
+
+fn dcf_char(span: Span) -> Spanned<Result> {
+    assert!(span.chars().count() == 3);
+    let c = span.chars().nth(1).unwrap();
+    match c {
+        c if is_dcf_char(c) => span.into_spanned(Ok(Token::CharLiteral(c))),
+        _ => span.into_spanned(Err(Error::UnexpectedChar(c, span, None))),
+    }
+}
+
+fn char_literal(span: Span) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    if !span.starts_with("'") {
+        return None;
+    }
+    // the literal is counted in chars, a non-ascii char can stand where an ascii char is expected
+    let mut chars = ['\0'; 4];
+    let len = span
+        .chars()
+        .take(4)
+        .zip(&mut chars)
+        .map(|(c, slot)| *slot = c)
+        .count();
+    let split = |n| {
+        span.split_at(
+            span.source()
+                .char_indices()
+                .nth(n)
+                .map_or(span.len(), |(i, _)| i),
+        )
+    };
+    if len < 3 {
+        // the end of the file cuts the literal short
+        let lit = span.split_at(span.source().trim_end().len()).0;
+        let err = if lit.source() == "''" {
+            Error::EmptyChar(lit)
+        } else {
+            Error::UnterminatedChar(lit)
+        };
+        Some((span.into_spanned(Err(err)), span.split_at(span.len()).1))
+    } else if chars[1] == '\\' {
+        // escaped char
+        if len < 4 {
+            Some((
+                span.into_spanned(Err(Error::UnterminatedChar(span))),
+                span.split_at(span.len()).1,
+            ))
+        } else {
+            let (lit, rem) = split(4);
+            Some((escaped_char(lit), rem))
+        }
+    } else if chars[1] == '\'' {
+        let (lit, rem) = split(2);
+        Some((lit.into_spanned(Err(Error::EmptyChar(lit))), rem))
+    } else if chars[2] != '\'' {
+        let (lit, rem) = split(2);
+        Some((lit.into_spanned(Err(Error::UnterminatedChar(lit))), rem))
+    } else {
+        let (lit, rem) = split(3);
+        Some((dcf_char(lit), rem))
+    }
+}
+
+/// decodes a char literal, the span has to contain the whole literal including its quotes.
+pub fn char_value(span: Span) -> std::result::Result<u8, Spanned<Error>> {
+    match span.first() {
+        None => Err(span.into_spanned(Error::EmptyChar(span))),
+        Some(first) => match char_literal(span) {
+            Some((lit, rem)) if rem.is_empty() => match lit.transpose() {
+                Ok(lit) => match lit.get() {
+                    Token::CharLiteral(c) => Ok(*c as u8),
+                    _ => unreachable!(),
+                },
+                Err(e) => Err(e),
+            },
+            Some((_, rem)) => {
+                Err(rem.into_spanned(Error::UnexpectedChar(rem.first().unwrap(), rem, None)))
+            }
+            None => Err(span.into_spanned(Error::UnexpectedChar(first, span, None))),
+        },
+    }
+}
+
+/// why an integer literal has no value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntLitError<'a> {
+    /// the value of the literal does not fit in an `i64`, the span is the whole literal.
+    Overflow(Span<'a>),
+    /// the literal has no digits or a digit that is not valid in its base, the span is the first
+    /// invalid digit or the empty span after the prefix.
+    InvalidDigit(Span<'a>),
+}
+
+/// the value of the digits of an integer literal in base `radix`, negated if `negative` so the
+/// smallest `i64` has a value although its magnitude does not fit.
+pub fn int_digits_value(
+    digits: Span,
+    radix: u32,
+    negative: bool,
+) -> std::result::Result<i64, IntLitError> {
+    if digits.is_empty() {
+        return Err(IntLitError::InvalidDigit(digits));
+    }
+    digits
+        .as_str()
+        .char_indices()
+        .try_fold(0i64, |acc, (i, c)| {
+            let digit = c.to_digit(radix).ok_or_else(|| {
+                let (_, rem) = digits.split_at(i);
+                IntLitError::InvalidDigit(rem.split_at(c.len_utf8()).0)
+            })? as i64;
+            acc.checked_mul(radix as i64)
+                .and_then(|acc| {
+                    if negative {
+                        acc.checked_sub(digit)
+                    } else {
+                        acc.checked_add(digit)
+                    }
+                })
+                .ok_or(IntLitError::Overflow(digits))
+        })
+}
+
+/// the value of an integer literal, decimal or hex with its `0x` prefix, the literal may start
+/// with a `-`, e.g. `-9223372036854775808` and `-0x8000000000000000` are in range, the spaces
+/// between the sign and the literal are skipped.
+pub fn parse_int_literal(span: Span) -> std::result::Result<i64, IntLitError> {
+    let (negative, digits) = match span.starts_with("-") {
+        true => (true, span.split_at(1).1.take_while(char::is_whitespace).1),
+        false => (false, span),
+    };
+    let (radix, digits) = match digits.starts_with("0x") {
+        true => (16, digits.split_at(2).1),
+        false => (10, digits),
+    };
+    int_digits_value(digits, radix, negative).map_err(|e| match e {
+        IntLitError::Overflow(_) => IntLitError::Overflow(span),
+        e => e,
+    })
+}
+
+fn string_literal(span: Span, options: LexerOptions) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    if span.starts_with("\"") {
+        // take the string literal even if it contains errors
+        // set `last_char` to '\\' to skip the first quote
+        let mut last_char = '\\';
+        let mut break_next = false;
+        let (lit, rem) = span.take_while(|c| {
+            if break_next {
+                false
+            } else if c == '"' && last_char != '\\' {
+                break_next = true;
+                true
+            } else {
+                last_char = c;
+                true
+            }
+        });
+
+        // collect errors in the string literal
+        if get_string_errors(lit, options.utf8).next().is_some() {
+            // an unterminated literal runs to the end of the file, the error does not cover the
+            // trailing spaces
+            let err = if break_next {
+                lit
+            } else {
+                lit.split_at(lit.source().trim_end().len()).0
+            };
+            Some((
+                lit.into_spanned(Err(Error::StringLiteral(err, options.utf8))),
+                rem,
+            ))
+        } else {
+            Some((lit.into_spanned(Ok(Token::StringLiteral)), rem))
+        }
+    } else {
+        None
+    }
+}
+
+fn is_ascii(c: char) -> bool {
+    c.is_ascii() && matches!(c as u8, 32..=126 | b'\t' | b'\n' | b'\r')
+}
+
+/// collect non-ascii chars
+fn non_ascii_graphic_chars(span: Span) -> Option<(Spanned<Result>, Span)> {
+    assert!(!span.is_empty());
+    // the text may end with the non-ascii chars
+    let (bad_chars, rem) = span
+        .split_once(is_ascii)
+        .unwrap_or_else(|| span.split_at(span.len()));
+    if bad_chars.is_empty() {
+        None
+    } else {
+        Some((
+            bad_chars.into_spanned(Err(Error::NonAsciiChars(bad_chars))),
+            rem,
+        ))
+    }
+}
+
+/// the comment is an error for its first non-ascii characters if `ascii_only_comments` is set.
+fn comment_chars<'a>(
+    (tok, rem): (Spanned<'a, Result<'a>>, Span<'a>),
+    options: LexerOptions,
+) -> (Spanned<'a, Result<'a>>, Span<'a>) {
+    let bad = tok.span().split_once(|c| !is_ascii(c));
+    match bad {
+        Some((_, bad)) if options.ascii_only_comments && tok.get().is_ok() => {
+            let (bad_chars, _) = bad
+                .split_once(is_ascii)
+                .unwrap_or_else(|| bad.split_at(bad.len()));
+            let span = tok.span();
+            (span.into_spanned(Err(Error::NonAsciiChars(bad_chars))), rem)
+        }
+        _ => (tok, rem),
+    }
+}
+
+/// the string literal is an error if it is longer than `max_string_len`.
+fn string_len<'a>(
+    (tok, rem): (Spanned<'a, Result<'a>>, Span<'a>),
+    options: LexerOptions,
+) -> (Spanned<'a, Result<'a>>, Span<'a>) {
+    match options.max_string_len {
+        Some(max) if tok.get().is_ok() => {
+            let content = &tok.fragment()[1..tok.fragment().len() - 1];
+            // an escape sequence is one character of the string
+            let len = content.chars().count() - content.matches('\\').count()
+                + content.matches("\\\\").count();
+            if len > max {
+                let span = tok.span();
+                (span.into_spanned(Err(Error::StringTooLong(span, max))), rem)
+            } else {
+                (tok, rem)
+            }
+        }
+        _ => (tok, rem),
+    }
+}
+
+fn token(span: Span, options: LexerOptions) -> Option<(Spanned<Result>, Span)> {
+    if span.is_empty() {
+        None
+    } else {
+        // the non_ascii_graphic_chars has to come before spaces
+        // skip_spaces skips some of illegal chars
+        non_ascii_graphic_chars(span)
+            .or_else(|| skip_spaces(span))
+            .or_else(|| skip_line_comment(span).map(|c| comment_chars(c, options)))
+            .or_else(|| {
+                skip_block_comment(span, options.nested_comments).map(|c| comment_chars(c, options))
+            })
+            .or_else(|| identifier(span, options))
+            .or_else(|| int_literal(span))
+            .or_else(|| char_literal(span))
+            .or_else(|| string_literal(span, options).map(|s| string_len(s, options)))
+            .or_else(|| symbol(span))
+    }
+}
+
+/// all the tokens of `text` including spaces and comments, without the `Eof` token.
+fn all_tokens(mut text: Span, options: LexerOptions) -> impl Iterator<Item = Spanned<Result>> {
+    std::iter::from_fn(move || {
+        if text.is_empty() {
+            None
+        } else {
+            let (tok, rem) = token(text, options)?;
+            text = rem;
+            Some(tok)
+        }
+    })
+}
+
+pub fn tokens(text: Span) -> impl Iterator<Item = Spanned<Result>> {
+    tokens_with_options(text, LexerOptions::default())
+}
+
+/// same as `tokens` but the dialect of the code is picked by `options`.
+pub fn tokens_with_options(
+    text: Span,
+    options: LexerOptions,
+) -> impl Iterator<Item = Spanned<Result>> {
+    use std::iter;
+    // the last token is remembered to give context to the unexpected characters
+    let mut last = None;
+    all_tokens(text, options)
+        .filter(|t| TriviaPiece::from_token(t).is_none())
+        .map(move |t| with_context(t, &mut last))
+        .chain(iter::once(
+            text.split_at(text.len()).1.into_spanned(Ok(Token::Eof)),
+        ))
+}
+
+/// gives the unexpected characters the token before them as context, `last` is the last token
+/// that is not an error.
+fn with_context<'a>(
+    t: Spanned<'a, Result<'a>>,
+    last: &mut Option<Spanned<'a, Token>>,
+) -> Spanned<'a, Result<'a>> {
+    match *t.get() {
+        Ok(tok) => {
+            *last = Some(t.span().into_spanned(tok));
+            t
+        }
+        Err(Error::UnexpectedChar(c, span, None)) => t
+            .span()
+            .into_spanned(Err(Error::UnexpectedChar(c, span, *last))),
+        Err(_) => t,
+    }
+}
+
+/// a lexer that is driven one token at a time, for the front ends that need to look ahead or
+/// backtrack. yields the same tokens as [`tokens_with_options`].
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    rest: Span<'a>,
+    options: LexerOptions,
+    /// the context of the unexpected characters, see `with_context`.
+    last: Option<Spanned<'a, Token>>,
+    /// whether the `Eof` token is lexed.
+    done: bool,
+    /// the tokens that are peeked but not consumed yet.
+    lookahead: std::collections::VecDeque<Spanned<'a, Result<'a>>>,
+}
+
+/// the state of a [`Lexer`] that it can be rolled back to.
+#[derive(Debug, Clone)]
+pub struct Checkpoint<'a>(Lexer<'a>);
+
+impl<'a> Lexer<'a> {
+    pub fn new(text: Span<'a>) -> Self {
+        Self::with_options(text, LexerOptions::default())
+    }
+
+    pub fn with_options(text: Span<'a>, options: LexerOptions) -> Self {
+        Self {
+            rest: text,
+            options,
+            last: None,
+            done: false,
+            lookahead: std::collections::VecDeque::new(),
+        }
+    }
+
+    /// lexes the token after the lookahead.
+    fn lex(&mut self) -> Option<Spanned<'a, Result<'a>>> {
+        while let Some((tok, rem)) = token(self.rest, self.options) {
+            self.rest = rem;
+            if TriviaPiece::from_token(&tok).is_none() {
+                return Some(with_context(tok, &mut self.last));
+            }
+        }
+        (!std::mem::replace(&mut self.done, true)).then(|| self.rest.into_spanned(Ok(Token::Eof)))
+    }
+
+    /// the next token without consuming it.
+    pub fn peek(&mut self) -> Option<&Spanned<'a, Result<'a>>> {
+        self.peek_nth(0)
+    }
+
+    /// the token `k` tokens after the next one without consuming any, `peek_nth(0)` is `peek()`.
+    pub fn peek_nth(&mut self, k: usize) -> Option<&Spanned<'a, Result<'a>>> {
+        while self.lookahead.len() <= k {
+            let tok = self.lex()?;
+            self.lookahead.push_back(tok);
+        }
+        self.lookahead.get(k)
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint(self.clone())
+    }
+
+    /// goes back to `checkpoint`, the tokens consumed since are yielded again.
+    pub fn rollback(&mut self, checkpoint: Checkpoint<'a>) {
+        *self = checkpoint.0;
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Spanned<'a, Result<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lookahead.pop_front().or_else(|| self.lex())
+    }
+}
+
+/// the recovery policy for the lexer errors, gives the best-effort token for `tok` so the parser
+/// can keep going after a lexical error instead of stopping.
+///
+/// a malformed literal stands for the literal it was meant to be and the characters that can not
+/// start a token become an [`Token::ErrorToken`] which the parser skips, the error itself still
+/// has to be reported by the caller.
+pub fn recover<'a>(tok: Spanned<'a, Result<'a>>) -> Spanned<'a, Token> {
+    tok.map(|tok| match tok {
+        Ok(tok) => tok,
+        Err(Error::StringLiteral(..) | Error::UnterminatedString(_) | Error::StringTooLong(..)) => {
+            Token::StringLiteral
+        }
+        Err(Error::EmptyChar(_) | Error::InvalidEscape(..) | Error::UnterminatedChar(_)) => {
+            Token::CharLiteral('\0')
+        }
+        Err(Error::EmptyHexLiteral(_)) => Token::HexLiteral,
+        Err(
+            Error::UnexpectedChar(..) | Error::NonAsciiChars(_) | Error::UnterminatedComment(_),
+        ) => Token::ErrorToken,
+    })
+}
+
+/// same as `tokens` but the lexing stops right after the first error, the end of file token is
+/// only yielded if there are no errors.
+pub fn tokens_until_error(text: Span) -> impl Iterator<Item = Spanned<Result>> {
+    let mut tokens = tokens(text);
+    let mut failed = false;
+    std::iter::from_fn(move || {
+        if failed {
+            None
+        } else {
+            let tok = tokens.next()?;
+            failed = tok.get().is_err();
+            Some(tok)
+        }
+    })
+}
+
+/// informational messages about valid code that is likely a mistake.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Note<'a> {
+    BlockCommentInLineComment(Span<'a>),
+}
+
+impl<'a> Note<'a> {
+    /// the note about the spaces or the comment `piece`, if any.
+    pub fn of(piece: TriviaPiece<'a>) -> Option<Self> {
+        match piece {
+            TriviaPiece::LineComment(span) => span
+                .find("/*")
+                .map(|i| Note::BlockCommentInLineComment(span.split_at(i).1.split_at(2).0)),
+            TriviaPiece::Space(_) | TriviaPiece::BlockComment(_) => None,
+        }
+    }
+}
+
+impl CCError for Note<'_> {
+    fn msgs(&self) -> Vec<(String, (usize, usize))> {
+        match self {
+            Note::BlockCommentInLineComment(span) => vec![(
+                "`/*` inside a line comment does not start a block comment".to_string(),
+                span.position(),
+            )],
+        }
+    }
+}
+
+/// true if `a` turns into `b` by inserting, removing or replacing a single character, or by
+/// swapping two adjacent ones.
+fn one_edit_apart(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let prefix = short.iter().zip(long).take_while(|(l, r)| l == r).count();
+    match long.len() - short.len() {
+        0 if prefix == short.len() => false,
+        0 => {
+            short[prefix + 1..] == long[prefix + 1..]
+                || (prefix + 1 < short.len()
+                    && short[prefix] == long[prefix + 1]
+                    && short[prefix + 1] == long[prefix]
+                    && short[prefix + 2..] == long[prefix + 2..])
+        }
+        1 => short[prefix..] == long[prefix + 1..],
+        _ => false,
+    }
+}
+
+/// the keyword that starts a statement or a declaration `ident` is one typo away from, e.g.
+/// `retrun` for `return`.
+pub fn similar_keyword(ident: &str) -> Option<Token> {
+    use Token::*;
+    [If, For, While, Break, Continue, Return, Int, Bool, Void]
+        .into_iter()
+        .find(|keyword| one_edit_apart(ident, &keyword.to_string()))
+}
+
+/// finds the `/*` markers inside line comments.
+pub fn notes(text: Span) -> impl Iterator<Item = Note> {
+    tokens_with_trivia(text).flat_map(|t| t.leading.into_iter().filter_map(Note::of))
+}
+
+/// writes the message of a single error into `msg` without intermediate allocations.
+fn write_error_msg(err: &Error, msg: &mut String) -> std::fmt::Result {
+    use std::fmt::Write;
+    match err {
+        Error::EmptyHexLiteral(span) => write!(msg, "invalid hex literal: {}", span.source()),
+        Error::EmptyChar(_) => msg.write_str("empty char literal"),
+        Error::InvalidEscape(c, _) => write!(msg, "invalid escape sequence: \\{}", c),
+        Error::UnexpectedChar(c, _, after) => {
+            write!(msg, "unexpected character: {}", c)?;
+            match after {
+                Some(tok) if matches!(tok.get(), Token::Identifier(_)) => {
+                    write!(msg, " after identifier `{}`", tok.fragment())
+                }
+                Some(tok) => write!(msg, " after `{}`", tok.fragment()),
+                None => Ok(()),
+            }
+        }
+        Error::UnterminatedString(_) => msg.write_str("unterminated string literal"),
+        Error::UnterminatedChar(_) => msg.write_str("unterminated char literal"),
+        Error::UnterminatedComment(_) => msg.write_str("unterminated block comment"),
+        Error::StringTooLong(_, max) => {
+            write!(msg, "string literal is longer than {} characters", max)
+        }
+        Error::NonAsciiChars(s) => {
+            // the bytes as they were read, not the `U+FFFD`s that replaced the invalid ones
+            msg.write_str("non-ascii characters: ")?;
+            s.original_chars().try_for_each(|c| match c {
+                Ok(c) => write!(msg, "0x{:02x} ", c as u8),
+                Err(bytes) => bytes.iter().try_for_each(|b| write!(msg, "0x{:02x} ", b)),
+            })
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn single_error_msg(err: &Error) -> String {
+    // enough for all the messages except the long non-ascii ones
+    let mut msg = String::with_capacity(match err {
+        Error::NonAsciiChars(s) => 32 + 5 * s.len(),
+        Error::UnexpectedChar(_, _, Some(tok)) => 48 + tok.fragment().len(),
+        _ => 32,
+    });
+    // writing to a string does not fail
+    write_error_msg(err, &mut msg).unwrap();
+    msg
+}
+
+impl<'a> CCError for Error<'a> {
+    fn msgs(&self) -> Vec<(String, (usize, usize))> {
+        match self {
+            Error::StringLiteral(str, utf8) => get_string_errors(*str, *utf8)
+                .map(|err| (single_error_msg(&err), err.position()))
+                .collect(),
+            _ => vec![(single_error_msg(self), self.position())],
+        }
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.span().source_name()
+    }
+
+    fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            // the value of the literal is unknown
+            Error::EmptyHexLiteral(span) => Some(Suggestion::guess(*span, "0x0")),
+            _ => None,
+        }
+    }
+
+    /// the lexer errors keep their phase when the parser reports them.
+    fn phase(&self) -> Option<Phase> {
+        Some(Phase::Lex)
+    }
+}
+
+impl CCError for IntLitError<'_> {
+    fn msgs(&self) -> Vec<(String, (usize, usize))> {
+        match self {
+            IntLitError::Overflow(span) => vec![(
+                format!(
+                    "integer literal `{}` is out of the range of a 64-bit integer",
+                    span.source()
+                ),
+                span.position(),
+            )],
+            IntLitError::InvalidDigit(span) => vec![(
+                format!("invalid digit in integer literal: `{}`", span.source()),
+                span.position(),
+            )],
+        }
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        match self {
+            IntLitError::Overflow(span) | IntLitError::InvalidDigit(span) => span.source_name(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,165 @@
+use crate::{error::CCError, span::SpanSource};
+
+use super::{tokens_with_options, Interner, LexerOptions, Token};
+
+/// a token yielded by a [`TokenStream`], it owns its text since the buffer it was lexed from is
+/// reused for the rest of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamedToken {
+    /// the token or the messages of the error, a string literal can have several errors.
+    pub token: std::result::Result<Token, Vec<String>>,
+    pub text: String,
+    /// the byte offset of the token from the start of the input.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// lexes the code read from `R` as it is read, only the tokens that are not complete yet are kept
+/// in memory so it can lex inputs that do not fit in memory or that are still being written.
+///
+/// yields the same tokens as [`tokens_with_options`] would for the whole input, including the
+/// `Eof` token, and stops after the first read error.
+pub struct TokenStream<R> {
+    reader: R,
+    options: LexerOptions,
+    /// the input that is not lexed yet, it starts with the last yielded token which gives the
+    /// context to the unexpected characters.
+    text: String,
+    /// the start of a utf-8 sequence that was split between two reads.
+    partial: Vec<u8>,
+    /// the offset, line and column of the start of `text` in the input.
+    start: (usize, usize, usize),
+    /// the number of tokens lexed from the start of `text` that are already yielded.
+    yielded: usize,
+    ready: std::collections::VecDeque<StreamedToken>,
+    eof: bool,
+    failed: bool,
+    /// the names of the identifiers, kept across the reads so a name keeps its symbol.
+    interner: Interner,
+}
+
+impl<R: std::io::Read> TokenStream<R> {
+    const READ_SIZE: usize = 8 * 1024;
+
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            reader,
+            options: LexerOptions::default(),
+            text: String::new(),
+            partial: Vec::new(),
+            start: (0, 1, 1),
+            yielded: 0,
+            ready: std::collections::VecDeque::new(),
+            eof: false,
+            failed: false,
+            interner: Interner::new(),
+        }
+    }
+
+    pub fn with_options(self, options: LexerOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    /// the names of the symbols of the identifiers yielded so far.
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    /// appends the next read to `text`, the invalid utf-8 sequences are replaced like
+    /// [`SpanSource::from_bytes`] does.
+    fn fill(&mut self) -> std::io::Result<()> {
+        // reading at least as much as is pending keeps lexing a long token linear
+        let mut buf = vec![0; Self::READ_SIZE.max(self.text.len())];
+        let read = loop {
+            match self.reader.read(&mut buf) {
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                read => break read?,
+            }
+        };
+        self.eof = read == 0;
+        self.partial.extend_from_slice(&buf[..read]);
+        let valid = match std::str::from_utf8(&self.partial) {
+            // a sequence at the end may be completed by the next read
+            Err(e) if e.error_len().is_none() && !self.eof => e.valid_up_to(),
+            _ => self.partial.len(),
+        };
+        self.text
+            .push_str(&String::from_utf8_lossy(&self.partial[..valid]));
+        self.partial.drain(..valid);
+        Ok(())
+    }
+
+    /// lexes `text` and queues the tokens that more input can not change, the tokens that reach
+    /// the end of `text` are lexed again once more of the input is read.
+    fn lex(&mut self) {
+        let code = SpanSource::new(&self.text);
+        let (offset, line, column) = self.start;
+        // the position of the start of the last yielded token that is not an error
+        let (mut pos, mut pos_line, mut pos_column) = (0, line, column);
+        let mut restart = None;
+        for (i, tok) in tokens_with_options(code.source(), self.options)
+            .enumerate()
+            .skip(self.yielded)
+        {
+            let span = tok.span();
+            if !self.eof && span.offset() + span.len() >= self.text.len() {
+                break;
+            }
+            let (tok_line, tok_column) =
+                advance(&self.text, pos, span.offset(), pos_line, pos_column);
+            if tok.get().is_ok() {
+                (pos, pos_line, pos_column) = (span.offset(), tok_line, tok_column);
+                restart = Some(i);
+            }
+            self.yielded = i + 1;
+            self.ready.push_back(StreamedToken {
+                // the names of the tokens lexed again are interned once, when they are yielded
+                token: match tok.get() {
+                    Ok(Token::Identifier(_)) => {
+                        Ok(Token::Identifier(self.interner.intern(span.source())))
+                    }
+                    tok => tok.map_err(|err| err.msgs().into_iter().map(|(msg, _)| msg).collect()),
+                },
+                text: span.source().to_string(),
+                offset: offset + span.offset(),
+                line: tok_line,
+                column: tok_column,
+            });
+        }
+        if let Some(restart) = restart {
+            self.yielded -= restart;
+            self.start = (offset + pos, pos_line, pos_column);
+            drop(code);
+            self.text.drain(..pos);
+        }
+    }
+}
+
+/// the line and column of `to` given those of `from`, the columns count bytes like
+/// [`Span::column`].
+fn advance(text: &str, from: usize, to: usize, line: usize, column: usize) -> (usize, usize) {
+    let skipped = &text.as_bytes()[from..to];
+    match skipped.iter().rposition(|&b| b == b'\n') {
+        Some(last) => (
+            line + skipped.iter().filter(|&&b| b == b'\n').count(),
+            to - from - last,
+        ),
+        None => (line, column + to - from),
+    }
+}
+
+impl<R: std::io::Read> Iterator for TokenStream<R> {
+    type Item = std::io::Result<StreamedToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() && !self.eof && !self.failed {
+            if let Err(e) = self.fill() {
+                self.failed = true;
+                return Some(Err(e));
+            }
+            self.lex();
+        }
+        self.ready.pop_front().map(Ok)
+    }
+}
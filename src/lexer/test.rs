@@ -0,0 +1,973 @@
+use super::Token::*;
+use super::*;
+
+// fn parsed<'a>(opt: Option<(Spanned<'a, Result<'a>>, Span<'a>)>) -> Spanned<'a, Result<'a>> {
+//     opt.unwrap().0
+// }
+
+fn rem<'a>(opt: Option<(Spanned<'a, Result>, Span<'a>)>) -> Span<'a> {
+    opt.unwrap().1
+}
+
+macro_rules! span {
+    ($span:ident, $text:expr) => {
+        let span_source = SpanSource::new($text);
+        let $span = span_source.source();
+    };
+}
+
+/// lexes the fragments of `expected` separated by spaces and checks that every fragment is
+/// lexed as its token.
+fn assert_tokens(expected: &[(Token, &[u8])]) {
+    let text = expected
+        .iter()
+        .map(|(_, fragment)| *fragment)
+        .collect::<Vec<_>>()
+        .join(&b' ');
+    let span_source = SpanSource::from_bytes(&text);
+    let found = tokens(span_source.source())
+        .map(|t| (t.get().unwrap(), t.fragment().as_bytes()))
+        .collect::<Vec<_>>();
+    assert_eq!(found.last(), Some(&(Eof, &b""[..])));
+    assert_eq!(&found[..found.len() - 1], expected);
+}
+
+#[test]
+fn fixture_keywords_and_symbols() {
+    // the names get their symbols in the order they are first lexed
+    let mut names = Interner::new();
+    let (printf, i_0) = (names.intern("printf"), names.intern("i_0"));
+    assert_tokens(&[
+        (Import, b"import"),
+        (Identifier(printf), b"printf"),
+        (Semicolon, b";"),
+        (For, b"for"),
+        (LeftParen, b"("),
+        (Identifier(i_0), b"i_0"),
+        (AddAssign, b"+="),
+        (Len, b"len"),
+        (RightParen, b")"),
+        (LessEqual, b"<="),
+        (Decrement, b"--"),
+        (Not, b"!"),
+        (NotEqual, b"!="),
+    ]);
+}
+
+#[test]
+fn fixture_literals() {
+    assert_tokens(&[
+        (DecimalLiteral, b"0123"),
+        (HexLiteral, b"0x1aF"),
+        (CharLiteral('a'), b"'a'"),
+        (CharLiteral('\n'), b"'\\n'"),
+        (StringLiteral, b"\"a \\\"b\\\" c\""),
+        (True, b"true"),
+        (False, b"false"),
+    ]);
+}
+
+#[test]
+fn identifier() {
+    use super::*;
+    let text = "abc";
+    span!(span, text);
+    let (s1, s2) = identifier(span, LexerOptions::default()).unwrap();
+    assert_eq!(
+        s1.get().unwrap(),
+        Identifier(span.span_source().intern("abc"))
+    );
+    assert_eq!(s1.fragment(), "abc");
+    assert_eq!(s2.source(), "");
+
+    let text = "_abc";
+    span!(span, text);
+    let (s1, s2) = identifier(span, LexerOptions::default()).unwrap();
+    assert_eq!(
+        s1.get().unwrap(),
+        Identifier(span.span_source().intern("_abc"))
+    );
+    assert_eq!(s1.fragment(), "_abc");
+    assert_eq!(s2.source(), "");
+
+    let text = "abc def";
+    span!(span, text);
+    let (s1, s2) = identifier(span, LexerOptions::default()).unwrap();
+    assert_eq!(
+        s1.get().unwrap(),
+        Identifier(span.span_source().intern("abc"))
+    );
+    assert_eq!(s1.fragment(), "abc");
+    assert_eq!(s2.source(), " def");
+
+    let text = "123abc";
+    span!(span, text);
+    assert!(identifier(span, LexerOptions::default()).is_none());
+}
+
+#[test]
+fn char_literal() {
+    use super::*;
+    let text = "'a'";
+    span!(span, text);
+    let (s1, s2) = char_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), CharLiteral('a'));
+    assert_eq!(s1.fragment(), "'a'");
+    assert_eq!(s2.source(), "");
+
+    let text = "'\\'";
+    span!(span, text);
+    let (s1, s2) = char_literal(span).unwrap();
+    assert!(matches!(s1.get().unwrap_err(), Error::UnterminatedChar(..)),);
+    assert_eq!(s1.fragment(), "'\\'");
+    assert_eq!(s2.source(), "");
+
+    let text = "'	'";
+    span!(span, text);
+    let (s1, s2) = char_literal(span).unwrap();
+    assert!(matches!(s1.get().unwrap_err(), Error::UnexpectedChar(..)),);
+    assert_eq!(s1.fragment(), "'	'");
+    assert_eq!(s2.source(), "");
+
+    let text = "'\\t'";
+    span!(span, text);
+    let (s1, s2) = char_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), CharLiteral('\t'));
+    assert_eq!(s1.fragment(), "'\\t'");
+    assert_eq!(s2.source(), "");
+}
+
+#[test]
+fn char_value() {
+    use super::*;
+    span!(span, "'a'");
+    assert_eq!(char_value(span), Ok(b'a'));
+    span!(span, "'\\n'");
+    assert_eq!(char_value(span), Ok(b'\n'));
+    span!(span, "'\\q'");
+    let err = char_value(span).unwrap_err();
+    assert!(matches!(err.get(), Error::InvalidEscape('q', _)));
+    assert_eq!(err.fragment(), "'\\q'");
+    span!(span, "'a'b");
+    let err = char_value(span).unwrap_err();
+    assert!(matches!(err.get(), Error::UnexpectedChar('b', _, None)));
+    span!(span, "a");
+    char_value(span).unwrap_err();
+}
+
+#[test]
+fn int_literal_value() {
+    use super::*;
+    let value = |text| {
+        span!(span, text);
+        parse_int_literal(span).map_err(|e| match e {
+            IntLitError::Overflow(span) => Err(span.source().to_string()),
+            IntLitError::InvalidDigit(span) => Ok(span.source().to_string()),
+        })
+    };
+    assert_eq!(value("0"), Ok(0));
+    assert_eq!(value("-42"), Ok(-42));
+    assert_eq!(value("0x1aF"), Ok(0x1af));
+    assert_eq!(value("9223372036854775807"), Ok(i64::MAX));
+    assert_eq!(value("-9223372036854775808"), Ok(i64::MIN));
+    assert_eq!(value("0x7fffffffffffffff"), Ok(i64::MAX));
+    assert_eq!(value("-0x8000000000000000"), Ok(i64::MIN));
+    let overflow = |text: &str| Err(Err(text.to_string()));
+    assert_eq!(
+        value("9223372036854775808"),
+        overflow("9223372036854775808")
+    );
+    assert_eq!(
+        value("-9223372036854775809"),
+        overflow("-9223372036854775809")
+    );
+    assert_eq!(value("0x8000000000000000"), overflow("0x8000000000000000"));
+    assert_eq!(value("0xffffffffffffffff"), overflow("0xffffffffffffffff"));
+    assert_eq!(value("12a"), Err(Ok("a".to_string())));
+    assert_eq!(value("0x"), Err(Ok("".to_string())));
+}
+
+#[test]
+fn string_literal() {
+    use super::*;
+    let text = "\"abc\"";
+    span!(span, text);
+    let (s1, s2) = string_literal(span, LexerOptions::default()).unwrap();
+    println!(
+        "{:?}",
+        get_string_errors(s1.span(), false).collect::<Vec<_>>()
+    );
+    assert_eq!(s1.get().unwrap(), StringLiteral);
+    assert_eq!(s1.fragment(), "\"abc\"");
+    assert_eq!(s2.source(), "");
+
+    let text = r#""\"abcdef\"""#;
+    span!(span, text);
+    let (s1, s2) = string_literal(span, LexerOptions::default()).unwrap();
+    assert_eq!(s1.get().unwrap(), StringLiteral);
+    assert_eq!(s1.fragment(), r#""\"abcdef\"""#);
+    assert_eq!(s2.source(), "");
+
+    let text = "\"abc alot of text that does not\\\" terminate with a quote";
+    span!(span, text);
+    let (s1, s2) = string_literal(span, LexerOptions::default()).unwrap();
+    s1.get().unwrap_err();
+    assert_eq!(
+        s1.fragment(),
+        "\"abc alot of text that does not\\\" terminate with a quote"
+    );
+    assert_eq!(s2.source(), "");
+}
+
+#[test]
+fn until_error() {
+    use super::*;
+    span!(span, "x @ y # z");
+    let toks = tokens_until_error(span)
+        .map(|t| t.fragment())
+        .collect::<Vec<_>>();
+    assert_eq!(toks, ["x", "@"]);
+    span!(span, "x y");
+    assert_eq!(tokens_until_error(span).count(), 3);
+}
+
+#[test]
+fn error_msgs() {
+    use super::*;
+    let text = "0x;\n'';\n'\\q';\n@;\n\"a\\qb\\zc\";\n\u{e9}\u{e8};\n'a\n/* abc";
+    span!(span, text);
+    let msgs = tokens(span)
+        .filter_map(|t| t.get().err().map(|e| e.msgs()))
+        .flatten()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        msgs,
+        [
+            ("invalid hex literal: 0x".to_string(), (1, 1)),
+            ("empty char literal".to_string(), (2, 1)),
+            ("invalid escape sequence: \\q".to_string(), (3, 1)),
+            ("unexpected character: @ after `;`".to_string(), (4, 1)),
+            ("invalid escape sequence: \\q".to_string(), (5, 4)),
+            ("invalid escape sequence: \\z".to_string(), (5, 7)),
+            ("non-ascii characters: 0xe9 0xe8 ".to_string(), (6, 1)),
+            ("unterminated char literal".to_string(), (7, 1)),
+            ("unterminated block comment".to_string(), (8, 1)),
+        ]
+    );
+}
+
+#[test]
+fn errors_by_position() {
+    use super::*;
+    span!(span, "@ x;\ny # '';\n0x");
+    let mut errors = tokens(span)
+        .filter_map(|t| t.transpose().err())
+        .collect::<Vec<_>>();
+    errors.reverse();
+    errors.swap(0, 1);
+    errors.sort_by(by_position);
+    let positions = errors
+        .iter()
+        .map(|e| (e.fragment(), e.position()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        positions,
+        [("@", (1, 1)), ("#", (2, 3)), ("''", (2, 5)), ("0x", (3, 1))]
+    );
+}
+
+#[test]
+fn unexpected_char_context() {
+    use super::*;
+    span!(span, "foo @ // x\n# /* y */ $ (\n");
+    let msgs = tokens(span)
+        .filter_map(|t| t.get().err().map(|e| e.msgs()))
+        .flatten()
+        .map(|(msg, _)| msg)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        msgs,
+        [
+            "unexpected character: @ after identifier `foo`",
+            // errors are not used as context
+            "unexpected character: # after identifier `foo`",
+            "unexpected character: $ after identifier `foo`",
+        ]
+    );
+    span!(span, "@");
+    let msgs = tokens(span).find_map(|t| t.get().err().map(|e| e.msgs()));
+    assert_eq!(msgs.unwrap()[0].0, "unexpected character: @");
+}
+
+#[test]
+fn single_unterminated_token() {
+    use super::*;
+    for (text, error, eof) in [
+        ("\"unterminated", "\"unterminated", (1, 14)),
+        ("\"unterminated\n", "\"unterminated", (2, 1)),
+        ("\"a\\\"\n\n", "\"a\\\"", (3, 1)),
+        ("/* unterminated", "/* unterminated", (1, 16)),
+        ("/* unterminated\n  \n", "/* unterminated", (3, 1)),
+    ] {
+        span!(span, text);
+        let toks = tokens(span).collect::<Vec<_>>();
+        assert_eq!(toks.len(), 2, "{:?}", text);
+        assert_eq!(toks[0].get().unwrap_err().span().source(), error);
+        assert_eq!(toks[1].copied(), Ok(Eof));
+        assert_eq!(toks[1].position(), eof);
+    }
+    // the last character of an unterminated literal is checked
+    span!(span, "\"a\\q\n");
+    let msgs = tokens(span).next().unwrap().get().unwrap_err().msgs();
+    assert_eq!(msgs[0].0, "invalid escape sequence: \\q");
+    assert_eq!(msgs[1].0, "unterminated string literal");
+}
+
+#[test]
+fn char_literal_at_eof() {
+    use super::*;
+    for (text, error) in [
+        ("x '", "'"),
+        ("x 'a", "'a"),
+        ("x '\n", "'"),
+        ("x '\\", "'\\"),
+    ] {
+        span!(span, text);
+        let toks = tokens(span).collect::<Vec<_>>();
+        assert_eq!(toks.len(), 3, "{:?}", text);
+        let err = toks[1].get().unwrap_err();
+        assert!(matches!(err, Error::UnterminatedChar(..)), "{:?}", err);
+        assert_eq!(err.span().source(), error);
+        assert_eq!(err.msgs()[0].0, "unterminated char literal");
+    }
+    span!(span, "x ''");
+    let toks = tokens(span).collect::<Vec<_>>();
+    assert!(matches!(toks[1].get(), Err(Error::EmptyChar(..))));
+}
+
+#[test]
+fn int_literal() {
+    use super::*;
+    let text = "123";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), DecimalLiteral);
+    assert_eq!(s1.fragment(), "123");
+    assert_eq!(s2.source(), "");
+
+    let text = "123abc";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), DecimalLiteral);
+    assert_eq!(s1.fragment(), "123");
+    assert_eq!(s2.source(), "abc");
+
+    let text = "12a111";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), DecimalLiteral);
+    assert_eq!(s1.fragment(), "12");
+    assert_eq!(s2.source(), "a111");
+
+    let text = "b1111";
+    span!(span, text);
+    assert!(int_literal(span).is_none());
+
+    let text = "0x123";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), HexLiteral);
+    assert_eq!(s1.fragment(), "0x123");
+    assert_eq!(s2.source(), "");
+
+    let text = "0x123abc";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), HexLiteral);
+    assert_eq!(s1.fragment(), "0x123abc");
+    assert_eq!(s2.source(), "");
+
+    let text = "0x123abcg";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    // really...
+    assert_eq!(s1.get().unwrap(), HexLiteral);
+    assert_eq!(s1.fragment(), "0x123abc");
+    assert_eq!(s2.source(), "g");
+
+    let text = "0x12gabcg";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    assert_eq!(s1.get().unwrap(), HexLiteral);
+    assert_eq!(s1.fragment(), "0x12");
+    assert_eq!(s2.source(), "gabcg");
+
+    let text = "0xtttt";
+    span!(span, text);
+    let (s1, s2) = int_literal(span).unwrap();
+    assert!(matches!(s1.get().unwrap_err(), Error::EmptyHexLiteral(..)));
+    assert_eq!(s1.fragment(), "0x");
+    assert_eq!(s2.source(), "tttt");
+}
+
+#[test]
+fn skip_spaces() {
+    use super::*;
+    span!(span, "    some text");
+    assert_eq!(rem(skip_spaces(span)).source(), "some text");
+    span!(span, "\n\n\t  \n some text");
+    assert_eq!(rem(skip_spaces(span)).source(), "some text");
+    span!(span, "   \n\t");
+    assert_eq!(rem(skip_spaces(span)).source(), "");
+}
+
+#[test]
+fn skip_line_comment() {
+    use super::*;
+    span!(span, "// comment\nsometext");
+    let span = skip_line_comment(span);
+    assert_eq!(rem(span).source(), "\nsometext");
+    span!(span, "// comment");
+    let span = skip_line_comment(span);
+    assert_eq!(rem(span).source(), "");
+    span!(span, "// foo /* bar\nx */");
+    let span = skip_line_comment(span);
+    assert_eq!(rem(span).source(), "\nx */");
+}
+
+#[test]
+fn block_comment_in_line_comment() {
+    use super::*;
+    span!(span, "// foo /* bar\nx // */");
+    assert_eq!(
+        tokens(span).map(|t| *t.get()).collect::<Vec<_>>(),
+        [Ok(Identifier(span.span_source().intern("x"))), Ok(Eof)]
+    );
+    let notes = notes(span).collect::<Vec<_>>();
+    assert_eq!(notes.len(), 1);
+    let Note::BlockCommentInLineComment(marker) = notes[0];
+    assert_eq!(marker.source(), "/*");
+    assert_eq!(marker.position(), (1, 8));
+}
+
+#[test]
+fn skip_block_comment() {
+    use super::*;
+    span!(span, "/* comment */sometext");
+    let span = skip_block_comment(span, false);
+    assert_eq!(span.unwrap().0.fragment(), "/* comment */");
+    assert_eq!(rem(span).source(), "sometext",);
+    span!(span, "/* comment ");
+    let span = skip_block_comment(span, false);
+    assert_eq!(rem(span).source(), "");
+
+    span!(span, "/**/");
+    let span = skip_block_comment(span, false);
+    assert_eq!(rem(span).source(), "");
+
+    span!(span, "/*/");
+    let rem = rem(skip_block_comment(span, false));
+    assert!(rem.is_empty())
+}
+
+#[test]
+fn symbol() {
+    use super::*;
+    let text = "==";
+    span!(span, text);
+    let (s1, s2) = symbol(span).unwrap();
+    assert_eq!(s1.get().unwrap(), EqualEqual);
+    assert_eq!(s1.fragment(), "==");
+    assert_eq!(s2.source(), "");
+
+    use std::iter;
+    let text = "=+-*/%&&||!<>?:";
+    span!(span, text);
+    let mut span = span;
+    let symbols = iter::from_fn(move || {
+        if span.is_empty() {
+            None
+        } else {
+            let (l, r) = symbol(span).unwrap();
+            span = r;
+            Some(l.get().unwrap())
+        }
+    })
+    .collect::<Vec<_>>();
+    assert_eq!(
+        symbols,
+        vec![
+            Assign, Plus, Minus, Star, Slash, Percent, And, Or, Not, Less, Greater, Question,
+            Colon,
+        ]
+    )
+}
+
+#[test]
+fn eof() {
+    use super::*;
+    span!(text, "some text ***  // comment");
+    let mut tokens = tokens(text).skip_while(|tok| tok.get().unwrap() != Token::Eof);
+    let eof = tokens.next().unwrap();
+    assert_eq!(eof.get().unwrap(), Token::Eof);
+    assert_eq!(eof.fragment(), "");
+    assert_eq!(eof.position(), (1, text.len() + 1));
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn similar_keyword() {
+    use super::*;
+    assert_eq!(similar_keyword("retrun"), Some(Token::Return));
+    assert_eq!(similar_keyword("whille"), Some(Token::While));
+    assert_eq!(similar_keyword("brek"), Some(Token::Break));
+    assert_eq!(similar_keyword("itn"), Some(Token::Int));
+    assert_eq!(similar_keyword("voidd"), Some(Token::Void));
+    assert_eq!(similar_keyword("return"), None);
+    assert_eq!(similar_keyword("counter"), None);
+    assert_eq!(similar_keyword("rtreun"), None);
+}
+
+#[test]
+fn semantic_tokens() {
+    use super::*;
+    span!(
+        span,
+        "int x; // x\nif (x) {\n  /* a\n  b */ f(\"s\", 0x1);\n}"
+    );
+    assert_eq!(
+        semantic_tokens(span),
+        [
+            [0, 0, 3, 0, 0], // int
+            [0, 4, 1, 4, 0], // x
+            [0, 1, 1, 1, 0], // ;
+            [0, 2, 4, 5, 0], // // x
+            [1, 0, 2, 0, 0], // if
+            [0, 3, 1, 1, 0], // (
+            [0, 1, 1, 4, 0], // x
+            [0, 1, 1, 1, 0], // )
+            [0, 2, 1, 1, 0], // {
+            [1, 2, 4, 5, 0], // /* a
+            [1, 0, 6, 5, 0], //   b */
+            [0, 7, 1, 4, 0], // f
+            [0, 1, 1, 1, 0], // (
+            [0, 1, 3, 3, 0], // "s"
+            [0, 3, 1, 1, 0], // ,
+            [0, 2, 3, 2, 0], // 0x1
+            [0, 3, 1, 1, 0], // )
+            [0, 1, 1, 1, 0], // ;
+            [1, 0, 1, 1, 0], // }
+        ]
+    );
+}
+
+#[test]
+fn line_counts() {
+    use super::*;
+    span!(
+        span,
+        "// header\n\nint x; // x\n/* a\n\n   b */\nvoid main() {\n\n  x = 1; /* c\n  */\n}\n"
+    );
+    assert_eq!(
+        line_counts(span),
+        LineCounts {
+            code: 4,
+            comment: 5,
+            blank: 2,
+            total: 11,
+        }
+    );
+}
+
+#[test]
+fn dollar_identifiers() {
+    use super::*;
+    let lex = |text, options| {
+        span!(span, text);
+        tokens_with_options(span, options)
+            .map(|tok| (tok.fragment().to_string(), tok.get().is_ok()))
+            .collect::<Vec<_>>()
+    };
+    let ident = |text: &str| (text.to_string(), true);
+    let error = |text: &str| (text.to_string(), false);
+    let eof = ident("");
+    let plain = LexerOptions::default();
+    assert_eq!(lex("$foo", plain), [error("$"), ident("foo"), eof.clone()]);
+    assert_eq!(
+        lex("a$b", plain),
+        [ident("a"), error("$"), ident("b"), eof.clone()]
+    );
+    let dollar = LexerOptions {
+        dollar_start: true,
+        dollar_continue: true,
+        ..plain
+    };
+    assert_eq!(lex("$foo", dollar), [ident("$foo"), eof.clone()]);
+    assert_eq!(lex("a$b", dollar), [ident("a$b"), eof.clone()]);
+    let continue_only = LexerOptions {
+        dollar_continue: true,
+        ..plain
+    };
+    assert_eq!(
+        lex("$foo", continue_only),
+        [error("$"), ident("foo"), eof.clone()]
+    );
+    assert_eq!(lex("a$b", continue_only), [ident("a$b"), eof]);
+}
+
+#[test]
+fn tokens_equal_ignoring_trivia() {
+    use super::*;
+    let program = b"int f(int x) {\n  return x + 1; // next\n}\n";
+    let reformatted = b"int f ( int x )\n{ /* one more */ return x+1; }";
+    assert!(tokens_equal_ignoring_trivia(program, reformatted));
+    assert!(!tokens_equal_ignoring_trivia(
+        program,
+        b"int f(int x) { return x + 2; }"
+    ));
+    assert!(!tokens_equal_ignoring_trivia(
+        program,
+        b"int f(int x) { return x + 1; } int y;"
+    ));
+    // `x+1` is not the same as `x + 1` if it is a string
+    assert!(!tokens_equal_ignoring_trivia(b"\"x+1\"", b"\"x + 1\""));
+}
+
+#[test]
+fn token_stream() {
+    use super::*;
+    /// reads one byte at a time so every token is split between reads.
+    struct Trickle<'a>(&'a [u8]);
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+    let text = "int a[0x10]; /* multi\nline */ void f() {\n  x = 'c' + \"a\\qb\";\n  \
+                y #= 0x; // é\n  z = ü + '';\n} /* open";
+    span!(span, text);
+    let expected = tokens(span)
+        .map(|tok| StreamedToken {
+            token: tok
+                .get()
+                .map_err(|err| err.msgs().into_iter().map(|(msg, _)| msg).collect()),
+            text: tok.fragment().to_string(),
+            offset: tok.span().offset(),
+            line: tok.line(),
+            column: tok.column(),
+        })
+        .collect::<Vec<_>>();
+    let streamed = TokenStream::from_reader(Trickle(text.as_bytes()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(streamed, expected);
+    let whole = TokenStream::from_reader(text.as_bytes())
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(whole, expected);
+}
+
+#[test]
+fn tokens_with_trivia() {
+    use super::*;
+    let text = "// header\nint x; /* a */ /* b\n */\nvoid f() { @ }  /* open";
+    span!(span, text);
+    let tokens = super::tokens_with_trivia(span).collect::<Vec<_>>();
+    let rebuilt = tokens
+        .iter()
+        .flat_map(|t| {
+            t.leading
+                .iter()
+                .map(|piece| piece.span().source())
+                .chain(std::iter::once(t.token.fragment()))
+        })
+        .collect::<String>();
+    assert_eq!(rebuilt, text);
+    let leading = |i: usize| -> Vec<_> {
+        tokens[i]
+            .leading
+            .iter()
+            .map(|piece| match piece {
+                TriviaPiece::Space(span) => ("space", span.source()),
+                TriviaPiece::LineComment(span) => ("line", span.source()),
+                TriviaPiece::BlockComment(span) => ("block", span.source()),
+            })
+            .collect()
+    };
+    assert_eq!(leading(0), [("line", "// header"), ("space", "\n")]);
+    assert_eq!(
+        leading(3),
+        [
+            ("space", " "),
+            ("block", "/* a */"),
+            ("space", " "),
+            ("block", "/* b\n */"),
+            ("space", "\n")
+        ]
+    );
+    // the unexpected character gets the same context as from `tokens`
+    let unexpected = tokens.iter().find(|t| t.token.get().is_err()).unwrap();
+    assert_eq!(
+        single_error_msg(&unexpected.token.get().unwrap_err()),
+        "unexpected character: @ after `{`"
+    );
+    let eof = tokens.last().unwrap();
+    assert_eq!(*eof.token.get(), Ok(Eof));
+    assert!(tokens.iter().map(|t| t.token).eq(super::tokens(span)));
+}
+
+#[test]
+fn interned_identifiers() {
+    use super::*;
+    span!(span, "x = y + x;");
+    let symbols = tokens(span)
+        .filter_map(|t| match t.get() {
+            Ok(Identifier(symbol)) => Some(*symbol),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(symbols.len(), 3);
+    assert_eq!(symbols[0], symbols[2]);
+    assert_ne!(symbols[0], symbols[1]);
+    assert_eq!(&*span.span_source().resolve(symbols[1]), "y");
+    assert_eq!(span.span_source().intern("x"), symbols[0]);
+    assert_eq!(span.span_source().clone().into_interner().len(), 2);
+
+    // the names of a source are dropped with it
+    let mut interner = Interner::new();
+    let a = interner.intern("a");
+    assert_eq!(interner.intern("a"), a);
+    assert_eq!(interner.resolve(a), "a");
+    let source = SpanSource::new("b a").with_interner(interner);
+    assert_eq!(
+        tokens(source.source()).next().unwrap().get(),
+        &Ok(Identifier(source.intern("b")))
+    );
+    assert_eq!(source.intern("a"), a);
+    assert_eq!(source.into_interner().len(), 2);
+}
+
+#[test]
+fn dialect_options() {
+    use super::*;
+    let lex = |text, options| {
+        span!(span, text);
+        tokens_with_options(span, options)
+            .map(|tok| match tok.get() {
+                Ok(_) => Ok(tok.fragment().to_string()),
+                Err(err) => Err(single_error_msg(err)),
+            })
+            .collect::<Vec<_>>()
+    };
+    let ok = |text: &str| Ok(text.to_string());
+    let plain = LexerOptions::default();
+
+    let short = LexerOptions {
+        max_string_len: Some(3),
+        ..plain
+    };
+    assert_eq!(lex("\"a\\nb\"", short), [ok("\"a\\nb\""), ok("")]);
+    assert_eq!(
+        lex("\"abcd\"", short),
+        [
+            Err("string literal is longer than 3 characters".to_string()),
+            ok("")
+        ]
+    );
+
+    let text = "x // caf\u{e9}\n";
+    assert_eq!(lex(text, plain), [ok("x"), ok("")]);
+    let ascii = LexerOptions {
+        ascii_only_comments: true,
+        ..plain
+    };
+    assert_eq!(
+        lex(text, ascii),
+        [
+            ok("x"),
+            Err("non-ascii characters: 0xe9 ".to_string()),
+            ok("")
+        ]
+    );
+
+    assert_eq!(lex("WHILE", plain), [ok("WHILE"), ok("")]);
+    let insensitive = LexerOptions {
+        case_insensitive_keywords: true,
+        ..plain
+    };
+    span!(span, "WHILE If whilex");
+    assert_eq!(
+        tokens_with_options(span, insensitive)
+            .map(|tok| tok.get().unwrap())
+            .collect::<Vec<_>>(),
+        [
+            While,
+            If,
+            Identifier(span.span_source().intern("whilex")),
+            Eof
+        ]
+    );
+
+    let text = "\tx\n\t\ty";
+    span!(span, text);
+    let tabs = LexerOptions {
+        tab_width: 4,
+        ..plain
+    };
+    let positions = tokens(span)
+        .map(|tok| (plain.position(tok.span()), tabs.position(tok.span())))
+        .collect::<Vec<_>>();
+    assert_eq!(positions[0], ((1, 2), (1, 5)));
+    assert_eq!(positions[1], ((2, 3), (2, 9)));
+}
+
+#[test]
+fn verify() {
+    use super::*;
+    let nested = LexerOptions {
+        nested_comments: true,
+        utf8: true,
+        max_string_len: Some(2),
+        ascii_only_comments: true,
+        dollar_start: true,
+        dollar_continue: true,
+        case_insensitive_keywords: true,
+        ..LexerOptions::default()
+    };
+    let check = |text: &str| {
+        span!(span, text);
+        assert_eq!(super::verify(span), Ok(()), "{text:?}");
+        assert_eq!(verify_with_options(span, nested), Ok(()), "{text:?}");
+    };
+    [
+        "",
+        "int x;",
+        "/* unterminated",
+        "\"abc",
+        "'",
+        "0x",
+        "\"\\q\" 'ab' @#é\u{7f}",
+        "// é\n/* /* */",
+    ]
+    .iter()
+    .for_each(|text| check(text));
+
+    // random texts of the characters that start or end tokens
+    let alphabet: Vec<char> = "ab1_x0F \t\n\r/*\"'\\-=<>!&|()é€😀$;\u{0}\u{7f}\u{a0}"
+        .chars()
+        .collect();
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+    (0..1000).for_each(|_| {
+        let text = (0..next() % 30)
+            .map(|_| alphabet[next() % alphabet.len()])
+            .collect::<String>();
+        check(&text);
+    });
+}
+
+#[test]
+fn nested_comments() {
+    use super::*;
+    let nested = LexerOptions {
+        nested_comments: true,
+        ..LexerOptions::default()
+    };
+    let lex = |text, options| {
+        span!(span, text);
+        all_tokens(span, options)
+            .map(|tok| (tok.get().map_err(|_| ()), tok.fragment().to_string()))
+            .collect::<Vec<_>>()
+    };
+    let comment = |text: &str| (Ok(BlockComment), text.to_string());
+    // the only name of the text gets the first symbol of its source
+    let x = Interner::new().intern("x");
+
+    assert_eq!(
+        lex("/* a /* b */ c */x", nested),
+        [
+            comment("/* a /* b */ c */"),
+            (Ok(Identifier(x)), "x".to_string()),
+        ]
+    );
+    assert_eq!(lex("/**/", nested), [comment("/**/")]);
+    // standard decaf ends the comment at the first `*/`
+    assert_eq!(
+        lex("/* a /* b */ c", LexerOptions::default())[..2],
+        [comment("/* a /* b */"), (Ok(Space), " ".to_string())]
+    );
+
+    // the error covers the outermost comment even if an inner one is closed
+    span!(span, "x\n/* a /* b */ c\n");
+    let err = tokens_with_options(span, nested)
+        .find_map(|tok| tok.get().err())
+        .unwrap();
+    assert!(matches!(err, Error::UnterminatedComment(_)));
+    assert_eq!(err.span().source(), "/* a /* b */ c");
+    assert_eq!(err.position(), (2, 1));
+}
+
+#[test]
+fn utf8_mode() {
+    use super::*;
+    let text = "s = \"h\u{e9}llo\"; // \u{fc}ber\nx\u{e9} = 1;";
+    span!(span, text);
+    let utf8 = LexerOptions {
+        utf8: true,
+        ..LexerOptions::default()
+    };
+    let errors = |options| {
+        tokens_with_options(span, options)
+            .filter_map(|tok| tok.get().err().map(|err| (err.msgs(), tok.fragment())))
+            .collect::<Vec<_>>()
+    };
+    // the unicode in identifiers is still an error
+    assert_eq!(
+        errors(utf8),
+        [(
+            vec![("non-ascii characters: 0xe9 ".to_string(), (2, 2))],
+            "\u{e9}"
+        )]
+    );
+    let plain = errors(LexerOptions::default());
+    assert_eq!(plain.len(), 2);
+    assert_eq!(
+        plain[0].0,
+        [("unexpected character: \u{e9}".to_string(), (1, 7))]
+    );
+
+    // the columns after the unicode characters count characters
+    let semicolon = tokens_with_options(span, utf8)
+        .find(|tok| *tok.get() == Ok(Semicolon))
+        .unwrap();
+    assert_eq!(semicolon.position(), (1, 13));
+    assert_eq!(utf8.position(semicolon.span()), (1, 12));
+}
+
+#[test]
+fn lexer_lookahead() {
+    use super::*;
+    span!(span, "x = a[1] @ // c\n;");
+    assert!(Lexer::new(span).eq(tokens(span)));
+
+    let mut lexer = Lexer::new(span);
+    assert_eq!(lexer.peek_nth(2).unwrap().fragment(), "a");
+    assert_eq!(lexer.peek().unwrap().fragment(), "x");
+    assert_eq!(lexer.next().unwrap().fragment(), "x");
+    let checkpoint = lexer.checkpoint();
+    let rest = lexer.by_ref().map(|t| t.fragment()).collect::<Vec<_>>();
+    assert_eq!(rest, ["=", "a", "[", "1", "]", "@", ";", ""]);
+    assert!(lexer.peek().is_none());
+    lexer.rollback(checkpoint);
+    assert_eq!(lexer.peek_nth(7).unwrap().get(), &Ok(Eof));
+    assert!(lexer.peek_nth(8).is_none());
+    assert!(lexer.eq(tokens(span).skip(1)));
+}
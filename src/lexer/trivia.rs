@@ -0,0 +1,82 @@
+use crate::span::{Span, SpanSource, Spanned};
+
+use super::{all_tokens, tokens, with_context, LexerOptions, Result, Token};
+
+/// the spaces and comments between the tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaPiece<'a> {
+    Space(Span<'a>),
+    LineComment(Span<'a>),
+    BlockComment(Span<'a>),
+}
+
+impl<'a> TriviaPiece<'a> {
+    pub(super) fn from_token(t: &Spanned<'a, Result<'a>>) -> Option<Self> {
+        match t.get() {
+            Ok(Token::Space) => Some(Self::Space(t.span())),
+            Ok(Token::LineComment) => Some(Self::LineComment(t.span())),
+            Ok(Token::BlockComment) => Some(Self::BlockComment(t.span())),
+            _ => None,
+        }
+    }
+
+    pub fn span(self) -> Span<'a> {
+        match self {
+            Self::Space(span) | Self::LineComment(span) | Self::BlockComment(span) => span,
+        }
+    }
+}
+
+/// a token and the trivia right before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenWithTrivia<'a> {
+    pub leading: Vec<TriviaPiece<'a>>,
+    pub token: Spanned<'a, Result<'a>>,
+}
+
+/// same as `tokens` but every token carries the spaces and comments before it, the trivia at the
+/// end of the file is carried by the `Eof` token. writing the trivia and the fragment of every
+/// token in order gives back `text` exactly.
+pub fn tokens_with_trivia(text: Span) -> impl Iterator<Item = TokenWithTrivia> {
+    let mut all = all_tokens(text, LexerOptions::default());
+    let mut last = None;
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let mut leading = Vec::new();
+        for t in all.by_ref() {
+            match TriviaPiece::from_token(&t) {
+                Some(piece) => leading.push(piece),
+                None => {
+                    return Some(TokenWithTrivia {
+                        leading,
+                        token: with_context(t, &mut last),
+                    })
+                }
+            }
+        }
+        done = true;
+        Some(TokenWithTrivia {
+            leading,
+            token: text.split_at(text.len()).1.into_spanned(Ok(Token::Eof)),
+        })
+    })
+}
+
+/// true if `a` and `b` lex to the same tokens once the spaces and comments are dropped, so they
+/// are the same program regardless of how they are formatted.
+///
+/// the tokens are compared by their kinds and fragments, the erroneous ones only by their
+/// fragments.
+pub fn tokens_equal_ignoring_trivia(a: &[u8], b: &[u8]) -> bool {
+    let (a, b) = (SpanSource::from_bytes(a), SpanSource::from_bytes(b));
+    let mut b_tokens = tokens(b.source());
+    tokens(a.source()).all(|a_tok| {
+        b_tokens.next().is_some_and(|b_tok| {
+            a_tok.get().as_ref().ok() == b_tok.get().as_ref().ok()
+                && a_tok.fragment() == b_tok.fragment()
+        })
+    }) && b_tokens.next().is_none()
+}
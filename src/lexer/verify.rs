@@ -0,0 +1,71 @@
+use crate::span::Span;
+
+use super::{token, tokens_with_options, LexerOptions, Token};
+
+/// an invariant of the lexer that does not hold for some text, the offsets are the byte offsets of
+/// the token breaking it from the start of the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// a token is empty, so lexing the rest of the text would never end.
+    EmptyToken(usize),
+    /// a token starts after the end of the token preceding it.
+    Gap(usize),
+    /// a token starts before the end of the token preceding it.
+    Overlap(usize),
+    /// the span of the error of a token is not inside the token.
+    ErrorOutsideToken(usize),
+    /// the tokens stop at the offset before the end of the text.
+    NotCovered(usize),
+    /// the `Eof` token is missing or is not an empty token at the end of the text.
+    MisplacedEof(usize),
+}
+
+/// lexes `text` again and checks the invariants the rest of the crate relies on: the tokens,
+/// including the spaces and comments, are not empty, follow each other without gaps and cover the
+/// whole text, and they are followed by a single `Eof` token at its end.
+///
+/// meant for fuzzers and property tests, any text, valid decaf or not, has to pass.
+pub fn verify(text: Span) -> std::result::Result<(), Violation> {
+    verify_with_options(text, LexerOptions::default())
+}
+
+/// same as [`verify`] but with the dialect `options`.
+pub fn verify_with_options(
+    text: Span,
+    options: LexerOptions,
+) -> std::result::Result<(), Violation> {
+    let (start, end) = (text.offset(), text.offset() + text.len());
+    let mut rest = text;
+    let mut prev_end = start;
+    while !rest.is_empty() {
+        let Some((tok, rem)) = token(rest, options) else {
+            return Err(Violation::NotCovered(prev_end - start));
+        };
+        let (beg, tok_end) = (tok.span().offset(), tok.span().offset() + tok.span().len());
+        if tok.span().is_empty() {
+            return Err(Violation::EmptyToken(beg - start));
+        } else if beg > prev_end {
+            return Err(Violation::Gap(beg - start));
+        } else if beg < prev_end {
+            return Err(Violation::Overlap(beg - start));
+        }
+        if let Err(e) = tok.get() {
+            let err_beg = e.span().offset();
+            if err_beg < beg || err_beg + e.span().len() > tok_end {
+                return Err(Violation::ErrorOutsideToken(beg - start));
+            }
+        }
+        prev_end = tok_end;
+        rest = rem;
+    }
+    if prev_end != end {
+        return Err(Violation::NotCovered(prev_end - start));
+    }
+    let mut eofs =
+        tokens_with_options(text, options).filter(|tok| matches!(tok.get(), Ok(Token::Eof)));
+    match (eofs.next(), eofs.next()) {
+        (Some(eof), None) if eof.span().offset() == end && eof.span().is_empty() => Ok(()),
+        (Some(eof), _) => Err(Violation::MisplacedEof(eof.span().offset() - start)),
+        (None, _) => Err(Violation::MisplacedEof(end - start)),
+    }
+}
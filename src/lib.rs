@@ -5,3 +5,262 @@ pub mod cst;
 pub mod ast;
 pub mod hir;
 pub mod span;
+pub mod unparse;
+
+use ast::Root;
+use cst::PRoot;
+use error::{Diagnostic, ErrorSink, Phase};
+use span::SpanSource;
+
+/// lexes and parses `code`, returning the parsed tree or the errors of the lexer and the parser.
+///
+/// the tree borrows `code`, [`semantic_check`] runs the semantic checks too and returns the owned
+/// [`Root`].
+pub fn parse<'a>(code: &'a SpanSource<'a>) -> Result<PRoot<'a>, Vec<Diagnostic>> {
+    let mut sink = ErrorSink::new();
+    sink.enter_source(code);
+    sink.enter_phase(Phase::Parse);
+    let mut parser = parser::Parser::new(lexer::tokens(code.source()), |e| sink.error(&e));
+    let proot = parser.doc_elems().collect();
+    parser.skip_rest();
+    drop(parser);
+    if sink.has_errors() {
+        Err(sink.into_diagnostics())
+    } else {
+        Ok(proot)
+    }
+}
+
+/// runs the front-end on the file `filename` containing `source`, returning the checked program
+/// or the errors of all the phases that ran with the warnings of the semantic lints, like the
+/// `semantics` command reports them.
+///
+/// the tokens with lexer errors are recovered so the parser still reports the errors in the rest
+/// of the file, the semantic checks only run if there are no lexer errors. the parser replaces
+/// what it could not parse with placeholders that the semantic checks skip, so they still report
+/// the errors of the rest of the program, the lints only run on a program without syntax errors.
+pub fn semantic_check(source: &[u8], filename: &str) -> Result<Root, Vec<Diagnostic>> {
+    let code = SpanSource::from_bytes(source).with_name(filename);
+    let mut sink = ErrorSink::new();
+    sink.enter_source(&code);
+    sink.enter_phase(Phase::Parse);
+    let mut lexed = true;
+    let mut parser = parser::Parser::new(lexer::tokens(code.source()), |e| {
+        lexed &= !matches!(e, parser::Error::Lexical(_));
+        sink.error(&e)
+    });
+    let proot = parser.doc_elems().collect();
+    parser.skip_rest();
+    drop(parser);
+    // do not check a program made of guessed tokens
+    if !lexed {
+        return Err(sink.into_diagnostics());
+    }
+    sink.enter_phase(Phase::Semantic);
+    if !sink.has_errors() {
+        // there is no interface file, so the imports take any arguments
+        let interface = hir::Interface::default();
+        hir::lints(&proot, &interface, hir::LintOptions::default(), &mut sink);
+    }
+    match Root::from_proot(proot) {
+        Ok(root) if !sink.has_errors() => Ok(root),
+        Ok(_) => Err(sink.into_diagnostics()),
+        Err(errs) => {
+            errs.iter().for_each(|err| sink.error(err));
+            Err(sink.into_diagnostics())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_program() {
+        let code = SpanSource::new("int g;\nvoid main() {\n  g = 1;\n}\n");
+        let proot = parse(&code).unwrap();
+        assert_eq!(proot.funcs[0].name().source(), "main");
+        assert_eq!(proot.decls[0].name().source(), "g");
+        // the undeclared names are left to the semantic checks
+        let code = SpanSource::new("void main() {\n  x = 1;\n}\n");
+        assert!(parse(&code).is_ok());
+    }
+
+    #[test]
+    fn semantic_check_program() {
+        let root = semantic_check(b"int g;\nvoid main() {\n  g = 1;\n}\n", "ok.dcf").unwrap();
+        assert!(root.functions.contains_key("main"));
+        assert!(root.globals.contains_key("g"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let code = SpanSource::named("bad.dcf", "void main() {\n  x = ;\n}\n@\n");
+        let diagnostics = parse(&code).unwrap_err();
+        let diagnostics = diagnostics
+            .iter()
+            .inspect(|diag| assert_eq!(diag.source_name(), Some("bad.dcf")))
+            .map(|diag| (diag.phase(), diag.position()))
+            .collect::<Vec<_>>();
+        // the missing expression, the parser skips the statement, and the stray character which
+        // keeps its phase though the parser reports it
+        assert_eq!(
+            diagnostics,
+            [(Some(Phase::Parse), (2, 7)), (Some(Phase::Lex), (4, 1))]
+        );
+
+        // the semantic checks still run on the statements around the parse errors
+        let source = b"void main() {\n  int x;\n  x = (1 + ;\n  break\n  y = x;\n}\n";
+        let diagnostics = semantic_check(source, "bad.dcf").unwrap_err();
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diag| (diag.phase(), diag.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                (Some(Phase::Parse), (3, 12)),
+                (Some(Phase::Parse), (3, 12)),
+                (Some(Phase::Parse), (4, 8)),
+                (Some(Phase::Semantic), (4, 3)),
+                (Some(Phase::Semantic), (5, 3)),
+            ]
+        );
+
+        let diagnostics = semantic_check(b"void main() {\n  x = 1;\n}\n", "bad.dcf").unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].phase(), Some(Phase::Semantic));
+        assert_eq!(diagnostics[0].position(), (2, 3));
+    }
+
+    #[test]
+    fn recovers_from_lexer_errors() {
+        // the malformed literals stand for literals and the stray characters are skipped, so the
+        // parser reports nothing
+        let code = SpanSource::new("void main() {\n  f(\"a\\q\", '', 0x);\n  @x = 1;\n}\n");
+        let diagnostics = parse(&code).unwrap_err();
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diag| (diag.phase(), diag.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                (Some(Phase::Lex), (2, 8)),
+                (Some(Phase::Lex), (2, 12)),
+                (Some(Phase::Lex), (2, 16)),
+                (Some(Phase::Lex), (3, 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn int_literal_range() {
+        let source = b"void main() {\n  int x;\n  x = -9223372036854775808;\n  x = -9223372036854775809;\n  x = 0x8000000000000000;\n}\n";
+        let diagnostics = semantic_check(source, "bad.dcf").unwrap_err();
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diag| (diag.msg(), diag.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                (
+                    "integer literal `-9223372036854775809` is out of the range of a 64-bit integer",
+                    (4, 7)
+                ),
+                (
+                    "integer literal `0x8000000000000000` is out of the range of a 64-bit integer",
+                    (5, 7)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_array_size_range() {
+        let source = b"int a[0x8000000000000000];\nvoid main() {}\n";
+        let diagnostics = semantic_check(source, "bad.dcf").unwrap_err();
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diag| (diag.msg(), diag.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [(
+                "integer literal `0x8000000000000000` is out of the range of a 64-bit integer",
+                (1, 7)
+            )]
+        );
+    }
+
+    #[test]
+    fn semantic_check_errors() {
+        use error::Severity;
+        let source = b"import f;\nvoid main() {\n  int x;\n  f(x);\n  x = y;\n}\n";
+        let diagnostics = semantic_check(source, "bad.dcf").unwrap_err();
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diag| (diag.severity(), diag.msg(), diag.position()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                (
+                    Severity::Warning,
+                    "variable `x` may be used before it is assigned",
+                    (4, 5)
+                ),
+                (Severity::Error, "Undeclared identifier `y`", (5, 7)),
+            ]
+        );
+        // the program parses, the errors are all semantic
+        let code = SpanSource::from_bytes(source);
+        assert!(parse(&code).is_ok());
+    }
+
+    #[test]
+    fn error_codes() {
+        use error::codes::{BREAK_OUTSIDE_LOOP, USE_BEFORE_ASSIGN};
+        let source = b"void main() {\n  int x;\n  x = x;\n  break;\n}\n";
+        let diagnostics = semantic_check(source, "bad.dcf").unwrap_err();
+        let codes = diagnostics
+            .iter()
+            .map(|diag| diag.code())
+            .collect::<Vec<_>>();
+        assert_eq!(codes, [Some(USE_BEFORE_ASSIGN), Some(BREAK_OUTSIDE_LOOP)]);
+        let shown = diagnostics[1].display("bad.dcf").to_string();
+        assert!(shown.starts_with("bad.dcf:4:3: \x1b[31merror[S0011]\x1b[0m: Break outside loop"));
+
+        // the parse errors have no code
+        let code = SpanSource::new("void main() {\n  x = ;\n}\n");
+        assert_eq!(parse(&code).unwrap_err()[0].code(), None);
+    }
+
+    #[test]
+    fn missing_semicolon_suggestion() {
+        let source = b"void main() {\n  int x\n  x = 1;\n}\n";
+        let code = SpanSource::from_bytes(source);
+        let diagnostics = parse(&code).unwrap_err();
+        let suggestion = diagnostics[0].suggestion().unwrap();
+        assert_eq!((suggestion.offset, suggestion.len), (21, 0));
+        assert_eq!(suggestion.replacement, ";");
+        assert!(suggestion.safe);
+
+        let edit = (suggestion.span(&code), suggestion.replacement.as_bytes());
+        let fixed = span::rewrite(source, &[edit]).unwrap();
+        assert_eq!(fixed, b"void main() {\n  int x;\n  x = 1;\n}\n");
+        assert!(semantic_check(&fixed, "fixed.dcf").is_ok());
+    }
+
+    #[test]
+    fn empty_hex_suggestion() {
+        let code = SpanSource::new("int x[0x];\nvoid main() { }\n");
+        let diagnostics = parse(&code).unwrap_err();
+        let suggestion = diagnostics[0].suggestion().unwrap();
+        assert_eq!((suggestion.offset, suggestion.len), (6, 2));
+        assert_eq!(suggestion.replacement, "0x0");
+        assert!(!suggestion.safe);
+    }
+}
@@ -1,5 +1,5 @@
-use crate::error::CCError;
-use crate::lexer::Token;
+use crate::error::{CCError, Label, Phase, Suggestion};
+use crate::lexer::{self, Token};
 use crate::span::*;
 
 use Error::*;
@@ -10,14 +10,27 @@ pub enum Error<'a> {
         found: Token,
         span: Span<'a>,
     },
+    /// any identifier was expected, e.g. the name of a declaration.
+    ExpectedIdentifier {
+        found: Token,
+        span: Span<'a>,
+    },
     ExpectedMatching {
         lspan: Span<'a>,
         left: Token,
         right: Token,
         rspan: Span<'a>,
     },
+    /// the file ended before the delimiter opened at `lspan` was closed.
+    UnclosedAtEof {
+        lspan: Span<'a>,
+        left: Token,
+        right: Token,
+    },
     ExpectedExpression(Span<'a>),
     ExpectedBlock(Span<'a>),
+    /// an `else` that does not follow the block of an `if`.
+    ElseWithoutIf(Span<'a>),
     ExpectedAssignExpr(Span<'a>),
     Unexpected(Token, Span<'a>),
     WrapInParens(Span<'a>),
@@ -35,6 +48,57 @@ pub enum Error<'a> {
     },
     ForInitHasToBeAssign(Span<'a>),
     ForUpdateIsIncOrCompound(Span<'a>),
+    VoidVariable(Span<'a>),
+    MissingMethodBody {
+        method: Span<'a>,
+        span: Span<'a>,
+    },
+    /// `=<` or `=>` written in place of `<=` or `>=`.
+    SwappedOperator {
+        span: Span<'a>,
+        intended: Token,
+    },
+    /// a statement that starts with an identifier one typo away from a keyword.
+    MisspelledKeyword {
+        ident: Span<'a>,
+        keyword: Token,
+        span: Span<'a>,
+    },
+    /// a lexical error of a token the parser reached, the parser goes on with the token
+    /// [`lexer::recover`] gives for it.
+    Lexical(lexer::Error<'a>),
+}
+
+impl<'a> Error<'a> {
+    /// the span the error is reported at.
+    pub fn span(&self) -> Span<'a> {
+        match *self {
+            Expected { span, .. }
+            | ExpectedIdentifier { span, .. }
+            | ExpectedMatching { rspan: span, .. }
+            | UnclosedAtEof { lspan: span, .. }
+            | ExpectedExpression(span)
+            | ExpectedBlock(span)
+            | ElseWithoutIf(span)
+            | ExpectedAssignExpr(span)
+            | Unexpected(_, span)
+            | WrapInParens(span)
+            | ImportAfterDecl {
+                import_pos: span, ..
+            }
+            | ImportAfterFunc {
+                import_pos: span, ..
+            }
+            | DeclAfterFunc { decl_pos: span, .. }
+            | ForInitHasToBeAssign(span)
+            | ForUpdateIsIncOrCompound(span)
+            | VoidVariable(span)
+            | MissingMethodBody { span, .. }
+            | SwappedOperator { span, .. }
+            | MisspelledKeyword { span, .. } => span,
+            Lexical(err) => err.span(),
+        }
+    }
 }
 
 impl CCError for Error<'_> {
@@ -48,6 +112,10 @@ impl CCError for Error<'_> {
                 format!("expected token: {}, found: {}", expected, found),
                 span.position(),
             )],
+            ExpectedIdentifier { found, span } => vec![(
+                format!("expected token: identifier, found: {}", found),
+                span.position(),
+            )],
             ExpectedMatching {
                 lspan,
                 right,
@@ -61,10 +129,18 @@ impl CCError for Error<'_> {
                 ),
                 rspan.position(),
             )],
+            UnclosedAtEof { lspan, left, right } => vec![(
+                format!("unclosed {left}, expected a matching {right} before the end of file"),
+                lspan.position(),
+            )],
             ExpectedExpression(span) => vec![(
                 format!("expected expression, found: {}", span.to_string()),
                 span.position(),
             )],
+            ElseWithoutIf(span) => vec![(
+                "`else` without a matching `if`".to_string(),
+                span.position(),
+            )],
             ExpectedBlock(span) => vec![(
                 format!("expected block, found: {}", span.to_string()),
                 span.position(),
@@ -127,6 +203,65 @@ impl CCError for Error<'_> {
                 "for update has to be an increment or compound assign expression".to_string(),
                 span.position(),
             )],
+            VoidVariable(span) => vec![(
+                format!(
+                    "`void` can only be used as a method return type: {}",
+                    span.to_string()
+                ),
+                span.position(),
+            )],
+            MissingMethodBody { method, span } => vec![(
+                format!(
+                    "method `{}` is missing its body, expected `{{` found: {}",
+                    method.to_string(),
+                    span.to_string()
+                ),
+                span.position(),
+            )],
+            SwappedOperator { span, .. } => vec![(
+                format!("`{}` is not an operator", span.to_string()),
+                span.position(),
+            )],
+            // the statement is still a missing assign expression, the typo is a label
+            MisspelledKeyword { span, .. } => ExpectedAssignExpr(*span).msgs(),
+            Lexical(err) => err.msgs(),
+        }
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        match *self {
+            SwappedOperator { span, intended } => {
+                vec![Label::new(span, format!("did you mean `{}`?", intended))]
+            }
+            MisspelledKeyword { ident, keyword, .. } => vec![Label::new(
+                ident,
+                format!("`{}` looks like a typo of `{}`", ident.to_string(), keyword),
+            )],
+            _ => vec![],
+        }
+    }
+
+    fn source_name(&self) -> Option<&str> {
+        self.span().source_name()
+    }
+
+    fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            // a missing semicolon is reported at the end of the token it should follow
+            Expected {
+                expected: Token::Semicolon,
+                span,
+                ..
+            } if span.is_empty() => Some(Suggestion::new(*span, ";")),
+            Lexical(err) => err.suggestion(),
+            _ => None,
+        }
+    }
+
+    fn phase(&self) -> Option<Phase> {
+        match self {
+            Lexical(err) => err.phase(),
+            _ => None,
         }
     }
 }
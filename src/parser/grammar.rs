@@ -0,0 +1,96 @@
+/// the grammar recognized by the parser, every production is named after the parser method that
+/// parses it.
+pub const GRAMMAR: &str = r#"(* void variables and methods without a body are parsed but reported *)
+doc_elems ::= doc_elem*
+doc_elem ::= import | field_or_function_decl
+import ::= "import" IDENTIFIER ";"
+field_or_function_decl ::= ret_type IDENTIFIER ( function_params_body | opt_size ( "," var_list )? ";" )
+ret_type ::= "void" | var_type
+var_type ::= "int" | "bool"
+var_list ::= var_decl ( "," var_decl )*
+var_decl ::= IDENTIFIER opt_size
+opt_size ::= ( "[" int_literal "]" )?
+function_params_body ::= func_params ( block | ";" )
+func_params ::= "(" ( func_param ( "," func_param )* )? ")"
+func_param ::= ret_type IDENTIFIER
+block ::= "{" block_elem* "}"
+block_elem ::= field_or_function_decl | stmt
+stmt ::= if_stmt | while_stmt | return_stmt | break_stmt | continue_stmt | call_or_assignment | for_stmt
+if_stmt ::= "if" expr block ( "else" block )?
+while_stmt ::= "while" expr block
+return_stmt ::= "return" expr? ";"
+break_stmt ::= "break" ";"
+continue_stmt ::= "continue" ";"
+call_or_assignment ::= call_or_loc assign_expr? ";"
+for_stmt ::= "for" "(" for_inner_parens block
+for_inner_parens ::= assign ";" expr ";" assign ")"
+assign ::= loc assign_expr
+loc ::= IDENTIFIER opt_index
+assign_expr ::= "++" | "--" | ( "=" | "+=" | "-=" ) expr
+expr ::= binary_expr ( "?" expr ":" expr )?
+(* the binary operators and their precedences are the ones of `PRECEDENCE` *)
+binary_expr ::= unit_expr ( BINARY_OP binary_expr )*
+unit_expr ::= len_expr | eliteral | neg | not | call_or_loc postfix_index | nested_expr
+len_expr ::= "len" "(" IDENTIFIER ")"
+neg ::= "-" unit_expr
+not ::= "!" unit_expr
+call_or_loc ::= IDENTIFIER ( call_args | opt_index )
+postfix_index ::= ( "[" expr "]" )*
+call_args ::= "(" ( call_arg ( "," call_arg )* )? ")"
+call_arg ::= string_literal | expr
+opt_index ::= ( "[" expr "]" )?
+nested_expr ::= "(" expr ")"
+eliteral ::= int_literal | char_literal | bool_literal
+int_literal ::= DECIMALLITERAL | HEXLITERAL
+char_literal ::= CHARLITERAL
+bool_literal ::= "true" | "false"
+string_literal ::= STRINGLITERAL
+"#;
+
+/// the names of the productions of [`GRAMMAR`] in the order they are defined.
+pub fn productions() -> impl Iterator<Item = &'static str> {
+    GRAMMAR
+        .lines()
+        .filter(|line| !line.starts_with("(*"))
+        .filter_map(|line| line.split_once(" ::= "))
+        .map(|(name, _)| name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::GRAMMAR;
+
+    fn productions() -> impl Iterator<Item = (&'static str, &'static str)> {
+        GRAMMAR
+            .lines()
+            .filter(|line| !line.starts_with("(*"))
+            .map(|line| line.split_once(" ::= ").unwrap())
+    }
+
+    #[test]
+    fn productions_are_parser_methods() {
+        let parser = include_str!("mod.rs")
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+        productions().for_each(|(name, _)| {
+            assert!(
+                parser.contains(&format!("fn{name}("))
+                    || parser.contains(&format!("binop!({name},")),
+                "production `{name}` has no parser method"
+            );
+        });
+    }
+
+    #[test]
+    fn productions_are_defined() {
+        let names = productions().map(|(name, _)| name).collect::<Vec<_>>();
+        productions().for_each(|(_, rule)| {
+            rule.split(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '"')
+                .filter(|word| word.starts_with(|c: char| c.is_ascii_lowercase()))
+                .for_each(|word| {
+                    assert!(names.contains(&word), "`{word}` is used but not defined");
+                });
+        });
+    }
+}
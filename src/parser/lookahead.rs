@@ -0,0 +1,58 @@
+use crate::lexer::{self, recover, Token};
+use crate::span::Spanned;
+
+/// the tokens the parser looks ahead at.
+///
+/// a token with a lexical error is replaced by what [`recover`] gives for it when it is reached,
+/// and the error is kept until the parser takes it with [`Self::take_errors`] to report it, so the
+/// parser itself only deals with tokens.
+#[derive(Debug)]
+pub(super) struct Lookahead<'a, I> {
+    tokens: I,
+    peeked: Option<Spanned<'a, Token>>,
+    errors: Vec<lexer::Error<'a>>,
+}
+
+impl<'a, I: Iterator<Item = Spanned<'a, lexer::Result<'a>>>> Lookahead<'a, I> {
+    pub(super) fn new(tokens: I) -> Self {
+        Self {
+            tokens,
+            peeked: None,
+            errors: vec![],
+        }
+    }
+
+    fn pull(&mut self) -> Option<Spanned<'a, Token>> {
+        let tok = self.tokens.next()?;
+        if let Err(err) = tok.get() {
+            self.errors.push(*err);
+        }
+        Some(recover(tok))
+    }
+
+    pub(super) fn peek(&mut self) -> Option<&Spanned<'a, Token>> {
+        if self.peeked.is_none() {
+            self.peeked = self.pull();
+        }
+        self.peeked.as_ref()
+    }
+
+    pub(super) fn next(&mut self) -> Option<Spanned<'a, Token>> {
+        self.peeked.take().or_else(|| self.pull())
+    }
+
+    pub(super) fn next_if(
+        &mut self,
+        pred: impl FnOnce(&Spanned<'a, Token>) -> bool,
+    ) -> Option<Spanned<'a, Token>> {
+        match self.peek() {
+            Some(tok) if pred(tok) => self.next(),
+            _ => None,
+        }
+    }
+
+    /// the lexical errors of the tokens reached since the last call.
+    pub(super) fn take_errors(&mut self) -> Vec<lexer::Error<'a>> {
+        std::mem::take(&mut self.errors)
+    }
+}
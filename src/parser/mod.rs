@@ -1,10 +1,21 @@
-use crate::{lexer::Token, span::*};
-use core::iter::Peekable;
+use crate::{
+    lexer::{self, similar_keyword, Token},
+    span::*,
+};
+use lookahead::Lookahead;
+use std::collections::HashMap;
 
 mod error;
+mod grammar;
+mod lookahead;
+mod precedence;
+mod report;
 use crate::cst::checker::*;
 use crate::cst::*;
 pub use error::*;
+pub use grammar::{productions, GRAMMAR};
+pub use precedence::{Assoc, BinaryOp, PRECEDENCE, UNARY_PRECEDENCE};
+pub use report::{check_file, ParseReport};
 use Error::*;
 
 type Result<T> = std::result::Result<T, ExitStatus>;
@@ -25,69 +36,208 @@ enum Or<T1, T2> {
     Second(T2),
 }
 
+/// limits on the errors the parser reports, by default every error is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// the parser stops once it reported this many errors, it parses the rest of the file as if it
+    /// ended there and does not report the errors after them.
+    pub max_errors: usize,
+    /// the parser stops at the first error, like a `max_errors` of 1.
+    pub stop_after_first: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            max_errors: usize::MAX,
+            stop_after_first: false,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// the number of errors the parser stops after.
+    fn error_limit(&self) -> usize {
+        if self.stop_after_first {
+            self.max_errors.min(1)
+        } else {
+            self.max_errors
+        }
+    }
+}
+
 #[derive(Debug)]
-pub struct Parser<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> {
-    tokens: Peekable<I>,
+pub struct Parser<'a, I: Iterator<Item = Spanned<'a, lexer::Result<'a>>>, EH: FnMut(Error<'a>)> {
+    tokens: Lookahead<'a, I>,
     error_callback: EH,
     last_pos: Span<'a>,
     error: bool,
+    /// the number of errors reported, the errors past the limit of `options` are not counted.
+    reported: usize,
+    options: ParserOptions,
+    /// the number of placeholders inserted for what could not be parsed.
+    recovered: usize,
+    /// the number of times each production was parsed, only counted when profiling.
+    profile: Option<HashMap<&'static str, usize>>,
 }
 
-macro_rules! binop {
-    ($name:ident, $sub:ident, $first:ident => $first_mapped:ident, $($token:ident => $token_mapped:ident),*) => {
-        fn $name(&mut self) -> Result<Expr<'a>> {
-            let op = move |p: &mut Parser<'a, I, EH>| {
-                p.consume(Token::$first)
-                    .map(|_| Op::$first_mapped)
-                    $(.or_else(|_| p.consume(Token::$token).map(|_| Op::$token_mapped)))*
-            };
-            let mut expr = self.$sub()?;
-            while let Ok(op) = op(self) {
-                let rhs = self.$sub().map_err(|_| { self.expected_expression() })?;
-                let span = expr.span().merge(rhs.span());
-                expr = Expr::new_binop(expr, rhs, op, span);
-            }
-            Ok(expr)
-        }
-    };
-}
-
-impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a, I, EH> {
+impl<'a, I: Iterator<Item = Spanned<'a, lexer::Result<'a>>>, EH: FnMut(Error<'a>)>
+    Parser<'a, I, EH>
+{
+    /// the tokens have to end with `Eof` like the ones of [`crate::lexer::tokens`], so an empty file
+    /// is parsed as an empty document.
+    ///
+    /// the lexical errors are reported to `eh` as [`Error::Lexical`] when the parser reaches their
+    /// token, which is replaced by what [`lexer::recover`] gives for it, so the errors of both come
+    /// in the order of the source. see [`Self::skip_rest`] for the tokens the parser never reaches.
     pub fn new(tokens: I, eh: EH) -> Self {
-        let mut tokens = tokens.peekable();
+        let mut tokens = Lookahead::new(tokens);
         let beg = tokens.peek().unwrap().span().split_at(0).0;
         Self {
             tokens,
             error_callback: eh,
             last_pos: beg,
             error: false,
+            reported: 0,
+            options: ParserOptions::default(),
+            recovered: 0,
+            profile: None,
+        }
+    }
+
+    /// counts how many times each production of the grammar is parsed.
+    pub fn with_profile(self) -> Self {
+        Self {
+            profile: Some(HashMap::new()),
+            ..self
+        }
+    }
+
+    /// limits the errors the parser reports, see [`ParserOptions`].
+    pub fn with_options(self, options: ParserOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    /// whether the parser stopped because it reported as many errors as its options allow.
+    pub fn stopped(&self) -> bool {
+        self.reported >= self.options.error_limit()
+    }
+
+    /// the number of times each production was parsed, `None` if the parser is not profiling.
+    pub fn profile(&self) -> Option<&HashMap<&'static str, usize>> {
+        self.profile.as_ref()
+    }
+
+    fn enter(&mut self, production: &'static str) {
+        if let Some(profile) = &mut self.profile {
+            *profile.entry(production).or_default() += 1;
         }
     }
 
+    /// the invalid tokens are reported as lexical errors, the parser goes on as if they were not
+    /// there.
+    fn skip_error_tokens(&mut self) {
+        while self
+            .tokens
+            .next_if(|t| *t.get() == Token::ErrorToken)
+            .is_some()
+        {}
+        self.tokens
+            .take_errors()
+            .into_iter()
+            .for_each(|err| self.report_error(Lexical(err)));
+    }
+
+    /// consumes the tokens left after parsing stopped, reporting their lexical errors, so every
+    /// lexical error of the file is reported even if the parser could not reach the end of it.
+    pub fn skip_rest(&mut self) {
+        while self.tokens.next().is_some() {}
+        self.skip_error_tokens();
+    }
+
+    /// a stopped parser sees the end of the file so every production it is in ends.
     fn peek(&mut self) -> Token {
+        if self.stopped() {
+            return Token::Eof;
+        }
+        self.skip_error_tokens();
         self.tokens.peek().map(|t| *t.get()).unwrap()
     }
 
     pub fn finised(&mut self) -> bool {
-        self.peek() == Token::Eof
+        !self.stopped() && self.peek() == Token::Eof
     }
 
     pub fn found_errors(&self) -> bool {
         self.error
     }
 
+    /// the number of placeholders, [`Expr::Error`] and [`PStmt::Error`], inserted so far.
+    pub fn recovered(&self) -> usize {
+        self.recovered
+    }
+
     fn expected_expression(&mut self) -> ExitStatus {
         let err = ExpectedExpression(self.cur_span());
         self.report_error(err);
         Dirty
     }
 
+    /// parses an expression that the grammar requires with `parse`. a missing expression is
+    /// reported and replaced by a zero-width [`Expr::Error`] after the previous token, so the
+    /// enclosing node is still built.
+    fn required_expr(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Expr<'a>>,
+    ) -> Result<Expr<'a>> {
+        match parse(self) {
+            Ok(expr) => Ok(expr),
+            Err(Clean) => {
+                self.expected_expression();
+                self.recovered += 1;
+                Ok(Expr::Error(self.last_pos))
+            }
+            Err(Dirty) => Err(self.expected_expression()),
+        }
+    }
+
     fn expected_assignexpr(&mut self) -> ExitStatus {
         let err = ExpectedAssignExpr(self.cur_span());
         self.report_error(err);
         Dirty
     }
 
+    /// like `expected_assignexpr` but hints at the keyword the statement that starts with `loc`
+    /// was meant to start with, e.g. `retrun x;`, or at the comparison meant by `x =< y`.
+    fn expected_assignexpr_after(&mut self, loc: &Location<'a>) -> ExitStatus {
+        let written = loc.ident().merge(self.cur_span());
+        let intended = match self.peek() {
+            Token::Less => Some(Token::LessEqual),
+            Token::Greater => Some(Token::GreaterEqual),
+            _ => None,
+        };
+        match (loc, similar_keyword(loc.ident().source())) {
+            _ if intended.is_some() && written.ends_with(&format!("={}", self.peek())) => {
+                let err = SwappedOperator {
+                    span: written.split_at(written.len() - 2).1,
+                    intended: intended.unwrap(),
+                };
+                self.report_error(err);
+                Dirty
+            }
+            (Location::Scalar(ident), Some(keyword)) => {
+                let err = MisspelledKeyword {
+                    ident: *ident,
+                    keyword,
+                    span: self.cur_span(),
+                };
+                self.report_error(err);
+                Dirty
+            }
+            _ => self.expected_assignexpr(),
+        }
+    }
+
     fn expected_block(&mut self) -> ExitStatus {
         let err = ExpectedBlock(self.cur_span());
         self.report_error(err);
@@ -97,27 +247,31 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     /// returns the next token and advances the iterator.
     /// panics if the iterator is empty.
     fn bump(&mut self) -> Spanned<'a, Token> {
+        self.skip_error_tokens();
         let poped = self.tokens.next().unwrap();
         let (tok, span) = poped.into_parts();
-        let (span, last_pos) = span.split_at(span.len());
-        self.last_pos = last_pos;
+        self.last_pos = span.after();
         span.into_spanned(tok)
     }
 
     fn ident(&mut self) -> Result<Span<'a>> {
         match self.peek() {
-            Token::Identifier => Ok(self.bump().span().into()),
+            Token::Identifier(_) => Ok(self.bump().span().into()),
             _ => Err(Clean),
         }
     }
 
     fn cur_span(&mut self) -> Span<'a> {
+        self.skip_error_tokens();
         self.tokens.peek().unwrap().span()
     }
 
     fn report_error(&mut self, error: Error<'a>) {
         self.error = true;
-        (self.error_callback)(error)
+        if !self.stopped() {
+            self.reported += 1;
+            (self.error_callback)(error)
+        }
     }
 
     fn expected_token(&mut self, token: Token) -> Error<'a> {
@@ -128,6 +282,22 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
         }
     }
 
+    fn expected_identifier(&mut self) -> Error<'a> {
+        ExpectedIdentifier {
+            found: self.peek(),
+            span: self.cur_span(),
+        }
+    }
+
+    /// a missing `;` is reported right after the previous token, where it has to be inserted.
+    fn missing_semicolon(&mut self) -> Error<'a> {
+        Expected {
+            expected: Token::Semicolon,
+            found: self.peek(),
+            span: self.last_pos,
+        }
+    }
+
     fn start_span(&mut self) -> Span<'a> {
         self.cur_span()
     }
@@ -137,6 +307,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn import(&mut self) -> Result<Import<'a>> {
+        self.enter("import");
         let beg = self.start_span();
         self.cur_span();
         self.consume(Token::Import)?;
@@ -147,13 +318,14 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
         })?;
         let span = self.end_span(beg);
         _ = self.consume(Token::Semicolon).map_err(|_| {
-            let error = self.expected_token(Token::Semicolon);
+            let error = self.missing_semicolon();
             self.report_error(error)
         });
         Ok(span.into_spanned(identifier).into())
     }
 
     fn len_expr(&mut self) -> Result<Expr<'a>> {
+        self.enter("len_expr");
         let beg = self.start_span();
         self.consume(Token::Len)?;
         self.consume(Token::LeftParen).map_err(|_| {
@@ -162,7 +334,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
             Dirty
         })?;
         let ident = self.ident().map_err(|_| {
-            let error = self.expected_token(Token::Identifier);
+            let error = self.expected_identifier();
             self.report_error(error);
             Dirty
         })?;
@@ -175,25 +347,28 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn neg(&mut self) -> Result<Expr<'a>> {
+        self.enter("neg");
         let beg = self.start_span();
         self.consume(Token::Minus)?;
-        let expr = self.unit_expr().map_err(|_| self.expected_expression())?;
+        let expr = self.required_expr(Self::unit_expr)?;
         let span = self.end_span(beg);
         Ok(Expr::new_neg(span.into_spanned(expr)))
     }
 
     fn not(&mut self) -> Result<Expr<'a>> {
+        self.enter("not");
         let beg = self.start_span();
         self.consume(Token::Not)?;
-        let expr = self.unit_expr().map_err(|_| self.expected_expression())?;
+        let expr = self.required_expr(Self::unit_expr)?;
         let span = self.end_span(beg);
         Ok(Expr::new_not(span.into_spanned(expr)))
     }
 
     fn opt_index(&mut self) -> Result<Option<Expr<'a>>> {
+        self.enter("opt_index");
         if self.peek() == Token::SquareLeft {
             _ = self.consume(Token::SquareLeft);
-            let expr = self.expr().map_err(|_| self.expected_expression())?;
+            let expr = self.required_expr(Self::expr)?;
             _ = self.consume(Token::SquareRight).map_err(|_| {
                 let error = self.expected_token(Token::SquareRight);
                 self.report_error(error)
@@ -205,6 +380,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn call_or_loc(&mut self) -> Result<Or<Call<'a>, Location<'a>>> {
+        self.enter("call_or_loc");
         let beg = self.start_span();
         let ident = self.ident()?;
         if self.peek() == Token::LeftParen {
@@ -224,7 +400,17 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
         }
     }
 
+    /// parses the indexes that follow a call or an indexed location, e.g. `f()[0]` or `a[i][j]`.
+    fn postfix_index(&mut self, beg: Span<'a>, mut expr: Expr<'a>) -> Result<Expr<'a>> {
+        self.enter("postfix_index");
+        while let Some(offset) = self.opt_index()? {
+            expr = Expr::new_index(expr, offset, self.end_span(beg));
+        }
+        Ok(expr)
+    }
+
     fn opt_size(&mut self) -> Result<Option<IntLiteral<'a>>> {
+        self.enter("opt_size");
         if self.peek() == Token::SquareLeft {
             self.bump();
             let lit = self.int_literal().map_err(|_| {
@@ -244,24 +430,46 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn var_type(&mut self) -> Result<Type> {
+        self.enter("var_type");
         self.consume(Token::Int)
             .map(|_| Type::Int)
             .or(self.consume(Token::Bool).map(|_| Type::Bool))
     }
 
+    /// parses a return type, `None` is `void`.
+    fn ret_type(&mut self) -> Result<Option<Type>> {
+        self.enter("ret_type");
+        self.consume(Token::Void)
+            .map(|_| None)
+            .or_else(|_| self.var_type().map(Some))
+    }
+
+    /// `void` is only a return type, variables declared as `void` are reported and parsed as
+    /// `int` variables.
+    fn var_ret_type(&mut self, ret: Option<Type>, beg: Span<'a>) -> Type {
+        ret.unwrap_or_else(|| {
+            let error = VoidVariable(self.end_span(beg));
+            self.report_error(error);
+            Type::Int
+        })
+    }
+
     fn func_param(&mut self) -> Result<PVar<'a>> {
-        self.var_type().and_then(|ty| {
+        self.enter("func_param");
+        let beg = self.start_span();
+        self.ret_type().and_then(|ret| {
             self.ident()
                 .map_err(|_| {
-                    let error = self.expected_token(Token::Identifier);
+                    let error = self.expected_identifier();
                     self.report_error(error);
                     Dirty
                 })
-                .map(|ident| PVar::scalar(ty, ident))
+                .map(|ident| PVar::scalar(self.var_ret_type(ret, beg), ident))
         })
     }
 
     fn func_params(&mut self) -> Result<Vec<PVar<'a>>> {
+        self.enter("func_params");
         use std::iter;
         let left_paren_span = self.cur_span();
         self.consume(Token::LeftParen)?;
@@ -308,30 +516,29 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     /// parses the parameters and body, (injects the parameters into the block).
-    fn function_params_body(&mut self) -> Result<(Vec<PVar<'a>>, Block<'a>)> {
+    /// a method declared without a body (`void f();`) is reported and gets an empty body.
+    fn function_params_body(&mut self, ident: Span<'a>) -> Result<(Vec<PVar<'a>>, Block<'a>)> {
+        self.enter("function_params_body");
         let params = self.func_params().map_err(|_| {
             let error = self.expected_token(Token::LeftParen);
             self.report_error(error);
             Dirty
         })?;
+        if self.peek() == Token::Semicolon {
+            let span = self.bump().span();
+            self.report_error(MissingMethodBody {
+                method: ident,
+                span,
+            });
+            return Ok((params, Block::new(span)));
+        }
         self.block()
             .map_err(|_| self.expected_block())
             .map(|body| (params, body))
     }
 
-    fn void_function(&mut self) -> Result<PFunction<'a>> {
-        let beg = self.start_span();
-        self.consume(Token::Void)?;
-        let name = self.ident().map_err(|_| {
-            let error = self.expected_token(Token::Identifier);
-            self.report_error(error);
-            Dirty
-        })?;
-        self.function_params_body()
-            .map(|(params, body)| PFunction::new(None, name, params, body, self.end_span(beg)))
-    }
-
     fn var_decl(&mut self, ty: Type) -> Result<PVar<'a>> {
+        self.enter("var_decl");
         let beg = self.start_span();
         self.ident().map(|ident| {
             self.opt_size()
@@ -341,6 +548,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn var_list(&mut self, ty: Type) -> Result<Vec<PVar<'a>>> {
+        self.enter("var_list");
         use std::iter;
         self.var_decl(ty).map(|first| {
             iter::once(first)
@@ -348,7 +556,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
                     self.consume(Token::Comma).ok()?;
                     self.var_decl(ty)
                         .map_err(|_| {
-                            let error = self.expected_token(Token::Identifier);
+                            let error = self.expected_identifier();
                             self.report_error(error);
                         })
                         .ok()
@@ -358,6 +566,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn field_or_function_decl(&mut self) -> Result<Or<Vec<PVar<'a>>, PFunction<'a>>> {
+        self.enter("field_or_function_decl");
         let beg = self.start_span();
         let vars_after_comma = |p: &mut Self, ty, var: PVar<'a>| {
             p.bump();
@@ -368,43 +577,39 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
                     vars
                 })
                 .unwrap_or_else(|_| {
-                    let error = p.expected_token(Token::Identifier);
+                    let error = p.expected_identifier();
                     p.report_error(error);
                     vec![var]
                 });
             _ = p.consume(Token::Semicolon).map_err(|_| {
-                let error = p.expected_token(Token::Semicolon);
+                let error = p.missing_semicolon();
                 p.report_error(error);
             });
 
             Ok(Or::First(vars))
         };
         match self.peek() {
-            Token::Void => self.void_function().map(Or::Second),
-            Token::Int | Token::Bool => {
-                let ty = self.var_type().unwrap();
+            Token::Void | Token::Int | Token::Bool => {
+                let ret = self.ret_type().unwrap();
                 let ident = self.ident().map_err(|_| {
-                    let error = self.expected_token(Token::Identifier);
+                    let error = self.expected_identifier();
                     self.report_error(error);
                     Dirty
                 })?;
-                match self.peek() {
-                    Token::LeftParen => self
-                        .function_params_body()
+                if self.peek() == Token::LeftParen {
+                    return self
+                        .function_params_body(ident)
                         .map(|(params, body)| {
-                            Or::Second(PFunction::new(
-                                Some(ty),
-                                ident,
-                                params,
-                                body,
-                                self.end_span(beg),
-                            ))
+                            Or::Second(PFunction::new(ret, ident, params, body, self.end_span(beg)))
                         })
                         .map_err(|_| {
                             let error = self.expected_token(Token::LeftParen);
                             self.report_error(error);
                             Dirty
-                        }),
+                        });
+                }
+                let ty = self.var_ret_type(ret, beg);
+                match self.peek() {
                     Token::SquareLeft => {
                         let var = self
                             .opt_size()
@@ -414,7 +619,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
                             vars_after_comma(self, ty, var)
                         } else {
                             _ = self.consume(Token::Semicolon).map_err(|_| {
-                                let error = self.expected_token(Token::Semicolon);
+                                let error = self.missing_semicolon();
                                 self.report_error(error)
                             });
                             Ok(Or::First(vec![var]))
@@ -426,7 +631,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
                         Ok(Or::First(vec![PVar::scalar(ty, ident)]))
                     }
                     _ => {
-                        let error = self.expected_token(Token::Semicolon);
+                        let error = self.missing_semicolon();
                         self.report_error(error);
                         Ok(Or::First(vec![PVar::scalar(ty, ident)]))
                     }
@@ -437,10 +642,11 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn nested_expr(&mut self) -> Result<Expr<'a>> {
+        self.enter("nested_expr");
         let beg = self.start_span();
         let left_paren_span = self.cur_span();
         self.consume(Token::LeftParen)?;
-        let expr = self.expr().map_err(|_| self.expected_expression())?;
+        let expr = self.required_expr(Self::expr)?;
         _ = self.consume(Token::RightParen).map_err(|_| {
             let err = ExpectedMatching {
                 lspan: left_paren_span,
@@ -454,6 +660,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn int_literal(&mut self) -> Result<Spanned<'a, Literal<'a>>> {
+        self.enter("int_literal");
         match self.peek() {
             Token::DecimalLiteral => Ok({
                 let span = self.bump().span();
@@ -468,6 +675,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn bool_literal(&mut self) -> Result<Spanned<'a, bool>> {
+        self.enter("bool_literal");
         match self.peek() {
             Token::True => Ok(self.bump().map(|_| true)),
             Token::False => Ok(self.bump().map(|_| false)),
@@ -476,6 +684,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn char_literal(&mut self) -> Result<Spanned<'a, char>> {
+        self.enter("char_literal");
         match self.peek() {
             Token::CharLiteral(c) => Ok(self.bump().map(|_| c)),
             _ => Err(Clean),
@@ -483,75 +692,41 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn expr(&mut self) -> Result<Expr<'a>> {
+        self.enter("expr");
         let beg = self.start_span();
-        let e1 = self.or()?;
+        let e1 = self.binary_expr(1)?;
         match self.peek() {
             Token::Question => {
                 self.bump();
-                let yes = self.expr().map_err(|_| self.expected_expression())?;
+                let yes = self.required_expr(Self::expr)?;
                 self.consume(Token::Colon).map_err(|_| {
                     let err = self.expected_token(Token::Colon);
                     self.report_error(err);
                     Dirty
                 })?;
-                let no = self.expr().map_err(|_| self.expected_expression())?;
+                let no = self.required_expr(Self::expr)?;
                 Ok(Expr::new_ter(e1, yes, no, self.end_span(beg)))
             }
             _ => Ok(e1),
         }
     }
 
-    // fn mul_div(&mut self) -> Result<Expr<'a>> {
-    //     let op = move |p: &mut Parser<'a, I, EH>| {
-    //         p.consume(Token::Star)
-    //             .map(|_| Op::Mul)
-    //             .or_else(|_| p.consume(Token::Slash).map(|_| Op::Div))
-    //             .or_else(|_| p.consume(Token::Percent).map(|_| Op::Mod))
-    //     };
-    //     let mut expr = self.unit_expr()?;
-    //     while let Ok(op) = op(self) {
-    //         let rhs = self.unit_expr().map_err(|_| {
-    //             self.report_error(ExpectedExpression);
-    //             Dirty
-    //         })?;
-    //         let span = expr.span().merge(*rhs.span());
-    //         expr = Expr::binop(expr, op, rhs, span);
-    //     }
-    //     Ok(expr)
-    // }
-
-    binop!(
-        mul_div,
-        unit_expr,
-        Star => Mul,
-        Slash => Div,
-        Percent => Mod
-    );
-
-    binop!(
-        add_sub,
-        mul_div,
-        Plus => Add,
-        Minus => Sub
-    );
-
-    binop!(ord,
-           add_sub,
-           Greater => Greater,
-           GreaterEqual => GreaterEqual,
-           Less => Less,
-           LessEqual => LessEqual);
-
-    binop!(eq,
-           ord,
-           EqualEqual => Equal,
-           NotEqual => NotEqual);
-
-    binop!(and, eq, And => And,);
-
-    binop!(or, and, Or => Or,);
+    /// parses the operands and the binary operators that bind at least as tight as `min`, the
+    /// operators are looked up in [`PRECEDENCE`].
+    fn binary_expr(&mut self, min: u8) -> Result<Expr<'a>> {
+        self.enter("binary_expr");
+        let mut expr = self.unit_expr()?;
+        while let Some(op) = BinaryOp::of(self.peek()).filter(|op| op.precedence >= min) {
+            self.bump();
+            let rhs = self.required_expr(|p| p.binary_expr(op.rhs_precedence()))?;
+            let span = expr.span().merge(rhs.span());
+            expr = Expr::new_binop(expr, rhs, op.op, span);
+        }
+        Ok(expr)
+    }
 
     fn string_literal(&mut self) -> Result<Span<'a>> {
+        self.enter("string_literal");
         match self.peek() {
             Token::StringLiteral => Ok(self.bump().span().into()),
             _ => Err(Clean),
@@ -559,6 +734,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn call_arg(&mut self) -> Result<Arg<'a>> {
+        self.enter("call_arg");
         // NOTE: The order matters here since string_literal can not return a dirty error signal so
         // we start with it.
         self.string_literal()
@@ -567,6 +743,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn call_args(&mut self) -> Result<Vec<Arg<'a>>> {
+        self.enter("call_args");
         use std::iter;
         let left_paren_span = self.cur_span();
         self.consume(Token::LeftParen)?;
@@ -594,6 +771,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn eliteral(&mut self) -> Result<Expr<'a>> {
+        self.enter("eliteral");
         self.int_literal()
             .map(|i| i.into())
             .or_else(|_| self.char_literal().map(|c| c.into()))
@@ -601,6 +779,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn unit_expr(&mut self) -> Result<Expr<'a>> {
+        self.enter("unit_expr");
         self.len_expr()
             .or_else(|e| {
                 if e == Dirty {
@@ -615,9 +794,13 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
                 if e == Dirty {
                     Err(Dirty)
                 } else {
-                    self.call_or_loc().map(|c_or_lo| match c_or_lo {
-                        Or::First(c) => c.into(),
-                        Or::Second(loc) => Expr::Loc(loc),
+                    let beg = self.start_span();
+                    self.call_or_loc().and_then(|c_or_lo| {
+                        let expr = match c_or_lo {
+                            Or::First(c) => c.into(),
+                            Or::Second(loc) => Expr::Loc(loc),
+                        };
+                        self.postfix_index(beg, expr)
                     })
                 }
             })
@@ -631,6 +814,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn block_elem(&mut self) -> Result<PBlockElem<'a>> {
+        self.enter("block_elem");
         let beg = self.start_span();
         self.field_or_function_decl()
             .map(|decl_or_func| match decl_or_func {
@@ -646,13 +830,29 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
             })
     }
 
-    // FIXME: the block can terminate with a really messed up status.
-    fn block(&mut self) -> Result<Block<'a>> {
+    /// reports the `else`s that do not follow an `if` and skips them with their blocks, an
+    /// `else if` goes on as an `if` statement.
+    fn skip_stray_else(&mut self) {
+        while self.peek() == Token::Else {
+            let err = ElseWithoutIf(self.cur_span());
+            self.report_error(err);
+            self.bump();
+            if self.peek() == Token::CurlyLeft {
+                let _ = self.block();
+            }
+        }
+    }
+
+    /// parses the elements of a block up to and including its closing `}`, the opening `{` has to
+    /// be consumed already.
+    fn block_elems_after(
+        &mut self,
+        left_bracket_span: Span<'a>,
+    ) -> impl Iterator<Item = PBlockElem<'a>> + '_ {
         use std::iter;
-        let left_bracket_span = self.cur_span();
-        self.consume(Token::CurlyLeft)?;
         let mut block_checker = BlockChecker::new();
-        Ok(iter::from_fn(|| {
+        iter::from_fn(move || {
+            self.skip_stray_else();
             // if it returns an error then we did not finish the block yet so we can continue
             self.consume(Token::CurlyRight).err()?;
             match self.block_elem().map(|elem| {
@@ -662,6 +862,17 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
                 elem
             }) {
                 Ok(elem) => Some(elem),
+                Err(_) if self.finised() => {
+                    // the enclosing blocks are unclosed too, they report their own brackets as
+                    // the recursion unwinds so the innermost one comes first.
+                    let err = UnclosedAtEof {
+                        lspan: left_bracket_span,
+                        left: Token::CurlyLeft,
+                        right: Token::CurlyRight,
+                    };
+                    self.report_error(err);
+                    None
+                }
                 Err(_) => {
                     // there were no curly bracket and we could not parse anything. so we start to
                     // recover from this by consuming all tokens to the next curly brakcket.
@@ -692,20 +903,72 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
                 }
             }
         })
-        .fold(Block::new(), |mut block, elem| {
-            block.add(elem);
-            block
-        }))
+    }
+
+    // FIXME: the block can terminate with a really messed up status.
+    fn block(&mut self) -> Result<Block<'a>> {
+        self.enter("block");
+        let left_bracket_span = self.cur_span();
+        self.consume(Token::CurlyLeft)?;
+        let mut block = self.block_elems_after(left_bracket_span).fold(
+            Block::new(left_bracket_span),
+            |mut block, elem| {
+                block.add(elem);
+                block
+            },
+        );
+        block.span = self.end_span(left_bracket_span);
+        Ok(block)
+    }
+
+    /// parses a single expression, e.g. a line typed in a REPL, without wrapping it in a method.
+    ///
+    /// the tokens after the expression are left to the caller, [`Self::finised`] tells if the
+    /// whole input was parsed. `None` is returned if there is no expression to parse, the errors
+    /// are reported to the callback.
+    pub fn parse_expr(&mut self) -> Option<Expr<'a>> {
+        self.expr()
+            .map_err(|e| {
+                if e == Clean {
+                    self.expected_expression();
+                }
+            })
+            .ok()
+    }
+
+    /// like [`Self::parse_expr`] but parses a single statement, declarations are not statements.
+    pub fn parse_stmt(&mut self) -> Option<PStmt<'a>> {
+        self.stmt()
+            .map_err(|e| {
+                if e == Clean {
+                    let err = Unexpected(self.peek(), self.cur_span());
+                    self.report_error(err);
+                }
+            })
+            .ok()
+    }
+
+    /// streams the declarations and statements of a block (e.g. a method body) one at a time
+    /// instead of collecting them into a `Block`.
+    pub fn block_elems(&mut self) -> impl Iterator<Item = PBlockElem<'a>> + '_ {
+        let left_bracket_span = self.cur_span();
+        let opened = self.consume(Token::CurlyLeft).is_ok();
+        if !opened {
+            self.expected_block();
+        }
+        opened
+            .then(|| self.block_elems_after(left_bracket_span))
+            .into_iter()
+            .flatten()
     }
 
     /// parses if statements, allows parsing conditions that is not surrounded by `()`
     fn if_stmt(&mut self) -> Result<PStmt<'a>> {
+        self.enter("if_stmt");
         let beg = self.start_span();
         self.consume(Token::If)?;
-        let cond = self.expr().map_err(|_| {
-            // FIXME: it is `(<expr>)` not an expression
-            self.expected_expression()
-        })?;
+        // FIXME: it is `(<expr>)` not an expression
+        let cond = self.required_expr(Self::expr)?;
         let yes = self.block().map_err(|err| {
             assert_eq!(err, Clean);
             self.expected_block()
@@ -723,9 +986,10 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn while_stmt(&mut self) -> Result<PStmt<'a>> {
+        self.enter("while_stmt");
         let beg = self.start_span();
         self.consume(Token::While)?;
-        let cond = self.expr().map_err(|_| self.expected_expression())?;
+        let cond = self.required_expr(Self::expr)?;
         self.block()
             .map(|body| PStmt::r#while(cond, body, self.end_span(beg)))
             .map_err(|e| {
@@ -735,46 +999,44 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn return_stmt(&mut self) -> Result<PStmt<'a>> {
+        self.enter("return_stmt");
         let beg = self.start_span();
         self.consume(Token::Return)?;
         let expr = self
             .expr()
             .map(Some)
             .or_else(|e| if e == Dirty { Err(Dirty) } else { Ok(None) })?;
-        self.consume(Token::Semicolon)
-            .map(|_| PStmt::r#return(expr, self.end_span(beg)))
-            .map_err(|_| {
-                let error = self.expected_token(Token::Semicolon);
-                self.report_error(error);
-                Dirty
-            })
+        _ = self.consume(Token::Semicolon).map_err(|_| {
+            let error = self.missing_semicolon();
+            self.report_error(error)
+        });
+        Ok(PStmt::r#return(expr, self.end_span(beg)))
     }
 
     fn break_stmt(&mut self) -> Result<PStmt<'a>> {
+        self.enter("break_stmt");
         let beg = self.start_span();
         self.consume(Token::Break)?;
-        self.consume(Token::Semicolon)
-            .map(|_| PStmt::r#break(self.end_span(beg)))
-            .map_err(|_| {
-                let error = self.expected_token(Token::Semicolon);
-                self.report_error(error);
-                Dirty
-            })
+        _ = self.consume(Token::Semicolon).map_err(|_| {
+            let error = self.missing_semicolon();
+            self.report_error(error)
+        });
+        Ok(PStmt::r#break(self.end_span(beg)))
     }
 
     fn continue_stmt(&mut self) -> Result<PStmt<'a>> {
+        self.enter("continue_stmt");
         let beg = self.start_span();
         self.consume(Token::Continue)?;
-        self.consume(Token::Semicolon)
-            .map(|_| PStmt::r#continue(self.end_span(beg)))
-            .map_err(|_| {
-                let error = self.expected_token(Token::Semicolon);
-                self.report_error(error);
-                Dirty
-            })
+        _ = self.consume(Token::Semicolon).map_err(|_| {
+            let error = self.missing_semicolon();
+            self.report_error(error)
+        });
+        Ok(PStmt::r#continue(self.end_span(beg)))
     }
 
     fn assign_expr(&mut self) -> Result<AssignExpr<'a>> {
+        self.enter("assign_expr");
         match self.peek() {
             Token::Increment => {
                 self.bump();
@@ -801,24 +1063,55 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn call_or_assignment(&mut self) -> Result<PStmt<'a>> {
+        self.enter("call_or_assignment");
         let beg = self.start_span();
         let stmt = self
             .call_or_loc()
             .and_then(|call_or_loc| match call_or_loc {
                 Or::First(call) => Ok(call.into()),
-                Or::Second(loc) => self
-                    .assign_expr()
-                    .map(|assignexpr| Assign::new(loc, assignexpr, self.end_span(beg)).into())
-                    .map_err(|_| self.expected_assignexpr()),
+                Or::Second(loc) => match self.assign_expr() {
+                    Ok(assignexpr) => Ok(Assign::new(loc, assignexpr, self.end_span(beg)).into()),
+                    Err(Clean) => {
+                        self.expected_assignexpr_after(&loc);
+                        self.skip_stmt();
+                        self.recovered += 1;
+                        Ok(PStmt::Error(self.end_span(beg)))
+                    }
+                    Err(Dirty) => Err(self.expected_assignexpr_after(&loc)),
+                },
             })?;
+        if let PStmt::Error(_) = stmt {
+            return Ok(stmt);
+        }
         _ = self.consume(Token::Semicolon).map_err(|_| {
-            let error = self.expected_token(Token::Semicolon);
+            let error = self.missing_semicolon();
             self.report_error(error)
         });
         Ok(stmt)
     }
 
+    /// skips the rest of a malformed statement up to and including its `;`, stopping before the
+    /// `}` of the enclosing block.
+    fn skip_stmt(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.peek() {
+                Token::Semicolon if depth == 0 => {
+                    self.bump();
+                    break;
+                }
+                Token::CurlyLeft => depth += 1,
+                Token::CurlyRight if depth == 0 => break,
+                Token::CurlyRight => depth -= 1,
+                Token::Eof => break,
+                _ => {}
+            }
+            self.bump();
+        }
+    }
+
     fn loc(&mut self) -> Result<Location<'a>> {
+        self.enter("loc");
         let beg = self.cur_span();
         self.ident().and_then(|ident| {
             self.opt_index()
@@ -827,6 +1120,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn assign(&mut self) -> Result<Assign<'a>> {
+        self.enter("assign");
         let beg = self.start_span();
         self.loc().and_then(|loc| {
             self.assign_expr()
@@ -850,14 +1144,15 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn for_inner_parens(&mut self) -> Result<(Assign<'a>, Expr<'a>, Assign<'a>)> {
+        self.enter("for_inner_parens");
         let assign = self.assign()?;
         _ = self.consume(Token::Semicolon).map_err(|_| {
-            let error = self.expected_token(Token::Semicolon);
+            let error = self.missing_semicolon();
             self.report_error(error);
         });
-        let expr = self.expr().map_err(|_| self.expected_expression())?;
+        let expr = self.required_expr(Self::expr)?;
         _ = self.consume(Token::Semicolon).map_err(|_| {
-            let error = self.expected_token(Token::Semicolon);
+            let error = self.missing_semicolon();
             self.report_error(error);
         });
         let update = self.assign().map_err(|_| self.expected_assignexpr())?;
@@ -869,6 +1164,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn for_stmt(&mut self) -> Result<PStmt<'a>> {
+        self.enter("for_stmt");
         let beg = self.start_span();
         self.consume(Token::For)?;
         self.consume(Token::LeftParen).map_err(|_| {
@@ -884,6 +1180,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn stmt(&mut self) -> Result<PStmt<'a>> {
+        self.enter("stmt");
         self.if_stmt()
             .or_else(|e| {
                 if e == Dirty {
@@ -934,6 +1231,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     fn doc_elem(&mut self) -> Result<PDocElem<'a>> {
+        self.enter("doc_elem");
         let beg = self.start_span();
         self.field_or_function_decl()
             .map(|field_or_func| match field_or_func {
@@ -950,6 +1248,7 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
     }
 
     pub fn doc_elems(&mut self) -> impl Iterator<Item = PDocElem<'a>> + '_ {
+        self.enter("doc_elems");
         use std::iter;
         let mut elem_checker = RootChecker::new();
         iter::from_fn(move || {
@@ -962,3 +1261,525 @@ impl<'a, I: Iterator<Item = Spanned<'a, Token>>, EH: FnMut(Error<'a>)> Parser<'a
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{error::CCError, lexer::tokens};
+
+    macro_rules! expr {
+        ($expr:ident, $text:expr) => {
+            let span_source = SpanSource::new($text);
+            let mut parser = Parser::new(tokens(span_source.source()), |_| {});
+            let $expr = parser.expr().unwrap();
+            assert!(parser.finised());
+            assert!(!parser.found_errors());
+        };
+    }
+
+    macro_rules! doc {
+        ($root:ident, $errors:ident, $text:expr) => {
+            let span_source = SpanSource::new($text);
+            let mut $errors = vec![];
+            let mut parser = Parser::new(tokens(span_source.source()), |e| $errors.push(e));
+            let $root: PRoot = parser.doc_elems().collect();
+            assert!(parser.finised());
+        };
+    }
+
+    /// collects the errors of documents the parser does not recover from.
+    macro_rules! errors {
+        ($errors:ident, $text:expr) => {
+            let span_source = SpanSource::new($text);
+            let mut $errors = vec![];
+            Parser::new(tokens(span_source.source()), |e| $errors.push(e))
+                .doc_elems()
+                .for_each(|_| {});
+        };
+    }
+
+    #[test]
+    fn neg_binds_tighter_than_mul() {
+        expr!(e, "-1 * 2");
+        match e {
+            Expr::BinOp {
+                op: Op::Mul,
+                lhs,
+                rhs,
+                ..
+            } => {
+                assert!(matches!(*lhs, Expr::Neg(..)));
+                assert_eq!(lhs.span().source(), "-1");
+                assert!(matches!(*rhs, Expr::Literal { .. }));
+            }
+            e => panic!("expected multiplication, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn not_binds_tighter_than_eq() {
+        expr!(e, "!a == b");
+        match e {
+            Expr::BinOp {
+                op: Op::Equal,
+                lhs,
+                rhs,
+                ..
+            } => {
+                assert!(matches!(*lhs, Expr::Not(..)));
+                assert_eq!(lhs.span().source(), "!a");
+                assert!(matches!(*rhs, Expr::Loc(Location::Scalar(..))));
+            }
+            e => panic!("expected equality, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn nested_unary() {
+        expr!(e, "- -x");
+        match e {
+            Expr::Neg(span, inner) => {
+                assert_eq!(span.source(), "- -x");
+                assert!(matches!(*inner, Expr::Neg(..)));
+                assert_eq!(inner.span().source(), "-x");
+            }
+            e => panic!("expected negation, found: {:?}", e),
+        }
+
+        expr!(e, "!!x");
+        match e {
+            Expr::Not(span, inner) => {
+                assert_eq!(span.source(), "!!x");
+                assert!(matches!(*inner, Expr::Not(..)));
+                assert_eq!(inner.span().source(), "!x");
+            }
+            e => panic!("expected logical not, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn empty_program() {
+        for text in ["", "  \n\t\n", "// nothing\n/* here */\n"] {
+            doc!(root, errors, text);
+            assert!(errors.is_empty(), "{:?}", errors);
+            assert!(root.imports.is_empty() && root.decls.is_empty() && root.funcs.is_empty());
+        }
+    }
+
+    #[test]
+    fn void_return_type() {
+        doc!(root, errors, "void main() { return; }");
+        assert!(errors.is_empty());
+        assert_eq!(root.funcs.len(), 1);
+        assert_eq!(root.funcs[0].ret, None);
+    }
+
+    #[test]
+    fn void_variable() {
+        doc!(
+            root,
+            errors,
+            "void g;\nvoid main(void p) {\n  void x, y[2];\n  x = p;\n}"
+        );
+        let spans = errors
+            .iter()
+            .map(|e| match e {
+                VoidVariable(span) => (span.source(), span.position()),
+                e => panic!("expected void variable error, found: {:?}", e),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [("void g", (1, 1)), ("void p", (2, 11)), ("void x", (3, 3))]
+        );
+        // the variables are still declared so their uses are checked.
+        assert_eq!(root.decls.len(), 1);
+        assert_eq!(root.funcs[0].args.len(), 1);
+        assert_eq!(root.funcs[0].body.decls().len(), 2);
+    }
+
+    #[test]
+    fn streamed_stmts() {
+        let span_source = SpanSource::new("{\n  int x;\n  x = 1;\n  f(x);\n  return;\n}");
+        let mut parser = Parser::new(tokens(span_source.source()), |_| {});
+        let elems = parser.block_elems().collect::<Vec<_>>();
+        assert!(parser.finised());
+        assert!(!parser.found_errors());
+        assert!(matches!(elems[0], PBlockElem::Decls(..)));
+        let stmts = elems[1..]
+            .iter()
+            .map(|elem| match elem {
+                PBlockElem::Stmt(stmt) => (stmt.span().source(), stmt.span().position()),
+                elem => panic!("expected statement, found: {}", elem.span().source()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stmts,
+            [("x = 1", (3, 3)), ("f(x)", (4, 3)), ("return;", (5, 3))]
+        );
+    }
+
+    #[test]
+    fn empty_method_body() {
+        doc!(root, errors, "void f() {}\nvoid main() {}");
+        assert!(errors.is_empty());
+        assert_eq!(root.funcs.len(), 2);
+        assert!(root.funcs[0].body.decls().is_empty());
+    }
+
+    #[test]
+    fn missing_method_body() {
+        doc!(root, errors, "void f();\nvoid main() {}");
+        match errors[..] {
+            [MissingMethodBody { method, span }] => {
+                assert_eq!(method.source(), "f");
+                assert_eq!((span.source(), span.position()), (";", (1, 9)));
+            }
+            ref errors => panic!("expected missing body error, found: {:?}", errors),
+        }
+        // parsing goes on after the declaration without a body.
+        assert_eq!(root.funcs.len(), 2);
+    }
+
+    #[test]
+    fn missing_semicolon() {
+        doc!(root, errors, "int x\nvoid main() {\n  x = 1\n  return;\n}");
+        let positions = errors
+            .iter()
+            .map(|e| match e {
+                Expected {
+                    expected: Token::Semicolon,
+                    span,
+                    ..
+                } => {
+                    assert!(span.is_empty());
+                    span.position()
+                }
+                e => panic!("expected missing semicolon, found: {:?}", e),
+            })
+            .collect::<Vec<_>>();
+        // right after the last token of the declaration and the statement
+        assert_eq!(positions, [(1, 6), (3, 8)]);
+        assert_eq!(root.funcs.len(), 1);
+    }
+
+    #[test]
+    fn placeholders() {
+        doc!(
+            root,
+            errors,
+            "void main() {\n  x = -;\n  if { }\n  retrun x;\n  break\n}"
+        );
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        match root.funcs[0].body.stmts() {
+            [PStmt::Assign(Assign {
+                op: AssignExpr::Assign(Expr::Neg(_, neg)),
+                ..
+            }), PStmt::If {
+                cond: Expr::Error(cond),
+                ..
+            }, PStmt::Error(skipped), PStmt::Break(_)] => {
+                // the missing expressions are inserted right after the previous token
+                let Expr::Error(operand) = **neg else {
+                    panic!("expected a placeholder, found: {:?}", neg)
+                };
+                assert!(operand.is_empty() && cond.is_empty());
+                assert_eq!(operand.position(), (2, 8));
+                assert_eq!(cond.position(), (3, 5));
+                assert_eq!(skipped.source(), "retrun x;");
+            }
+            stmts => panic!("expected placeholders, found: {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn lexical_errors() {
+        doc!(root, errors, "void main() {\n  x = 0x + 1 @;\n  y = ;\n}\n");
+        // in the order of the source, along with the parse errors
+        let positions = errors
+            .iter()
+            .map(|err| (matches!(err, Lexical(_)), err.span().position()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [(true, (2, 7)), (true, (2, 14)), (false, (3, 7))]
+        );
+        assert_eq!(errors[0].phase(), Some(crate::error::Phase::Lex));
+        // the malformed literal is parsed as the literal it was meant to be
+        assert!(matches!(
+            root.funcs[0].body.stmts()[0],
+            PStmt::Assign(Assign {
+                op: AssignExpr::Assign(Expr::BinOp { .. }),
+                ..
+            })
+        ));
+
+        // the tokens after the parser stopped are only reached by `skip_rest`
+        let span_source = SpanSource::new("int x;\n}\n@\n");
+        let lexical_errors = |skip_rest: bool| {
+            let mut errors = vec![];
+            let mut parser = Parser::new(tokens(span_source.source()), |e| errors.push(e));
+            parser.doc_elems().for_each(drop);
+            assert!(!parser.finised());
+            if skip_rest {
+                parser.skip_rest();
+            }
+            drop(parser);
+            errors
+                .iter()
+                .filter(|err| matches!(err, Lexical(_)))
+                .count()
+        };
+        assert_eq!(lexical_errors(false), 0);
+        assert_eq!(lexical_errors(true), 1);
+    }
+
+    #[test]
+    fn error_limit() {
+        let span_source = SpanSource::new("void main() {\n  x = ;\n  y = -;\n  z = @;\n}\n");
+        let parse = |options: ParserOptions| {
+            let mut errors = vec![];
+            let mut parser =
+                Parser::new(tokens(span_source.source()), |e| errors.push(e)).with_options(options);
+            let root = parser.doc_elems().collect::<PRoot>();
+            let stopped = (parser.stopped(), parser.finised());
+            parser.skip_rest();
+            drop(parser);
+            let positions = errors
+                .iter()
+                .map(|err| err.span().position())
+                .collect::<Vec<_>>();
+            (positions, stopped, root.funcs.len())
+        };
+        let all = parse(ParserOptions::default());
+        assert_eq!(
+            all,
+            (vec![(2, 7), (3, 8), (4, 7), (4, 8)], (false, true), 1)
+        );
+        let capped = parse(ParserOptions {
+            max_errors: 2,
+            ..Default::default()
+        });
+        // the function is still built from what was parsed before stopping
+        assert_eq!(capped, (vec![(2, 7), (3, 8)], (true, false), 1));
+        let first = parse(ParserOptions {
+            stop_after_first: true,
+            ..Default::default()
+        });
+        assert_eq!(first, (vec![(2, 7)], (true, false), 1));
+    }
+
+    #[test]
+    fn misspelled_keyword() {
+        errors!(errors, "int f() {\n  retrun x;\n}");
+        // the parser skips the rest of the statement after the first error
+        match errors[..] {
+            [ref err @ MisspelledKeyword {
+                ident,
+                keyword: Token::Return,
+                span,
+            }, ..] => {
+                assert_eq!((ident.source(), ident.position()), ("retrun", (2, 3)));
+                assert_eq!(span.source(), "x");
+                assert_eq!(err.labels()[0].msg, "`retrun` looks like a typo of `return`");
+            }
+            ref errors => panic!("expected misspelled keyword, found: {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn swapped_operator() {
+        errors!(errors, "void main() {\n  x =< y;\n}");
+        match errors[..] {
+            [ref err @ SwappedOperator {
+                span,
+                intended: Token::LessEqual,
+            }, ..] => {
+                assert_eq!((span.source(), span.position()), ("=<", (2, 5)));
+                assert_eq!(err.labels()[0].msg, "did you mean `<=`?");
+            }
+            ref errors => panic!("expected swapped operator, found: {:?}", errors),
+        }
+        errors!(errors, "void main() {\n  x = < y;\n}");
+        assert!(
+            matches!(errors[..], [ExpectedAssignExpr(..), ..]),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn identifier_is_not_misspelled_keyword() {
+        errors!(errors, "int f() {\n  counter x;\n}");
+        assert!(
+            matches!(errors[..], [ExpectedAssignExpr(span), ..] if span.source() == "x"),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn chained_index() {
+        expr!(e, "a[i][j][k]");
+        let Expr::Index { base, offset, span } = e else {
+            panic!("expected index, found: {:?}", e);
+        };
+        assert_eq!(span.source(), "a[i][j][k]");
+        assert_eq!(offset.span().source(), "k");
+        let Expr::Index { base, offset, span } = *base else {
+            panic!("expected index, found: {:?}", base);
+        };
+        assert_eq!(span.source(), "a[i][j]");
+        assert_eq!(offset.span().source(), "j");
+        assert!(matches!(*base, Expr::Loc(Location::Index { .. })));
+        assert_eq!(base.span().source(), "a[i]");
+    }
+
+    #[test]
+    fn indexed_call() {
+        expr!(e, "f(x)[0] + 1");
+        let Expr::BinOp { lhs, .. } = e else {
+            panic!("expected addition, found: {:?}", e);
+        };
+        let Expr::Index { base, offset, span } = *lhs else {
+            panic!("expected index, found: {:?}", lhs);
+        };
+        assert_eq!((span.source(), span.position()), ("f(x)[0]", (1, 1)));
+        assert!(matches!(*base, Expr::Call(..)));
+        assert_eq!(base.span().source(), "f(x)");
+        assert_eq!(offset.span().source(), "0");
+    }
+
+    #[test]
+    fn fragments() {
+        macro_rules! parser {
+            ($parser:ident, $errors:ident, $text:expr) => {
+                let span_source = SpanSource::new($text);
+                let mut $errors = vec![];
+                let mut $parser = Parser::new(tokens(span_source.source()), |e| $errors.push(e));
+            };
+        }
+
+        parser!(parser, errors, "a[i] + f(1) * 2");
+        let e = parser.parse_expr().unwrap();
+        assert_eq!(e.span().source(), "a[i] + f(1) * 2");
+        assert!(parser.finised());
+        drop(parser);
+        assert!(errors.is_empty());
+
+        parser!(parser, errors, "1 2");
+        assert_eq!(parser.parse_expr().unwrap().span().source(), "1");
+        assert!(!parser.finised());
+        drop(parser);
+        assert!(errors.is_empty());
+
+        parser!(parser, errors, ")");
+        assert!(parser.parse_expr().is_none());
+        drop(parser);
+        assert!(matches!(errors[..], [ExpectedExpression(_)]));
+
+        parser!(parser, errors, "while (x) { x = f(x); }\nreturn;");
+        assert!(matches!(parser.parse_stmt(), Some(PStmt::While { .. })));
+        assert!(matches!(parser.parse_stmt(), Some(PStmt::Return { .. })));
+        assert!(parser.finised());
+        drop(parser);
+        assert!(errors.is_empty());
+
+        parser!(parser, errors, "if x { }");
+        assert!(matches!(parser.parse_stmt(), Some(PStmt::If { .. })));
+        drop(parser);
+        assert!(matches!(errors[..], [WrapInParens(_)]));
+
+        parser!(parser, errors, "int x;");
+        assert!(parser.parse_stmt().is_none());
+        drop(parser);
+        assert!(matches!(errors[..], [Unexpected(Token::Int, _)]));
+    }
+
+    #[test]
+    fn precedence() {
+        /// the expression with every binary operation in parentheses.
+        fn grouped(e: &Expr) -> String {
+            match e {
+                Expr::BinOp { lhs, rhs, op, .. } => {
+                    let token = PRECEDENCE.iter().find(|bop| bop.op == *op).unwrap().token;
+                    format!("({} {} {})", grouped(lhs), token, grouped(rhs))
+                }
+                e => e.span().source().to_string(),
+            }
+        }
+        expr!(e, "a - b - c * d % e < f + g == h || i && j != k");
+        assert_eq!(
+            grouped(&e),
+            "(((((a - b) - ((c * d) % e)) < (f + g)) == h) || (i && (j != k)))"
+        );
+    }
+
+    #[test]
+    fn profile() {
+        let span_source = SpanSource::new("void main() {\n  int x;\n  x = 1 + 2 * x;\n}");
+        let mut parser = Parser::new(tokens(span_source.source()), |_| {}).with_profile();
+        parser.doc_elems().for_each(|_| {});
+        let profile = parser.profile().unwrap();
+        let count = |production| profile.get(production).copied().unwrap_or(0);
+        assert_eq!(count("doc_elems"), 1);
+        // the declaration and the statement are both tried as declarations first
+        assert_eq!(count("field_or_function_decl"), 4);
+        assert_eq!(count("block_elem"), 2);
+        assert_eq!(count("stmt"), 1);
+        assert_eq!(count("expr"), 1);
+        // once for each operand
+        assert_eq!(count("binary_expr"), 3);
+        assert_eq!(count("unit_expr"), 3);
+        assert_eq!(count("call_or_loc"), 2);
+        assert_eq!(count("for_stmt"), 0);
+        assert!(profile
+            .keys()
+            .all(|production| GRAMMAR.contains(&format!("\n{production} ::= "))));
+
+        let mut parser = Parser::new(tokens(span_source.source()), |_| {});
+        parser.doc_elems().for_each(|_| {});
+        assert!(parser.profile().is_none());
+    }
+
+    #[test]
+    fn unclosed_blocks_at_eof() {
+        errors!(errors, "void main() {\n  if (x) {\n    y = 1;\n");
+        let unclosed = errors
+            .iter()
+            .map(|e| match e {
+                UnclosedAtEof {
+                    lspan,
+                    left: Token::CurlyLeft,
+                    ..
+                } => lspan.position(),
+                e => panic!("expected unclosed bracket, found: {:?}", e),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(unclosed, [(2, 10), (1, 13)]);
+    }
+
+    #[test]
+    fn method_missing_closing_bracket() {
+        errors!(errors, "void f() {\n  return;\n\nvoid main() {\n}\n");
+        match errors[..] {
+            [ref err @ UnclosedAtEof { lspan, .. }, ..] => {
+                assert_eq!(lspan.position(), (1, 10));
+                assert_eq!(err.msgs()[0].1, (1, 10));
+            }
+            ref errors => panic!("expected unclosed bracket, found: {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn else_without_if() {
+        errors!(errors, "void main() {\n  x = 1;\n  else { }\n}");
+        match errors[..] {
+            [ElseWithoutIf(span)] => {
+                assert_eq!(span.source(), "else");
+                assert_eq!(span.position(), (3, 3));
+            }
+            ref errors => panic!("expected else without if, found: {:?}", errors),
+        }
+    }
+}
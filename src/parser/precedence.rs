@@ -0,0 +1,112 @@
+//! the binary operators, how tightly they bind and how they associate.
+
+use crate::{cst::Op, lexer::Token};
+
+/// how a chain of operators of the same precedence is grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a - b - c` would be `a - (b - c)`.
+    Right,
+}
+
+/// a binary operator of the precedence table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryOp {
+    pub token: Token,
+    pub op: Op,
+    /// the operators with a higher precedence bind tighter, the lowest precedence is 1.
+    pub precedence: u8,
+    pub assoc: Assoc,
+}
+
+impl BinaryOp {
+    const fn left(token: Token, op: Op, precedence: u8) -> Self {
+        Self {
+            token,
+            op,
+            precedence,
+            assoc: Assoc::Left,
+        }
+    }
+
+    /// the operator spelled `token`.
+    pub fn of(token: Token) -> Option<Self> {
+        PRECEDENCE.iter().copied().find(|op| op.token == token)
+    }
+
+    /// the entry of `op` in the table, which has every binary operator of the syntax.
+    pub fn of_op(op: Op) -> Self {
+        PRECEDENCE
+            .iter()
+            .copied()
+            .find(|bin| bin.op == op)
+            .expect("every binary operator has a precedence")
+    }
+
+    /// the least precedence of the operators that can be in the left operand without parentheses,
+    /// an operator of the same precedence only can if it associates to the left.
+    pub const fn lhs_precedence(self) -> u8 {
+        match self.assoc {
+            Assoc::Left => self.precedence,
+            Assoc::Right => self.precedence + 1,
+        }
+    }
+
+    /// the least precedence of the operators that can be parsed in the right operand, an operator
+    /// of the same precedence only continues the right operand if it associates to the right.
+    pub const fn rhs_precedence(self) -> u8 {
+        match self.assoc {
+            Assoc::Left => self.precedence + 1,
+            Assoc::Right => self.precedence,
+        }
+    }
+}
+
+/// the binary operators from the loosest to the tightest binding, the ternary operator binds
+/// looser than all of them and the unary operators tighter.
+pub const PRECEDENCE: &[BinaryOp] = &[
+    BinaryOp::left(Token::Or, Op::Or, 1),
+    BinaryOp::left(Token::And, Op::And, 2),
+    BinaryOp::left(Token::EqualEqual, Op::Equal, 3),
+    BinaryOp::left(Token::NotEqual, Op::NotEqual, 3),
+    BinaryOp::left(Token::Greater, Op::Greater, 4),
+    BinaryOp::left(Token::GreaterEqual, Op::GreaterEqual, 4),
+    BinaryOp::left(Token::Less, Op::Less, 4),
+    BinaryOp::left(Token::LessEqual, Op::LessEqual, 4),
+    BinaryOp::left(Token::Plus, Op::Add, 5),
+    BinaryOp::left(Token::Minus, Op::Sub, 5),
+    BinaryOp::left(Token::Star, Op::Mul, 6),
+    BinaryOp::left(Token::Slash, Op::Div, 6),
+    BinaryOp::left(Token::Percent, Op::Mod, 6),
+];
+
+/// the precedence of the unary operators, one more than the tightest binary operator.
+pub const UNARY_PRECEDENCE: u8 = {
+    let (mut i, mut max) = (0, 0);
+    while i < PRECEDENCE.len() {
+        if PRECEDENCE[i].precedence > max {
+            max = PRECEDENCE[i].precedence;
+        }
+        i += 1;
+    }
+    max + 1
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn table() {
+        assert!(PRECEDENCE
+            .windows(2)
+            .all(|ops| ops[0].precedence <= ops[1].precedence));
+        assert!(PRECEDENCE.iter().all(|op| op.precedence > 0));
+        assert_eq!(BinaryOp::of(Token::Minus).unwrap().op, Op::Sub);
+        assert_eq!(BinaryOp::of(Token::Assign), None);
+        assert!(PRECEDENCE.iter().all(|op| BinaryOp::of_op(op.op) == *op));
+        assert_eq!(UNARY_PRECEDENCE, 7);
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use super::{productions, Parser};
+use crate::{lexer::tokens, span::SpanSource};
+
+/// a summary of parsing a file, see [`check_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseReport {
+    /// the number of errors reported by the lexer and the parser.
+    pub errors: usize,
+    /// the number of placeholders the parser inserted for what it could not parse.
+    pub recovered: usize,
+    /// whether the parser reached the end of the file.
+    pub finished: bool,
+    /// the number of times each production of the grammar was parsed, the productions that were
+    /// not parsed are counted as zero.
+    pub coverage: BTreeMap<&'static str, usize>,
+}
+
+impl ParseReport {
+    /// whether the file is a valid program as far as the parser can tell.
+    pub fn is_ok(&self) -> bool {
+        self.finished && self.errors == 0
+    }
+
+    /// the productions of the grammar that were never parsed.
+    pub fn uncovered(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.coverage
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(production, _)| *production)
+    }
+}
+
+/// lexes and parses `source` without keeping the tree, counting the errors and the productions
+/// of the grammar that were parsed.
+pub fn check_file(source: &[u8]) -> ParseReport {
+    let code = SpanSource::from_bytes(source);
+    let mut errors = 0;
+    let mut parser = Parser::new(tokens(code.source()), |_| errors += 1).with_profile();
+    parser.doc_elems().for_each(drop);
+    let finished = parser.finised();
+    parser.skip_rest();
+    let recovered = parser.recovered();
+    let mut coverage = productions().map(|p| (p, 0)).collect::<BTreeMap<_, _>>();
+    coverage.extend(parser.profile().unwrap());
+    drop(parser);
+    ParseReport {
+        errors,
+        recovered,
+        finished,
+        coverage,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_file() {
+        let report = check_file(b"int x;\nvoid main() {\n  x = 1 + 2;\n}\n");
+        assert!(report.is_ok());
+        assert_eq!(report.recovered, 0);
+        assert_eq!(report.coverage["binary_expr"], 2);
+        assert_eq!(report.coverage.len(), productions().count());
+        assert!(report
+            .uncovered()
+            .any(|production| production == "for_stmt"));
+        assert!(report
+            .uncovered()
+            .all(|production| report.coverage[production] == 0));
+    }
+
+    #[test]
+    fn invalid_file() {
+        let report = check_file(b"void main() {\n  x = -;\n  retrun x;\n  y = 1 @;\n}\n");
+        assert!(!report.is_ok());
+        // the missing operand, the misspelled `return` and the stray character
+        assert_eq!(report.errors, 3);
+        assert_eq!(report.recovered, 2);
+        assert!(report.finished);
+    }
+}
@@ -1,33 +1,211 @@
 use std::{
+    borrow::Cow,
+    cell::{Ref, RefCell},
+    cmp::Ordering,
     fmt::Debug,
     hash::Hash,
     ops::{Index, Range, RangeFrom, RangeFull, RangeTo},
     slice,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
 };
 
-#[derive(Clone, PartialEq, Eq)]
+use crate::lexer::{Interner, Symbol};
+
+/// identifies a span source, every source gets its own id even if it has the same text as another
+/// one, e.g. a clone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceId(u64);
+
+impl SourceId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, AtomicOrdering::Relaxed))
+    }
+}
+
 pub struct SpanSource<'a> {
-    source: &'a str,
+    id: SourceId,
+    source: Cow<'a, str>,
+    name: Option<&'a str>,
     lines: Vec<*const u8>,
     lengths: Vec<usize>,
+    /// the offsets of the `\n`s that replaced a `\r\n` when the line endings were normalized.
+    crlfs: Vec<usize>,
+    /// the offsets of the `U+FFFD`s that replaced invalid utf-8 with the bytes they replaced.
+    invalid: Vec<(usize, Vec<u8>)>,
+    /// the names of the identifiers lexed from the source.
+    interner: RefCell<Interner>,
+}
+
+/// the sources are compared by their ids, the spans of a source point into its own buffer so a
+/// source is only equal to itself.
+impl PartialEq for SpanSource<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for SpanSource<'_> {}
+
+impl Clone for SpanSource<'_> {
+    fn clone(&self) -> Self {
+        // the line pointers have to point into the cloned source
+        Self {
+            name: self.name,
+            crlfs: self.crlfs.clone(),
+            invalid: self.invalid.clone(),
+            interner: self.interner.clone(),
+            ..Self::from_cow(self.source.clone())
+        }
+    }
 }
 
 impl<'a> SpanSource<'a> {
     pub fn new(source: &'a str) -> Self {
+        Self::from_bytes(source.as_bytes())
+    }
+
+    /// invalid utf-8 sequences are replaced with `U+FFFD` which the lexer reports as non-ascii
+    /// characters. the bytes they replace are kept, the columns and [`Span::original_bytes`] are
+    /// those of `source`.
+    pub fn from_bytes(source: &'a [u8]) -> Self {
+        if let Ok(source) = std::str::from_utf8(source) {
+            return Self::from_cow(Cow::Borrowed(source));
+        }
+        let mut text = String::with_capacity(source.len());
+        let mut invalid = vec![];
+        for chunk in source.utf8_chunks() {
+            text.push_str(chunk.valid());
+            if !chunk.invalid().is_empty() {
+                invalid.push((text.len(), chunk.invalid().to_vec()));
+                text.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+        Self {
+            invalid,
+            ..Self::from_cow(Cow::Owned(text))
+        }
+    }
+
+    /// a source read from the file `name`, the spans of the source know their file.
+    pub fn named(name: &'a str, source: &'a str) -> Self {
+        Self::new(source).with_name(name)
+    }
+
+    pub fn with_name(self, name: &'a str) -> Self {
+        Self {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    pub fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+
+    pub fn id(&self) -> SourceId {
+        self.id
+    }
+
+    /// the source with the names of `interner`, e.g. to give the identifiers of an edited text the
+    /// symbols they had before the edit.
+    pub fn with_interner(self, interner: Interner) -> Self {
+        Self {
+            interner: RefCell::new(interner),
+            ..self
+        }
+    }
+
+    /// the interner of the identifiers lexed from the source.
+    pub fn into_interner(self) -> Interner {
+        self.interner.into_inner()
+    }
+
+    /// the symbol of `name` in the interner of the source.
+    pub fn intern(&self, name: &str) -> Symbol {
+        self.interner.borrow_mut().intern(name)
+    }
+
+    /// the name of `symbol`, which has to be interned in this source.
+    pub fn resolve(&self, symbol: Symbol) -> Ref<'_, str> {
+        Ref::map(self.interner.borrow(), |interner| interner.resolve(symbol))
+    }
+
+    /// replaces `\r\n` and lone `\r`s with `\n` so that lines are counted the same regardless of
+    /// the platform the file was written on, see [`SpanSource::original_offset`].
+    pub fn normalize_line_endings(self) -> Self {
+        if !self.source.contains('\r') {
+            return self;
+        }
+        let mut crlfs = vec![];
+        let mut invalid = vec![];
+        let mut replaced = self.invalid.iter().peekable();
+        let mut normalized = String::with_capacity(self.source.len());
+        let mut chars = self.source.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if let Some((_, bytes)) = replaced.next_if(|(offset, _)| *offset == i) {
+                invalid.push((normalized.len(), bytes.clone()));
+            }
+            if c == '\r' {
+                if chars.peek().map(|(_, c)| *c) == Some('\n') {
+                    chars.next();
+                    crlfs.push(normalized.len());
+                }
+                normalized.push('\n');
+            } else {
+                normalized.push(c);
+            }
+        }
+        Self {
+            name: self.name,
+            crlfs,
+            invalid,
+            interner: self.interner,
+            ..Self::from_cow(Cow::Owned(normalized))
+        }
+    }
+
+    /// how many more bytes the replacements of invalid utf-8 in `range` take than the bytes they
+    /// replaced.
+    fn replaced_len(&self, range: Range<usize>) -> usize {
+        let start = self
+            .invalid
+            .partition_point(|(offset, _)| *offset < range.start);
+        let end = self
+            .invalid
+            .partition_point(|(offset, _)| *offset < range.end);
+        self.invalid[start..end]
+            .iter()
+            .map(|(_, bytes)| char::REPLACEMENT_CHARACTER.len_utf8() - bytes.len())
+            .sum()
+    }
+
+    /// the offset of the byte at `offset` in the bytes the source was read from, before its line
+    /// endings were normalized and its invalid utf-8 was replaced.
+    pub fn original_offset(&self, offset: usize) -> usize {
+        offset + self.crlfs.partition_point(|&crlf| crlf < offset) - self.replaced_len(0..offset)
+    }
+
+    fn from_cow(source: Cow<'a, str>) -> Self {
         let lines = source
             .split(|c| c == '\n')
             .map(|line| line.as_ptr())
             .collect();
         let lengths = source.split(|c| c == '\n').map(|line| line.len()).collect();
         Self {
+            id: SourceId::next(),
             source,
+            name: None,
             lines,
             lengths,
+            crlfs: vec![],
+            invalid: vec![],
+            interner: RefCell::default(),
         }
     }
 
     pub fn get_line(&self, span: Span) -> Span {
-        assert!(span.span_source == self);
+        assert_eq!(span.source_id(), self.id, "the span is from another source");
         Span {
             source: self
                 .line(
@@ -54,10 +232,13 @@ impl<'a> SpanSource<'a> {
             .unwrap_or_else(|i| i)
     }
 
+    /// the column of `span` in the bytes the source was read from, see [`Self::from_bytes`].
     pub fn get_column(&self, span: Span<'a>) -> usize {
         let line_num = self.get_line_number(span);
         let line = self.line(line_num).unwrap();
-        span.source().as_ptr() as usize - line.as_ptr() as usize + 1
+        let start = line.as_ptr() as usize - self.source.as_ptr() as usize;
+        let column = span.source().as_ptr() as usize - line.as_ptr() as usize + 1;
+        column - self.replaced_len(start..span.offset())
     }
 
     pub fn get_pos(&self, span: Span<'a>) -> (usize, usize) {
@@ -74,7 +255,40 @@ impl<'a> SpanSource<'a> {
     pub fn source(&self) -> Span {
         Span {
             span_source: self,
-            source: self.source,
+            source: &self.source,
+        }
+    }
+}
+
+/// the lines of a source with their line numbers, a trailing newline does not start a new line.
+#[derive(Clone)]
+pub struct Lines<'s, 'a> {
+    source: &'s SpanSource<'a>,
+    next: usize,
+    count: usize,
+}
+
+impl<'s> Iterator for Lines<'s, '_> {
+    type Item = (usize, &'s [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.next < self.count).then(|| {
+            self.next += 1;
+            (self.next, self.source.line(self.next).unwrap().as_bytes())
+        })
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s SpanSource<'a> {
+    type Item = (usize, &'s [u8]);
+    type IntoIter = Lines<'s, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let unterminated = !self.source.is_empty() && !self.source.ends_with('\n');
+        Lines {
+            source: self,
+            next: 0,
+            count: self.lines.len() - 1 + usize::from(unterminated),
         }
     }
 }
@@ -142,6 +356,29 @@ impl<'a, T> Spanned<'a, T> {
     pub fn into_parts(self) -> (T, Span<'a>) {
         (self.data, self.span)
     }
+
+    /// splits the span at `index` and attaches `left` and `right` to the two halves, used to
+    /// reinterpret a token without lexing it again, e.g. a `>=` as a `>` followed by a `=`.
+    pub fn split_at<L, R>(
+        &self,
+        index: usize,
+        left: L,
+        right: R,
+    ) -> (Spanned<'a, L>, Spanned<'a, R>) {
+        let (lspan, rspan) = self.span.split_at(index);
+        (Spanned::new(lspan, left), Spanned::new(rspan, right))
+    }
+}
+
+/// orders spanned values by their `(line, column)` regardless of the data they carry, e.g. to
+/// sort diagnostics with `sort_by(by_position)`.
+pub fn by_position<T>(lhs: &Spanned<T>, rhs: &Spanned<T>) -> Ordering {
+    debug_assert_eq!(
+        lhs.span().source_id(),
+        rhs.span().source_id(),
+        "the positions of spans from different sources are not comparable"
+    );
+    lhs.position().cmp(&rhs.position())
 }
 
 impl<'a, T, E> Spanned<'a, Result<T, E>> {
@@ -198,6 +435,26 @@ impl<'a> Span<'a> {
         self.source
     }
 
+    /// the id of the source the span is in.
+    pub fn source_id(&self) -> SourceId {
+        self.span_source.id
+    }
+
+    /// the name of the file the span is in, if the source is named.
+    pub fn source_name(&self) -> Option<&'a str> {
+        self.span_source.name()
+    }
+
+    /// the source the span is in.
+    pub fn span_source(&self) -> &'a SpanSource<'a> {
+        self.span_source
+    }
+
+    /// the symbol of the text of the span in the interner of its source.
+    pub fn symbol(&self) -> Symbol {
+        self.span_source.intern(self.source)
+    }
+
     pub fn bytes(&self) -> impl Iterator<Item = u8> + 'a {
         self.source().bytes()
     }
@@ -210,6 +467,38 @@ impl<'a> Span<'a> {
         self.span_source.get_column(*self)
     }
 
+    /// the text of the line before the span.
+    fn before(&self) -> &'a str {
+        let line = self.span_source.get_line(*self);
+        &line.source()[..self.offset() - line.offset()]
+    }
+
+    /// the characters of the span, with the bytes of the invalid utf-8 that the source replaced
+    /// with `U+FFFD` in place of the replacements, see [`SpanSource::from_bytes`].
+    pub fn original_chars(&self) -> impl Iterator<Item = Result<char, &'a [u8]>> + 'a {
+        let (offset, invalid) = (self.offset(), &self.span_source.invalid);
+        self.source.char_indices().map(move |(i, c)| {
+            match invalid.binary_search_by_key(&(offset + i), |(offset, _)| *offset) {
+                Ok(replaced) => Err(&invalid[replaced].1[..]),
+                Err(_) => Ok(c),
+            }
+        })
+    }
+
+    /// like `column_with_tab_width` but the column counts the characters before the span instead
+    /// of the bytes.
+    pub fn char_column(&self, tab_width: usize) -> usize {
+        let before = self.before();
+        let tabs = before.bytes().filter(|&b| b == b'\t').count();
+        before.chars().count() + tabs * tab_width - tabs + 1
+    }
+
+    /// the column of the span when a tab takes `tab_width` columns instead of one.
+    pub fn column_with_tab_width(&self, tab_width: usize) -> usize {
+        let tabs = self.before().bytes().filter(|&b| b == b'\t').count();
+        self.column() - 1 + tabs * tab_width - tabs + 1
+    }
+
     pub const fn len(&self) -> usize {
         self.source().len()
     }
@@ -292,8 +581,35 @@ impl<'a> Span<'a> {
         }
     }
 
+    /// returns true if the byte at `offset` of the source is in the span.
+    pub fn contains(&self, offset: usize) -> bool {
+        (self.offset()..self.offset() + self.len()).contains(&offset)
+    }
+
+    /// returns true if `other` is within the span, both spans have to be from the same source.
+    pub fn contains_span(&self, other: Span) -> bool {
+        debug_assert_eq!(
+            self.source_id(),
+            other.source_id(),
+            "the spans are from different sources"
+        );
+        self.offset() <= other.offset()
+            && other.offset() + other.len() <= self.offset() + self.len()
+    }
+
+    /// an empty span right after the end of this span.
+    pub fn after(&self) -> Self {
+        self.split_at(self.len()).1
+    }
+
+    /// the span from the start of this span to the end of `other`, both spans have to be from the
+    /// same source.
     pub fn merge(self, other: Self) -> Self {
-        assert!(self.span_source == other.span_source);
+        assert_eq!(
+            self.source_id(),
+            other.source_id(),
+            "the spans are from different sources"
+        );
         let beg = self.offset();
         let end = other.offset() + other.len();
         Self {
@@ -337,9 +653,135 @@ impl<'a> AsRef<str> for Span<'a> {
     }
 }
 
+/// the place of a span in its source without borrowing the source, for the trees that outlive the
+/// source they are checked from like [`crate::ast::Root`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceRange {
+    offset: usize,
+    len: usize,
+    line: usize,
+    column: usize,
+}
+
+impl SourceRange {
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+
+    pub const fn position(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    /// returns true if the byte at `offset` of the source is in the range.
+    pub fn contains(&self, offset: usize) -> bool {
+        (self.offset..self.offset + self.len).contains(&offset)
+    }
+
+    /// the range from the start of this range to the end of `other`.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            len: other.offset + other.len - self.offset,
+            ..self
+        }
+    }
+
+    /// the span of `source` the range was taken from.
+    pub fn span<'a>(&self, source: &'a SpanSource) -> Span<'a> {
+        source.source().split_at(self.offset).1.split_at(self.len).0
+    }
+}
+
+impl From<Span<'_>> for SourceRange {
+    fn from(span: Span<'_>) -> Self {
+        Self {
+            offset: span.offset(),
+            len: span.len(),
+            line: span.line(),
+            column: span.column(),
+        }
+    }
+}
+
+/// a node of a syntax tree that knows the part of the source it was parsed from, the nodes of the
+/// parse tree return the [`Span`] itself and the nodes of the checked tree a [`SourceRange`].
+pub trait HasSpan {
+    type Span: Copy;
+
+    fn span(&self) -> Self::Span;
+}
+
+impl<'a> HasSpan for Span<'a> {
+    type Span = Span<'a>;
+
+    fn span(&self) -> Span<'a> {
+        *self
+    }
+}
+
+impl<'a, T> HasSpan for Spanned<'a, T> {
+    type Span = Span<'a>;
+
+    fn span(&self) -> Span<'a> {
+        self.span
+    }
+}
+
+/// why a list of edits cannot be applied, with the index of the edit at fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditError {
+    /// the edit starts before the edit preceding it.
+    Unsorted(usize),
+    /// the edit starts inside the span of the edit preceding it.
+    Overlapping(usize),
+    /// the edit ends past the end of the source.
+    OutOfBounds(usize),
+}
+
+/// replaces the bytes of `source` covered by the span of each edit with its replacement, an empty
+/// span inserts the replacement at its offset.
+///
+/// the spans are applied by their byte offsets, so they have to come from a span source of
+/// `source` that is valid utf-8 and whose line endings were not normalized. the edits have to be
+/// sorted by their offsets and must not overlap.
+pub fn rewrite(source: &[u8], edits: &[(Span, &[u8])]) -> Result<Vec<u8>, EditError> {
+    let mut rewritten = Vec::with_capacity(source.len());
+    let mut prev: Option<(usize, usize)> = None;
+    for (i, (span, replacement)) in edits.iter().enumerate() {
+        let (beg, end) = (span.offset(), span.offset() + span.len());
+        match prev {
+            Some((prev_beg, _)) if beg < prev_beg => return Err(EditError::Unsorted(i)),
+            Some((_, prev_end)) if beg < prev_end => return Err(EditError::Overlapping(i)),
+            _ if end > source.len() => return Err(EditError::OutOfBounds(i)),
+            _ => {}
+        }
+        let copied = prev.map_or(0, |(_, prev_end)| prev_end);
+        rewritten.extend_from_slice(&source[copied..beg]);
+        rewritten.extend_from_slice(replacement);
+        prev = Some((beg, end));
+    }
+    rewritten.extend_from_slice(&source[prev.map_or(0, |(_, end)| end)..]);
+    Ok(rewritten)
+}
+
 #[cfg(test)]
 mod test {
-    use crate::span::SpanSource;
+    use crate::span::{EditError, SpanSource};
 
     #[test]
     fn split_at_same_line() {
@@ -406,6 +848,115 @@ mod test {
         assert_eq!(s2.column(), 7);
     }
 
+    #[test]
+    fn after() {
+        use crate::lexer::tokens;
+        let span_source = SpanSource::new("int foo;\nbar");
+        let toks = tokens(span_source.source()).collect::<Vec<_>>();
+        let foo = toks[1].span();
+        assert_eq!(foo.source(), "foo");
+        let after = foo.after();
+        assert!(after.is_empty());
+        assert_eq!(after.offset(), foo.offset() + foo.len());
+        assert_eq!(after.position(), (1, 8));
+        assert_eq!(after.position(), toks[2].span().position());
+        // the end of the last token on a line is still on that line
+        assert_eq!(toks[2].span().after().position(), (1, 9));
+    }
+
+    #[test]
+    fn normalize_line_endings() {
+        let text = "int x;\r\nbool y;\rvoid z;\r\n";
+        let span_source = SpanSource::new(text).normalize_line_endings();
+        let source = span_source.source();
+        assert_eq!(source.source(), "int x;\nbool y;\nvoid z;\n");
+        assert_eq!(span_source.lines().count(), 4);
+        let (_, z) = source.split_at(source.find("z").unwrap());
+        assert_eq!(z.position(), (3, 6));
+        assert_eq!(
+            span_source.original_offset(z.offset()),
+            text.find("z").unwrap()
+        );
+        // the `\n` of a `\r\n` maps to its `\r`
+        assert_eq!(span_source.original_offset(6), 6);
+        assert_eq!(span_source.original_offset(7), 8);
+    }
+
+    #[test]
+    fn from_bytes() {
+        use crate::lexer::{tokens, Error};
+        let text = b"int x;\nx = \xff;";
+        let span_source = SpanSource::from_bytes(text);
+        let mut toks = tokens(span_source.source()).skip(5);
+        let err = toks.next().unwrap();
+        assert!(matches!(err.get(), Err(Error::NonAsciiChars(..))));
+        assert_eq!(err.position(), (2, 5));
+        assert_eq!(
+            toks.next().unwrap().get(),
+            &Ok(crate::lexer::Token::Semicolon)
+        );
+
+        // the columns and the messages are those of the bytes that were read
+        let text = b"int x;\r\nx = \xff + \xe9\x80;\n";
+        let span_source = SpanSource::from_bytes(text).normalize_line_endings();
+        let toks = tokens(span_source.source())
+            .skip(5)
+            .take(4)
+            .collect::<Vec<_>>();
+        let positions = toks.iter().map(|tok| tok.position()).collect::<Vec<_>>();
+        assert_eq!(positions, [(2, 5), (2, 7), (2, 9), (2, 11)]);
+        assert_eq!(span_source.original_offset(toks[3].span().offset()), 18);
+        let msg = |tok: &super::Spanned<crate::lexer::Result>| {
+            use crate::error::CCError;
+            tok.get().as_ref().unwrap_err().msgs()
+        };
+        assert_eq!(msg(&toks[0])[0].0, "non-ascii characters: 0xff ");
+        assert_eq!(msg(&toks[2])[0].0, "non-ascii characters: 0xe9 0x80 ");
+
+        // valid utf-8 is not copied
+        let text = "int x;";
+        let span_source = SpanSource::from_bytes(text.as_bytes());
+        assert_eq!(span_source.source().source().as_ptr(), text.as_ptr());
+    }
+
+    #[test]
+    fn numbered_lines() {
+        let span_source = SpanSource::new("int x;\n\nx = 1;");
+        let lines = (&span_source).into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [(1, &b"int x;"[..]), (2, &b""[..]), (3, &b"x = 1;"[..])]
+        );
+
+        let span_source = SpanSource::new("int x;\n");
+        let mut count = 0;
+        for (n, line) in &span_source {
+            assert_eq!((n, line), (1, &b"int x;"[..]));
+            count += 1;
+        }
+        assert_eq!(count, 1);
+
+        assert_eq!((&SpanSource::new("")).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn split_spanned() {
+        use crate::lexer::{tokens, Token};
+        let span_source = SpanSource::new("x\n  >= 1");
+        let tok = tokens(span_source.source())
+            .nth(1)
+            .unwrap()
+            .map(|t| t.unwrap());
+        assert_eq!(tok.get(), &Token::GreaterEqual);
+        let (greater, assign) = tok.split_at(1, Token::Greater, Token::Assign);
+        assert_eq!(greater.get(), &Token::Greater);
+        assert_eq!(greater.fragment(), ">");
+        assert_eq!(greater.position(), (2, 3));
+        assert_eq!(assign.get(), &Token::Assign);
+        assert_eq!(assign.fragment(), "=");
+        assert_eq!(assign.position(), (2, 4));
+    }
+
     #[test]
     fn merge() {
         // consecutive slices
@@ -465,4 +1016,59 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn rewrite() {
+        let text = "int x\nvoid f() { return y; }\n";
+        let span_source = SpanSource::new(text);
+        let source = span_source.source();
+        let x = source.split_at(4).1.split_at(1).0;
+        let y = source.split_at(text.find('y').unwrap()).1.split_at(1).0;
+        let rewritten = crate::span::rewrite(
+            text.as_bytes(),
+            &[(x.after(), b";"), (y, b"0"), (source.after(), b"// end\n")],
+        );
+        assert_eq!(
+            String::from_utf8(rewritten.unwrap()).unwrap(),
+            "int x;\nvoid f() { return 0; }\n// end\n"
+        );
+        assert_eq!(
+            crate::span::rewrite(text.as_bytes(), &[(y, b"0"), (x, b"z")]),
+            Err(EditError::Unsorted(1))
+        );
+        assert_eq!(
+            crate::span::rewrite(text.as_bytes(), &[(source, b""), (y, b"0")]),
+            Err(EditError::Overlapping(1))
+        );
+        assert_eq!(
+            crate::span::rewrite(b"int", &[(y, b"0")]),
+            Err(EditError::OutOfBounds(0))
+        );
+    }
+
+    #[test]
+    fn source_ids() {
+        let (a, b) = (SpanSource::new("int x;"), SpanSource::new("int x;"));
+        assert_ne!(a.id(), b.id());
+        assert_ne!(a.id(), a.clone().id());
+        let (x, semi) = (a.source().split_at(4).1, a.source().split_at(5).1);
+        assert_eq!(x.source_id(), a.id());
+        assert!(x.contains_span(semi));
+        assert!(!semi.contains_span(x));
+        assert_eq!(x.merge(semi).source(), "x;");
+    }
+
+    #[test]
+    #[should_panic(expected = "the spans are from different sources")]
+    fn merge_across_sources() {
+        let (a, b) = (SpanSource::new("int x;"), SpanSource::new("int x;"));
+        a.source().split_at(4).0.merge(b.source().split_at(4).1);
+    }
+
+    #[test]
+    #[should_panic(expected = "the span is from another source")]
+    fn line_of_another_source() {
+        let (a, b) = (SpanSource::new("int x;"), SpanSource::new("int x;"));
+        a.get_line(b.source());
+    }
 }
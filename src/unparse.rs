@@ -0,0 +1,368 @@
+//! prints a parsed program back as decaf code in a consistent layout.
+
+use crate::cst::{
+    Arg, Assign, AssignExpr, Block, Call, Expr, IntLiteral, Location, Op, PFunction, PRoot, PStmt,
+    PVar,
+};
+
+/// where the `{` of a block goes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BraceStyle {
+    /// at the end of the line that opens the block, e.g. `if (x) {`.
+    #[default]
+    SameLine,
+    /// on its own line indented like the line that opens the block.
+    NextLine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnparseOptions {
+    /// the number of spaces each nested block is indented by.
+    pub indent: usize,
+    pub brace_style: BraceStyle,
+}
+
+impl Default for UnparseOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            brace_style: BraceStyle::default(),
+        }
+    }
+}
+
+pub(crate) const fn op(op: Op) -> &'static str {
+    match op {
+        Op::Add => "+",
+        Op::Sub => "-",
+        Op::Mul => "*",
+        Op::Div => "/",
+        Op::Mod => "%",
+        Op::Less => "<",
+        Op::LessEqual => "<=",
+        Op::Greater => ">",
+        Op::GreaterEqual => ">=",
+        Op::Equal => "==",
+        Op::NotEqual => "!=",
+        Op::And => "&&",
+        Op::Or => "||",
+    }
+}
+
+fn expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Len { id, .. } => format!("len({})", id.source()),
+        Expr::Nested(_, inner) => format!("({})", self::expr(inner)),
+        Expr::Not(_, e) => format!("!{}", self::expr(e)),
+        // `- -x` is not printed as `--x`, which is lexed as a decrement
+        Expr::Neg(_, e) => match self::expr(e) {
+            e if e.starts_with('-') => format!("- {}", e),
+            e => format!("-{}", e),
+        },
+        Expr::Ter { cond, yes, no, .. } => format!(
+            "{} ? {} : {}",
+            self::expr(cond),
+            self::expr(yes),
+            self::expr(no)
+        ),
+        Expr::Call(call) => self::call(call),
+        Expr::Loc(loc) => location(loc),
+        Expr::Index { base, offset, .. } => {
+            format!("{}[{}]", self::expr(base), self::expr(offset))
+        }
+        // the literals are printed as they are written, e.g. `0x1F` or `'\n'`
+        Expr::Literal { span, .. } => span.source().to_string(),
+        Expr::BinOp { op, lhs, rhs, .. } => {
+            format!("{} {} {}", self::expr(lhs), self::op(*op), self::expr(rhs))
+        }
+        // a placeholder for a missing expression is printed as nothing
+        Expr::Error(_) => String::new(),
+    }
+}
+
+fn location(loc: &Location) -> String {
+    match loc {
+        Location::Scalar(ident) => ident.source().to_string(),
+        Location::Index { ident, offset, .. } => format!("{}[{}]", ident.source(), expr(offset)),
+    }
+}
+
+fn call(call: &Call) -> String {
+    let args = call
+        .args
+        .iter()
+        .map(|arg| match arg {
+            Arg::String(span) => span.source().to_string(),
+            Arg::Expr(e) => expr(e),
+        })
+        .collect::<Vec<_>>();
+    format!("{}({})", call.name.source(), args.join(", "))
+}
+
+fn assign(assign: &Assign) -> String {
+    let lhs = location(&assign.lhs);
+    match &assign.op {
+        AssignExpr::Inc => format!("{}++", lhs),
+        AssignExpr::Dec => format!("{}--", lhs),
+        AssignExpr::AddAssign(e) => format!("{} += {}", lhs, expr(e)),
+        AssignExpr::SubAssign(e) => format!("{} -= {}", lhs, expr(e)),
+        AssignExpr::Assign(e) => format!("{} = {}", lhs, expr(e)),
+    }
+}
+
+fn var(var: &PVar) -> String {
+    match var {
+        PVar::Array {
+            ty, ident, size, ..
+        } => match size {
+            // the span of a hex literal leaves out its `0x`
+            IntLiteral::Hex(digits) => format!("{} {}[0x{}]", ty, ident.source(), digits.source()),
+            IntLiteral::Decimal(size) => format!("{} {}[{}]", ty, ident.source(), size.source()),
+        },
+        PVar::Scalar { ty, ident } => format!("{} {}", ty, ident.source()),
+    }
+}
+
+struct Printer {
+    out: String,
+    depth: usize,
+    options: UnparseOptions,
+}
+
+impl Printer {
+    fn line(&mut self, text: &str) {
+        if !text.is_empty() {
+            let indent = self.depth * self.options.indent;
+            self.out.push_str(&format!("{:indent$}{}", "", text));
+        }
+        self.out.push('\n');
+    }
+
+    /// prints `header` and the `{` that opens the block after it.
+    fn open(&mut self, header: &str) {
+        match self.options.brace_style {
+            BraceStyle::SameLine => self.line(&format!("{} {{", header)),
+            BraceStyle::NextLine => {
+                self.line(header);
+                self.line("{");
+            }
+        }
+        self.depth += 1;
+    }
+
+    /// prints the contents of the block and its closing `}`.
+    fn block(&mut self, block: &Block) {
+        block
+            .decls()
+            .iter()
+            .for_each(|decl| self.line(&format!("{};", var(decl))));
+        block.stmts().iter().for_each(|stmt| self.stmt(stmt));
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn stmt(&mut self, stmt: &PStmt) {
+        match stmt {
+            PStmt::Call(call) => self.line(&format!("{};", self::call(call))),
+            PStmt::Assign(assign) => self.line(&format!("{};", self::assign(assign))),
+            PStmt::Return { expr: Some(e), .. } => self.line(&format!("return {};", expr(e))),
+            PStmt::Return { expr: None, .. } => self.line("return;"),
+            PStmt::Break(_) => self.line("break;"),
+            PStmt::Continue(_) => self.line("continue;"),
+            // the skipped source is kept as it is written
+            PStmt::Error(span) => self.line(span.source().trim()),
+            PStmt::If { cond, yes, no, .. } => {
+                self.open(&format!("if ({})", condition(cond)));
+                self.block(yes);
+                if let Some(no) = no {
+                    if self.options.brace_style == BraceStyle::SameLine {
+                        // the `}` of the `if` block shares its line with the `else`
+                        self.out.truncate(self.out.len() - 1);
+                        self.out.push_str(" else {\n");
+                        self.depth += 1;
+                    } else {
+                        self.open("else");
+                    }
+                    self.block(no);
+                }
+            }
+            PStmt::While { cond, body, .. } => {
+                self.open(&format!("while ({})", condition(cond)));
+                self.block(body);
+            }
+            PStmt::For {
+                init,
+                cond,
+                update,
+                body,
+                ..
+            } => {
+                self.open(&format!(
+                    "for ({}; {}; {})",
+                    assign(init),
+                    expr(cond),
+                    assign(update)
+                ));
+                self.block(body);
+            }
+        }
+    }
+
+    fn function(&mut self, func: &PFunction) {
+        let args = func.args.iter().map(var).collect::<Vec<_>>();
+        self.open(&format!(
+            "{} {}({})",
+            func.ret.map_or("void".to_string(), |ret| ret.to_string()),
+            func.name().source(),
+            args.join(", ")
+        ));
+        self.block(&func.body);
+    }
+}
+
+/// the parentheses around the conditions of `if` and `while` are part of the statement.
+fn condition(cond: &Expr) -> String {
+    match cond {
+        Expr::Nested(_, inner) => expr(inner),
+        cond => expr(cond),
+    }
+}
+
+/// prints `root` with its blocks indented by `options.indent` spaces per level, the imports and
+/// the fields come first and every method is preceded by an empty line.
+pub fn unparse(root: &PRoot, options: UnparseOptions) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        depth: 0,
+        options,
+    };
+    root.imports
+        .iter()
+        .for_each(|import| printer.line(&format!("import {};", import.name().source())));
+    root.decls
+        .iter()
+        .for_each(|decl| printer.line(&format!("{};", var(decl))));
+    root.funcs.iter().for_each(|func| {
+        if !printer.out.is_empty() {
+            printer.line("");
+        }
+        printer.function(func)
+    });
+    printer.out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{lexer::tokens_equal_ignoring_trivia, span::SpanSource};
+
+    const CODE: &str = "import printf;\nint a[0x10];\nint f(int x, bool b) { if (b) { \
+                        while (x > 0) { x -= 1; } } else { return -x; } return a[x % 16]; }\n\
+                        void main() { int i; for (i = 0; i < 3; i++) { printf(\"%d\", f(i, true)); } }";
+
+    macro_rules! unparse {
+        ($out:ident, $text:expr, $options:expr) => {
+            let span_source = SpanSource::new($text);
+            let root = crate::parse(&span_source).unwrap();
+            let $out = unparse(&root, $options);
+        };
+    }
+
+    #[test]
+    fn indent_4() {
+        unparse!(out, CODE, UnparseOptions::default());
+        assert_eq!(
+            out,
+            "import printf;\n\
+             int a[0x10];\n\
+             \n\
+             int f(int x, bool b) {\n\
+            \x20   if (b) {\n\
+            \x20       while (x > 0) {\n\
+            \x20           x -= 1;\n\
+            \x20       }\n\
+            \x20   } else {\n\
+            \x20       return -x;\n\
+            \x20   }\n\
+            \x20   return a[x % 16];\n\
+             }\n\
+             \n\
+             void main() {\n\
+            \x20   int i;\n\
+            \x20   for (i = 0; i < 3; i++) {\n\
+            \x20       printf(\"%d\", f(i, true));\n\
+            \x20   }\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn indent_2() {
+        unparse!(
+            out,
+            CODE,
+            UnparseOptions {
+                indent: 2,
+                ..UnparseOptions::default()
+            }
+        );
+        assert_eq!(
+            out,
+            "import printf;\n\
+             int a[0x10];\n\
+             \n\
+             int f(int x, bool b) {\n\
+            \x20 if (b) {\n\
+            \x20   while (x > 0) {\n\
+            \x20     x -= 1;\n\
+            \x20   }\n\
+            \x20 } else {\n\
+            \x20   return -x;\n\
+            \x20 }\n\
+            \x20 return a[x % 16];\n\
+             }\n\
+             \n\
+             void main() {\n\
+            \x20 int i;\n\
+            \x20 for (i = 0; i < 3; i++) {\n\
+            \x20   printf(\"%d\", f(i, true));\n\
+            \x20 }\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn next_line_braces() {
+        unparse!(
+            out,
+            "void main() { if (x) { y = 1; } else { y = 2; } }",
+            UnparseOptions {
+                indent: 2,
+                brace_style: BraceStyle::NextLine,
+            }
+        );
+        assert_eq!(
+            out,
+            "void main()\n{\n  if (x)\n  {\n    y = 1;\n  }\n  else\n  {\n    y = 2;\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn reparses_the_same() {
+        unparse!(once, CODE, UnparseOptions::default());
+        unparse!(twice, &once, UnparseOptions::default());
+        assert_eq!(once, twice);
+        assert!(tokens_equal_ignoring_trivia(
+            CODE.as_bytes(),
+            once.as_bytes()
+        ));
+    }
+
+    #[test]
+    fn nested_negation() {
+        let code = "void main() {\n    x = - -x;\n    y = - - -1 - -(-y);\n}\n";
+        unparse!(out, code, UnparseOptions::default());
+        // the negations are printed back as they are written
+        assert_eq!(out, code);
+    }
+}
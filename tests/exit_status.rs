@@ -0,0 +1,49 @@
+//! runs the `decafcc` binary and checks the exit code it reports to the shell.
+
+use std::process::Command;
+
+/// writes `code` to a file unique to `name` and runs the binary on it with `args`, returns whether
+/// it exited successfully.
+fn run(name: &str, code: &str, args: &[&str]) -> bool {
+    let path = std::env::temp_dir().join(format!("decafcc-exit-status-{name}.dcf"));
+    std::fs::write(&path, code).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_decafcc"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap()
+        .status;
+    std::fs::remove_file(&path).unwrap();
+    assert!(status.code().is_some(), "killed by a signal");
+    status.success()
+}
+
+const UNUSED_LOCAL: &str = "void main() {\n  int x;\n}\n";
+
+#[test]
+fn warnings() {
+    assert!(run("warnings", UNUSED_LOCAL, &["-t", "semantics"]));
+}
+
+#[test]
+fn denied_warnings() {
+    assert!(!run(
+        "denied-warnings",
+        UNUSED_LOCAL,
+        &["-t", "semantics", "--deny-warnings"]
+    ));
+}
+
+#[test]
+fn parse_error() {
+    assert!(!run(
+        "parse-error",
+        "void main() {\n  x = ;\n}\n",
+        &["-t", "check"]
+    ));
+}
+
+#[test]
+fn usage_error() {
+    assert!(!run("usage-error", UNUSED_LOCAL, &["--stop-at=foo"]));
+}
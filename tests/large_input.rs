@@ -0,0 +1,78 @@
+//! lexes a generated 10MB file and checks that the lexer does not allocate per token.
+//!
+//! run with `cargo test --test large_input -- --ignored`.
+//!
+//! it also checks that [`TokenStream`] lexing from a reader sees the same tokens as collecting
+//! them from the whole text first, even when the reader returns the text in small reads.
+
+use dcfrs::{
+    lexer::{tokens, Token, TokenStream},
+    span::SpanSource,
+};
+
+mod common;
+
+use common::allocations;
+
+const SIZE: usize = 10 * 1024 * 1024;
+
+const CHUNK: &str = "int a[0x10], b; // counters\n\
+                     /* block\n comment */ void f(int x) {\n  \
+                     if (x >= 10 && !b) { a[x] += 'c' - '\\n'; }\n  \
+                     printf(\"%d\\t\", x);\n}\n";
+
+#[test]
+#[ignore]
+fn lex_10mb_without_allocating() {
+    let text = CHUNK.repeat(SIZE / CHUNK.len() + 1);
+    let code = SpanSource::new(&text);
+    // the source interns a name the first time it sees it, which allocates once per name
+    tokens(code.source()).for_each(drop);
+
+    let before = allocations();
+    let (count, errors) = tokens(code.source()).fold((0, 0), |(count, errors), tok| {
+        (count + 1, errors + usize::from(tok.get().is_err()))
+    });
+    let allocations = allocations() - before;
+
+    assert_eq!(errors, 0);
+    assert!(count > SIZE / 10, "only {} tokens", count);
+    assert_eq!(allocations, 0);
+    let last = tokens(code.source()).last().unwrap();
+    assert_eq!(last.copied(), Ok(Token::Eof));
+}
+
+/// a reader that returns at most `.1` bytes per read, so the tokens straddle the reads.
+struct SmallReads<'a>(&'a [u8], usize);
+
+impl std::io::Read for SmallReads<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.0.len().min(buf.len()).min(self.1);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn streamed_tokens_match_collected() {
+    let text = CHUNK.repeat(1000) + "@ x = \"\\q\";";
+    let code = SpanSource::new(&text);
+    let collected = tokens(code.source()).collect::<Vec<_>>();
+    let mut streamed = 0;
+    for tok in TokenStream::from_reader(SmallReads(text.as_bytes(), 7)) {
+        let tok = tok.unwrap();
+        let expected = &collected[streamed];
+        assert_eq!(
+            (tok.text.as_str(), tok.token.ok(), tok.offset),
+            (
+                expected.fragment(),
+                expected.get().ok(),
+                expected.span().offset()
+            )
+        );
+        assert_eq!((tok.line, tok.column), expected.position());
+        streamed += 1;
+    }
+    assert_eq!(streamed, collected.len());
+}
@@ -0,0 +1,62 @@
+//! runs the `decafcc` binary and checks that the artifact of a mode goes to stdout and the report
+//! to stderr.
+
+use std::process::{Command, Output};
+
+/// writes `code` to a file unique to `name` and runs the binary on it with `args`.
+fn run(name: &str, code: &str, args: &[&str]) -> Output {
+    let path = std::env::temp_dir().join(format!("decafcc-output-{name}.dcf"));
+    std::fs::write(&path, code).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_decafcc"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn fix() {
+    let output = run("fix", "void main() {\n  int x\n  x = 1;\n}\n", &["--fix"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "void main() {\n  int x;\n  x = 1;\n}\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("mode: fix\n"), "{}", stderr);
+    assert!(stderr.ends_with("applied 1 fix\n"), "{}", stderr);
+}
+
+#[test]
+fn count_lines_of_code() {
+    let output = run(
+        "count-lines-of-code",
+        "// main\nvoid main() {\n}\n",
+        &["--count-lines-of-code"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "code 2\ncomment 1\nblank 0\ntotal 3\n"
+    );
+}
+
+#[test]
+fn dump_symbols() {
+    let output = run(
+        "dump-symbols",
+        "void main() {\n}\n",
+        &["--dump-symbols=json"],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("main"));
+}
+
+#[test]
+fn scanner_output_stays_on_stderr() {
+    let output = run("scanner", "void main() {\n}\n", &["-t", "scan"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}